@@ -3,19 +3,47 @@ mod models;
 mod util;
 mod services;
 
-use std::path::PathBuf;
-use util::{ServerFileManager, ServerInstance, ServerCreationStatus, JarCacheManager, CacheStats, ServerPropertiesManager};
-use services::version_manager::{VersionManager, VersionSummary};
-use services::unified_server_service::UnifiedServerService;
-use services::rcon_manager::{RconManager, RconConfig};
+use std::path::{Path, PathBuf};
+use util::{BackupCompletedEvent, BackupInfo, BackupManager, ClearWorldResult, RestoreResult, BackupRetention, BackupSchedule, ConfigValidationReport, ServerFileManager, ServerInstance, ServerCreationStatus, JarCacheManager, CacheAuditEntry, CacheEntry, CacheStats, ServerPropertiesManager, AppSettings, AppSettingsManager, PropertySchema, PropertyUpdateResult, server_connect_host};
+use services::version_manager::{AllVersionsResponse, CombinationSupport, VersionManager, VersionSummary};
+use services::unified_server_service::{UnifiedServerService, ReattachReport, LaunchCommand, LaunchOptions, MaintenanceGuard, RepairReport};
+use services::rcon_manager::{RconManager, RconConfig, create_rcon_properties, generate_strong_rcon_password};
 use services::simple_rcon_monitor::{SimpleRconMonitor, ServerStatus};
-use models::version::{LoaderType, VersionResponse};
+use models::version::{LoaderType, MinecraftVersion, VersionResponse, PaperBuild};
 use models::query::{QueryResponse, QueryConfig};
+use models::{ServerDetails, BulkOperationResult, BatchRequest, BatchResult, HttpApiStatus, DashboardServer, DashboardSnapshot};
 use services::query_service::QueryService;
-use std::collections::HashMap;
+use services::diagnostics::{DiagnosticReport, DiagnosticTarget};
+use services::server_bundle::BundleManifest;
+use services::loader_detection::DetectedLoader;
+use services::port_manager::{PortKey, PortMap};
+use services::server_files::FileEntry;
+use services::server_icon::ServerIconResult;
+use services::whitelist::WhitelistEntry;
+use services::player_profile::PlayerProfile;
+use services::mod_loader_strategy::MemoryRecommendation;
+use services::paper_strategy::PaperUpdateInfo;
+use services::update_check::UpdateCheck;
+use services::backup_scheduler::BackupScheduler;
+use services::server_templates::{ServerTemplate, TEMPLATES_DIR};
+use services::mod_loader_strategy::EffectiveMemory;
+use services::mod_loader_strategy::{set_install_progress_sender, InstallProgressEvent};
+use services::java_runtime::{bundled_java_path, get_or_install_jre, required_java_version, JavaInstallation};
+use services::world_info::WorldSize;
+use services::world_preset::{WorldPreset, WorldPresetResult};
+use services::crash_reports::{CrashReport, CrashReportInfo, ServerCrashedEvent};
+use services::rcon_commands::{ActionResult, SeedSource, SpawnPoint, Weather, WorldBorder, WorldSeed};
+use services::gamerules::{GameruleInfo, GameruleValue};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use rand::Rng;
+use tauri::Emitter;
+#[cfg(feature = "http-api")]
+use services::http_api::{ApiServerSummary, HttpApiHandle, ServerController};
+
+/// Placeholder so the API state can be declared when the http-api feature is off
+#[cfg(not(feature = "http-api"))]
+struct HttpApiHandle;
 
 
 // Global unified server service
@@ -34,6 +62,28 @@ lazy_static::lazy_static! {
         Arc::new(Mutex::new(monitor))
     };
     
+    static ref REATTACH_REPORT: Arc<Mutex<ReattachReport>> = {
+        Arc::new(Mutex::new(ReattachReport::default()))
+    };
+    
+    static ref HTTP_API: Arc<Mutex<Option<HttpApiHandle>>> = {
+        Arc::new(Mutex::new(None))
+    };
+    
+    /// Last Paper update check per server, so get_server_details doesn't hit the Paper API
+    static ref PAPER_UPDATES: Arc<Mutex<HashMap<String, PaperUpdateInfo>>> = {
+        Arc::new(Mutex::new(HashMap::new()))
+    };
+    
+    /// Last up-to-date check per server, for the dashboard
+    static ref SERVER_UPDATES: Arc<Mutex<HashMap<String, UpdateCheck>>> = {
+        Arc::new(Mutex::new(HashMap::new()))
+    };
+    
+    static ref BACKUP_SCHEDULER: Arc<Mutex<BackupScheduler>> = {
+        Arc::new(Mutex::new(BackupScheduler::new()))
+    };
+    
     static ref MONITORING_INITIALIZED: Arc<Mutex<bool>> = {
         Arc::new(Mutex::new(false))
     };
@@ -48,14 +98,39 @@ fn parse_loader_type(loader: &str) -> Result<LoaderType, String> {
         "neoforge" => Ok(LoaderType::NeoForge),
         "paper" => Ok(LoaderType::Paper),
         "quilt" => Ok(LoaderType::Quilt),
+        "spigot" => Ok(LoaderType::Spigot),
         _ => Err(format!("Invalid loader type: {}", loader)),
     }
 }
 
+/// Pre-flight check that the loader has builds for the Minecraft version. When the version
+/// lists can't be fetched the download step reports the real problem, so that isn't fatal here.
+async fn ensure_combination_supported(loader: &LoaderType, minecraft_version: &str) -> Result<(), String> {
+    let version_manager = create_version_manager()?;
+    match version_manager.is_combination_supported(loader.clone(), minecraft_version).await {
+        Ok(CombinationSupport { supported: false, reason }) => {
+            Err(reason.unwrap_or_else(|| format!("{:?} does not support Minecraft {}", loader, minecraft_version)))
+        }
+        Ok(_) => Ok(()),
+        Err(e) => {
+            log::warn!("Could not check {:?} support for Minecraft {}: {}", loader, minecraft_version, e);
+            Ok(())
+        }
+    }
+}
+
 fn get_storage_path(server_name: &str) -> PathBuf {
     PathBuf::from("storage").join(server_name)
 }
 
+fn create_app_settings_manager() -> AppSettingsManager {
+    AppSettingsManager::new(PathBuf::from("storage/app_settings.json"))
+}
+
+fn create_backup_manager() -> BackupManager {
+    BackupManager::new(PathBuf::from("storage/backups"))
+}
+
 fn create_version_manager() -> Result<VersionManager, String> {
     let cache_dir = PathBuf::from("storage/version_cache");
     VersionManager::new(cache_dir).map_err(|e| e.to_string())
@@ -107,27 +182,354 @@ fn get_all_server_instances() -> Result<Vec<ServerInstance>, String> {
     manager.get_all_instances().map_err(|e| e.to_string())
 }
 
+/// Labels a server; tags are trimmed and compared without regard to case, so adding one twice is a no-op
+#[tauri::command]
+fn add_tag(name: String, tag: String) -> Result<Vec<String>, String> {
+    let tag = tag.trim().to_string();
+    if tag.is_empty() || tag.len() > 32 {
+        return Err("Tags must be between 1 and 32 characters".to_string());
+    }
+    
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    let mut instance = manager.get_instance(&name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", name))?;
+    
+    if !instance.has_tag(&tag) {
+        instance.tags.push(tag);
+    }
+    let tags = instance.tags.clone();
+    manager.update_instance(&name, instance).map_err(|e| e.to_string())?;
+    Ok(tags)
+}
+
+#[tauri::command]
+fn remove_tag(name: String, tag: String) -> Result<Vec<String>, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    let mut instance = manager.get_instance(&name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", name))?;
+    
+    instance.tags.retain(|existing| !existing.eq_ignore_ascii_case(tag.trim()));
+    let tags = instance.tags.clone();
+    manager.update_instance(&name, instance).map_err(|e| e.to_string())?;
+    Ok(tags)
+}
+
+#[tauri::command]
+fn get_servers_by_tag(tag: String) -> Result<Vec<ServerInstance>, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    manager.get_instances_by_tag(&tag).map_err(|e| e.to_string())
+}
+
+/// Checks server_config.json and, if it is damaged, recovers every readable server entry
+#[tauri::command]
+fn validate_config() -> Result<ConfigValidationReport, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    ServerFileManager::new(config_path).validate_config().map_err(|e| e.to_string())
+}
+
+/// Persists a drag-and-drop order of the server list
+#[tauri::command]
+fn reorder_servers(ordered_names: Vec<String>) -> Result<Vec<ServerInstance>, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    manager.reorder_instances(&ordered_names).map_err(|e| e.to_string())?;
+    manager.get_all_instances().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_server_details(server_name: String) -> Result<ServerDetails, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    let instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
+    
+    let (is_running, is_sleeping, uptime_secs) = {
+        let service = UNIFIED_SERVER_SERVICE.lock().await;
+        (
+            service.is_server_running(&server_name).await,
+            service.is_wake_listener_armed(&server_name).await,
+            service.get_uptime(&server_name).await,
+        )
+    };
+    let status = get_server_monitor_status(server_name.clone()).await;
+    let disk_free_bytes = util::available_space(&PathBuf::from("storage"));
+    let storage_path = get_storage_path(&server_name);
+    let world_size_bytes = tokio::task::spawn_blocking(move || {
+        services::world_info::world_size(&storage_path, false).total_bytes
+    })
+    .await
+    .ok();
+    
+    Ok(ServerDetails {
+        instance,
+        is_running,
+        is_sleeping,
+        status,
+        disk_free_bytes,
+        world_size_bytes,
+        uptime_secs,
+        paper_update_available: PAPER_UPDATES.lock().await.get(&server_name).map(|info| info.update_available),
+    })
+}
+
+/// Status, players, uptime and resource usage of every server in one call. Reads the monitor's
+/// cached state instead of querying servers, so it is cheap enough to poll.
+#[tauri::command]
+async fn get_dashboard_snapshot() -> Result<DashboardSnapshot, String> {
+    let instances = get_all_server_instances()?;
+    let statuses = SERVER_MONITOR.lock().await.get_all_statuses().await;
+    
+    let mut servers = Vec::with_capacity(instances.len());
+    for instance in instances {
+        let player_count = SERVER_MONITOR.lock().await.get_roster(&instance.name).await.map(|players| players.len());
+        let (uptime_secs, usage) = {
+            let service = UNIFIED_SERVER_SERVICE.lock().await;
+            (service.get_uptime(&instance.name).await, service.get_resource_usage(&instance.name).await)
+        };
+        let status = statuses.get(&instance.name).copied().unwrap_or(ServerStatus::Offline);
+        let update_available = SERVER_UPDATES.lock().await.get(&instance.name).map(|check| check.update_available);
+        
+        servers.push(DashboardServer {
+            status: status.as_str().to_string(),
+            name: instance.name,
+            loader: instance.mod_loader,
+            version: instance.version,
+            player_count,
+            uptime_secs,
+            cpu_percent: usage.map(|(cpu, _)| cpu),
+            memory_bytes: usage.map(|(_, memory)| memory),
+            update_available,
+        });
+    }
+    
+    Ok(DashboardSnapshot { generated_at: chrono::Utc::now(), servers })
+}
+
+/// Arguments of the batchable commands that take a server; camelCase like invoke() arguments
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ServerNameArgs {
+    server_name: String,
+}
+
+/// Runs one read-only command for batch(). Commands not listed here are refused, so a batch
+/// can never start, stop or change anything.
+async fn dispatch_batched(command: &str, args: serde_json::Value) -> Result<serde_json::Value, String> {
+    fn server_name(args: serde_json::Value) -> Result<String, String> {
+        serde_json::from_value::<ServerNameArgs>(args)
+            .map(|args| args.server_name)
+            .map_err(|e| format!("Invalid arguments: {}", e))
+    }
+    fn to_value(value: impl serde::Serialize) -> Result<serde_json::Value, String> {
+        serde_json::to_value(value).map_err(|e| e.to_string())
+    }
+    
+    match command {
+        "get_all_server_instances" => to_value(get_all_server_instances()?),
+        "get_dashboard_snapshot" => to_value(get_dashboard_snapshot().await?),
+        "get_all_server_monitor_statuses" => to_value(get_all_server_monitor_statuses().await),
+        "get_running_servers" => to_value(get_running_servers().await),
+        "get_port_map" => to_value(get_port_map().await?),
+        "get_reattach_report" => to_value(get_reattach_report().await?),
+        "get_server_details" => to_value(get_server_details(server_name(args)?).await?),
+        "get_server_uptime" => to_value(get_server_uptime(server_name(args)?).await?),
+        "get_server_monitor_status" => to_value(get_server_monitor_status(server_name(args)?).await),
+        "is_server_running" => to_value(is_server_running(server_name(args)?).await),
+        "is_rcon_connected" => to_value(is_rcon_connected(server_name(args)?).await),
+        "get_online_players" => to_value(get_online_players(server_name(args)?).await?),
+        "get_server_icon" => to_value(get_server_icon(server_name(args)?)?),
+        "list_backups" => to_value(list_backups(server_name(args)?)?),
+        "list_crash_reports" => to_value(list_crash_reports(server_name(args)?)?),
+        _ => Err(format!("'{}' can't be batched; only read-only commands are allowed", command)),
+    }
+}
+
+/// Runs several read-only commands in one IPC round trip, e.g. everything a dashboard refresh
+/// needs. Each request gets its own result; one failing doesn't affect the others.
+#[tauri::command]
+async fn batch(requests: Vec<BatchRequest>) -> Vec<BatchResult> {
+    let mut results = Vec::with_capacity(requests.len());
+    for request in requests {
+        results.push(match dispatch_batched(&request.command, request.args).await {
+            Ok(data) => BatchResult::succeeded(&request.command, data),
+            Err(e) => BatchResult::failed(&request.command, e),
+        });
+    }
+    results
+}
+
+/// Seconds since the server started, or None while it is offline
+#[tauri::command]
+async fn get_server_uptime(server_name: String) -> Result<Option<u64>, String> {
+    Ok(UNIFIED_SERVER_SERVICE.lock().await.get_uptime(&server_name).await)
+}
+
+/// Size of the server's world folders; `refresh` forces a new measurement instead of the cached one
+#[tauri::command]
+async fn get_world_size(server_name: String, refresh: Option<bool>) -> Result<WorldSize, String> {
+    let storage_path = get_storage_path(&server_name);
+    if !storage_path.exists() {
+        return Err(format!("Server instance '{}' not found", server_name));
+    }
+    
+    tokio::task::spawn_blocking(move || {
+        services::world_info::world_size(&storage_path, refresh.unwrap_or(false))
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
-fn remove_server_instance(name: String) -> Result<String, String> {
+async fn remove_server_instance(name: String) -> Result<String, String> {
     let config_path = PathBuf::from("storage/server_config.json");
     let manager = ServerFileManager::new(config_path);
     
     manager.remove_instance(&name).map_err(|e| e.to_string())?;
+    BACKUP_SCHEDULER.lock().await.disarm(&name);
     
     Ok(format!("Server instance '{}' removed successfully", name))
 }
 
 #[tauri::command]
-fn delete_server_completely(name: String) -> Result<String, String> {
+async fn delete_server_completely(name: String) -> Result<String, String> {
     let config_path = PathBuf::from("storage/server_config.json");
     let storage_path = PathBuf::from("storage");
     let manager = ServerFileManager::new(config_path);
     
     manager.remove_instance_with_storage(&name, &storage_path).map_err(|e| e.to_string())?;
+    BACKUP_SCHEDULER.lock().await.disarm(&name);
     
     Ok(format!("Server instance '{}' and its files deleted successfully", name))
 }
 
+/// Moves a stashed world back into the folder of a server whose re-creation failed, along with
+/// the server.properties that names it
+fn put_world_back(server_path: &Path, stash_path: &Path, level_name: &str, old_properties: Option<&[u8]>) -> Result<(), String> {
+    std::fs::create_dir_all(server_path).map_err(|e| e.to_string())?;
+    let properties_path = server_path.join("server.properties");
+    if let Some(content) = old_properties.filter(|_| !properties_path.exists()) {
+        std::fs::write(&properties_path, content).map_err(|e| e.to_string())?;
+    }
+    services::world_info::restore_world(stash_path, server_path, level_name).map_err(|e| e.to_string())
+}
+
+/// Deletes and re-creates a server with a new version or loader, optionally keeping its world.
+/// Its server.properties and instance settings carry over. Returns warnings about how the kept
+/// world may be affected.
+#[tauri::command]
+async fn recreate_server(
+    name: String,
+    new_version: String,
+    new_loader: String,
+    new_loader_version: String,
+    keep_world: bool,
+) -> Result<Vec<String>, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let storage_path = PathBuf::from("storage");
+    let manager = ServerFileManager::new(config_path);
+    
+    let instance = manager.get_instance(&name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", name))?;
+    
+    if UNIFIED_SERVER_SERVICE.lock().await.is_server_running(&name).await {
+        return Err("Stop the server before recreating it".to_string());
+    }
+    
+    let current_loader = parse_loader_type(&instance.mod_loader)?;
+    let new_loader_type = parse_loader_type(&new_loader)?;
+    let server_path = get_storage_path(&name);
+    let stash_path = storage_path.join(format!(".recreate-{}", name));
+    
+    let mut warnings = Vec::new();
+    let mut kept_level = None;
+    if keep_world {
+        // Check the world against the new version before anything is deleted
+        let world_path = services::world_info::world_folder(&server_path);
+        if let Some(world_name) = services::world_info::read_world_version(&world_path).ok().and_then(|v| v.name) {
+            match services::world_info::compare_release_versions(&world_name, &new_version) {
+                Some(std::cmp::Ordering::Greater) => {
+                    return Err(format!(
+                        "The world was last saved with Minecraft {}; it cannot be opened with {}",
+                        world_name, new_version
+                    ));
+                }
+                Some(std::cmp::Ordering::Less) => warnings.push(format!(
+                    "The world will be upgraded from {} to {} on first start and cannot be opened with older versions afterwards",
+                    world_name, new_version
+                )),
+                _ => {}
+            }
+        }
+        warnings.extend(services::unified_server_service::migration_warnings(&server_path, &current_loader, &new_loader_type));
+        
+        kept_level = services::world_info::stash_world(&server_path, &stash_path)
+            .map_err(|e| format!("Failed to move the world aside: {}", e))?;
+        if kept_level.is_none() {
+            warnings.push("The server has no world yet, so there was nothing to keep".to_string());
+        }
+    }
+    
+    let properties_path = server_path.join("server.properties");
+    // None when the server never generated one; the new server keeps its defaults then
+    let old_properties = std::fs::read(&properties_path).ok();
+    
+    let recreated = match manager.remove_instance_with_storage(&name, &storage_path) {
+        Ok(()) => create_server_transactional(name.clone(), new_version, new_loader, new_loader_version).await,
+        Err(e) => Err(e.to_string()),
+    };
+    
+    if let Err(e) = recreated {
+        let Some(level_name) = kept_level else {
+            return Err(format!("Failed to recreate server '{}': {}", name, e));
+        };
+        // Put the world back where it was so it isn't left in a hidden folder
+        return Err(match put_world_back(&server_path, &stash_path, &level_name, old_properties.as_deref()) {
+            Ok(()) => format!("Failed to recreate server '{}': {}. The world was put back in {:?}", name, e, server_path),
+            Err(_) => format!("Failed to recreate server '{}': {}. The world was kept in {:?}", name, e, stash_path),
+        });
+    }
+    
+    // The new instance only knows its version and loader; everything else comes from the old one
+    let new_instance = manager.get_instance(&name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", name))?;
+    let carried = ServerInstance {
+        version: new_instance.version,
+        mod_loader: new_instance.mod_loader,
+        mod_loader_version: new_instance.mod_loader_version,
+        storage_path: new_instance.storage_path,
+        creation_status: new_instance.creation_status,
+        needs_restart: false,
+        ..instance
+    };
+    manager.update_instance(&name, carried).map_err(|e| e.to_string())?;
+    if let Some(content) = &old_properties {
+        std::fs::write(&properties_path, content)
+            .map_err(|e| format!("Server '{}' was recreated but its server.properties could not be restored: {}", name, e))?;
+    }
+    
+    if let Some(level_name) = kept_level {
+        services::world_info::restore_world(&stash_path, &server_path, &level_name)
+            .map_err(|e| format!("Server '{}' was recreated but restoring the world from {:?} failed: {}", name, stash_path, e))?;
+    }
+    
+    for warning in &warnings {
+        log::warn!("Recreate warning for {}: {}", name, warning);
+    }
+    Ok(warnings)
+}
+
 #[tauri::command]
 fn update_server_description(name: String, description: String) -> Result<String, String> {
     let config_path = PathBuf::from("storage/server_config.json");
@@ -152,127 +554,310 @@ fn update_server_description(name: String, description: String) -> Result<String
 }
 
 #[tauri::command]
-fn update_server_memory(name: String, memory_mb: u32) -> Result<String, String> {
+fn update_server_auto_start(name: String, auto_start: bool) -> Result<String, String> {
     let config_path = PathBuf::from("storage/server_config.json");
     let manager = ServerFileManager::new(config_path);
     
-    // Get the current instance
     let mut instance = manager.get_instance(&name)
         .map_err(|e| e.to_string())?
         .ok_or_else(|| format!("Server instance '{}' not found", name))?;
     
-    // Update memory
-    instance.memory_mb = memory_mb;
+    instance.auto_start = auto_start;
+    manager.update_instance(&name, instance).map_err(|e| e.to_string())?;
     
-    // For Forge servers, also update user_jvm_args.txt
-    if instance.mod_loader == "forge" {
-        update_forge_jvm_args(&instance.storage_path, memory_mb)
-            .map_err(|e| e.to_string())?;
-    }
+    Ok(format!(
+        "Server '{}' will {}start automatically",
+        name,
+        if auto_start { "" } else { "no longer " }
+    ))
+}
+
+/// Detached servers survive Allay closing but can only be stopped through RCON or by PID;
+/// the change applies from the next start
+#[tauri::command]
+fn update_server_detached_mode(name: String, detached_mode: bool) -> Result<String, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
     
-    // Save the updated instance
+    let mut instance = manager.get_instance(&name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", name))?;
+    
+    instance.detached_mode = detached_mode;
     manager.update_instance(&name, instance).map_err(|e| e.to_string())?;
     
-    Ok(format!("Server '{}' memory updated to {}MB successfully", name, memory_mb))
+    Ok(format!(
+        "Server '{}' will {}keep running when Allay closes (applies from the next start)",
+        name,
+        if detached_mode { "" } else { "no longer " }
+    ))
 }
 
-fn update_forge_jvm_args(server_path: &PathBuf, memory_mb: u32) -> Result<(), std::io::Error> {
-    let jvm_args_path = server_path.join("user_jvm_args.txt");
+/// Turns the "nogui" launch flag on or off. Forge/NeoForge servers started through
+/// run.sh/run.bat don't take it from Allay, which the returned message points out.
+#[tauri::command]
+fn update_server_nogui(name: String, nogui: bool) -> Result<String, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
     
-    // Convert MB to GB for JVM args
-    let memory_gb = memory_mb / 1024;
-    let memory_arg = format!("-Xmx{}G", memory_gb);
+    let mut instance = manager.get_instance(&name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", name))?;
     
-    if jvm_args_path.exists() {
-        // Read existing content
-        let content = std::fs::read_to_string(&jvm_args_path)?;
-        let mut lines: Vec<String> = content.lines().map(|s| s.to_string()).collect();
-        
-        // Find and replace existing -Xmx argument or add to the end
-        let mut found = false;
-        for line in &mut lines {
-            if line.trim().starts_with("-Xmx") {
-                *line = memory_arg.clone();
-                found = true;
-                break;
-            }
-        }
-        
-        if !found {
-            lines.push(memory_arg);
-        }
-        
-        // Write back to the file
-        let new_content = lines.join("\n");
-        std::fs::write(&jvm_args_path, new_content)?;
-    } else {
-        // Create a new file with a memory argument
-        std::fs::write(&jvm_args_path, memory_arg)?;
+    instance.nogui = nogui;
+    let loader_type = parse_loader_type(&instance.mod_loader)?;
+    manager.update_instance(&name, instance).map_err(|e| e.to_string())?;
+    
+    let storage_path = get_storage_path(&name);
+    let uses_run_script = matches!(loader_type, LoaderType::Forge | LoaderType::NeoForge)
+        && (storage_path.join("run.sh").exists() || storage_path.join("run.bat").exists());
+    if uses_run_script {
+        return Ok(format!(
+            "Saved, but server '{}' starts through a Forge/NeoForge run script; GUI mode only applies to servers launched directly from their JAR",
+            name
+        ));
     }
     
-    Ok(())
+    Ok(format!(
+        "Server '{}' will start {} the server GUI (applies from the next start)",
+        name,
+        if nogui { "without" } else { "with" }
+    ))
 }
 
-// Version management commands
 #[tauri::command]
-async fn get_minecraft_versions(loader: String, force_refresh: bool, minecraft_version: Option<String>) -> Result<VersionResponse, String> {
-    let manager = create_version_manager()?;
-    let loader_type = parse_loader_type(&loader)?;
+fn update_server_auto_update_paper(name: String, auto_update: bool) -> Result<String, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
     
-    manager.get_versions_for_minecraft(loader_type, force_refresh, minecraft_version).await.map_err(|e| e.to_string())
+    let mut instance = manager.get_instance(&name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", name))?;
+    
+    if parse_loader_type(&instance.mod_loader)? != LoaderType::Paper {
+        return Err(format!("Server '{}' does not run Paper", name));
+    }
+    
+    instance.auto_update_paper = auto_update;
+    manager.update_instance(&name, instance).map_err(|e| e.to_string())?;
+    
+    Ok(format!(
+        "Server '{}' will {}update to the newest Paper build before starting",
+        name,
+        if auto_update { "" } else { "no longer " }
+    ))
 }
 
+/// Replaces the server's environment variables; they apply from the next start
 #[tauri::command]
-async fn get_all_minecraft_versions(force_refresh: bool) -> Result<HashMap<String, VersionResponse>, String> {
-    let manager = create_version_manager()?;
-    manager.get_all_versions(force_refresh).await.map_err(|e| e.to_string())
+async fn update_server_env_vars(name: String, env_vars: HashMap<String, String>) -> Result<String, String> {
+    if let Some(key) = env_vars.keys().find(|key| !util::is_valid_env_var_name(key)) {
+        return Err(format!("'{}' is not a valid environment variable name", key));
+    }
+    if let Some(key) = env_vars.iter().find(|(_, value)| value.contains('\0')).map(|(key, _)| key) {
+        return Err(format!("The value of {} contains a NUL character", key));
+    }
+    
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    let mut instance = manager.get_instance(&name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", name))?;
+    
+    let count = env_vars.len();
+    instance.env_vars = env_vars;
+    manager.update_instance(&name, instance).map_err(|e| e.to_string())?;
+    flag_restart_if_running(&name).await;
+    
+    Ok(format!("Saved {} environment variable(s) for server '{}'", count, name))
 }
 
 #[tauri::command]
-async fn get_version_summary() -> Result<VersionSummary, String> {
-    let manager = create_version_manager()?;
-    manager.get_version_summary().await.map_err(|e| e.to_string())
+fn update_server_stop_countdown(name: String, seconds: Option<u32>) -> Result<String, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    let mut instance = manager.get_instance(&name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", name))?;
+    
+    instance.stop_countdown_secs = seconds.filter(|s| *s > 0);
+    let message = match instance.stop_countdown_secs {
+        Some(s) => format!("Players on '{}' will be warned {} seconds before it stops", name, s),
+        None => format!("Server '{}' will stop without a countdown", name),
+    };
+    manager.update_instance(&name, instance).map_err(|e| e.to_string())?;
+    
+    Ok(message)
 }
 
 #[tauri::command]
-async fn refresh_version_cache(loader: Option<String>) -> Result<HashMap<String, bool>, String> {
-    let manager = create_version_manager()?;
+fn update_server_idle_shutdown(name: String, minutes: Option<u32>) -> Result<String, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
     
-    let loader_type = if let Some(loader) = loader {
-        Some(parse_loader_type(&loader)?)
-    } else {
-        None
+    let mut instance = manager.get_instance(&name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", name))?;
+    
+    // Zero is treated the same as disabling idle shutdown
+    instance.idle_shutdown_minutes = minutes.filter(|m| *m > 0);
+    let message = match instance.idle_shutdown_minutes {
+        Some(m) => format!("Server '{}' will stop after {} minutes without players", name, m),
+        None => format!("Idle shutdown disabled for server '{}'", name),
     };
+    manager.update_instance(&name, instance).map_err(|e| e.to_string())?;
     
-    manager.refresh_cache(loader_type).await.map_err(|e| e.to_string())
+    Ok(message)
 }
 
 #[tauri::command]
-fn clear_version_cache(loader: Option<String>) -> Result<String, String> {
-    let manager = create_version_manager()?;
+async fn update_server_wake_on_demand(name: String, enabled: bool) -> Result<String, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
     
-    if let Some(loader) = loader {
-        let loader_type = parse_loader_type(&loader)?;
-        manager.clear_cache(&loader_type).map_err(|e| e.to_string())?;
-        Ok(format!("Cache cleared for {}", loader))
-    } else {
-        manager.clear_all_cache().map_err(|e| e.to_string())?;
-        Ok("All version cache cleared".to_string())
+    let mut instance = manager.get_instance(&name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", name))?;
+    
+    instance.wake_on_demand = enabled;
+    manager.update_instance(&name, instance).map_err(|e| e.to_string())?;
+    
+    // Release the port right away instead of waiting for the next connection
+    if !enabled {
+        UNIFIED_SERVER_SERVICE.lock().await.disarm_wake_listener(&name).await;
     }
+    
+    Ok(format!(
+        "Wake-on-demand {} for server '{}'",
+        if enabled { "enabled" } else { "disabled" },
+        name
+    ))
 }
 
+/// Stores the server's memory and returns the heap it will really get at launch, which
+/// differs from the setting when it is below the minimum heap
 #[tauri::command]
-async fn download_server_jar(
-    server_name: String,
-    loader: String,
-    minecraft_version: String,
+async fn update_server_memory(name: String, memory_mb: u32) -> Result<EffectiveMemory, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    // Get the current instance
+    let mut instance = manager.get_instance(&name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", name))?;
+    
+    // Update memory
+    instance.memory_mb = memory_mb;
+    
+    // For Forge servers, also update user_jvm_args.txt
+    if instance.mod_loader == "forge" {
+        update_forge_jvm_args(&instance.storage_path, memory_mb)
+            .map_err(|e| e.to_string())?;
+    }
+    
+    // Save the updated instance
+    manager.update_instance(&name, instance).map_err(|e| e.to_string())?;
+    flag_restart_if_running(&name).await;
+    
+    let effective = services::mod_loader_strategy::effective_memory(memory_mb);
+    if let Some(warning) = &effective.warning {
+        log::warn!("Server '{}': {}", name, warning);
+    }
+    Ok(effective)
+}
+
+fn update_forge_jvm_args(server_path: &PathBuf, memory_mb: u32) -> Result<(), std::io::Error> {
+    // Same sizing as start_server, which rewrites this file before every launch
+    let (max_memory_mb, min_memory_mb) = services::mod_loader_strategy::heap_sizes_mb(memory_mb);
+    
+    services::mod_loader_strategy::write_user_jvm_args(
+        server_path,
+        &format!("-Xmx{}M", max_memory_mb),
+        &format!("-Xms{}M", min_memory_mb),
+    )
+}
+
+// Version management commands
+#[tauri::command]
+async fn get_minecraft_versions(loader: String, force_refresh: bool, minecraft_version: Option<String>) -> Result<VersionResponse, String> {
+    let manager = create_version_manager()?;
+    let loader_type = parse_loader_type(&loader)?;
+    
+    manager.get_versions_for_minecraft(loader_type, force_refresh, minecraft_version).await.map_err(|e| e.to_string())
+}
+
+/// Whether a loader has builds for a Minecraft version, with the reason if not, so the UI can
+/// reject a combination before creation starts
+#[tauri::command]
+async fn is_combination_supported(loader: String, minecraft_version: String) -> Result<CombinationSupport, String> {
+    let loader_type = parse_loader_type(&loader)?;
+    let version_manager = create_version_manager()?;
+    version_manager.is_combination_supported(loader_type, &minecraft_version).await.map_err(|e| e.to_string())
+}
+
+/// Filters a loader's cached versions by a substring such as "1.20", newest first
+#[tauri::command]
+async fn search_versions(loader: String, query: String) -> Result<Vec<MinecraftVersion>, String> {
+    let loader_type = parse_loader_type(&loader)?;
+    let version_manager = create_version_manager()?;
+    version_manager.search_versions(loader_type, &query).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_all_minecraft_versions(force_refresh: bool) -> Result<AllVersionsResponse, String> {
+    let manager = create_version_manager()?;
+    manager.get_all_versions(force_refresh).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_version_summary() -> Result<VersionSummary, String> {
+    let manager = create_version_manager()?;
+    manager.get_version_summary().await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn refresh_version_cache(loader: Option<String>) -> Result<HashMap<String, bool>, String> {
+    let manager = create_version_manager()?;
+    
+    let loader_type = if let Some(loader) = loader {
+        Some(parse_loader_type(&loader)?)
+    } else {
+        None
+    };
+    
+    manager.refresh_cache(loader_type).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn clear_version_cache(loader: Option<String>) -> Result<String, String> {
+    let manager = create_version_manager()?;
+    
+    if let Some(loader) = loader {
+        let loader_type = parse_loader_type(&loader)?;
+        manager.clear_cache(&loader_type).map_err(|e| e.to_string())?;
+        Ok(format!("Cache cleared for {}", loader))
+    } else {
+        manager.clear_all_cache().map_err(|e| e.to_string())?;
+        Ok("All version cache cleared".to_string())
+    }
+}
+
+#[tauri::command]
+async fn download_server_jar(
+    server_name: String,
+    loader: String,
+    minecraft_version: String,
     loader_version: Option<String>,
 ) -> Result<String, String> {
-    let service = UNIFIED_SERVER_SERVICE.lock().await;
     let storage_path = get_storage_path(&server_name);
     let loader_type = parse_loader_type(&loader)?;
+    // BuildTools compiles Spigot while "downloading", so this needs the server's Java too
+    let java_command = resolve_java_command(&minecraft_version).await?;
+    let installer = UNIFIED_SERVER_SERVICE.lock().await.installer().with_java_command(&java_command);
     
-    match service.download_server_jar(
+    match installer.download_server_jar(
         loader_type,
         minecraft_version,
         loader_version,
@@ -292,9 +877,10 @@ async fn setup_server(
 ) -> Result<String, String> {
     let storage_path = get_storage_path(&server_name);
     let loader_type = parse_loader_type(&loader)?;
-    let service = UNIFIED_SERVER_SERVICE.lock().await;
+    let java_command = resolve_java_command(&minecraft_version).await?;
+    let installer = UNIFIED_SERVER_SERVICE.lock().await.installer().with_java_command(&java_command);
     
-    match service.setup_server(
+    match installer.setup_server(
         &server_name,
         loader_type,
         &minecraft_version,
@@ -306,18 +892,100 @@ async fn setup_server(
     }
 }
 
+/// Stops a hung download or loader install; the create that started it rolls back as on
+/// any other failure.
+#[tauri::command]
+fn cancel_setup(server_name: String) -> Result<String, String> {
+    if services::unified_server_service::cancel_setup(&server_name) {
+        Ok(format!("Cancelling setup of '{}'", server_name))
+    } else {
+        Err(format!("No setup is running for '{}'", server_name))
+    }
+}
+
+/// The Java a server on `minecraft_version` runs on: the bundled runtime for that version when
+/// enabled in the app settings (downloaded on first use), otherwise `java` from PATH. Loader
+/// installers, BuildTools and first-run setups use it too, so they see the same Java.
+async fn resolve_java_command(minecraft_version: &str) -> Result<String, String> {
+    let settings = create_app_settings_manager().load_settings().unwrap_or_default();
+    if !settings.use_bundled_java {
+        return Ok("java".to_string());
+    }
+    let major_version = required_java_version(minecraft_version);
+    let java = get_or_install_jre(&reqwest::Client::new(), major_version).await
+        .map_err(|e| format!("Failed to install Java {}: {}", major_version, e))?;
+    Ok(java.to_string_lossy().to_string())
+}
+
+/// Like resolve_java_command, but never downloads: None while the bundled runtime is enabled
+/// but not installed yet
+fn installed_java_command(minecraft_version: &str) -> Option<String> {
+    let settings = create_app_settings_manager().load_settings().unwrap_or_default();
+    if !settings.use_bundled_java {
+        return Some("java".to_string());
+    }
+    bundled_java_path(required_java_version(minecraft_version))
+        .map(|path| path.to_string_lossy().to_string())
+}
+
+/// How a server is launched, on the Java from resolve_java_command
+async fn load_launch_options(server_name: &str) -> Result<LaunchOptions, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let file_manager = ServerFileManager::new(config_path);
+    
+    let java_command = match file_manager.get_instance(server_name) {
+        Ok(Some(instance)) => resolve_java_command(&instance.version).await?,
+        _ => "java".to_string(),
+    };
+    
+    Ok(LaunchOptions {
+        memory_mb: file_manager.get_server_memory(server_name).unwrap_or(2048), // Default 2GB if not found
+        detached: file_manager.is_detached_mode(server_name),
+        env_vars: file_manager.get_env_vars(server_name),
+        java_command,
+        nogui: file_manager.is_nogui(server_name),
+    })
+}
+
+/// Installs (if needed) the bundled Java runtime for `major_version` and returns its java path
+#[tauri::command]
+async fn install_java_runtime(major_version: u32) -> Result<String, String> {
+    let java = get_or_install_jre(&reqwest::Client::new(), major_version).await
+        .map_err(|e| format!("Failed to install Java {}: {}", major_version, e))?;
+    Ok(java.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+fn set_use_bundled_java(enabled: bool) -> Result<(), String> {
+    let manager = create_app_settings_manager();
+    let mut settings = manager.load_settings().map_err(|e| e.to_string())?;
+    settings.use_bundled_java = enabled;
+    manager.save_settings(&settings).map_err(|e| e.to_string())
+}
+
+/// Stamps the start time and clears needs_restart; a failure is only logged since the server is up
+fn record_server_started(file_manager: &ServerFileManager, server_name: &str) {
+    if let Err(e) = file_manager.record_server_started(server_name) {
+        log::warn!("Failed to record the start time of {}: {}", server_name, e);
+    }
+}
+
 #[tauri::command]
 async fn start_server(server_name: String, loader: String) -> Result<String, String> {
     let storage_path = get_storage_path(&server_name);
     let loader_type = parse_loader_type(&loader)?;
     
-    // Get server memory configuration
     let config_path = PathBuf::from("storage/server_config.json");
     let file_manager = ServerFileManager::new(config_path);
-    let memory_mb = match file_manager.get_instance(&server_name) {
-        Ok(Some(instance)) => instance.memory_mb,
-        _ => 2048, // Default 2GB if not found
-    };
+    let launch_options = load_launch_options(&server_name).await?;
+    
+    let auto_update = matches!(file_manager.get_instance(&server_name), Ok(Some(instance)) if instance.auto_update_paper);
+    if auto_update && loader_type == LoaderType::Paper {
+        // A failed update check must not keep the server from starting
+        if let Err(e) = apply_update(server_name.clone()).await {
+            log::warn!("Automatic Paper update for {} failed: {}", server_name, e);
+        }
+    }
     
     // Start RCON monitoring for this server
     {
@@ -327,11 +995,12 @@ async fn start_server(server_name: String, loader: String) -> Result<String, Str
     
     let service = UNIFIED_SERVER_SERVICE.lock().await;
     
-    match service.start_server(&server_name, &storage_path, loader_type, memory_mb).await {
+    match service.start_server(&server_name, &storage_path, loader_type, &launch_options).await {
         Ok(_) => {
             // Server process started successfully
             // Monitoring will detect when it's actually responding and update to online
-            println!("Server '{}' process started, monitoring will detect when fully online", server_name);
+            log::info!("Server '{}' process started, monitoring will detect when fully online", server_name);
+            record_server_started(&file_manager, &server_name);
             Ok(format!("Server '{}' started successfully", server_name))
         },
         Err(e) => {
@@ -344,7 +1013,65 @@ async fn start_server(server_name: String, loader: String) -> Result<String, Str
 }
 
 #[tauri::command]
-async fn stop_server(server_name: String) -> Result<String, String> {
+async fn get_launch_command(server_name: String, loader: String) -> Result<LaunchCommand, String> {
+    let storage_path = get_storage_path(&server_name);
+    let loader_type = parse_loader_type(&loader)?;
+    
+    let config_path = PathBuf::from("storage/server_config.json");
+    let file_manager = ServerFileManager::new(config_path);
+    let memory_mb = match file_manager.get_instance(&server_name) {
+        Ok(Some(instance)) => instance.memory_mb,
+        _ => 2048,
+    };
+    
+    // Show the bundled runtime only once it's installed; starting is what installs it
+    let java_command = match file_manager.get_instance(&server_name) {
+        Ok(Some(instance)) => installed_java_command(&instance.version).unwrap_or_else(|| "java".to_string()),
+        _ => "java".to_string(),
+    };
+    
+    UNIFIED_SERVER_SERVICE.lock().await
+        .resolve_launch_command(&storage_path, loader_type, memory_mb, &java_command, file_manager.is_nogui(&server_name))
+        .map_err(|e| e.to_string())
+}
+
+/// Announces an upcoming stop or restart in chat, sleeping between marks. Without a working
+/// RCON connection the countdown is skipped and the caller stops right away.
+async fn broadcast_shutdown_countdown(server_name: &str, seconds: u32, action: &str) {
+    let rcon_manager = services::rcon_global::get_rcon_manager();
+    let marks = services::rcon_commands::countdown_marks(seconds);
+    
+    for (index, remaining) in marks.iter().enumerate() {
+        let message = services::rcon_commands::countdown_message(action, *remaining);
+        if let Err(e) = rcon_manager.execute_command(server_name, &message) {
+            if index == 0 {
+                log::info!("RCON unavailable for {}, skipping shutdown countdown: {}", server_name, e);
+                return;
+            }
+            // Players already saw the earlier warnings, so keep to the announced time
+            log::warn!("Failed to broadcast countdown to {}: {}", server_name, e);
+        }
+        
+        let next = marks.get(index + 1).copied().unwrap_or(0);
+        tokio::time::sleep(std::time::Duration::from_secs((remaining - next) as u64)).await;
+    }
+}
+
+/// Runs the countdown configured for the server (or `countdown_secs` if given) when it is running
+async fn countdown_before_stop(server_name: &str, countdown_secs: Option<u32>, action: &str) {
+    let seconds = countdown_secs.unwrap_or_else(|| {
+        ServerFileManager::new(PathBuf::from("storage/server_config.json")).get_stop_countdown(server_name)
+    });
+    if seconds > 0 && UNIFIED_SERVER_SERVICE.lock().await.is_server_running(server_name).await {
+        broadcast_shutdown_countdown(server_name, seconds, action).await;
+    }
+}
+
+/// `countdown_secs` overrides the server's configured countdown; pass 0 to stop immediately
+#[tauri::command]
+async fn stop_server(server_name: String, countdown_secs: Option<u32>) -> Result<String, String> {
+    countdown_before_stop(&server_name, countdown_secs, "stopping").await;
+    
     let service = UNIFIED_SERVER_SERVICE.lock().await;
     
     match service.stop_server(&server_name).await {
@@ -364,360 +1091,1687 @@ async fn stop_server(server_name: String) -> Result<String, String> {
 }
 
 #[tauri::command]
-async fn toggle_server(server_name: String, loader: String) -> Result<String, String> {
+async fn restart_server(server_name: String, loader: String, countdown_secs: Option<u32>) -> Result<String, String> {
+    // Count down before taking the service lock so other servers aren't blocked meanwhile
+    countdown_before_stop(&server_name, countdown_secs, "restarting").await;
+    
+    let storage_path = get_storage_path(&server_name);
+    let loader_type = parse_loader_type(&loader)?;
+    
+    let launch_options = load_launch_options(&server_name).await?;
+    
+    // Hold the service for the whole sequence so no other start/stop can interleave
     let service = UNIFIED_SERVER_SERVICE.lock().await;
-    let is_running = service.is_server_running(&server_name).await;
     
-    if is_running {
-        // Stop the server
-        match service.stop_server(&server_name).await {
-            Ok(_) => Ok(format!("Server '{}' stopped successfully", server_name)),
-            Err(e) => Err(format!("Failed to stop server '{}': {}", server_name, e)),
+    if service.is_server_running(&server_name).await {
+        {
+            let monitor = SERVER_MONITOR.lock().await;
+            monitor.update_server_status(&server_name, ServerStatus::Stopping).await;
         }
-    } else {
-        // Start the server
-        let storage_path = get_storage_path(&server_name);
-        let loader_type = parse_loader_type(&loader)?;
         
-        // Get memory allocation from the server file manager
-        let config_path = PathBuf::from("storage/server_config.json");
-        let file_manager = ServerFileManager::new(config_path);
-        let memory_mb = file_manager.get_server_memory(&server_name)
-            .unwrap_or(2048); // Default to 2GB if not found
+        service.stop_server_and_wait(&server_name, std::time::Duration::from_secs(60)).await
+            .map_err(|e| format!("Failed to stop server '{}': {}", server_name, e))?;
         
-        match service.start_server(&server_name, &storage_path, loader_type, memory_mb).await {
-            Ok(_) => Ok(format!("Server '{}' started successfully", server_name)),
-            Err(e) => Err(format!("Failed to start server '{}': {}", server_name, e)),
+        {
+            let rcon_manager = RCON_MANAGER.lock().await;
+            rcon_manager.handle_server_offline(&server_name);
+        }
+        services::rcon_global::get_rcon_manager().handle_server_offline(&server_name);
+        
+        let monitor = SERVER_MONITOR.lock().await;
+        monitor.update_server_status(&server_name, ServerStatus::Offline).await;
+    }
+    
+    // Re-register monitoring; it switches to Online once RCON responds
+    {
+        let monitor = SERVER_MONITOR.lock().await;
+        monitor.start_monitoring(server_name.clone()).await;
+        monitor.update_server_status(&server_name, ServerStatus::Starting).await;
+    }
+    
+    match service.start_server(&server_name, &storage_path, loader_type, &launch_options).await {
+        Ok(_) => {
+            record_server_started(&ServerFileManager::new(PathBuf::from("storage/server_config.json")), &server_name);
+            Ok(format!("Server '{}' restarted successfully", server_name))
+        },
+        Err(e) => {
+            let monitor = SERVER_MONITOR.lock().await;
+            monitor.update_server_status(&server_name, ServerStatus::Offline).await;
+            monitor.stop_monitoring(&server_name).await;
+            Err(format!("Failed to start server '{}': {}", server_name, e))
         }
     }
 }
 
+/// Reports whether Java can be run (from PATH unless `java_path` is given) and which version
+/// and vendor it is, so missing or outdated Java can be flagged before creating a server
 #[tauri::command]
-fn get_server_loader_type(server_name: String) -> Result<String, String> {
+async fn check_java(java_path: Option<String>) -> JavaInstallation {
+    let java_command = java_path.unwrap_or_else(|| "java".to_string());
+    tokio::task::spawn_blocking(move || services::java_runtime::check_java(&java_command))
+        .await
+        .unwrap_or_else(|e| {
+            log::warn!("Java check failed: {}", e);
+            JavaInstallation {
+                found: false,
+                version: None,
+                full_version: None,
+                vendor: None,
+                path: String::new(),
+            }
+        })
+}
+
+#[tauri::command]
+async fn diagnose_server(server_name: String) -> Result<DiagnosticReport, String> {
     let config_path = PathBuf::from("storage/server_config.json");
     let manager = ServerFileManager::new(config_path);
     
-    match manager.get_instance(&server_name) {
-        Ok(Some(server)) => Ok(server.mod_loader.clone()),
-        Ok(None) => Err(format!("Server '{}' not found", server_name)),
-        Err(e) => Err(format!("Failed to get server info: {}", e)),
-    }
+    let instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
+    
+    let loader_type = parse_loader_type(&instance.mod_loader)?;
+    let storage_path = get_storage_path(&server_name);
+    let is_running = UNIFIED_SERVER_SERVICE.lock().await.is_server_running(&server_name).await;
+    let java_command = installed_java_command(&instance.version);
+    
+    let target = DiagnosticTarget {
+        server_name: &server_name,
+        server_path: &storage_path,
+        loader: loader_type,
+        minecraft_version: &instance.version,
+        java_command: java_command.as_deref(),
+        memory_mb: instance.memory_mb,
+        is_running,
+    };
+    
+    Ok(services::diagnostics::diagnose_server(&target))
 }
 
 #[tauri::command]
-async fn is_server_running(server_name: String) -> bool {
-    let monitor = SERVER_MONITOR.lock().await;
-    let status = monitor.get_server_status(&server_name).await;
+async fn kill_server(server_name: String) -> Result<String, String> {
+    let service = UNIFIED_SERVER_SERVICE.lock().await;
     
-    match status {
-        ServerStatus::Online => true,
-        _ => false,
+    service.kill_server(&server_name).await
+        .map_err(|e| format!("Failed to kill server '{}': {}", server_name, e))?;
+    
+    // Stop monitoring and make sure no RCON connection outlives the process
+    {
+        let monitor = SERVER_MONITOR.lock().await;
+        monitor.stop_monitoring(&server_name).await;
+    }
+    {
+        let rcon_manager = RCON_MANAGER.lock().await;
+        rcon_manager.handle_server_offline(&server_name);
     }
+    services::rcon_global::get_rcon_manager().handle_server_offline(&server_name);
+    
+    Ok(format!("Server '{}' killed", server_name))
 }
 
-fn get_server_port(server_name: &str) -> Option<u16> {
-    // Try to read port from server.properties
-    let storage_path = std::path::PathBuf::from("storage").join(server_name);
-    let properties_path = storage_path.join("server.properties");
-    
-    if properties_path.exists() {
-        let properties_manager = ServerPropertiesManager::new(properties_path);
-        
-        if let Ok(port_str) = properties_manager.get_property("server-port") {
-            if let Ok(port) = port_str.parse::<u16>() {
-                return Some(port);
-            }
+/// Called after changing a setting that only applies on restart. Flags a running server as
+/// needing one and returns whether it was running.
+async fn flag_restart_if_running(server_name: &str) -> bool {
+    let is_running = UNIFIED_SERVER_SERVICE.lock().await.is_server_running(server_name).await;
+    if is_running {
+        let manager = ServerFileManager::new(PathBuf::from("storage/server_config.json"));
+        if let Err(e) = manager.mark_needs_restart(server_name) {
+            log::warn!("Failed to flag {} as needing a restart: {}", server_name, e);
         }
     }
+    is_running
+}
+
+/// Updates one of a server's ports and reports whether a restart is needed to apply it
+async fn set_managed_port(server_name: &str, key: PortKey, port: u16) -> Result<bool, String> {
+    let changed = services::port_manager::set_port(server_name, key, port, &PathBuf::from("storage"))
+        .map_err(|e| e.to_string())?;
     
-    None // Return None to use the default port
+    Ok(changed && flag_restart_if_running(server_name).await)
 }
 
+/// Every server's game, RCON and Query ports, whether it is running, and ports shared by two servers
 #[tauri::command]
-async fn query_server_status(server_name: String) -> Result<QueryResponse, String> {
-    let port = get_server_port(&server_name).unwrap_or(25565);
-    
-    let config = QueryConfig {
-        host: "127.0.0.1".to_string(),
-        port,
-        timeout_ms: 5000, // 5 second timeout for detailed query
-    };
-    
-    let query_service = QueryService::new(config);
-    let response = query_service.query_server().await;
-    
-    Ok(response)
+async fn get_port_map() -> Result<PortMap, String> {
+    let running: HashSet<String> = UNIFIED_SERVER_SERVICE.lock().await.get_running_servers().await.into_iter().collect();
+    services::port_manager::get_port_map(Path::new("storage"), &running).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-async fn start_server_monitoring(server_name: String) -> Result<String, String> {
-    let monitor = SERVER_MONITOR.lock().await;
-    monitor.start_monitoring(server_name.clone()).await;
-    Ok(format!("Started RCON monitoring for server: {}", server_name))
+async fn set_server_port(server_name: String, port: u16) -> Result<bool, String> {
+    set_managed_port(&server_name, PortKey::Server, port).await
 }
 
 #[tauri::command]
-async fn stop_server_monitoring(server_name: String) -> Result<String, String> {
-    let monitor = SERVER_MONITOR.lock().await;
-    monitor.stop_monitoring(&server_name).await;
-    Ok(format!("Stopped RCON monitoring for server: {}", server_name))
+async fn set_rcon_port(server_name: String, port: u16) -> Result<bool, String> {
+    set_managed_port(&server_name, PortKey::Rcon, port).await
 }
 
 #[tauri::command]
-async fn get_server_monitor_status(server_name: String) -> String {
-    let monitor = SERVER_MONITOR.lock().await;
-    let status = monitor.get_server_status(&server_name).await;
-    
-    match status {
-        ServerStatus::Offline => "offline".to_string(),
-        ServerStatus::Online => "online".to_string(),
+async fn set_query_port(server_name: String, port: u16) -> Result<bool, String> {
+    set_managed_port(&server_name, PortKey::Query, port).await
+}
+
+/// Binds a server to one network interface by setting server-ip; an empty address listens on
+/// all of them. Returns whether a restart is needed to apply it.
+#[tauri::command]
+async fn set_server_bind_ip(server_name: String, ip: String) -> Result<bool, String> {
+    let ip = ip.trim();
+    if !ip.is_empty() && ip.parse::<std::net::IpAddr>().is_err() {
+        return Err(format!("'{}' is not a valid IP address", ip));
+    }
+    
+    let properties_path = get_storage_path(&server_name).join("server.properties");
+    if !properties_path.exists() {
+        return Err("Server properties file not found".to_string());
     }
+    let properties_manager = ServerPropertiesManager::new(properties_path);
+    let current = properties_manager.get_property("server-ip").unwrap_or_default();
+    if current.trim() == ip {
+        return Ok(false);
+    }
+    properties_manager.update_property("server-ip", ip).map_err(|e| e.to_string())?;
+    
+    Ok(flag_restart_if_running(&server_name).await)
 }
 
 #[tauri::command]
-async fn get_all_server_monitor_statuses() -> HashMap<String, String> {
-    let monitor = SERVER_MONITOR.lock().await;
-    let all_statuses = monitor.get_all_statuses().await;
+async fn set_level_name(server_name: String, new_name: String) -> Result<String, String> {
+    if UNIFIED_SERVER_SERVICE.lock().await.is_server_running(&server_name).await {
+        return Err(format!("Stop server '{}' before renaming its world", server_name));
+    }
     
-    all_statuses.into_iter()
-        .map(|(name, status)| {
-            let status_str = match status {
-                ServerStatus::Offline => "offline".to_string(),
-                ServerStatus::Online => "online".to_string(),
-            };
-            (name, status_str)
-        })
-        .collect()
+    services::world_info::rename_world(&get_storage_path(&server_name), &new_name)
+        .map_err(|e| e.to_string())?;
+    Ok(format!("World of '{}' renamed to {}", server_name, new_name.trim()))
+}
+
+/// Reads the game port a server will bind, falling back to the Minecraft default
+fn read_server_port(server_name: &str) -> u16 {
+    let properties_path = get_storage_path(server_name).join("server.properties");
+    ServerPropertiesManager::new(properties_path)
+        .load_properties()
+        .map(|properties| properties.server_port)
+        .unwrap_or(25565)
 }
 
 #[tauri::command]
-async fn update_server_monitor_status(server_name: String, status: String) -> Result<String, String> {
-    let monitor_status = match status.as_str() {
-        "offline" => ServerStatus::Offline,
-        "online" => ServerStatus::Online,
-        _ => return Err("Invalid status (only 'offline' and 'online' supported)".to_string()),
-    };
+async fn start_all_servers(stagger_ms: Option<u64>) -> Result<Vec<BulkOperationResult>, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
     
-    let monitor = SERVER_MONITOR.lock().await;
-    monitor.update_server_status(&server_name, monitor_status).await;
-    Ok(format!("Updated server {} status to: {}", server_name, status))
+    let instances: Vec<ServerInstance> = manager.get_all_instances()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|instance| instance.creation_status == ServerCreationStatus::Completed)
+        .collect();
+    
+    // Spread JVM startups out so they don't all spike the CPU at once
+    let stagger = std::time::Duration::from_millis(stagger_ms.unwrap_or(3000));
+    
+    Ok(start_servers_staggered(instances, stagger).await)
+}
+
+/// Starts each stopped instance in name order, skipping any whose game port is already taken
+async fn start_servers_staggered(
+    mut instances: Vec<ServerInstance>,
+    stagger: std::time::Duration,
+) -> Vec<BulkOperationResult> {
+    instances.sort_by(|a, b| a.name.cmp(&b.name));
+    
+    // Ports already claimed by running servers or servers started earlier in this batch
+    let mut claimed_ports: HashMap<u16, String> = HashMap::new();
+    let mut pending = Vec::new();
+    {
+        let service = UNIFIED_SERVER_SERVICE.lock().await;
+        for instance in instances {
+            if service.is_server_running(&instance.name).await {
+                claimed_ports.insert(read_server_port(&instance.name), instance.name.clone());
+            } else {
+                pending.push(instance);
+            }
+        }
+    }
+    
+    let mut results = Vec::new();
+    let mut started_any = false;
+    
+    for instance in pending {
+        let port = read_server_port(&instance.name);
+        
+        if let Some(owner) = claimed_ports.get(&port) {
+            results.push(BulkOperationResult::failed(
+                &instance.name,
+                format!("Port {} is already used by server '{}'", port, owner),
+            ));
+            continue;
+        }
+        if !services::diagnostics::is_port_available(port, false) {
+            results.push(BulkOperationResult::failed(
+                &instance.name,
+                format!("Port {} is already in use by another program", port),
+            ));
+            continue;
+        }
+        
+        if started_any && !stagger.is_zero() {
+            tokio::time::sleep(stagger).await;
+        }
+        
+        match start_server(instance.name.clone(), instance.mod_loader.clone()).await {
+            Ok(_) => {
+                claimed_ports.insert(port, instance.name.clone());
+                started_any = true;
+                results.push(BulkOperationResult::succeeded(&instance.name));
+            }
+            Err(e) => results.push(BulkOperationResult::failed(&instance.name, e)),
+        }
+    }
+    
+    results
 }
 
 #[tauri::command]
-async fn get_running_servers() -> Vec<String> {
+async fn stop_all_servers(timeout_secs: Option<u64>) -> Result<Vec<BulkOperationResult>, String> {
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(timeout_secs.unwrap_or(120));
+    
+    // Hold the service for the whole sequence so nothing is started while we stop
     let service = UNIFIED_SERVER_SERVICE.lock().await;
-    service.get_running_servers().await
+    let mut running = service.get_running_servers().await;
+    running.sort();
+    
+    let mut results = Vec::new();
+    
+    for server_name in running {
+        {
+            let monitor = SERVER_MONITOR.lock().await;
+            monitor.update_server_status(&server_name, ServerStatus::Stopping).await;
+        }
+        
+        // Servers late in the list get whatever is left of the total budget and are killed once it runs out
+        let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+        let outcome = service.stop_server_and_wait(&server_name, remaining).await;
+        
+        {
+            let rcon_manager = RCON_MANAGER.lock().await;
+            rcon_manager.handle_server_offline(&server_name);
+        }
+        services::rcon_global::get_rcon_manager().handle_server_offline(&server_name);
+        {
+            let monitor = SERVER_MONITOR.lock().await;
+            monitor.stop_monitoring(&server_name).await;
+        }
+        
+        match outcome {
+            Ok(_) => results.push(BulkOperationResult::succeeded(&server_name)),
+            Err(e) => results.push(BulkOperationResult::failed(&server_name, e.to_string())),
+        }
+    }
+    
+    Ok(results)
 }
 
-// JAR Cache management commands
 #[tauri::command]
-fn get_jar_cache_stats() -> Result<CacheStats, String> {
-    let cache_dir = PathBuf::from("storage/version_cache");
-    let jar_cache = JarCacheManager::new(cache_dir).map_err(|e| e.to_string())?;
-    jar_cache.get_cache_stats().map_err(|e| e.to_string())
+async fn toggle_server(server_name: String, loader: String) -> Result<String, String> {
+    let service = UNIFIED_SERVER_SERVICE.lock().await;
+    let is_running = service.is_server_running(&server_name).await;
+    
+    if is_running {
+        // Stop the server
+        match service.stop_server(&server_name).await {
+            Ok(_) => Ok(format!("Server '{}' stopped successfully", server_name)),
+            Err(e) => Err(format!("Failed to stop server '{}': {}", server_name, e)),
+        }
+    } else {
+        // Start the server
+        let storage_path = get_storage_path(&server_name);
+        let loader_type = parse_loader_type(&loader)?;
+        
+        let launch_options = load_launch_options(&server_name).await?;
+        
+        match service.start_server(&server_name, &storage_path, loader_type, &launch_options).await {
+            Ok(_) => {
+                record_server_started(&ServerFileManager::new(PathBuf::from("storage/server_config.json")), &server_name);
+                Ok(format!("Server '{}' started successfully", server_name))
+            },
+            Err(e) => Err(format!("Failed to start server '{}': {}", server_name, e)),
+        }
+    }
 }
 
 #[tauri::command]
-fn clear_jar_cache(
-    loader: Option<String>,
-    minecraft_version: Option<String>,
-    loader_version: Option<String>,
-) -> Result<String, String> {
-    let cache_dir = PathBuf::from("storage/version_cache");
-    let jar_cache = JarCacheManager::new(cache_dir).map_err(|e| e.to_string())?;
+async fn migrate_loader(
+    server_name: String,
+    new_loader: String,
+    new_loader_version: Option<String>,
+) -> Result<Vec<String>, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
     
-    if let (Some(loader_str), Some(mc_version)) = (loader, minecraft_version) {
-        let loader_type = parse_loader_type(&loader_str)?;
-        
-        jar_cache.clear_jar_cache(&loader_type, &mc_version, loader_version.as_deref())
+    let mut instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
+    
+    // Stored as given, so "Fabric" would otherwise become a loader nothing else recognises
+    let new_loader = new_loader.trim().to_lowercase();
+    let current_loader = parse_loader_type(&instance.mod_loader)?;
+    let new_loader_type = parse_loader_type(&new_loader)?;
+    if new_loader_type == current_loader {
+        return Err(format!("Server '{}' already runs {}", server_name, new_loader));
+    }
+    let storage_path = get_storage_path(&server_name);
+    let java_command = resolve_java_command(&instance.version).await?;
+    
+    // The download and installer run take minutes; only this server is held meanwhile
+    let _maintenance = reserve_stopped_server(&server_name, "changing its loader").await?;
+    let installer = UNIFIED_SERVER_SERVICE.lock().await.installer().with_java_command(&java_command);
+    let warnings = installer.migrate_loader(
+        &server_name,
+        &storage_path,
+        current_loader,
+        new_loader_type,
+        &instance.version,
+        new_loader_version.as_deref(),
+    ).await.map_err(|e| format!("Failed to migrate server '{}': {}", server_name, e))?;
+    
+    instance.mod_loader = new_loader.clone();
+    instance.mod_loader_version = new_loader_version.unwrap_or_else(|| "none".to_string());
+    
+    if instance.mod_loader == "forge" {
+        update_forge_jvm_args(&instance.storage_path, instance.memory_mb)
             .map_err(|e| e.to_string())?;
-        Ok(format!("Cleared JAR cache for {} {}", loader_str, mc_version))
-    } else {
-        jar_cache.clear_all_jar_cache().map_err(|e| e.to_string())?;
-        Ok("Cleared all JAR cache".to_string())
     }
+    
+    manager.update_instance(&server_name, instance).map_err(|e| e.to_string())?;
+    
+    Ok(warnings)
 }
 
+/// Removes the server's mod loader and switches it to the vanilla server of the same version.
+/// Returns warnings about content the world may lose.
 #[tauri::command]
-fn is_jar_cached(
-    loader: String,
-    minecraft_version: String,
-    loader_version: Option<String>,
-) -> Result<bool, String> {
-    let cache_dir = PathBuf::from("storage/version_cache");
-    let jar_cache = JarCacheManager::new(cache_dir).map_err(|e| e.to_string())?;
-    let loader_type = parse_loader_type(&loader)?;
+async fn revert_to_vanilla(server_name: String) -> Result<Vec<String>, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
     
-    Ok(jar_cache.is_jar_cached(&loader_type, &minecraft_version, loader_version.as_deref()))
+    let mut instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
+    
+    let current_loader = parse_loader_type(&instance.mod_loader)?;
+    let storage_path = get_storage_path(&server_name);
+    let java_command = resolve_java_command(&instance.version).await?;
+    
+    let _maintenance = reserve_stopped_server(&server_name, "changing its loader").await?;
+    let installer = UNIFIED_SERVER_SERVICE.lock().await.installer().with_java_command(&java_command);
+    let warnings = installer.revert_to_vanilla(
+        &server_name,
+        &storage_path,
+        current_loader,
+        &instance.version,
+    ).await.map_err(|e| format!("Failed to revert server '{}' to vanilla: {}", server_name, e))?;
+    
+    instance.mod_loader = "vanilla".to_string();
+    instance.mod_loader_version = "none".to_string();
+    instance.auto_update_paper = false;
+    manager.update_instance(&server_name, instance).map_err(|e| e.to_string())?;
+    
+    PAPER_UPDATES.lock().await.remove(&server_name);
+    Ok(warnings)
 }
 
-// Server properties management commands
+/// Backs up and deletes a stopped server's world folders, keeping its config, mods and loader,
+/// so the next start generates fresh terrain from the current seed
 #[tauri::command]
-fn get_server_motd(server_name: String) -> Result<String, String> {
-    let server_path = PathBuf::from("storage").join(&server_name);
-    let properties_path = server_path.join("server.properties");
+async fn clear_world(server_name: String) -> Result<ClearWorldResult, String> {
+    let storage_path = get_storage_path(&server_name);
+    if !storage_path.is_dir() {
+        return Err(format!("Server instance '{}' not found", server_name));
+    }
     
-    if !properties_path.exists() {
-        return Ok("A Minecraft Server".to_string()); // Default MOTD
+    // Archiving the world takes a while; keep the server from being started meanwhile
+    let _maintenance = reserve_stopped_server(&server_name, "clearing its world").await?;
+    
+    let backup = backup_server(&server_name, true, "before-clear-world")
+        .await
+        .map_err(|e| format!("Backup failed, the world was not cleared: {}", e))?;
+    
+    let removed_folders = services::world_info::clear_world(&storage_path).map_err(|e| e.to_string())?;
+    // Refresh the cached size so the dashboard doesn't keep showing the old world
+    services::world_info::world_size(&storage_path, true);
+    
+    Ok(ClearWorldResult { removed_folders, backup })
+}
+
+/// Backs up a server and then prunes its backups by its retention policy
+async fn backup_server(server_name: &str, include_world: bool, label: &'static str) -> Result<BackupInfo, String> {
+    let storage_path = get_storage_path(server_name);
+    if !storage_path.is_dir() {
+        return Err(format!("Server instance '{}' not found", server_name));
     }
+    let retention = ServerFileManager::new(PathBuf::from("storage/server_config.json")).get_backup_retention(server_name);
     
-    let properties_manager = ServerPropertiesManager::new(properties_path);
+    let server_name = server_name.to_string();
+    tokio::task::spawn_blocking(move || {
+        let backup_manager = create_backup_manager();
+        let backup = backup_manager
+            .create_backup(&server_name, &storage_path, include_world, label)
+            .map_err(|e| e.to_string())?;
+        if let Err(e) = backup_manager.prune_backups(&server_name, &retention) {
+            log::warn!("Failed to prune backups of {}: {}", server_name, e);
+        }
+        Ok(backup)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Backs up a server whether or not it is running. While it runs, world saving is paused over
+/// RCON ("save-off" and "save-all flush") so the copied world is consistent, and turned back
+/// on afterwards even if the backup fails.
+async fn backup_live_server(server_name: &str, include_world: bool, label: &'static str) -> Result<BackupInfo, String> {
+    let is_running = UNIFIED_SERVER_SERVICE.lock().await.is_server_running(server_name).await;
+    if !is_running || !include_world {
+        return backup_server(server_name, include_world, label).await;
+    }
     
-    match properties_manager.get_property("motd") {
-        Ok(motd) => Ok(motd),
-        Err(_) => Ok("A Minecraft Server".to_string()), // Default MOTD if reading fails
+    execute_connected_rcon_command(server_name, "save-off")
+        .map_err(|e| format!("Cannot pause world saving on '{}' for the backup: {}", server_name, e))?;
+    let result = match execute_connected_rcon_command(server_name, "save-all flush") {
+        Ok(_) => backup_server(server_name, include_world, label).await,
+        Err(e) => Err(format!("Failed to flush the world of '{}' before the backup: {}", server_name, e)),
+    };
+    if let Err(e) = execute_connected_rcon_command(server_name, "save-on") {
+        log::error!("Failed to turn world saving back on for {}: {}", server_name, e);
     }
+    result
 }
 
 #[tauri::command]
-fn get_server_max_players(server_name: String) -> Result<u32, String> {
-    let server_path = PathBuf::from("storage").join(&server_name);
-    let properties_path = server_path.join("server.properties");
+async fn create_backup(server_name: String, include_world: bool) -> Result<BackupInfo, String> {
+    backup_live_server(&server_name, include_world, "").await
+}
+
+/// Sets when the server is backed up automatically; None turns automatic backups off
+#[tauri::command]
+async fn update_server_backup_schedule(name: String, schedule: Option<BackupSchedule>) -> Result<String, String> {
+    match &schedule {
+        Some(BackupSchedule::Interval { minutes }) if *minutes < 5 => {
+            return Err("Automatic backups must be at least 5 minutes apart".to_string());
+        }
+        Some(BackupSchedule::Daily { time }) if services::backup_scheduler::parse_schedule_time(time).is_none() => {
+            return Err(format!("Invalid backup time '{}', expected HH:MM", time));
+        }
+        _ => {}
+    }
     
-    if !properties_path.exists() {
-        return Ok(20); // Default max players
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    let mut instance = manager.get_instance(&name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", name))?;
+    
+    instance.backup_schedule = schedule.clone();
+    manager.update_instance(&name, instance).map_err(|e| e.to_string())?;
+    
+    let message = match &schedule {
+        Some(BackupSchedule::Interval { minutes }) => format!("Server '{}' will be backed up every {} minutes", name, minutes),
+        Some(BackupSchedule::Daily { time }) => format!("Server '{}' will be backed up daily at {}", name, time),
+        None => format!("Automatic backups disabled for '{}'", name),
+    };
+    BACKUP_SCHEDULER.lock().await.arm(&name, schedule);
+    Ok(message)
+}
+
+/// The server's backups, newest first, with what each one contains
+#[tauri::command]
+fn list_backups(server_name: String) -> Result<Vec<BackupInfo>, String> {
+    create_backup_manager().list_backups(&server_name).map_err(|e| e.to_string())
+}
+
+/// Applies the server's retention policy now and returns the deleted backups
+#[tauri::command]
+fn prune_backups(server_name: String) -> Result<Vec<String>, String> {
+    let retention = ServerFileManager::new(PathBuf::from("storage/server_config.json")).get_backup_retention(&server_name);
+    create_backup_manager().prune_backups(&server_name, &retention).map_err(|e| e.to_string())
+}
+
+/// The server's crash reports, newest first
+#[tauri::command]
+fn list_crash_reports(server_name: String) -> Result<Vec<CrashReportInfo>, String> {
+    services::crash_reports::list_crash_reports(&get_storage_path(&server_name)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn read_crash_report(server_name: String, filename: String) -> Result<CrashReport, String> {
+    services::crash_reports::read_crash_report(&get_storage_path(&server_name), &filename).map_err(|e| e.to_string())
+}
+
+/// Sets the console commands sent, in order, to stop a server. Returns a warning when none of
+/// them is "stop" or "shutdown", since the server may then never exit and gets killed.
+#[tauri::command]
+fn update_server_stop_commands(name: String, stop_commands: Vec<String>) -> Result<Option<String>, String> {
+    let stop_commands: Vec<String> = stop_commands
+        .iter()
+        .map(|command| command.trim().trim_start_matches('/').to_string())
+        .filter(|command| !command.is_empty())
+        .collect();
+    if stop_commands.is_empty() {
+        return Err("At least one stop command is required".to_string());
     }
     
-    let properties_manager = ServerPropertiesManager::new(properties_path);
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
     
-    match properties_manager.get_property("max-players") {
-        Ok(max_players_str) => {
-            match max_players_str.parse::<u32>() {
-                Ok(max_players) => Ok(max_players),
-                Err(_) => Ok(20), // Default if parsing fails
-            }
-        },
-        Err(_) => Ok(20), // Default max players if property not found
+    let mut instance = manager.get_instance(&name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", name))?;
+    
+    let stops_server = stop_commands.iter().any(|command| {
+        let command = command.to_lowercase();
+        command == "stop" || command == "shutdown" || command.starts_with("stop ") || command.starts_with("shutdown ")
+    });
+    instance.stop_commands = stop_commands;
+    manager.update_instance(&name, instance).map_err(|e| e.to_string())?;
+    
+    if stops_server {
+        Ok(None)
+    } else {
+        Ok(Some(format!(
+            "None of the stop commands of '{}' is \"stop\" or \"shutdown\"; if they don't shut it down it will be killed after the stop timeout",
+            name
+        )))
+    }
+}
+
+#[tauri::command]
+fn update_server_backup_retention(name: String, keep_last: Option<u32>, keep_days: Option<u32>) -> Result<String, String> {
+    if keep_last == Some(0) || keep_days == Some(0) {
+        return Err("Retention limits must be at least 1; leave a limit empty to disable it".to_string());
+    }
+    
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    let mut instance = manager.get_instance(&name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", name))?;
+    
+    instance.backup_retention = BackupRetention { keep_last, keep_days };
+    manager.update_instance(&name, instance).map_err(|e| e.to_string())?;
+    
+    Ok(format!("Backup retention updated for '{}'", name))
+}
+
+/// Reserves a stopped server for `action` (e.g. "restoring a backup"), which then runs without
+/// holding the service lock; the server can't be started until the guard is dropped
+async fn reserve_stopped_server(server_name: &str, action: &str) -> Result<MaintenanceGuard, String> {
+    let service = UNIFIED_SERVER_SERVICE.lock().await;
+    if service.is_server_running(server_name).await {
+        return Err(format!("Stop server '{}' before {}", server_name, action));
+    }
+    service.begin_maintenance(server_name).await
+        .ok_or_else(|| format!("Server '{}' is busy with another task; try again once it is done", server_name))
+}
+
+/// Restores a stopped server from one of its backups, backing up its current state first
+#[tauri::command]
+async fn restore_backup(server_name: String, file_name: String) -> Result<RestoreResult, String> {
+    let storage_path = get_storage_path(&server_name);
+    if !storage_path.is_dir() {
+        return Err(format!("Server instance '{}' not found", server_name));
+    }
+    
+    // The restore takes a while; keep the server from being started meanwhile
+    let _maintenance = reserve_stopped_server(&server_name, "restoring a backup").await?;
+    
+    let retention = ServerFileManager::new(PathBuf::from("storage/server_config.json")).get_backup_retention(&server_name);
+    let result = {
+        let storage_path = storage_path.clone();
+        tokio::task::spawn_blocking(move || {
+            create_backup_manager().restore_backup(&server_name, &file_name, &storage_path, &retention)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?
+    };
+    services::world_info::world_size(&storage_path, true);
+    
+    Ok(result)
+}
+
+#[tauri::command]
+fn get_server_loader_type(server_name: String) -> Result<String, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    match manager.get_instance(&server_name) {
+        Ok(Some(server)) => Ok(server.mod_loader.clone()),
+        Ok(None) => Err(format!("Server '{}' not found", server_name)),
+        Err(e) => Err(format!("Failed to get server info: {}", e)),
+    }
+}
+
+#[tauri::command]
+async fn is_server_running(server_name: String) -> bool {
+    let monitor = SERVER_MONITOR.lock().await;
+    let status = monitor.get_server_status(&server_name).await;
+    
+    match status {
+        ServerStatus::Online => true,
+        _ => false,
+    }
+}
+
+fn get_server_port(server_name: &str) -> Option<u16> {
+    // Try to read port from server.properties
+    let storage_path = std::path::PathBuf::from("storage").join(server_name);
+    let properties_path = storage_path.join("server.properties");
+    
+    if properties_path.exists() {
+        let properties_manager = ServerPropertiesManager::new(properties_path);
+        
+        if let Ok(port_str) = properties_manager.get_property("server-port") {
+            if let Ok(port) = port_str.parse::<u16>() {
+                return Some(port);
+            }
+        }
+    }
+    
+    None // Return None to use the default port
+}
+
+#[tauri::command]
+async fn query_server_status(server_name: String) -> Result<QueryResponse, String> {
+    let port = get_server_port(&server_name).unwrap_or(25565);
+    let settings = create_app_settings_manager().load_settings().unwrap_or_default();
+    
+    let config = QueryConfig {
+        host: server_connect_host(&server_name),
+        port,
+        timeout_ms: 5000, // 5 second budget for detailed query
+        retries: util::clamp_query_retries(settings.query_retries),
+    };
+    
+    let query_service = QueryService::new(config);
+    let response = query_service.query_server().await;
+    
+    Ok(response)
+}
+
+#[tauri::command]
+async fn query_server_full(server_name: String) -> Result<QueryResponse, String> {
+    // Query listens on query.port, which may differ from the game port
+    let properties_path = get_storage_path(&server_name).join("server.properties");
+    if !properties_path.exists() {
+        return Err("Server properties file not found".to_string());
+    }
+    
+    let properties = ServerPropertiesManager::new(properties_path)
+        .load_properties()
+        .map_err(|e| e.to_string())?;
+    
+    if !properties.enable_query {
+        return Err(format!("Query is disabled for server '{}' (enable-query=false)", server_name));
+    }
+    
+    let settings = create_app_settings_manager().load_settings().unwrap_or_default();
+    
+    let config = QueryConfig {
+        host: properties.connect_host(),
+        port: properties.query_port,
+        timeout_ms: 5000,
+        retries: util::clamp_query_retries(settings.query_retries),
+    };
+    
+    let query_service = QueryService::new(config);
+    Ok(query_service.query_full_stats().await)
+}
+
+#[tauri::command]
+async fn start_server_monitoring(server_name: String) -> Result<String, String> {
+    let monitor = SERVER_MONITOR.lock().await;
+    monitor.start_monitoring(server_name.clone()).await;
+    Ok(format!("Started RCON monitoring for server: {}", server_name))
+}
+
+#[tauri::command]
+async fn stop_server_monitoring(server_name: String) -> Result<String, String> {
+    let monitor = SERVER_MONITOR.lock().await;
+    monitor.stop_monitoring(&server_name).await;
+    Ok(format!("Stopped RCON monitoring for server: {}", server_name))
+}
+
+#[tauri::command]
+async fn get_server_monitor_status(server_name: String) -> String {
+    let monitor = SERVER_MONITOR.lock().await;
+    let status = monitor.get_server_status(&server_name).await;
+    
+    match status {
+        ServerStatus::Offline => "offline".to_string(),
+        ServerStatus::Starting => "starting".to_string(),
+        ServerStatus::Online => "online".to_string(),
+        ServerStatus::Stopping => "stopping".to_string(),
+    }
+}
+
+#[tauri::command]
+async fn get_all_server_monitor_statuses() -> HashMap<String, String> {
+    let monitor = SERVER_MONITOR.lock().await;
+    let all_statuses = monitor.get_all_statuses().await;
+    
+    all_statuses.into_iter()
+        .map(|(name, status)| {
+            let status_str = match status {
+                ServerStatus::Offline => "offline".to_string(),
+                ServerStatus::Starting => "starting".to_string(),
+                ServerStatus::Online => "online".to_string(),
+                ServerStatus::Stopping => "stopping".to_string(),
+            };
+            (name, status_str)
+        })
+        .collect()
+}
+
+#[tauri::command]
+async fn update_server_monitor_status(server_name: String, status: String) -> Result<String, String> {
+    let monitor_status = match status.as_str() {
+        "offline" => ServerStatus::Offline,
+        "starting" => ServerStatus::Starting,
+        "online" => ServerStatus::Online,
+        "stopping" => ServerStatus::Stopping,
+        _ => return Err("Invalid status (expected 'offline', 'starting', 'online' or 'stopping')".to_string()),
+    };
+    
+    let monitor = SERVER_MONITOR.lock().await;
+    monitor.update_server_status(&server_name, monitor_status).await;
+    Ok(format!("Updated server {} status to: {}", server_name, status))
+}
+
+#[tauri::command]
+async fn get_running_servers() -> Vec<String> {
+    let service = UNIFIED_SERVER_SERVICE.lock().await;
+    service.get_running_servers().await
+}
+
+// JAR Cache management commands
+#[tauri::command]
+fn get_jar_cache_stats() -> Result<CacheStats, String> {
+    let cache_dir = PathBuf::from("storage/version_cache");
+    let jar_cache = JarCacheManager::new(cache_dir).map_err(|e| e.to_string())?;
+    jar_cache.get_cache_stats().map_err(|e| e.to_string())
+}
+
+/// Cache keys of the JARs registered servers run; instances with an unknown loader are skipped
+fn referenced_cache_keys(jar_cache: &JarCacheManager) -> Result<HashSet<String>, String> {
+    let manager = ServerFileManager::new(PathBuf::from("storage/server_config.json"));
+    let instances = manager.get_all_instances().map_err(|e| e.to_string())?;
+    Ok(instances
+        .iter()
+        .filter_map(|instance| {
+            let loader = parse_loader_type(&instance.mod_loader).ok()?;
+            let loader_version = Some(instance.mod_loader_version.as_str()).filter(|v| !v.is_empty());
+            Some(jar_cache.get_jar_cache_key(&loader, &instance.version, loader_version))
+        })
+        .collect())
+}
+
+/// Cached JARs no registered server uses, with their sizes
+#[tauri::command]
+fn find_orphaned_cache_entries() -> Result<Vec<CacheEntry>, String> {
+    let jar_cache = JarCacheManager::new(PathBuf::from("storage/version_cache")).map_err(|e| e.to_string())?;
+    let referenced = referenced_cache_keys(&jar_cache)?;
+    jar_cache.find_orphaned_entries(&referenced).map_err(|e| e.to_string())
+}
+
+/// Deletes the cached JARs no registered server uses and returns what was removed
+#[tauri::command]
+fn clear_orphaned_cache() -> Result<Vec<CacheEntry>, String> {
+    let jar_cache = JarCacheManager::new(PathBuf::from("storage/version_cache")).map_err(|e| e.to_string())?;
+    let referenced = referenced_cache_keys(&jar_cache)?;
+    jar_cache.clear_orphaned_entries(&referenced).map_err(|e| e.to_string())
+}
+
+/// Re-hashes every cached JAR and reports which ones are damaged or can't be checked
+#[tauri::command]
+async fn audit_cache() -> Result<Vec<CacheAuditEntry>, String> {
+    tokio::task::spawn_blocking(|| {
+        let jar_cache = JarCacheManager::new(PathBuf::from("storage/version_cache")).map_err(|e| e.to_string())?;
+        jar_cache.audit_cache().map_err(|e| e.to_string())
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[tauri::command]
+fn clear_jar_cache(
+    loader: Option<String>,
+    minecraft_version: Option<String>,
+    loader_version: Option<String>,
+) -> Result<String, String> {
+    let cache_dir = PathBuf::from("storage/version_cache");
+    let jar_cache = JarCacheManager::new(cache_dir).map_err(|e| e.to_string())?;
+    
+    if let (Some(loader_str), Some(mc_version)) = (loader, minecraft_version) {
+        let loader_type = parse_loader_type(&loader_str)?;
+        
+        jar_cache.clear_jar_cache(&loader_type, &mc_version, loader_version.as_deref())
+            .map_err(|e| e.to_string())?;
+        Ok(format!("Cleared JAR cache for {} {}", loader_str, mc_version))
+    } else {
+        jar_cache.clear_all_jar_cache().map_err(|e| e.to_string())?;
+        Ok("Cleared all JAR cache".to_string())
+    }
+}
+
+#[tauri::command]
+fn is_jar_cached(
+    loader: String,
+    minecraft_version: String,
+    loader_version: Option<String>,
+) -> Result<bool, String> {
+    let cache_dir = PathBuf::from("storage/version_cache");
+    let jar_cache = JarCacheManager::new(cache_dir).map_err(|e| e.to_string())?;
+    let loader_type = parse_loader_type(&loader)?;
+    
+    Ok(jar_cache.is_jar_cached(&loader_type, &minecraft_version, loader_version.as_deref()))
+}
+
+// Server properties management commands
+#[tauri::command]
+fn get_server_motd(server_name: String) -> Result<String, String> {
+    let server_path = PathBuf::from("storage").join(&server_name);
+    let properties_path = server_path.join("server.properties");
+    
+    if !properties_path.exists() {
+        return Ok("A Minecraft Server".to_string()); // Default MOTD
+    }
+    
+    let properties_manager = ServerPropertiesManager::new(properties_path);
+    
+    match properties_manager.get_property("motd") {
+        Ok(motd) => Ok(motd),
+        Err(_) => Ok("A Minecraft Server".to_string()), // Default MOTD if reading fails
+    }
+}
+
+#[tauri::command]
+fn get_server_max_players(server_name: String) -> Result<u32, String> {
+    let server_path = PathBuf::from("storage").join(&server_name);
+    let properties_path = server_path.join("server.properties");
+    
+    if !properties_path.exists() {
+        return Ok(20); // Default max players
+    }
+    
+    let properties_manager = ServerPropertiesManager::new(properties_path);
+    
+    match properties_manager.get_property("max-players") {
+        Ok(max_players_str) => {
+            match max_players_str.parse::<u32>() {
+                Ok(max_players) => Ok(max_players),
+                Err(_) => Ok(20), // Default if parsing fails
+            }
+        },
+        Err(_) => Ok(20), // Default max players if property not found
+    }
+}
+
+#[tauri::command]
+async fn update_server_property(
+    server_name: String,
+    property_key: String,
+    property_value: String,
+) -> Result<String, String> {
+    let server_path = PathBuf::from("storage").join(&server_name);
+    let properties_path = server_path.join("server.properties");
+    
+    let properties_manager = ServerPropertiesManager::new(properties_path);
+    
+    properties_manager.update_property(&property_key, &property_value)
+        .map_err(|e| e.to_string())?;
+    flag_restart_if_running(&server_name).await;
+    
+    Ok(format!("Updated {} to {}", property_key, property_value))
+}
+
+/// Applies several property changes with one load and one save. Returns the rejected keys
+/// with their reasons; nothing is written unless every change is valid.
+#[tauri::command]
+async fn update_server_properties(
+    server_name: String,
+    changes: HashMap<String, String>,
+) -> Result<PropertyUpdateResult, String> {
+    let properties_path = get_storage_path(&server_name).join("server.properties");
+    if !properties_path.exists() {
+        return Err("Server properties file not found".to_string());
+    }
+    
+    let result = ServerPropertiesManager::new(properties_path)
+        .update_properties(&changes)
+        .map_err(|e| e.to_string())?;
+    if !result.updated.is_empty() {
+        flag_restart_if_running(&server_name).await;
+    }
+    Ok(result)
+}
+
+/// Applies a shared server.properties template after substituting its ${NAME} placeholders.
+/// ${SERVER_NAME} is always available; nothing is written if any resulting value is invalid.
+#[tauri::command]
+async fn apply_properties_template(
+    server_name: String,
+    template_string: String,
+    mut vars: HashMap<String, String>,
+) -> Result<PropertyUpdateResult, String> {
+    let server_path = get_storage_path(&server_name);
+    if !server_path.is_dir() {
+        return Err(format!("Server '{}' has no storage folder", server_name));
+    }
+    
+    vars.entry("SERVER_NAME".to_string()).or_insert_with(|| server_name.clone());
+    let result = ServerPropertiesManager::new(server_path.join("server.properties"))
+        .apply_template(&template_string, &vars)
+        .map_err(|e| e.to_string())?;
+    if !result.updated.is_empty() {
+        flag_restart_if_running(&server_name).await;
+    }
+    Ok(result)
+}
+
+/// Type, bounds and default of every server.properties key, for rendering the settings editor
+#[tauri::command]
+fn get_property_schema() -> Vec<PropertySchema> {
+    util::property_schema()
+}
+
+// System information commands
+#[tauri::command]
+async fn create_server_transactional(
+    name: String,
+    version: String,
+    mod_loader: String,
+    mod_loader_version: String,
+) -> Result<String, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let storage_path = PathBuf::from("storage");
+    let manager = ServerFileManager::new(config_path);
+    
+    // Initialize a config file if it doesn't exist
+    manager.initialize_config().map_err(|e| e.to_string())?;
+    
+    // Check if a server already exists
+    if manager.instance_exists(&name).map_err(|e| e.to_string())? {
+        return Err(format!("Server instance '{}' already exists", name));
+    }
+    
+    let loader_type = parse_loader_type(&mod_loader)?;
+    ensure_combination_supported(&loader_type, &version).await?;
+    // Installers and BuildTools run on the Java the server will use, installed before anything is created
+    let java_command = resolve_java_command(&version).await?;
+    
+    log::info!("Starting transactional server creation for: {}", name);
+    
+    // Step 1: Create a server instance with PENDING status
+    let instance = ServerInstance::new(
+        name.clone(),
+        version.clone(),
+        mod_loader.clone(),
+        mod_loader_version.clone(),
+        &storage_path,
+    ).map_err(|e| e.to_string())?;
+    
+    manager.add_instance(instance).map_err(|e| e.to_string())?;
+    manager.create_storage_directory(&name, &storage_path).map_err(|e| {
+        // If directory creation fails, remove from config
+        let _ = manager.remove_instance(&name);
+        e.to_string()
+    })?;
+    
+    log::info!("Server instance created with PENDING status");
+    
+    // Step 2: Download server JAR. Setups such as a BuildTools build take minutes, so they run
+    // on an installer instead of keeping the service (and every other server) locked.
+    let installer = UNIFIED_SERVER_SERVICE.lock().await.installer().with_java_command(&java_command);
+    let server_storage_path = get_storage_path(&name);
+    
+    // Prepare loader version reference
+    let loader_version_ref = if mod_loader != "vanilla" { 
+        Some(mod_loader_version.as_str()) 
+    } else { 
+        None 
+    };
+    
+    // Download JAR with rollback on failure
+    match installer.download_server_jar(
+        loader_type.clone(),
+        version.clone(),
+        loader_version_ref.map(|s| s.to_string()),
+        server_storage_path.clone(),
+    ).await {
+        Ok(_) => {
+            // Update status to JAR_DOWNLOADED
+            manager.update_server_status(&name, ServerCreationStatus::JarDownloaded)
+                .map_err(|e| e.to_string())?;
+            log::info!("JAR downloaded successfully, status updated to JAR_DOWNLOADED");
+        },
+        Err(e) => {
+            // Rollback: mark as failed and cleanup
+            let _ = manager.update_server_status(&name, ServerCreationStatus::Failed);
+            let _ = manager.remove_instance_with_storage(&name, &storage_path);
+            return Err(format!("Failed to download server JAR: {}", e));
+        }
+    }
+    
+    // Step 3: Setup server with rollback on failure
+    match installer.setup_server(
+        &name,
+        loader_type,
+        &version,
+        loader_version_ref,
+        &server_storage_path,
+    ).await {
+        Ok(_) => {
+            // Update status to SETUP_COMPLETE
+            manager.update_server_status(&name, ServerCreationStatus::SetupComplete)
+                .map_err(|e| e.to_string())?;
+            log::info!("Server setup completed, status updated to SETUP_COMPLETE");
+        },
+        Err(e) => {
+            // Rollback: mark as failed and cleanup
+            let _ = manager.update_server_status(&name, ServerCreationStatus::Failed);
+            let _ = manager.remove_instance_with_storage(&name, &storage_path);
+            return Err(format!("Failed to setup server: {}", e));
+        }
+    }
+    
+    // Step 4: Mark as completed
+    manager.update_server_status(&name, ServerCreationStatus::Completed)
+        .map_err(|e| e.to_string())?;
+    
+    log::info!("Server '{}' created successfully with COMPLETED status", name);
+    
+    Ok(format!("Server instance '{}' created successfully", name))
+}
+
+/// Saves a server's loader, versions, launch settings and server.properties as a reusable
+/// template. `property_keys` picks the properties to keep; by default all but ports and passwords.
+#[tauri::command]
+fn save_server_as_template(
+    server_name: String,
+    template_name: String,
+    property_keys: Option<Vec<String>>,
+) -> Result<ServerTemplate, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    let instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
+    let properties = ServerPropertiesManager::new(get_storage_path(&server_name).join("server.properties"))
+        .load_properties()
+        .map_err(|e| e.to_string())?;
+    
+    let template = ServerTemplate::from_server(&template_name, &instance, &properties, property_keys.as_deref());
+    services::server_templates::save_template(Path::new(TEMPLATES_DIR), &template).map_err(|e| e.to_string())?;
+    Ok(template)
+}
+
+#[tauri::command]
+fn list_templates() -> Result<Vec<ServerTemplate>, String> {
+    services::server_templates::list_templates(Path::new(TEMPLATES_DIR)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_template(template_name: String) -> Result<String, String> {
+    services::server_templates::delete_template(Path::new(TEMPLATES_DIR), &template_name).map_err(|e| e.to_string())?;
+    Ok(format!("Template '{}' deleted", template_name))
+}
+
+/// Copies a template's launch settings and server.properties onto a server
+fn apply_template_settings(manager: &ServerFileManager, server_name: &str, template: ServerTemplate) -> Result<(), String> {
+    let mut instance = manager.get_instance(server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
+    instance.memory_mb = template.memory_mb;
+    instance.env_vars = template.env_vars;
+    instance.nogui = template.nogui;
+    manager.update_instance(server_name, instance).map_err(|e| e.to_string())?;
+    
+    let changes: HashMap<String, String> = template.properties.into_iter().collect();
+    let result = ServerPropertiesManager::new(get_storage_path(server_name).join("server.properties"))
+        .update_properties(&changes)
+        .map_err(|e| e.to_string())?;
+    if !result.errors.is_empty() {
+        return Err(format!("invalid server.properties: {:?}", result.errors));
+    }
+    Ok(())
+}
+
+/// Creates a server like create_server_transactional and then applies the template's launch
+/// settings and server.properties to it. If they can't be applied the new server is removed again.
+#[tauri::command]
+async fn create_server_from_template(template_name: String, new_server_name: String) -> Result<String, String> {
+    let template = services::server_templates::load_template(Path::new(TEMPLATES_DIR), &template_name)
+        .map_err(|e| e.to_string())?;
+    
+    create_server_transactional(
+        new_server_name.clone(),
+        template.minecraft_version.clone(),
+        template.mod_loader.clone(),
+        template.mod_loader_version.clone(),
+    ).await?;
+    
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    if let Err(e) = apply_template_settings(&manager, &new_server_name, template) {
+        // A server that silently differs from its template is worse than none
+        let _ = manager.remove_instance_with_storage(&new_server_name, Path::new("storage"));
+        return Err(format!("Failed to apply template '{}', the server was not created: {}", template_name, e));
+    }
+    
+    Ok(format!("Server '{}' created from template '{}'", new_server_name, template_name))
+}
+
+/// Re-runs setup and regenerates missing files for a server that was half-created or damaged
+#[tauri::command]
+async fn repair_server(server_name: String) -> Result<RepairReport, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    let instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
+    let java_command = resolve_java_command(&instance.version).await?;
+    
+    let service = UNIFIED_SERVER_SERVICE.lock().await;
+    if service.is_server_running(&server_name).await {
+        return Err("Stop the server before repairing it".to_string());
+    }
+    
+    let loader_type = parse_loader_type(&instance.mod_loader)?;
+    let loader_version = if instance.mod_loader != "vanilla" {
+        Some(instance.mod_loader_version.as_str())
+    } else {
+        None
+    };
+    
+    // The creation status tells whether setup ever got past the download
+    let setup_finished = matches!(
+        instance.creation_status,
+        ServerCreationStatus::SetupComplete | ServerCreationStatus::Completed
+    );
+    
+    let mut report = service.installer().with_java_command(&java_command).repair_server(
+        &server_name,
+        loader_type,
+        &instance.version,
+        loader_version,
+        &get_storage_path(&server_name),
+        setup_finished,
+    ).await.map_err(|e| format!("Failed to repair server: {}", e))?;
+    
+    if instance.creation_status != ServerCreationStatus::Completed {
+        manager.update_server_status(&server_name, ServerCreationStatus::Completed)
+            .map_err(|e| e.to_string())?;
+        report.fixed.push("Marked the server as ready".to_string());
+    }
+    
+    Ok(report)
+}
+
+#[tauri::command]
+async fn export_server_bundle(
+    server_name: String,
+    dest_path: String,
+    include_world: Option<bool>,
+    include_secrets: Option<bool>,
+) -> Result<BundleManifest, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    let instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
+    
+    let include_world = include_world.unwrap_or(false);
+    if include_world && UNIFIED_SERVER_SERVICE.lock().await.is_server_running(&server_name).await {
+        return Err("Stop the server before exporting its world".to_string());
+    }
+    
+    services::server_bundle::export_server_bundle(
+        &instance,
+        &get_storage_path(&server_name),
+        &PathBuf::from(dest_path),
+        include_world,
+        include_secrets.unwrap_or(false),
+    ).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn import_server_bundle(bundle_path: String, new_name: Option<String>) -> Result<String, String> {
+    let bundle_path = PathBuf::from(bundle_path);
+    let manifest = services::server_bundle::read_bundle_manifest(&bundle_path)
+        .map_err(|e| e.to_string())?;
+    
+    let name = new_name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| manifest.instance.name.clone());
+    
+    // Re-downloads the loader by version and generates a fresh EULA, RCON password and properties
+    create_server_transactional(
+        name.clone(),
+        manifest.instance.version.clone(),
+        manifest.instance.mod_loader.clone(),
+        manifest.instance.mod_loader_version.clone(),
+    ).await?;
+    
+    services::server_bundle::extract_bundle_files(&bundle_path, &get_storage_path(&name))
+        .map_err(|e| format!("Server '{}' was created but restoring the bundle failed: {}", name, e))?;
+    
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    if let Some(mut instance) = manager.get_instance(&name).map_err(|e| e.to_string())? {
+        instance.description = manifest.instance.description.clone();
+        instance.memory_mb = manifest.instance.memory_mb;
+        instance.rcon_host = manifest.instance.rcon_host.clone();
+        instance.rcon_port = manifest.instance.rcon_port;
+        instance.rcon_password = manifest.instance.rcon_password.clone();
+        manager.update_instance(&name, instance).map_err(|e| e.to_string())?;
+    }
+    
+    let mut message = format!("Server '{}' imported from bundle", name);
+    let missing = manifest.mods.len() + manifest.plugins.len();
+    if missing > 0 {
+        message.push_str(&format!(
+            "; {} mod/plugin JAR(s) listed in the bundle must be reinstalled",
+            missing
+        ));
+    }
+    if let Ok(world_version) = detect_world_version(name.clone()).await {
+        if world_version != manifest.instance.version {
+            message.push_str(&format!(
+                "; the world was last saved with Minecraft {} but the server runs {}",
+                world_version, manifest.instance.version
+            ));
+        }
+    }
+    Ok(message)
+}
+
+#[tauri::command]
+async fn ensure_fabric_api(server_name: String) -> Result<String, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    let instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
+    
+    if parse_loader_type(&instance.mod_loader)? != LoaderType::Fabric {
+        return Err(format!("Server '{}' does not run Fabric", server_name));
+    }
+    
+    let client = reqwest::Client::new();
+    match services::mod_installer::ensure_fabric_api(&client, &get_storage_path(&server_name), &instance.version).await {
+        Ok(Some(path)) => Ok(format!(
+            "Installed {}",
+            path.file_name().unwrap_or_default().to_string_lossy()
+        )),
+        Ok(None) => Ok("Fabric API is already installed".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+#[tauri::command]
+async fn install_mod_from_path(server_name: String, source_path: String) -> Result<String, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    let instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
+    
+    let loader_type = parse_loader_type(&instance.mod_loader)?;
+    let installed = services::mod_installer::install_mod_from_path(
+        &get_storage_path(&server_name),
+        &loader_type,
+        &PathBuf::from(&source_path),
+    ).map_err(|e| e.to_string())?;
+    
+    let mut message = format!(
+        "Installed {}",
+        installed.file_name().unwrap_or_default().to_string_lossy()
+    );
+    
+    // Fabric mods that need Fabric API crash the server on start when it is missing
+    if loader_type == LoaderType::Fabric && services::mod_installer::requires_fabric_api(&installed) {
+        let client = reqwest::Client::new();
+        match services::mod_installer::ensure_fabric_api(&client, &get_storage_path(&server_name), &instance.version).await {
+            Ok(Some(path)) => {
+                message.push_str(&format!(
+                    "; installed required dependency {}",
+                    path.file_name().unwrap_or_default().to_string_lossy()
+                ));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                log::warn!("Could not install Fabric API for {}: {}", server_name, e);
+                message.push_str(&format!("; this mod requires Fabric API, which could not be installed: {}", e));
+            }
+        }
+    }
+    
+    Ok(message)
+}
+
+/// Every Paper build for a Minecraft version with its channel, so experimental builds can be flagged
+#[tauri::command]
+async fn get_paper_builds(minecraft_version: String) -> Result<Vec<PaperBuild>, String> {
+    let client = reqwest::Client::new();
+    services::paper_strategy::PaperStrategy::get_builds(&client, &minecraft_version)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Changelog (commit summaries) and channel of one Paper build
+#[tauri::command]
+async fn get_paper_build_info(minecraft_version: String, build: u32) -> Result<PaperBuild, String> {
+    let client = reqwest::Client::new();
+    services::paper_strategy::PaperStrategy::get_build(&client, &minecraft_version, build)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Switches a stopped Paper server to a specific build (or back to the latest with None),
+/// replacing its JAR. The build is stored as the loader version so re-downloads stay on it.
+#[tauri::command]
+async fn pin_paper_build(server_name: String, build: Option<u32>) -> Result<String, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    let mut instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
+    
+    if parse_loader_type(&instance.mod_loader)? != LoaderType::Paper {
+        return Err(format!("Server '{}' does not run Paper", server_name));
+    }
+    
+    // The download takes a while; keep the server from being started meanwhile
+    let _maintenance = reserve_stopped_server(&server_name, "changing its Paper build").await?;
+    let installer = UNIFIED_SERVER_SERVICE.lock().await.installer();
+    
+    let mut note = String::new();
+    if let Some(build) = build {
+        let client = reqwest::Client::new();
+        let info = services::paper_strategy::PaperStrategy::get_build(&client, &instance.version, build)
+            .await
+            .map_err(|e| e.to_string())?;
+        if info.is_experimental() {
+            note = " (experimental build)".to_string();
+        }
+    }
+    
+    let loader_version = build.map(|b| b.to_string()).unwrap_or_else(|| "latest".to_string());
+    let storage_path = get_storage_path(&server_name);
+    let jar_path = installer.download_server_jar(
+        LoaderType::Paper,
+        instance.version.clone(),
+        Some(loader_version.clone()),
+        storage_path.clone(),
+    ).await.map_err(|e| format!("Failed to download Paper JAR: {}", e))?;
+    
+    // The start command picks the first paper-*.jar, so only the selected one may remain
+    if let Ok(entries) = std::fs::read_dir(&storage_path) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with("paper-") && name.ends_with(".jar") && entry.path() != jar_path {
+                let _ = std::fs::remove_file(entry.path());
+            }
+        }
     }
+    
+    instance.mod_loader_version = loader_version;
+    manager.update_instance(&server_name, instance).map_err(|e| e.to_string())?;
+    
+    Ok(match build {
+        Some(build) => format!("Server '{}' is pinned to Paper build {}{}", server_name, build, note),
+        None => format!("Server '{}' now uses the latest Paper build", server_name),
+    })
 }
 
+/// Compares a Paper server's installed build with the newest one from the Paper API
 #[tauri::command]
-fn update_server_property(
-    server_name: String,
-    property_key: String,
-    property_value: String,
-) -> Result<String, String> {
-    let server_path = PathBuf::from("storage").join(&server_name);
-    let properties_path = server_path.join("server.properties");
+async fn check_for_updates(server_name: String) -> Result<PaperUpdateInfo, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
     
-    let properties_manager = ServerPropertiesManager::new(properties_path);
+    let instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
     
-    properties_manager.update_property(&property_key, &property_value)
-        .map_err(|e| e.to_string())?;
+    if parse_loader_type(&instance.mod_loader)? != LoaderType::Paper {
+        return Err(format!("Server '{}' does not run Paper", server_name));
+    }
     
-    Ok(format!("Updated {} to {}", property_key, property_value))
+    let info = services::paper_strategy::PaperStrategy::check_for_update(
+        &reqwest::Client::new(),
+        &get_storage_path(&server_name),
+        &instance.version,
+        &instance.mod_loader_version,
+    ).await.map_err(|e| e.to_string())?;
+    
+    PAPER_UPDATES.lock().await.insert(server_name, info.clone());
+    Ok(info)
 }
 
-// System information commands
+/// Compares a server's loader version (or Paper build) and Minecraft version with the newest
+/// available; a Minecraft upgrade is flagged as a major change
 #[tauri::command]
-async fn create_server_transactional(
-    name: String,
-    version: String,
-    mod_loader: String,
-    mod_loader_version: String,
-) -> Result<String, String> {
+async fn check_server_up_to_date(server_name: String) -> Result<UpdateCheck, String> {
     let config_path = PathBuf::from("storage/server_config.json");
-    let storage_path = PathBuf::from("storage");
     let manager = ServerFileManager::new(config_path);
     
-    // Initialize a config file if it doesn't exist
-    manager.initialize_config().map_err(|e| e.to_string())?;
+    let instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
     
-    // Check if a server already exists
-    if manager.instance_exists(&name).map_err(|e| e.to_string())? {
-        return Err(format!("Server instance '{}' already exists", name));
-    }
+    let check = services::update_check::check_up_to_date(
+        &services::version_service::VersionService::new(),
+        &reqwest::Client::new(),
+        &get_storage_path(&server_name),
+        parse_loader_type(&instance.mod_loader)?,
+        &instance.version,
+        &instance.mod_loader_version,
+    ).await.map_err(|e| e.to_string())?;
     
-    println!("Starting transactional server creation for: {}", name);
+    SERVER_UPDATES.lock().await.insert(server_name, check.clone());
+    Ok(check)
+}
+
+/// Installs the newest Paper build on a stopped server. The previous JAR is kept as
+/// <name>.jar.bak and the server is pinned to the new build so its version stays known.
+#[tauri::command]
+async fn apply_update(server_name: String) -> Result<PaperUpdateInfo, String> {
+    let mut info = check_for_updates(server_name.clone()).await?;
+    if !info.update_available {
+        return Ok(info);
+    }
     
-    // Step 1: Create a server instance with PENDING status
-    let instance = ServerInstance::new(
-        name.clone(),
-        version.clone(),
-        mod_loader.clone(),
-        mod_loader_version.clone(),
-        &storage_path,
-    ).map_err(|e| e.to_string())?;
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    let mut instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
     
-    manager.add_instance(instance).map_err(|e| e.to_string())?;
-    manager.create_storage_directory(&name, &storage_path).map_err(|e| {
-        // If directory creation fails, remove from config
-        let _ = manager.remove_instance(&name);
-        e.to_string()
-    })?;
+    // The download takes a while; keep the server from being started meanwhile
+    let _maintenance = reserve_stopped_server(&server_name, "updating Paper").await?;
+    let installer = UNIFIED_SERVER_SERVICE.lock().await.installer();
     
-    println!("Server instance created with PENDING status");
+    let build = info.latest_build.to_string();
+    let storage_path = get_storage_path(&server_name);
+    let jar_path = installer.download_server_jar(
+        LoaderType::Paper,
+        instance.version.clone(),
+        Some(build.clone()),
+        storage_path.clone(),
+    ).await.map_err(|e| format!("Failed to download Paper build {}: {}", build, e))?;
     
-    // Step 2: Download server JAR
-    let loader_type = match parse_loader_type(&mod_loader) {
-        Ok(loader_type) => loader_type,
-        Err(e) => {
-            // Cleanup on invalid loader
-            let _ = manager.remove_instance_with_storage(&name, &storage_path);
-            return Err(e);
+    // The start command picks the first paper-*.jar, so move the old ones out of its way
+    for entry in std::fs::read_dir(&storage_path).map_err(|e| e.to_string())?.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with("paper-") && name.ends_with(".jar.bak") {
+            let _ = std::fs::remove_file(entry.path());
+        } else if name.starts_with("paper-") && name.ends_with(".jar") && entry.path() != jar_path {
+            std::fs::rename(entry.path(), storage_path.join(format!("{}.bak", name)))
+                .map_err(|e| format!("Failed to back up {}: {}", name, e))?;
         }
-    };
+    }
     
-    let service = UNIFIED_SERVER_SERVICE.lock().await;
-    let server_storage_path = get_storage_path(&name);
+    instance.mod_loader_version = build;
+    manager.update_instance(&server_name, instance).map_err(|e| e.to_string())?;
     
-    // Prepare loader version reference
-    let loader_version_ref = if mod_loader != "vanilla" { 
-        Some(mod_loader_version.as_str()) 
-    } else { 
-        None 
-    };
+    log::info!("Updated Paper on {} from build {:?} to {}", server_name, info.installed_build, info.latest_build);
+    info.installed_build = Some(info.latest_build);
+    info.update_available = false;
+    SERVER_UPDATES.lock().await.remove(&server_name);
+    PAPER_UPDATES.lock().await.insert(server_name, info.clone());
+    Ok(info)
+}
+
+#[tauri::command]
+async fn search_modrinth_projects(
+    query: String,
+    loader: String,
+    mc_version: String,
+) -> Result<Vec<services::modrinth::ModrinthProject>, String> {
+    let loader_type = parse_loader_type(&loader)?;
+    let client = reqwest::Client::new();
+    services::modrinth::search_projects(&client, &query, &loader_type, &mc_version)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn install_modrinth_project(
+    server_name: String,
+    project_id: String,
+    version_id: Option<String>,
+) -> Result<String, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
     
-    // Download JAR with rollback on failure
-    match service.download_server_jar(
-        loader_type.clone(),
-        version.clone(),
-        loader_version_ref.map(|s| s.to_string()),
-        server_storage_path.clone(),
-    ).await {
-        Ok(_) => {
-            // Update status to JAR_DOWNLOADED
-            manager.update_server_status(&name, ServerCreationStatus::JarDownloaded)
-                .map_err(|e| e.to_string())?;
-            println!("JAR downloaded successfully, status updated to JAR_DOWNLOADED");
-        },
-        Err(e) => {
-            // Rollback: mark as failed and cleanup
-            let _ = manager.update_server_status(&name, ServerCreationStatus::Failed);
-            let _ = manager.remove_instance_with_storage(&name, &storage_path);
-            return Err(format!("Failed to download server JAR: {}", e));
-        }
-    }
+    let instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
     
-    // Step 3: Setup server with rollback on failure
-    match service.setup_server(
-        &name,
-        loader_type,
-        &version,
-        loader_version_ref,
-        &server_storage_path,
-    ).await {
-        Ok(_) => {
-            // Update status to SETUP_COMPLETE
-            manager.update_server_status(&name, ServerCreationStatus::SetupComplete)
-                .map_err(|e| e.to_string())?;
-            println!("Server setup completed, status updated to SETUP_COMPLETE");
-        },
-        Err(e) => {
-            // Rollback: mark as failed and cleanup
-            let _ = manager.update_server_status(&name, ServerCreationStatus::Failed);
-            let _ = manager.remove_instance_with_storage(&name, &storage_path);
-            return Err(format!("Failed to setup server: {}", e));
-        }
-    }
+    let loader_type = parse_loader_type(&instance.mod_loader)?;
+    let client = reqwest::Client::new();
+    let installed = services::modrinth::install_project(
+        &client,
+        &get_storage_path(&server_name),
+        &loader_type,
+        &instance.version,
+        &project_id,
+        version_id.as_deref(),
+    ).await.map_err(|e| e.to_string())?;
     
-    // Step 4: Mark as completed
-    manager.update_server_status(&name, ServerCreationStatus::Completed)
+    Ok(format!(
+        "Installed {}",
+        installed.file_name().unwrap_or_default().to_string_lossy()
+    ))
+}
+
+#[tauri::command]
+fn set_server_icon(server_name: String, source_image_path: String) -> Result<ServerIconResult, String> {
+    let storage_path = get_storage_path(&server_name);
+    if !storage_path.exists() {
+        return Err(format!("Server instance '{}' not found", server_name));
+    }
+    services::server_icon::set_server_icon(&storage_path, &PathBuf::from(source_image_path))
+        .map_err(|e| e.to_string())
+}
+
+/// Base64-encoded PNG of the current icon, or None when the server has none
+#[tauri::command]
+fn get_server_icon(server_name: String) -> Result<Option<String>, String> {
+    services::server_icon::get_server_icon(&get_storage_path(&server_name))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn remove_server_icon(server_name: String) -> Result<String, String> {
+    let removed = services::server_icon::remove_server_icon(&get_storage_path(&server_name))
+        .map_err(|e| e.to_string())?;
+    Ok(if removed {
+        format!("Removed the icon of server '{}'", server_name)
+    } else {
+        format!("Server '{}' has no icon", server_name)
+    })
+}
+
+#[tauri::command]
+fn list_server_files(server_name: String, relative_path: Option<String>) -> Result<Vec<FileEntry>, String> {
+    let relative_path = relative_path.unwrap_or_default();
+    services::server_files::list_files(&get_storage_path(&server_name), &relative_path)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn read_server_file(server_name: String, relative_path: String) -> Result<String, String> {
+    services::server_files::read_text_file(&get_storage_path(&server_name), &relative_path)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn write_server_file(server_name: String, relative_path: String, content: String) -> Result<String, String> {
+    services::server_files::write_text_file(&get_storage_path(&server_name), &relative_path, &content)
         .map_err(|e| e.to_string())?;
+    Ok(format!("Saved {}", relative_path))
+}
+
+/// Writes the level-type and generator-settings for a world preset in the format of the
+/// server's Minecraft version. `biome` is used by the Flat and SingleBiome presets.
+#[tauri::command]
+fn set_world_preset(server_name: String, preset: WorldPreset, biome: Option<String>) -> Result<WorldPresetResult, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
     
-    println!("Server '{}' created successfully with COMPLETED status", name);
+    let instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
     
-    Ok(format!("Server instance '{}' created successfully", name))
+    services::world_preset::apply_world_preset(
+        &get_storage_path(&server_name),
+        &instance.version,
+        preset,
+        biome.as_deref(),
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// Guesses the loader, Minecraft version and loader version of an existing server directory,
+/// e.g. one about to be imported. None when it doesn't look like a server at all.
+#[tauri::command]
+fn detect_server_loader(path: String) -> Result<Option<DetectedLoader>, String> {
+    services::loader_detection::detect_server_loader(Path::new(&path)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn detect_world_version(server_name: String) -> Result<String, String> {
+    let world_path = services::world_info::world_folder(&get_storage_path(&server_name));
+    let version = services::world_info::read_world_version(&world_path)
+        .map_err(|e| e.to_string())?;
+    
+    // Worlds last saved before 1.9 only record the format, not the game version
+    version.name.ok_or_else(|| match version.data_version {
+        Some(data_version) => format!("World has data version {} but no version name", data_version),
+        None => "World was last saved before Minecraft 1.9 and does not record its version".to_string(),
+    })
 }
 
 #[tauri::command]
@@ -738,11 +2792,11 @@ fn cleanup_incomplete_servers() -> Result<Vec<String>, String> {
     for server_name in incomplete_servers {
         match manager.cleanup_incomplete_server(&server_name, &storage_path) {
             Ok(_) => {
-                println!("Cleaned up incomplete server: {}", server_name);
+                log::info!("Cleaned up incomplete server: {}", server_name);
                 cleaned_servers.push(server_name);
             },
             Err(e) => {
-                println!("Failed to cleanup server {}: {}", server_name, e);
+                log::warn!("Failed to cleanup server {}: {}", server_name, e);
             }
         }
     }
@@ -758,23 +2812,29 @@ async fn setup_rcon_for_server(
     port: u16,
     _password: String, // This parameter is ignored, we use the one from server.properties
 ) -> Result<String, String> {
-    println!("Setting up RCON for server: {}", server_name);
+    log::info!("Setting up RCON for server: {}", server_name);
     
     // Get the actual password from server.properties
     let server_path = PathBuf::from("storage").join(&server_name);
     let properties_path = server_path.join("server.properties");
     
+    // A server that was never started has no server.properties yet; create one with RCON on
+    let created = !properties_path.exists() && server_path.is_dir();
+    if created {
+        create_rcon_properties(&properties_path, port).map_err(|e| e.to_string())?;
+    }
+    
     let actual_password = if properties_path.exists() {
         let properties_manager = ServerPropertiesManager::new(properties_path.clone());
         
         // Get the existing RCON password from server.properties
         match properties_manager.get_property("rcon.password") {
             Ok(existing_password) if !existing_password.is_empty() => {
-                println!("Using existing RCON password from server.properties: '{}'", existing_password);
+                log::debug!("Using existing RCON password from server.properties");
                 existing_password
             },
             _ => {
-                println!("No RCON password found in server.properties, this shouldn't happen");
+                log::warn!("No RCON password found in server.properties, this shouldn't happen");
                 return Err("No RCON password found in server.properties".to_string());
             }
         }
@@ -782,7 +2842,7 @@ async fn setup_rcon_for_server(
         return Err("Server properties file not found".to_string());
     };
     
-    println!("RCON config - host: '{}', port: {}, password: '{}'", host, port, actual_password);
+    log::debug!("RCON config - host: '{}', port: {}", host, port);
     
     let rcon_manager = RCON_MANAGER.lock().await;
     
@@ -795,60 +2855,313 @@ async fn setup_rcon_for_server(
     rcon_manager.add_server(server_name.clone(), config);
     
     // Ensure RCON is enabled in server.properties (don't change the password)
-    if properties_path.exists() {
+    let mut was_enabled = true;
+    if properties_path.exists() && !created {
         let properties_manager = ServerPropertiesManager::new(properties_path);
+        was_enabled = properties_manager.get_property("enable-rcon").is_ok_and(|enabled| enabled == "true");
         
         // Ensure RCON is enabled and the port is correct
         let _ = properties_manager.update_property("enable-rcon", "true");
         let _ = properties_manager.update_property("rcon.port", &port.to_string());
         // Don't update the password - keep the existing one
         
-        println!("RCON enabled in server.properties for {} (password unchanged)", server_name);
+        log::info!("RCON enabled in server.properties for {} (password unchanged)", server_name);
+    }
+    drop(rcon_manager);
+    if !was_enabled {
+        flag_restart_if_running(&server_name).await;
+    }
+    
+    if created || !was_enabled {
+        return Ok(format!(
+            "RCON configured for server '{}'; (re)start the server for RCON to take effect",
+            server_name
+        ));
+    }
+    Ok(format!("RCON configured for server '{}' with password from server.properties", server_name))
+}
+
+#[tauri::command]
+async fn connect_rcon(server_name: String) -> Result<String, String> {
+    log::info!("Attempting to connect to RCON for server: {}", server_name);
+    let rcon_manager = RCON_MANAGER.lock().await;
+    
+    match rcon_manager.connect(&server_name) {
+        Ok(_) => {
+            log::info!("Successfully connected to RCON for server: {}", server_name);
+            Ok(format!("Connected to RCON server '{}'", server_name))
+        },
+        Err(e) => {
+            log::warn!("Failed to connect to RCON for server {}: {}", server_name, e);
+            Err(e.to_string())
+        }
+    }
+}
+
+#[tauri::command]
+async fn disconnect_rcon(server_name: String) -> Result<String, String> {
+    let rcon_manager = RCON_MANAGER.lock().await;
+    
+    rcon_manager.disconnect(&server_name);
+    
+    Ok(format!("Disconnected from RCON server '{}'", server_name))
+}
+
+#[tauri::command]
+async fn is_rcon_connected(server_name: String) -> bool {
+    let rcon_manager = services::rcon_global::get_rcon_manager();
+    rcon_manager.is_connected(&server_name)
+}
+
+#[tauri::command]
+async fn execute_rcon_command(server_name: String, command: String) -> Result<String, String> {
+    let rcon_manager = services::rcon_global::get_rcon_manager();
+    
+    rcon_manager.execute_command(&server_name, &command)
+        .map_err(|e| e.to_string())
+}
+
+/// Like execute_rcon_command, but a reply the game reports as an error ("Unknown command",
+/// "No player was found", ...) is returned as Err, and § codes are stripped
+#[tauri::command]
+async fn execute_rcon_game_command(server_name: String, command: String) -> Result<String, String> {
+    let rcon_manager = services::rcon_global::get_rcon_manager();
+    
+    rcon_manager.execute_game_command(&server_name, &command)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn get_online_players(server_name: String) -> Result<Vec<String>, String> {
+    // The monitor keeps the roster fresh while a server is online
+    if let Some(players) = SERVER_MONITOR.lock().await.get_roster(&server_name).await {
+        return Ok(players);
+    }
+    
+    let response = services::rcon_global::get_rcon_manager()
+        .execute_command(&server_name, "list")
+        .map_err(|e| e.to_string())?;
+    services::rcon_commands::parse_list_response(&response)
+        .map(|list| list.players)
+        .ok_or_else(|| format!("Unexpected response to list: {}", response))
+}
+
+/// The RCON manager, provided the server's RCON session is already open
+fn connected_rcon_manager(server_name: &str) -> Result<Arc<RconManager>, String> {
+    let rcon_manager = services::rcon_global::get_rcon_manager();
+    if !rcon_manager.is_connected(server_name) {
+        return Err(format!("RCON is not connected for server '{}'", server_name));
+    }
+    Ok(rcon_manager)
+}
+
+/// Runs a game command on a server whose RCON session is already open
+fn execute_connected_rcon_command(server_name: &str, command: &str) -> Result<String, String> {
+    connected_rcon_manager(server_name)?
+        .execute_game_command(server_name, command)
+        .map_err(|e| e.to_string())
+}
+
+/// The world seed: the generated one over RCON while the server is online, otherwise the
+/// level-seed configured in server.properties
+#[tauri::command]
+async fn get_world_seed(server_name: String) -> Result<WorldSeed, String> {
+    if services::rcon_global::get_rcon_manager().is_connected(&server_name) {
+        let response = execute_connected_rcon_command(&server_name, "seed")?;
+        let seed = services::rcon_commands::parse_seed_response(&response)
+            .ok_or_else(|| format!("Unexpected response to seed: {}", response))?;
+        return Ok(WorldSeed { seed: Some(seed), configured: None, source: SeedSource::Rcon });
+    }
+    
+    let properties_path = get_storage_path(&server_name).join("server.properties");
+    if !properties_path.exists() {
+        return Err("Server properties file not found".to_string());
     }
+    let level_seed = ServerPropertiesManager::new(properties_path)
+        .get_property("level-seed")
+        .map_err(|e| e.to_string())?;
+    let level_seed = level_seed.trim();
+    Ok(WorldSeed {
+        seed: level_seed.parse().ok(),
+        configured: Some(level_seed.to_string()).filter(|seed| !seed.is_empty()),
+        source: SeedSource::Properties,
+    })
+}
+
+#[tauri::command]
+async fn set_world_spawn(server_name: String, x: i32, y: i32, z: i32) -> Result<SpawnPoint, String> {
+    let command = services::rcon_commands::setworldspawn_command(x, y, z)?;
+    let response = execute_connected_rcon_command(&server_name, &command)?;
+    
+    services::rcon_commands::parse_spawn_response(&response)
+        .ok_or_else(|| format!("Unexpected response to setworldspawn: {}", response))
+}
+
+#[tauri::command]
+async fn set_world_border(
+    server_name: String,
+    diameter: f64,
+    center_x: f64,
+    center_z: f64,
+) -> Result<WorldBorder, String> {
+    let [center_command, set_command] = services::rcon_commands::worldborder_commands(diameter, center_x, center_z)?;
+    
+    let response = execute_connected_rcon_command(&server_name, &center_command)?;
+    let (center_x, center_z) = services::rcon_commands::parse_worldborder_center_response(&response, (center_x, center_z))
+        .ok_or_else(|| format!("Unexpected response to worldborder center: {}", response))?;
+    
+    let response = execute_connected_rcon_command(&server_name, &set_command)?;
+    let diameter = services::rcon_commands::parse_worldborder_set_response(&response, diameter)
+        .ok_or_else(|| format!("Unexpected response to worldborder set: {}", response))?;
+    
+    Ok(WorldBorder { diameter, center_x, center_z })
+}
+
+/// Sets the time of day from a name (day, noon, night, midnight) or tick count; returns the ticks
+#[tauri::command]
+async fn set_time(server_name: String, value: String) -> Result<u32, String> {
+    let ticks = services::rcon_commands::time_ticks(&value)?;
+    let response = execute_connected_rcon_command(&server_name, &format!("time set {}", ticks))?;
+    
+    services::rcon_commands::parse_time_response(&response)
+        .ok_or_else(|| format!("Unexpected response to time set: {}", response))
+}
+
+/// Sets clear, rain or thunder weather, optionally for `duration` seconds
+#[tauri::command]
+async fn set_weather(server_name: String, weather: String, duration: Option<u32>) -> Result<Weather, String> {
+    let weather = Weather::parse(&weather)?;
+    let manager = ServerFileManager::new(PathBuf::from("storage/server_config.json"));
+    let instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
+    
+    let command = services::rcon_commands::weather_command(weather, duration, &instance.version);
+    let response = execute_connected_rcon_command(&server_name, &command)?;
+    
+    services::rcon_commands::parse_weather_response(&response)
+        .ok_or_else(|| format!("Unexpected response to weather: {}", response))
+}
+
+#[tauri::command]
+async fn teleport_player(server_name: String, player: String, x: f64, y: f64, z: f64) -> Result<ActionResult, String> {
+    let command = services::rcon_commands::teleport_command(&player, x, y, z)?;
+    let response = connected_rcon_manager(&server_name)?.execute_game_command(&server_name, &command);
     
-    Ok(format!("RCON configured for server '{}' with password from server.properties", server_name))
+    services::rcon_commands::classify_action_response(response, &["Teleported"])
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-async fn connect_rcon(server_name: String) -> Result<String, String> {
-    println!("Attempting to connect to RCON for server: {}", server_name);
-    let rcon_manager = RCON_MANAGER.lock().await;
+async fn give_item(server_name: String, player: String, item: String, count: u32) -> Result<ActionResult, String> {
+    let command = services::rcon_commands::give_command(&player, &item, count)?;
+    let response = connected_rcon_manager(&server_name)?.execute_game_command(&server_name, &command);
     
-    match rcon_manager.connect(&server_name) {
-        Ok(_) => {
-            println!("Successfully connected to RCON for server: {}", server_name);
-            Ok(format!("Connected to RCON server '{}'", server_name))
-        },
-        Err(e) => {
-            println!("Failed to connect to RCON for server {}: {}", server_name, e);
-            Err(e.to_string())
-        }
+    // "Gave 64 [Diamond] to Steve" (1.13+) or "Given [Diamond] * 64 to Steve" (older)
+    services::rcon_commands::classify_action_response(response, &["Gave ", "Given "])
+        .map_err(|e| e.to_string())
+}
+
+/// Adds a player to the whitelist: over RCON while the server runs (it would overwrite the
+/// file otherwise), or by editing whitelist.json while it is stopped. Returns the updated list.
+#[tauri::command]
+async fn add_to_whitelist(server_name: String, player_name: String) -> Result<Vec<WhitelistEntry>, String> {
+    // The name ends up in a command line, so selectors and extra arguments are refused up front
+    services::rcon_commands::check_player_name(player_name.trim())?;
+    let server_path = get_storage_path(&server_name);
+    
+    if UNIFIED_SERVER_SERVICE.lock().await.is_server_running(&server_name).await {
+        services::rcon_global::get_rcon_manager()
+            .execute_game_command(&server_name, &format!("whitelist add {}", player_name.trim()))
+            .map_err(|e| e.to_string())?;
+    } else {
+        let online_mode = ServerPropertiesManager::new(server_path.join("server.properties"))
+            .load_properties()
+            .map(|properties| properties.online_mode)
+            .unwrap_or(true);
+        let entry = services::whitelist::resolve_player(&reqwest::Client::new(), &player_name, online_mode)
+            .await
+            .map_err(|e| e.to_string())?;
+        services::whitelist::add_offline(&server_path, entry).map_err(|e| e.to_string())?;
     }
+    
+    services::whitelist::read_whitelist(&server_path).map_err(|e| e.to_string())
 }
 
+/// Removes a player from the whitelist, over RCON or in whitelist.json like add_to_whitelist
 #[tauri::command]
-async fn disconnect_rcon(server_name: String) -> Result<String, String> {
-    let rcon_manager = RCON_MANAGER.lock().await;
+async fn remove_from_whitelist(server_name: String, player_name: String) -> Result<Vec<WhitelistEntry>, String> {
+    services::rcon_commands::check_player_name(player_name.trim())?;
+    let server_path = get_storage_path(&server_name);
     
-    rcon_manager.disconnect(&server_name);
+    if UNIFIED_SERVER_SERVICE.lock().await.is_server_running(&server_name).await {
+        services::rcon_global::get_rcon_manager()
+            .execute_game_command(&server_name, &format!("whitelist remove {}", player_name.trim()))
+            .map_err(|e| e.to_string())?;
+    } else if !services::whitelist::remove_offline(&server_path, &player_name).map_err(|e| e.to_string())? {
+        return Err(format!("{} is not on the whitelist", player_name.trim()));
+    }
     
-    Ok(format!("Disconnected from RCON server '{}'", server_name))
+    services::whitelist::read_whitelist(&server_path).map_err(|e| e.to_string())
 }
 
+/// Whitelisted players from whitelist.json, which the server keeps current while it runs
 #[tauri::command]
-async fn is_rcon_connected(server_name: String) -> bool {
-    let rcon_manager = services::rcon_global::get_rcon_manager();
-    rcon_manager.is_connected(&server_name)
+fn list_whitelist(server_name: String) -> Result<Vec<WhitelistEntry>, String> {
+    services::whitelist::read_whitelist(&get_storage_path(&server_name)).map_err(|e| e.to_string())
 }
 
+/// A player's UUID and head (base64 PNG) from Mojang; UUIDs are cached for good, heads for a day
 #[tauri::command]
-async fn execute_rcon_command(server_name: String, command: String) -> Result<String, String> {
-    let rcon_manager = services::rcon_global::get_rcon_manager();
-    
-    rcon_manager.execute_command(&server_name, &command)
+async fn get_player_profile(name: String) -> Result<PlayerProfile, String> {
+    services::player_profile::get_player_profile(&reqwest::Client::new(), &name)
+        .await
         .map_err(|e| e.to_string())
 }
 
+/// Current value of a gamerule, typed as a bool or number
+#[tauri::command]
+async fn get_gamerule(server_name: String, rule: String) -> Result<GameruleValue, String> {
+    let manager = ServerFileManager::new(PathBuf::from("storage/server_config.json"));
+    let instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
+    services::gamerules::check_rule(&rule, &instance.version)?;
+    
+    let response = execute_connected_rcon_command(&server_name, &format!("gamerule {}", rule))?;
+    let raw = services::gamerules::parse_gamerule_response(&rule, &response)
+        .ok_or_else(|| format!("Unexpected response to gamerule: {}", response))?;
+    services::gamerules::typed_value(&rule, &raw)
+}
+
+/// Sets a gamerule and returns the value the server confirmed
+#[tauri::command]
+async fn set_gamerule(server_name: String, rule: String, value: String) -> Result<GameruleValue, String> {
+    let manager = ServerFileManager::new(PathBuf::from("storage/server_config.json"));
+    let instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
+    services::gamerules::check_rule(&rule, &instance.version)?;
+    let requested = services::gamerules::typed_value(&rule, &value)?;
+    
+    let response = execute_connected_rcon_command(&server_name, &format!("gamerule {} {}", rule, value.trim()))?;
+    // Some old versions only reply "Game rule has been updated"
+    match services::gamerules::parse_gamerule_response(&rule, &response) {
+        Some(raw) => services::gamerules::typed_value(&rule, &raw),
+        None => Ok(requested),
+    }
+}
+
+/// Vanilla gamerules available on the server's Minecraft version, with their types and defaults
+#[tauri::command]
+fn list_gamerules(server_name: String) -> Result<Vec<GameruleInfo>, String> {
+    let manager = ServerFileManager::new(PathBuf::from("storage/server_config.json"));
+    let instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
+    Ok(services::gamerules::list_gamerules(&instance.version))
+}
+
 #[tauri::command]
 async fn test_rcon_connection(server_name: String) -> Result<bool, String> {
     let rcon_manager = services::rcon_global::get_rcon_manager();
@@ -874,7 +3187,7 @@ async fn remove_rcon_server(server_name: String) -> Result<String, String> {
 
 #[tauri::command]
 async fn wait_for_server_ready(server_name: String, max_wait_seconds: u64) -> Result<bool, String> {
-    println!("Waiting for server '{}' to be fully ready (max {} seconds)", server_name, max_wait_seconds);
+    log::info!("Waiting for server '{}' to be fully ready (max {} seconds)", server_name, max_wait_seconds);
     
     let start_time = std::time::Instant::now();
     let max_duration = std::time::Duration::from_secs(max_wait_seconds);
@@ -893,16 +3206,16 @@ async fn wait_for_server_ready(server_name: String, max_wait_seconds: u64) -> Re
         // Check if we can connect to RCON (basic test)
         let rcon_manager = RCON_MANAGER.lock().await;
         if rcon_manager.is_connected(&server_name) {
-            println!("Server '{}' appears to be ready (RCON already connected)", server_name);
+            log::info!("Server '{}' appears to be ready (RCON already connected)", server_name);
             return Ok(true);
         }
         drop(rcon_manager);
         
-        println!("Server '{}' still starting up... ({:.1}s elapsed)", 
+        log::trace!("Server '{}' still starting up... ({:.1}s elapsed)", 
                  server_name, start_time.elapsed().as_secs_f32());
     }
     
-    println!("Timeout waiting for server '{}' to be ready", server_name);
+    log::warn!("Timeout waiting for server '{}' to be ready", server_name);
     Ok(true) // Return true anyway, let RCON connection handle the rest
 }
 
@@ -917,19 +3230,12 @@ fn fix_server_rcon_password(server_name: String) -> Result<String, String> {
     
     let properties_manager = ServerPropertiesManager::new(properties_path);
     
-    // Generate a new password without special characters
-    let mut rng = rand::thread_rng();
-    let mut new_password = String::from("allay_");
-    let chars = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
-    for _ in 0..4 {
-        let idx = rng.gen_range(0..chars.len());
-        new_password.push(chars.chars().nth(idx).unwrap());
-    }
+    let new_password = generate_strong_rcon_password(16);
     
     // Update the password in server.properties
     match properties_manager.update_property("rcon.password", &new_password) {
         Ok(_) => {
-            println!("Updated RCON password for server '{}' to: '{}'", server_name, new_password);
+            log::info!("Updated RCON password for server '{}'", server_name);
             Ok(format!("RCON password updated to: {}", new_password))
         },
         Err(e) => Err(format!("Failed to update RCON password: {}", e))
@@ -953,6 +3259,87 @@ fn check_server_rcon_enabled(server_name: String) -> Result<bool, String> {
     }
 }
 
+/// Enables or disables RCON for a server, keeping server.properties and the RCON managers in sync.
+/// Returns true when the server is running and must be restarted for the change to apply.
+#[tauri::command]
+async fn set_rcon_enabled(server_name: String, enabled: bool) -> Result<bool, String> {
+    let server_path = get_storage_path(&server_name);
+    let properties_path = server_path.join("server.properties");
+    
+    if !properties_path.exists() {
+        return Err("Server properties file not found".to_string());
+    }
+    
+    let properties_manager = ServerPropertiesManager::new(properties_path);
+    let mut properties = properties_manager.load_properties().map_err(|e| e.to_string())?;
+    let mut changed = properties.enable_rcon != enabled;
+    
+    if enabled {
+        properties.enable_rcon = true;
+        if properties.rcon_port == 0 {
+            properties.rcon_port = 25575;
+            changed = true;
+        }
+        if properties.rcon_password.len() < 12 {
+            properties.rcon_password = generate_strong_rcon_password(16);
+            changed = true;
+        }
+    } else {
+        properties.enable_rcon = false;
+    }
+    
+    if changed {
+        properties_manager.save_properties(&properties).map_err(|e| e.to_string())?;
+    }
+    
+    let global_rcon_manager = services::rcon_global::get_rcon_manager();
+    if enabled {
+        let config = RconConfig::for_server(&server_name);
+        RCON_MANAGER.lock().await.add_server(server_name.clone(), config.clone());
+        global_rcon_manager.add_server(server_name.clone(), config);
+    } else {
+        RCON_MANAGER.lock().await.remove_server(&server_name);
+        global_rcon_manager.remove_server(&server_name);
+    }
+    
+    Ok(changed && flag_restart_if_running(&server_name).await)
+}
+
+/// Stores RCON host/port/password overrides for a server, e.g. one running on another machine.
+/// Empty or missing values clear the override.
+#[tauri::command]
+async fn set_rcon_override(
+    server_name: String,
+    host: Option<String>,
+    port: Option<u16>,
+    password: Option<String>,
+) -> Result<String, String> {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    let mut instance = manager.get_instance(&server_name)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Server instance '{}' not found", server_name))?;
+    
+    instance.rcon_host = host.map(|h| h.trim().to_string()).filter(|h| !h.is_empty());
+    instance.rcon_port = port;
+    instance.rcon_password = password.filter(|p| !p.is_empty());
+    manager.update_instance(&server_name, instance).map_err(|e| e.to_string())?;
+    
+    // Re-register with the resolved config and drop any connection to the old endpoint
+    let config = RconConfig::for_server(&server_name);
+    {
+        let rcon_manager = RCON_MANAGER.lock().await;
+        rcon_manager.disconnect(&server_name);
+        rcon_manager.add_server(server_name.clone(), config.clone());
+    }
+    let global_rcon_manager = services::rcon_global::get_rcon_manager();
+    global_rcon_manager.disconnect(&server_name);
+    global_rcon_manager.add_server(server_name.clone(), config.clone());
+    
+    Ok(format!("RCON for '{}' will use {}:{}", server_name, config.host, config.port))
+}
+
 #[tauri::command]
 fn get_server_rcon_password(server_name: String) -> Result<String, String> {
     let server_path = PathBuf::from("storage").join(&server_name);
@@ -980,7 +3367,7 @@ fn get_server_rcon_password(server_name: String) -> Result<String, String> {
 fn get_system_memory_mb() -> Result<u64, String> {
     use sysinfo::System;
     
-    println!("Detecting system memory...");
+    log::debug!("Detecting system memory...");
     
     // Initialize system info
     let mut system = System::new_all();
@@ -990,30 +3377,50 @@ fn get_system_memory_mb() -> Result<u64, String> {
     let total_memory_bytes = system.total_memory();
     
     if total_memory_bytes == 0 {
-        println!("Warning: Could not detect system memory, using fallback");
+        log::warn!("Warning: Could not detect system memory, using fallback");
         return Ok(8192); // 8GB fallback
     }
     
     // Convert bytes to megabytes (1 MB = 1024 * 1024 bytes)
     let total_memory_mb = total_memory_bytes / (1024 * 1024);
     
-    println!("Detected system memory: {} MB ({} GB)", total_memory_mb, total_memory_mb / 1024);
+    log::debug!("Detected system memory: {} MB ({} GB)", total_memory_mb, total_memory_mb / 1024);
     
     // Sanity check - ensure we have at least 1GB detected
     if total_memory_mb < 1024 {
-        println!("Warning: Detected memory ({} MB) seems too low, using fallback", total_memory_mb);
+        log::warn!("Warning: Detected memory ({} MB) seems too low, using fallback", total_memory_mb);
         return Ok(4096); // 4GB fallback for systems with very low detected memory
     }
     
     // Sanity check - cap at 1TB to prevent unrealistic values
     if total_memory_mb > 1024 * 1024 {
-        println!("Warning: Detected memory ({} MB) seems too high, capping at 1TB", total_memory_mb);
+        log::warn!("Warning: Detected memory ({} MB) seems too high, capping at 1TB", total_memory_mb);
         return Ok(1024 * 1024); // 1TB cap
     }
     
     Ok(total_memory_mb)
 }
 
+/// Suggested min/recommended/max memory for a new server, capped to what this machine can spare
+#[tauri::command]
+fn recommend_memory(
+    loader: String,
+    minecraft_version: String,
+    expected_players: u32,
+    mod_count: u32,
+) -> Result<MemoryRecommendation, String> {
+    let loader_type = parse_loader_type(&loader)?;
+    let system_mb = get_system_memory_mb()?;
+    
+    Ok(services::mod_loader_strategy::recommend_memory_mb(
+        &loader_type,
+        &minecraft_version,
+        expected_players,
+        mod_count,
+        system_mb,
+    ))
+}
+
 #[tauri::command]
 async fn initialize_server_monitoring() -> Result<String, String> {
     let mut initialized = MONITORING_INITIALIZED.lock().await;
@@ -1021,11 +3428,251 @@ async fn initialize_server_monitoring() -> Result<String, String> {
         return Ok("Simple RCON monitoring already initialized".to_string());
     }
     
+    let settings = create_app_settings_manager().load_settings().unwrap_or_default();
+    
     let mut monitor = SERVER_MONITOR.lock().await;
+    monitor.set_interval(settings.monitor_interval_secs);
     monitor.start_background_monitoring();
     *initialized = true;
     
-    Ok("Simple RCON monitoring initialized (5s intervals)".to_string())
+    Ok(format!("Simple RCON monitoring initialized ({}s intervals)", monitor.interval().as_secs()))
+}
+
+/// Exposes the command layer to the HTTP API so both share the same behavior
+#[cfg(feature = "http-api")]
+struct AppServerController;
+
+#[cfg(feature = "http-api")]
+#[async_trait::async_trait]
+impl ServerController for AppServerController {
+    async fn list_servers(&self) -> Result<Vec<ApiServerSummary>, String> {
+        let mut summaries = Vec::new();
+        for instance in get_all_server_instances()? {
+            summaries.push(self.server_status(&instance.name).await?);
+        }
+        Ok(summaries)
+    }
+    
+    async fn server_status(&self, server_name: &str) -> Result<ApiServerSummary, String> {
+        let details = get_server_details(server_name.to_string()).await?;
+        Ok(ApiServerSummary {
+            name: details.instance.name,
+            version: details.instance.version,
+            mod_loader: details.instance.mod_loader,
+            is_running: details.is_running,
+            status: details.status,
+        })
+    }
+    
+    async fn start_server(&self, server_name: &str) -> Result<String, String> {
+        let loader = get_server_loader_type(server_name.to_string())?;
+        start_server(server_name.to_string(), loader).await
+    }
+    
+    async fn stop_server(&self, server_name: &str) -> Result<String, String> {
+        stop_server(server_name.to_string(), None).await
+    }
+    
+    async fn execute_command(&self, server_name: &str, command: &str) -> Result<String, String> {
+        execute_rcon_command(server_name.to_string(), command.to_string()).await
+    }
+    
+    async fn dashboard_snapshot(&self) -> Result<DashboardSnapshot, String> {
+        get_dashboard_snapshot().await
+    }
+}
+
+/// Starts, restarts or stops the HTTP API to match the saved settings
+async fn apply_http_api_settings(settings: &AppSettings) -> Result<(), String> {
+    let mut api = HTTP_API.lock().await;
+    
+    #[cfg(feature = "http-api")]
+    {
+        if let Some(handle) = api.take() {
+            handle.shutdown();
+        }
+        if !settings.http_api_enabled {
+            return Ok(());
+        }
+        
+        let token = settings.http_api_token.clone()
+            .ok_or_else(|| "HTTP API token is not set".to_string())?;
+        let address: std::net::SocketAddr = format!("{}:{}", settings.http_api_bind, settings.http_api_port)
+            .parse()
+            .map_err(|e| format!("Invalid HTTP API address: {}", e))?;
+        
+        let handle = services::http_api::start_http_api(address, token, Arc::new(AppServerController))
+            .await
+            .map_err(|e| format!("Failed to start HTTP API: {}", e))?;
+        *api = Some(handle);
+        Ok(())
+    }
+    
+    #[cfg(not(feature = "http-api"))]
+    {
+        *api = None;
+        if settings.http_api_enabled {
+            Err("Allay was built without the http-api feature".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+async fn http_api_status(settings: &AppSettings) -> HttpApiStatus {
+    let api = HTTP_API.lock().await;
+    let configured_address = format!("{}:{}", settings.http_api_bind, settings.http_api_port);
+    
+    // Report the real address once bound, which differs when port 0 was requested
+    #[cfg(feature = "http-api")]
+    let address = api.as_ref().map(|handle| handle.address.to_string()).unwrap_or(configured_address);
+    #[cfg(not(feature = "http-api"))]
+    let address = configured_address;
+    
+    HttpApiStatus {
+        available: cfg!(feature = "http-api"),
+        enabled: settings.http_api_enabled,
+        running: api.is_some(),
+        address,
+        token: settings.http_api_token.clone(),
+    }
+}
+
+#[tauri::command]
+async fn get_http_api_status() -> Result<HttpApiStatus, String> {
+    let settings = create_app_settings_manager().load_settings().map_err(|e| e.to_string())?;
+    Ok(http_api_status(&settings).await)
+}
+
+#[tauri::command]
+async fn set_http_api_enabled(enabled: bool, bind: Option<String>, port: Option<u16>) -> Result<HttpApiStatus, String> {
+    let manager = create_app_settings_manager();
+    let mut settings = manager.load_settings().map_err(|e| e.to_string())?;
+    
+    settings.http_api_enabled = enabled;
+    if let Some(bind) = bind.map(|b| b.trim().to_string()).filter(|b| !b.is_empty()) {
+        settings.http_api_bind = bind;
+    }
+    if let Some(port) = port {
+        settings.http_api_port = port;
+    }
+    if enabled && settings.http_api_token.is_none() {
+        settings.http_api_token = Some(generate_strong_rcon_password(32));
+    }
+    
+    apply_http_api_settings(&settings).await?;
+    manager.save_settings(&settings).map_err(|e| e.to_string())?;
+    
+    Ok(http_api_status(&settings).await)
+}
+
+#[tauri::command]
+async fn regenerate_http_api_token() -> Result<HttpApiStatus, String> {
+    let manager = create_app_settings_manager();
+    let mut settings = manager.load_settings().map_err(|e| e.to_string())?;
+    
+    settings.http_api_token = Some(generate_strong_rcon_password(32));
+    manager.save_settings(&settings).map_err(|e| e.to_string())?;
+    
+    // Restart so the old token stops working immediately
+    apply_http_api_settings(&settings).await?;
+    Ok(http_api_status(&settings).await)
+}
+
+/// Sets or clears (empty url) the status-change webhook for a server
+#[tauri::command]
+fn set_server_webhook(
+    server_name: String,
+    url: Option<String>,
+    template: Option<String>,
+    events: Option<Vec<String>>,
+) -> Result<String, String> {
+    let manager = create_app_settings_manager();
+    let mut settings = manager.load_settings().map_err(|e| e.to_string())?;
+    
+    match url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty()) {
+        Some(url) => {
+            if !url.starts_with("https://") && !url.starts_with("http://") {
+                return Err("Webhook URL must start with http:// or https://".to_string());
+            }
+            settings.webhooks.insert(server_name.clone(), util::WebhookConfig {
+                url,
+                template: template.filter(|t| !t.trim().is_empty()),
+                events: events.filter(|e| !e.is_empty()).unwrap_or_else(util::default_webhook_events),
+            });
+        }
+        None => {
+            settings.webhooks.remove(&server_name);
+        }
+    }
+    
+    manager.save_settings(&settings).map_err(|e| e.to_string())?;
+    Ok(format!("Webhook updated for server '{}'", server_name))
+}
+
+#[tauri::command]
+async fn test_server_webhook(server_name: String) -> Result<String, String> {
+    services::notification_service::send_test_notification(&server_name).await?;
+    Ok(format!("Test notification sent for server '{}'", server_name))
+}
+
+/// Servers re-attached or found crashed when the app started
+#[tauri::command]
+async fn get_reattach_report() -> Result<ReattachReport, String> {
+    Ok(REATTACH_REPORT.lock().await.clone())
+}
+
+#[tauri::command]
+fn get_app_settings() -> Result<AppSettings, String> {
+    create_app_settings_manager().load_settings().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn set_query_retries(retries: u32) -> Result<u32, String> {
+    let retries = util::clamp_query_retries(retries);
+    
+    let manager = create_app_settings_manager();
+    let mut settings = manager.load_settings().map_err(|e| e.to_string())?;
+    settings.query_retries = retries;
+    manager.save_settings(&settings).map_err(|e| e.to_string())?;
+    Ok(retries)
+}
+
+#[tauri::command]
+async fn set_monitor_interval(seconds: u64) -> Result<u64, String> {
+    let seconds = util::clamp_monitor_interval(seconds);
+    
+    let manager = create_app_settings_manager();
+    let mut settings = manager.load_settings().map_err(|e| e.to_string())?;
+    settings.monitor_interval_secs = seconds;
+    manager.save_settings(&settings).map_err(|e| e.to_string())?;
+    
+    // Restarts the background task with the new period if it is already running
+    let mut monitor = SERVER_MONITOR.lock().await;
+    monitor.set_interval(seconds);
+    
+    Ok(seconds)
+}
+
+#[tauri::command]
+fn set_log_level(level: String) -> Result<String, String> {
+    let filter = util::parse_log_level(&level);
+    let level = filter.to_string().to_lowercase();
+
+    let manager = create_app_settings_manager();
+    let mut settings = manager.load_settings().map_err(|e| e.to_string())?;
+    settings.log_level = level.clone();
+    manager.save_settings(&settings).map_err(|e| e.to_string())?;
+
+    util::set_app_log_level(filter);
+    Ok(level)
+}
+
+#[tauri::command]
+fn get_app_log_path() -> Result<String, String> {
+    let path = util::app_log_path();
+    let absolute = std::env::current_dir().map_err(|e| e.to_string())?.join(path);
+    Ok(absolute.to_string_lossy().to_string())
 }
 
 #[tauri::command]
@@ -1036,30 +3683,218 @@ async fn diagnose_event_system() -> Result<String, String> {
     let diagnostic = format!(
         "🎯 Simple RCON Monitor Status:\n\
          • Servers monitored: {}\n\
-         • Mode: RCON-only monitoring (5s intervals)\n\
+         • Mode: RCON-only monitoring ({}s intervals)\n\
          • Status: {}",
         statuses.len(),
+        monitor.interval().as_secs(),
         if statuses.is_empty() { "No servers" } else { "Active" }
     );
     
-    println!("{}", diagnostic);
+    log::info!("{}", diagnostic);
     Ok(diagnostic)
 }
 
+/// Puts a wake listener on the game port of an idle-stopped server if it opted in
+async fn arm_wake_on_demand(server_name: &str, wake_sender: tokio::sync::mpsc::UnboundedSender<String>) {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    let wake_on_demand = matches!(manager.get_instance(server_name), Ok(Some(instance)) if instance.wake_on_demand);
+    if !wake_on_demand {
+        return;
+    }
+    
+    let port = read_server_port(server_name);
+    let service = UNIFIED_SERVER_SERVICE.lock().await;
+    if let Err(e) = service.arm_wake_listener(server_name, port, wake_sender).await {
+        log::warn!("{}", e);
+    }
+}
+
+/// Tells the UI and the desktop that a server crashed, along with its newest crash report
+fn report_crash(app_handle: &tauri::AppHandle, server_name: &str) {
+    services::notification_service::notify_status_change(server_name, "crashed");
+    let event = ServerCrashedEvent {
+        server_name: server_name.to_string(),
+        latest_crash_report: services::crash_reports::latest_crash_report(&get_storage_path(server_name)),
+    };
+    if let Err(e) = app_handle.emit("server-crashed", &event) {
+        log::warn!("Failed to emit server-crashed for {}: {}", server_name, e);
+    }
+}
+
+/// Checks a server that went offline without being stopped. RCON drops before the JVM has
+/// finished exiting, so give the process a moment; if it keeps running it is only unreachable.
+async fn check_unexpected_exit(app_handle: &tauri::AppHandle, server_name: String) {
+    for _ in 0..10 {
+        let exited = UNIFIED_SERVER_SERVICE.lock().await.reap_exited_server(&server_name).await;
+        if let Some(crashed) = exited {
+            SERVER_MONITOR.lock().await.stop_monitoring(&server_name).await;
+            // Some versions exit with 0 after a crash, but they still write a report first;
+            // a clean exit without one is a "stop" typed in the console
+            let wrote_report = services::crash_reports::latest_crash_report(&get_storage_path(&server_name))
+                .and_then(|report| report.modified)
+                .is_some_and(|modified| chrono::Utc::now() - modified < chrono::Duration::minutes(1));
+            if crashed || wrote_report {
+                log::warn!("{} exited unexpectedly", server_name);
+                report_crash(app_handle, &server_name);
+            }
+            return;
+        }
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+}
+
+/// Starts a sleeping server after a player tried to connect to it
+async fn wake_server(app_handle: &tauri::AppHandle, server_name: String) {
+    if let Err(e) = app_handle.emit("server-waking", &server_name) {
+        log::warn!("Failed to emit server-waking event: {}", e);
+    }
+    
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    let loader = match manager.get_instance(&server_name) {
+        Ok(Some(instance)) => instance.mod_loader,
+        _ => {
+            log::warn!("Cannot wake {}: instance not found", server_name);
+            return;
+        }
+    };
+    
+    match start_server(server_name.clone(), loader).await {
+        Ok(_) => {
+            let monitor = SERVER_MONITOR.lock().await;
+            monitor.update_server_status(&server_name, ServerStatus::Starting).await;
+        }
+        Err(e) => log::warn!("Failed to wake {}: {}", server_name, e),
+    }
+}
+
+/// Runs a scheduled backup, reporting it through the "backup-started" and "backup-completed" events
+async fn run_scheduled_backup(app_handle: &tauri::AppHandle, server_name: String) {
+    if let Err(e) = app_handle.emit("backup-started", &server_name) {
+        log::warn!("Failed to emit backup-started event: {}", e);
+    }
+    
+    let event = match backup_live_server(&server_name, true, "scheduled").await {
+        Ok(backup) => BackupCompletedEvent { server_name, backup: Some(backup), error: None },
+        Err(e) => {
+            log::warn!("Scheduled backup of {} failed: {}", server_name, e);
+            BackupCompletedEvent { server_name, backup: None, error: Some(e) }
+        }
+    };
+    if let Err(e) = app_handle.emit("backup-completed", &event) {
+        log::warn!("Failed to emit backup-completed event: {}", e);
+    }
+}
+
+/// Arms the backup timer of every server that has a schedule
+async fn arm_backup_schedules() {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    let instances = match manager.get_all_instances() {
+        Ok(instances) => instances,
+        Err(e) => {
+            log::warn!("Failed to load instances for backup schedules: {}", e);
+            return;
+        }
+    };
+    
+    let mut scheduler = BACKUP_SCHEDULER.lock().await;
+    for instance in instances {
+        if instance.backup_schedule.is_some() {
+            scheduler.arm(&instance.name, instance.backup_schedule);
+        }
+    }
+}
+
+/// Starts every instance flagged auto_start. Failures are reported through the
+/// "auto-start-failed" event so they never block app startup.
+async fn auto_start_servers(app_handle: tauri::AppHandle) {
+    let config_path = PathBuf::from("storage/server_config.json");
+    let manager = ServerFileManager::new(config_path);
+    
+    let instances: Vec<ServerInstance> = match manager.get_all_instances() {
+        Ok(instances) => instances
+            .into_iter()
+            .filter(|instance| instance.auto_start && instance.creation_status == ServerCreationStatus::Completed)
+            .collect(),
+        Err(e) => {
+            log::warn!("Failed to load instances for auto-start: {}", e);
+            return;
+        }
+    };
+    
+    if instances.is_empty() {
+        return;
+    }
+    
+    log::info!("Auto-starting {} server(s)", instances.len());
+    let results = start_servers_staggered(instances, std::time::Duration::from_secs(3)).await;
+    
+    let failures: Vec<BulkOperationResult> = results.into_iter().filter(|result| !result.success).collect();
+    if !failures.is_empty() {
+        for failure in &failures {
+            log::warn!("Auto-start failed for {}: {}", failure.server_name, failure.error.as_deref().unwrap_or("unknown error"));
+        }
+        if let Err(e) = app_handle.emit("auto-start-failed", &failures) {
+            log::warn!("Failed to emit auto-start event: {}", e);
+        }
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let settings = create_app_settings_manager().load_settings().unwrap_or_default();
+    util::init_app_logger(util::parse_log_level(&settings.log_level));
+    log::info!("Allay starting (log level: {})", settings.log_level);
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .invoke_handler(tauri::generate_handler![
             greet,
             create_server_instance,
             create_server_transactional,
+            repair_server,
             cleanup_incomplete_servers,
+            export_server_bundle,
+            import_server_bundle,
+            detect_world_version,
+            set_world_preset,
+            set_server_icon,
+            get_server_icon,
+            remove_server_icon,
+            list_server_files,
+            read_server_file,
+            write_server_file,
+            ensure_fabric_api,
+            install_mod_from_path,
+            search_modrinth_projects,
+            get_paper_builds,
+            get_paper_build_info,
+            pin_paper_build,
+            check_for_updates,
+            apply_update,
+            install_modrinth_project,
             get_all_server_instances,
+            get_server_details,
+            get_world_size,
+            get_server_uptime,
+            get_dashboard_snapshot,
             remove_server_instance,
             delete_server_completely,
+            recreate_server,
             update_server_description,
             update_server_memory,
+            update_server_auto_start,
+            update_server_detached_mode,
+            update_server_auto_update_paper,
+            update_server_nogui,
+            update_server_idle_shutdown,
+            update_server_stop_countdown,
+            update_server_env_vars,
+            update_server_wake_on_demand,
             get_minecraft_versions,
             get_all_minecraft_versions,
             get_version_summary,
@@ -1069,10 +3904,16 @@ pub fn run() {
             setup_server,
             start_server,
             stop_server,
+            get_launch_command,
+            restart_server,
+            kill_server,
+            start_all_servers,
+            stop_all_servers,
             toggle_server,
             get_server_loader_type,
             is_server_running,
             query_server_status,
+            query_server_full,
             start_server_monitoring,
             stop_server_monitoring,
             get_server_monitor_status,
@@ -1087,19 +3928,92 @@ pub fn run() {
             get_server_motd,
             get_server_max_players,
             update_server_property,
+            update_server_properties,
+            get_property_schema,
             setup_rcon_for_server,
             connect_rcon,
             disconnect_rcon,
             is_rcon_connected,
             execute_rcon_command,
+            execute_rcon_game_command,
+            get_online_players,
             test_rcon_connection,
+            set_world_spawn,
+            set_world_border,
+            set_time,
+            set_weather,
+            teleport_player,
+            give_item,
+            add_to_whitelist,
+            remove_from_whitelist,
+            list_whitelist,
+            get_player_profile,
+            get_gamerule,
+            set_gamerule,
+            list_gamerules,
             get_connected_rcon_servers,
             remove_rcon_server,
             wait_for_server_ready,
             check_server_rcon_enabled,
             fix_server_rcon_password,
             get_server_rcon_password,
-            get_system_memory_mb
+            set_rcon_enabled,
+            set_rcon_override,
+            set_server_port,
+            set_rcon_port,
+            set_query_port,
+            set_level_name,
+            diagnose_server,
+            check_java,
+            install_java_runtime,
+            set_use_bundled_java,
+            get_system_memory_mb,
+            recommend_memory,
+            get_app_settings,
+            set_log_level,
+            get_app_log_path,
+            set_monitor_interval,
+            set_query_retries,
+            get_reattach_report,
+            set_server_webhook,
+            test_server_webhook,
+            get_http_api_status,
+            set_http_api_enabled,
+            regenerate_http_api_token,
+            migrate_loader,
+            revert_to_vanilla,
+            clear_world,
+            create_backup,
+            list_backups,
+            is_combination_supported,
+            prune_backups,
+            update_server_backup_retention,
+            restore_backup,
+            update_server_backup_schedule,
+            save_server_as_template,
+            list_templates,
+            delete_template,
+            create_server_from_template,
+            add_tag,
+            remove_tag,
+            get_servers_by_tag,
+            reorder_servers,
+            validate_config,
+            search_versions,
+            cancel_setup,
+            set_server_bind_ip,
+            get_port_map,
+            find_orphaned_cache_entries,
+            clear_orphaned_cache,
+            audit_cache,
+            get_world_seed,
+            update_server_stop_commands,
+            list_crash_reports,
+            read_crash_report,
+            check_server_up_to_date,
+            batch,
+            apply_properties_template,
+            detect_server_loader
         ])
         .setup(|app| {
             // Set app handle for event emission in Simple RCON Monitor
@@ -1107,8 +4021,91 @@ pub fn run() {
             
             tauri::async_runtime::spawn(async move {
                 let mut monitor = SERVER_MONITOR.lock().await;
-                monitor.set_app_handle(app_handle);
-                println!("🎯 Simple RCON Monitor configured with app handle for events");
+                monitor.set_app_handle(app_handle.clone());
+                log::info!("Simple RCON Monitor configured with app handle for events");
+                
+                // Idle servers are stopped through the regular stop path so monitoring and RCON are cleaned up
+                let (idle_sender, mut idle_receiver) = tokio::sync::mpsc::unbounded_channel::<String>();
+                monitor.set_idle_shutdown_sender(idle_sender);
+                let (offline_sender, mut offline_receiver) = tokio::sync::mpsc::unbounded_channel::<String>();
+                monitor.set_offline_sender(offline_sender);
+                drop(monitor);
+                
+                let offline_app_handle = app_handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    while let Some(server_name) = offline_receiver.recv().await {
+                        check_unexpected_exit(&offline_app_handle, server_name).await;
+                    }
+                });
+                
+                let (wake_sender, mut wake_receiver) = tokio::sync::mpsc::unbounded_channel::<String>();
+                tauri::async_runtime::spawn(async move {
+                    while let Some(server_name) = idle_receiver.recv().await {
+                        // Nobody is online to warn
+                        if let Err(e) = stop_server(server_name.clone(), Some(0)).await {
+                            log::warn!("Idle shutdown failed: {}", e);
+                            SERVER_MONITOR.lock().await.cancel_idle_shutdown(&server_name).await;
+                            continue;
+                        }
+                        arm_wake_on_demand(&server_name, wake_sender.clone()).await;
+                    }
+                });
+                
+                let (backup_sender, mut backup_receiver) = tokio::sync::mpsc::unbounded_channel::<String>();
+                BACKUP_SCHEDULER.lock().await.set_trigger_sender(backup_sender);
+                let backup_app_handle = app_handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    while let Some(server_name) = backup_receiver.recv().await {
+                        run_scheduled_backup(&backup_app_handle, server_name).await;
+                    }
+                });
+                arm_backup_schedules().await;
+                
+                let (progress_sender, mut progress_receiver) = tokio::sync::mpsc::unbounded_channel::<InstallProgressEvent>();
+                set_install_progress_sender(progress_sender);
+                let progress_app_handle = app_handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    while let Some(event) = progress_receiver.recv().await {
+                        if let Err(e) = progress_app_handle.emit("install-progress", &event) {
+                            log::warn!("Failed to emit install progress: {}", e);
+                        }
+                    }
+                });
+                
+                let wake_app_handle = app_handle.clone();
+                tauri::async_runtime::spawn(async move {
+                    while let Some(server_name) = wake_receiver.recv().await {
+                        wake_server(&wake_app_handle, server_name).await;
+                    }
+                });
+                
+                // A damaged config would otherwise hide every server
+                match ServerFileManager::new(PathBuf::from("storage/server_config.json")).validate_config() {
+                    Ok(report) if !report.valid => {
+                        log::warn!("Repaired server_config.json, dropped entries: {:?}", report.dropped);
+                    }
+                    Ok(_) => {}
+                    Err(e) => log::error!("Failed to validate server_config.json: {}", e),
+                }
+                
+                // Pick up servers that kept running while the app was closed
+                let report = UNIFIED_SERVER_SERVICE.lock().await.reattach_servers().await;
+                let monitor = SERVER_MONITOR.lock().await;
+                for server_name in &report.reattached {
+                    monitor.start_monitoring(server_name.clone()).await;
+                }
+                drop(monitor);
+                for server_name in &report.crashed {
+                    report_crash(&app_handle, server_name);
+                }
+                *REATTACH_REPORT.lock().await = report;
+                
+                auto_start_servers(app_handle).await;
+                
+                let settings = create_app_settings_manager().load_settings().unwrap_or_default();
+                if let Err(e) = apply_http_api_settings(&settings).await {
+                    log::warn!("{}", e);
+                }
             });
             
             Ok(())
@@ -0,0 +1,12 @@
+use serde::Serialize;
+
+/// State of the local HTTP control API as shown in settings
+#[derive(Debug, Clone, Serialize)]
+pub struct HttpApiStatus {
+    /// False when Allay was built without the http-api feature
+    pub available: bool,
+    pub enabled: bool,
+    pub running: bool,
+    pub address: String,
+    pub token: Option<String>,
+}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// One command in a batch, with the same arguments the frontend would pass to invoke()
+#[derive(Debug, Clone, Deserialize)]
+pub struct BatchRequest {
+    pub command: String,
+    #[serde(default)]
+    pub args: Value,
+}
+
+/// Outcome of one batched command, in the order the requests were given
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchResult {
+    pub command: String,
+    pub success: bool,
+    pub data: Option<Value>,
+    pub error: Option<String>,
+}
+
+impl BatchResult {
+    pub fn succeeded(command: &str, data: Value) -> Self {
+        Self {
+            command: command.to_string(),
+            success: true,
+            data: Some(data),
+            error: None,
+        }
+    }
+
+    pub fn failed(command: &str, error: impl Into<String>) -> Self {
+        Self {
+            command: command.to_string(),
+            success: false,
+            data: None,
+            error: Some(error.into()),
+        }
+    }
+}
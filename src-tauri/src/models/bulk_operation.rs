@@ -0,0 +1,27 @@
+use serde::Serialize;
+
+/// Outcome for one server in a start-all/stop-all operation
+#[derive(Debug, Clone, Serialize)]
+pub struct BulkOperationResult {
+    pub server_name: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl BulkOperationResult {
+    pub fn succeeded(server_name: &str) -> Self {
+        Self {
+            server_name: server_name.to_string(),
+            success: true,
+            error: None,
+        }
+    }
+
+    pub fn failed(server_name: &str, error: impl Into<String>) -> Self {
+        Self {
+            server_name: server_name.to_string(),
+            success: false,
+            error: Some(error.into()),
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// Every managed server in one structure, for external dashboards to poll
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardSnapshot {
+    pub generated_at: DateTime<Utc>,
+    pub servers: Vec<DashboardServer>,
+}
+
+/// A server's state as the monitor last saw it
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardServer {
+    pub name: String,
+    pub loader: String,
+    pub version: String,
+    pub status: String,
+    /// From the monitor's last roster poll; None while it hasn't seen the server online
+    pub player_count: Option<usize>,
+    pub uptime_secs: Option<u64>,
+    /// Share of one core used since the previous snapshot; the first snapshot reads 0
+    pub cpu_percent: Option<f32>,
+    pub memory_bytes: Option<u64>,
+    /// Result of the last check_server_up_to_date, None if it was never checked
+    pub update_available: Option<bool>,
+}
@@ -1,10 +1,20 @@
 
 pub mod server;
 pub mod server_state;
+pub mod server_details;
+pub mod dashboard;
+pub mod bulk_operation;
+pub mod batch;
+pub mod api;
 pub mod version;
 pub mod query;
 
 pub use server::Server;
 pub use server_state::{ServerState, ServerType};
+pub use server_details::ServerDetails;
+pub use dashboard::{DashboardServer, DashboardSnapshot};
+pub use bulk_operation::BulkOperationResult;
+pub use batch::{BatchRequest, BatchResult};
+pub use api::HttpApiStatus;
 pub use version::*;
 pub use query::*;
\ No newline at end of file
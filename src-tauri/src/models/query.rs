@@ -8,6 +8,17 @@ pub struct QueryResponse {
     pub motd: Option<String>,
     pub version: Option<String>,
     pub error: Option<String>,
+    /// Names of online players (full-stat query only)
+    #[serde(default)]
+    pub players: Vec<String>,
+    /// Plugins reported by the server, e.g. by Paper (full-stat query only)
+    #[serde(default)]
+    pub plugins: Vec<String>,
+    /// World name reported as "map" (full-stat query only)
+    #[serde(default)]
+    pub map: Option<String>,
+    #[serde(default)]
+    pub game_type: Option<String>,
 }
 
 impl QueryResponse {
@@ -19,6 +30,10 @@ impl QueryResponse {
             motd: None,
             version: None,
             error: Some(error),
+            players: Vec::new(),
+            plugins: Vec::new(),
+            map: None,
+            game_type: None,
         }
     }
     
@@ -30,6 +45,10 @@ impl QueryResponse {
             motd: Some(motd),
             version: Some(version),
             error: None,
+            players: Vec::new(),
+            plugins: Vec::new(),
+            map: None,
+            game_type: None,
         }
     }
 }
@@ -38,7 +57,10 @@ impl QueryResponse {
 pub struct QueryConfig {
     pub host: String,
     pub port: u16,
+    /// Total time budget, shared across all attempts
     pub timeout_ms: u64,
+    /// Extra attempts after the first one fails (UDP responses can be dropped on busy servers)
+    pub retries: u32,
 }
 
 impl Default for QueryConfig {
@@ -47,6 +69,7 @@ impl Default for QueryConfig {
             host: "127.0.0.1".to_string(),
             port: 25565,
             timeout_ms: 5000,
+            retries: 0,
         }
     }
 }
\ No newline at end of file
@@ -0,0 +1,21 @@
+use crate::util::ServerInstance;
+use serde::Serialize;
+
+/// Aggregated view of a server for the dashboard
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerDetails {
+    #[serde(flatten)]
+    pub instance: ServerInstance,
+    pub is_running: bool,
+    /// Stopped for being idle, with a wake listener waiting for a player to connect
+    pub is_sleeping: bool,
+    pub status: String,
+    /// Free space on the filesystem holding the storage folder, if it could be measured
+    pub disk_free_bytes: Option<u64>,
+    /// Total size of the world folders, from a measurement up to ten minutes old
+    pub world_size_bytes: Option<u64>,
+    /// Seconds since the server process started, None while offline
+    pub uptime_secs: Option<u64>,
+    /// Result of the last Paper update check, None if it was never checked (or isn't Paper)
+    pub paper_update_available: Option<bool>,
+}
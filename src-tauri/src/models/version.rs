@@ -20,7 +20,7 @@ pub enum VersionType {
     Alpha,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum LoaderType {
     Vanilla,
     Fabric,
@@ -28,6 +28,7 @@ pub enum LoaderType {
     NeoForge,
     Paper,
     Quilt,
+    Spigot,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -116,6 +117,46 @@ pub struct PaperProject {
     pub versions: Vec<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct PaperBuilds {
+    pub builds: Vec<PaperBuild>,
+}
+
+/// A single Paper build as returned by the PaperMC v2 API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperBuild {
+    pub build: u32,
+    pub time: DateTime<Utc>,
+    /// "default" for stable builds, "experimental" for early builds of a new Minecraft version
+    pub channel: String,
+    #[serde(default)]
+    pub promoted: bool,
+    #[serde(default)]
+    pub changes: Vec<PaperChange>,
+    #[serde(default)]
+    pub downloads: std::collections::HashMap<String, PaperDownload>,
+}
+
+impl PaperBuild {
+    pub fn is_experimental(&self) -> bool {
+        self.channel.eq_ignore_ascii_case("experimental")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperChange {
+    pub commit: String,
+    pub summary: String,
+    #[serde(default)]
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaperDownload {
+    pub name: String,
+    pub sha256: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct QuiltVersions {
     pub game: Vec<QuiltGameVersion>,
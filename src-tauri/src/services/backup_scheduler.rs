@@ -0,0 +1,191 @@
+use crate::util::BackupSchedule;
+use chrono::{DateTime, Local, NaiveTime, TimeZone};
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::task::JoinHandle;
+
+/// Sends the server name on the trigger channel whenever one of its automatic backups is due.
+/// The backup itself is run by whoever receives the name, so it goes through the same path as
+/// a manual backup.
+pub struct BackupScheduler {
+    trigger_sender: Option<UnboundedSender<String>>,
+    timers: HashMap<String, JoinHandle<()>>,
+}
+
+impl BackupScheduler {
+    pub fn new() -> Self {
+        Self {
+            trigger_sender: None,
+            timers: HashMap::new(),
+        }
+    }
+
+    pub fn set_trigger_sender(&mut self, sender: UnboundedSender<String>) {
+        self.trigger_sender = Some(sender);
+    }
+
+    /// Starts (or replaces) the timer of a server; None just cancels it
+    pub fn arm(&mut self, server_name: &str, schedule: Option<BackupSchedule>) {
+        self.disarm(server_name);
+
+        let Some(schedule) = schedule else {
+            return;
+        };
+        let Some(sender) = self.trigger_sender.clone() else {
+            log::warn!("Backup scheduler is not running; {} will not be backed up automatically", server_name);
+            return;
+        };
+
+        let name = server_name.to_string();
+        let timer = tokio::spawn(async move {
+            loop {
+                let Some(wait) = time_until_next_run(&schedule, Local::now()) else {
+                    log::warn!("Invalid backup schedule for {}: {:?}", name, schedule);
+                    return;
+                };
+                tokio::time::sleep(wait).await;
+                if sender.send(name.clone()).is_err() {
+                    return;
+                }
+            }
+        });
+
+        log::info!("Scheduled automatic backups of {}", server_name);
+        self.timers.insert(server_name.to_string(), timer);
+    }
+
+    pub fn disarm(&mut self, server_name: &str) {
+        if let Some(timer) = self.timers.remove(server_name) {
+            timer.abort();
+        }
+    }
+}
+
+/// Parses the "HH:MM" time of a daily schedule
+pub fn parse_schedule_time(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time.trim(), "%H:%M").ok()
+}
+
+/// How long from `now` until the schedule is next due, or None if the schedule is invalid.
+/// Intervals count from when the timer was armed, so they restart with the app.
+pub fn time_until_next_run<Tz: TimeZone>(schedule: &BackupSchedule, now: DateTime<Tz>) -> Option<Duration> {
+    match schedule {
+        BackupSchedule::Interval { minutes } if *minutes > 0 => Some(Duration::from_secs(*minutes as u64 * 60)),
+        BackupSchedule::Interval { .. } => None,
+        BackupSchedule::Daily { time } => {
+            let time = parse_schedule_time(time)?;
+            let today = now.date_naive().and_time(time);
+            let next = if today > now.naive_local() {
+                today
+            } else {
+                today + chrono::Duration::days(1)
+            };
+            // A time skipped by a DST change runs an hour later instead
+            let zone = now.timezone();
+            let next = zone
+                .from_local_datetime(&next)
+                .earliest()
+                .or_else(|| zone.from_local_datetime(&(next + chrono::Duration::hours(1))).earliest())?;
+            (next - now).to_std().ok()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{FixedOffset, MappedLocalTime, NaiveDate, NaiveDateTime};
+
+    /// 2024-03-31T01:00:00Z, when Central European clocks jumped from 02:00 to 03:00
+    const SPRING_FORWARD: i64 = 1_711_846_800;
+
+    /// Central European time around the 2024 spring change, enough to test a skipped hour
+    #[derive(Debug, Clone, Copy)]
+    struct SpringForwardZone;
+
+    impl SpringForwardZone {
+        fn winter() -> FixedOffset {
+            FixedOffset::east_opt(3600).unwrap()
+        }
+
+        fn summer() -> FixedOffset {
+            FixedOffset::east_opt(7200).unwrap()
+        }
+    }
+
+    impl TimeZone for SpringForwardZone {
+        type Offset = FixedOffset;
+
+        fn from_offset(_: &FixedOffset) -> Self {
+            SpringForwardZone
+        }
+
+        fn offset_from_local_date(&self, local: &NaiveDate) -> MappedLocalTime<FixedOffset> {
+            self.offset_from_local_datetime(&local.and_time(NaiveTime::MIN))
+        }
+
+        fn offset_from_local_datetime(&self, local: &NaiveDateTime) -> MappedLocalTime<FixedOffset> {
+            // Local 02:00-03:00 on the day of the change never happens
+            let local_seconds = local.and_utc().timestamp();
+            if local_seconds < SPRING_FORWARD + 3600 {
+                MappedLocalTime::Single(Self::winter())
+            } else if local_seconds < SPRING_FORWARD + 7200 {
+                MappedLocalTime::None
+            } else {
+                MappedLocalTime::Single(Self::summer())
+            }
+        }
+
+        fn offset_from_utc_date(&self, utc: &NaiveDate) -> FixedOffset {
+            self.offset_from_utc_datetime(&utc.and_time(NaiveTime::MIN))
+        }
+
+        fn offset_from_utc_datetime(&self, utc: &NaiveDateTime) -> FixedOffset {
+            if utc.and_utc().timestamp() < SPRING_FORWARD {
+                Self::winter()
+            } else {
+                Self::summer()
+            }
+        }
+    }
+
+    fn at<Tz: TimeZone>(zone: Tz, date: (i32, u32, u32), hour: u32, minute: u32) -> DateTime<Tz> {
+        zone.with_ymd_and_hms(date.0, date.1, date.2, hour, minute, 0).unwrap()
+    }
+
+    fn daily(time: &str) -> BackupSchedule {
+        BackupSchedule::Daily { time: time.to_string() }
+    }
+
+    #[test]
+    fn intervals_must_be_positive() {
+        let now = at(FixedOffset::east_opt(0).unwrap(), (2024, 6, 1), 12, 0);
+        let every_hour = BackupSchedule::Interval { minutes: 60 };
+        assert_eq!(time_until_next_run(&every_hour, now), Some(Duration::from_secs(3600)));
+        assert_eq!(time_until_next_run(&BackupSchedule::Interval { minutes: 0 }, now), None);
+    }
+
+    #[test]
+    fn daily_runs_later_today_or_tomorrow() {
+        let zone = FixedOffset::east_opt(3600).unwrap();
+        let now = at(zone, (2024, 6, 1), 12, 0);
+        assert_eq!(time_until_next_run(&daily("18:30"), now), Some(Duration::from_secs(6 * 3600 + 1800)));
+        // A time that has passed, including right now, is tomorrow's
+        assert_eq!(time_until_next_run(&daily("12:00"), now), Some(Duration::from_secs(24 * 3600)));
+        assert_eq!(time_until_next_run(&daily(" 03:00 "), now), Some(Duration::from_secs(15 * 3600)));
+        assert_eq!(time_until_next_run(&daily("25:00"), now), None);
+        assert_eq!(time_until_next_run(&daily("noon"), now), None);
+    }
+
+    #[test]
+    fn daily_time_skipped_by_dst_runs_an_hour_later() {
+        // 02:30 doesn't exist on the day of the change, so the backup runs at 03:30 summer time
+        let evening_before = at(SpringForwardZone, (2024, 3, 30), 23, 0);
+        assert_eq!(time_until_next_run(&daily("02:30"), evening_before), Some(Duration::from_secs(3 * 3600 + 1800)));
+
+        // Times across the change are counted in real time: 22:00 to 04:00 is only five hours
+        let night_before = at(SpringForwardZone, (2024, 3, 30), 22, 0);
+        assert_eq!(time_until_next_run(&daily("04:00"), night_before), Some(Duration::from_secs(5 * 3600)));
+    }
+}
@@ -0,0 +1,141 @@
+use chrono::Local;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A console log is continued in a new file once it reaches this size
+const MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+/// Oldest console logs of a server are deleted beyond this total
+const MAX_TOTAL_BYTES: u64 = 50 * 1024 * 1024;
+const FILE_PREFIX: &str = "allay-console-";
+
+/// Everything a server process printed, written to storage/<server>/logs/allay-console-*.log.
+/// Unlike the game's latest.log this also holds JVM output from before logging started,
+/// such as startup crashes and missing-class errors.
+pub struct ConsoleLog {
+    logs_dir: PathBuf,
+    file: File,
+    written: u64,
+}
+
+impl ConsoleLog {
+    /// Starts a new console log file for a server, pruning old ones past the size cap
+    pub fn create(server_path: &Path) -> io::Result<Self> {
+        let logs_dir = server_path.join("logs");
+        fs::create_dir_all(&logs_dir)?;
+        let (file, path) = open_new_file(&logs_dir)?;
+        prune_console_logs(&logs_dir, &path);
+        Ok(Self { logs_dir, file, written: 0 })
+    }
+
+    /// The open file, for processes whose output is redirected straight into it
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    fn write_line(&mut self, line: &[u8]) -> io::Result<()> {
+        if self.written > 0 && self.written + line.len() as u64 > MAX_FILE_BYTES {
+            let (file, path) = open_new_file(&self.logs_dir)?;
+            self.file = file;
+            self.written = 0;
+            prune_console_logs(&self.logs_dir, &path);
+        }
+        self.file.write_all(line)?;
+        self.written += line.len() as u64;
+        Ok(())
+    }
+}
+
+/// Copies the process output into `log` line by line on background threads until the
+/// process closes its pipes. stderr lines are marked so they stand out in the file.
+pub fn capture_output(
+    log: ConsoleLog,
+    server_name: &str,
+    stdout: Option<impl Read + Send + 'static>,
+    stderr: Option<impl Read + Send + 'static>,
+) {
+    let log = Arc::new(Mutex::new(log));
+
+    if let Some(stdout) = stdout {
+        spawn_copier(log.clone(), server_name, stdout, "");
+    }
+    if let Some(stderr) = stderr {
+        spawn_copier(log, server_name, stderr, "[stderr] ");
+    }
+}
+
+fn spawn_copier(log: Arc<Mutex<ConsoleLog>>, server_name: &str, output: impl Read + Send + 'static, prefix: &'static str) {
+    let server_name = server_name.to_string();
+    thread::spawn(move || {
+        let mut reader = BufReader::new(output);
+        let mut line = Vec::new();
+        loop {
+            line.clear();
+            line.extend_from_slice(prefix.as_bytes());
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) => break,
+                Ok(_) => {
+                    if !line.ends_with(b"\n") {
+                        line.push(b'\n');
+                    }
+                    let mut log = log.lock().unwrap();
+                    if let Err(e) = log.write_line(&line) {
+                        log::warn!("Stopped writing the console log of {}: {}", server_name, e);
+                        break;
+                    }
+                }
+                Err(e) => {
+                    log::debug!("Console output of {} ended: {}", server_name, e);
+                    break;
+                }
+            }
+        }
+
+        // Keep draining so a full pipe never blocks the server
+        let _ = io::copy(&mut reader, &mut io::sink());
+    });
+}
+
+fn open_new_file(logs_dir: &Path) -> io::Result<(File, PathBuf)> {
+    let stamp = Local::now().format("%Y-%m-%d_%H-%M-%S");
+    let mut path = logs_dir.join(format!("{}{}.log", FILE_PREFIX, stamp));
+    let mut counter = 1;
+    while path.exists() {
+        path = logs_dir.join(format!("{}{}-{}.log", FILE_PREFIX, stamp, counter));
+        counter += 1;
+    }
+    Ok((File::create(&path)?, path))
+}
+
+/// Deletes the oldest console logs until the rest fit in MAX_TOTAL_BYTES, never touching `current`
+fn prune_console_logs(logs_dir: &Path, current: &Path) {
+    let mut logs: Vec<(PathBuf, u64)> = match fs::read_dir(logs_dir) {
+        Ok(entries) => entries
+            .flatten()
+            .filter(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                name.starts_with(FILE_PREFIX) && name.ends_with(".log")
+            })
+            .map(|entry| (entry.path(), entry.metadata().map(|m| m.len()).unwrap_or(0)))
+            .collect(),
+        Err(_) => return,
+    };
+    // The timestamped names sort oldest first
+    logs.sort();
+
+    let mut total: u64 = logs.iter().map(|(_, size)| size).sum();
+    for (path, size) in logs {
+        if total <= MAX_TOTAL_BYTES {
+            break;
+        }
+        if path == current {
+            continue;
+        }
+        match fs::remove_file(&path) {
+            Ok(()) => total -= size,
+            Err(e) => log::warn!("Failed to prune console log {:?}: {}", path, e),
+        }
+    }
+}
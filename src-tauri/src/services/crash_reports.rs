@@ -0,0 +1,167 @@
+use crate::services::server_files::resolve_server_path;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+/// Where the game writes a report each time the server crashes
+const CRASH_REPORTS_DIR: &str = "crash-reports";
+
+/// A crash report file with the headers needed for a list view
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReportInfo {
+    pub file_name: String,
+    pub size: u64,
+    pub modified: Option<DateTime<Utc>>,
+    /// The "Description:" header, e.g. "Exception in server tick loop"
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub info: CrashReportInfo,
+    /// The "// ..." line under the title
+    pub summary: Option<String>,
+    /// The "Time:" header as written by the game
+    pub time: Option<String>,
+    pub content: String,
+}
+
+/// Sent as "server-crashed" so the UI can fetch the report right away
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerCrashedEvent {
+    pub server_name: String,
+    pub latest_crash_report: Option<CrashReportInfo>,
+}
+
+/// Reads (summary, time, description) from the header of a crash report:
+///
+/// ```text
+/// ---- Minecraft Crash Report ----
+/// // Who set us up the TNT?
+///
+/// Time: 2024-05-01 12:00:00
+/// Description: Exception in server tick loop
+/// ```
+fn parse_headers(content: &str) -> (Option<String>, Option<String>, Option<String>) {
+    let (mut summary, mut time, mut description) = (None, None, None);
+    // The headers end where the stack trace starts; don't scan megabytes of it
+    for line in content.lines().take(20) {
+        let line = line.trim();
+        if let Some(comment) = line.strip_prefix("//") {
+            summary.get_or_insert_with(|| comment.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("Time:") {
+            time.get_or_insert_with(|| value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("Description:") {
+            description.get_or_insert_with(|| value.trim().to_string());
+        }
+    }
+    (summary, time, description)
+}
+
+/// The start of a report, enough for its headers
+fn read_header(path: &Path) -> String {
+    let mut header = Vec::new();
+    if let Ok(file) = fs::File::open(path) {
+        let _ = file.take(4096).read_to_end(&mut header);
+    }
+    String::from_utf8_lossy(&header).to_string()
+}
+
+fn report_info(path: &Path, content: &str) -> CrashReportInfo {
+    let metadata = fs::metadata(path).ok();
+    CrashReportInfo {
+        file_name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        size: metadata.as_ref().map(|m| m.len()).unwrap_or(0),
+        modified: metadata.and_then(|m| m.modified().ok()).map(DateTime::<Utc>::from),
+        description: parse_headers(content).2,
+    }
+}
+
+/// The server's crash reports, newest first; empty when it never crashed
+pub fn list_crash_reports(server_path: &Path) -> Result<Vec<CrashReportInfo>> {
+    let dir = server_path.join(CRASH_REPORTS_DIR);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut reports: Vec<CrashReportInfo> = fs::read_dir(&dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "txt"))
+        .map(|path| report_info(&path, &read_header(&path)))
+        .collect();
+    reports.sort_by(|a, b| b.modified.cmp(&a.modified).then_with(|| b.file_name.cmp(&a.file_name)));
+    Ok(reports)
+}
+
+/// The newest crash report, if there is one
+pub fn latest_crash_report(server_path: &Path) -> Option<CrashReportInfo> {
+    list_crash_reports(server_path).ok()?.into_iter().next()
+}
+
+/// Reads one crash report by file name
+pub fn read_crash_report(server_path: &Path, file_name: &str) -> Result<CrashReport> {
+    if file_name.contains(['/', '\\']) {
+        return Err(anyhow!("Invalid crash report name '{}'", file_name));
+    }
+    if !server_path.join(CRASH_REPORTS_DIR).is_dir() {
+        return Err(anyhow!("Crash report '{}' not found", file_name));
+    }
+    let path = resolve_server_path(server_path, &format!("{}/{}", CRASH_REPORTS_DIR, file_name))?;
+    if !path.is_file() {
+        return Err(anyhow!("Crash report '{}' not found", file_name));
+    }
+
+    let content = String::from_utf8_lossy(&fs::read(&path)?).to_string();
+    let (summary, time, _) = parse_headers(&content);
+    Ok(CrashReport {
+        info: report_info(&path, &content),
+        summary,
+        time,
+        content,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPORT: &str = "---- Minecraft Crash Report ----\n\
+        // Who set us up the TNT?\n\
+        \n\
+        Time: 2024-05-01 12:00:00\n\
+        Description: Exception in server tick loop\n\
+        \n\
+        java.lang.NullPointerException: Cannot invoke \"Object.toString()\"\n";
+
+    #[test]
+    fn reads_the_report_headers() {
+        let (summary, time, description) = parse_headers(REPORT);
+        assert_eq!(summary.as_deref(), Some("Who set us up the TNT?"));
+        assert_eq!(time.as_deref(), Some("2024-05-01 12:00:00"));
+        assert_eq!(description.as_deref(), Some("Exception in server tick loop"));
+    }
+
+    #[test]
+    fn missing_headers_are_none() {
+        assert_eq!(parse_headers("---- Minecraft Crash Report ----\nTime: now\r\n"), (None, Some("now".to_string()), None));
+        assert_eq!(parse_headers(""), (None, None, None));
+    }
+
+    #[test]
+    fn keeps_the_first_occurrence() {
+        let content = format!("{}\n-- Details --\nDescription: Ticking entity\n// not the summary\n", REPORT);
+        let (summary, _, description) = parse_headers(&content);
+        assert_eq!(summary.as_deref(), Some("Who set us up the TNT?"));
+        assert_eq!(description.as_deref(), Some("Exception in server tick loop"));
+    }
+
+    #[test]
+    fn ignores_headers_past_the_top_of_the_file() {
+        let content = format!("{}Description: Too late\n", "\tat net.minecraft.Foo.bar(Foo.java)\n".repeat(20));
+        assert_eq!(parse_headers(&content), (None, None, None));
+    }
+}
@@ -0,0 +1,309 @@
+use crate::models::version::LoaderType;
+use crate::services::java_runtime::{check_java as find_java, required_java_version};
+use crate::services::mod_loader_strategy::get_strategy;
+use crate::util::{available_space, format_bytes, ServerProperties};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::net::{TcpListener, UdpSocket};
+use std::path::{Path, PathBuf};
+
+/// Free space below which world generation is likely to fail
+const MIN_FREE_SPACE_BYTES: u64 = 512 * 1024 * 1024;
+/// Free space below which we warn that the world may not have room to grow
+const LOW_FREE_SPACE_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticCheck {
+    pub name: String,
+    pub status: CheckStatus,
+    pub message: String,
+}
+
+impl DiagnosticCheck {
+    fn new(name: &str, status: CheckStatus, message: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticReport {
+    pub server_name: String,
+    pub checks: Vec<DiagnosticCheck>,
+    /// True when no check failed (warnings are allowed)
+    pub can_start: bool,
+}
+
+/// Everything the diagnostics need to know about a server instance
+pub struct DiagnosticTarget<'a> {
+    pub server_name: &'a str,
+    pub server_path: &'a PathBuf,
+    pub loader: LoaderType,
+    pub minecraft_version: &'a str,
+    /// The Java the server is launched with; "java" for the one in PATH, None while the
+    /// bundled runtime is enabled but not downloaded yet
+    pub java_command: Option<&'a str>,
+    pub memory_mb: u32,
+    pub is_running: bool,
+}
+
+/// Runs every startup check for a server and collects the results
+pub fn diagnose_server(target: &DiagnosticTarget) -> DiagnosticReport {
+    let properties = read_properties(target.server_path);
+
+    let mut checks = vec![
+        check_java(target.java_command, target.minecraft_version),
+        check_launch_files(target),
+        check_eula(target.server_path),
+        check_properties(target.server_path, &properties),
+    ];
+    checks.extend(check_ports(&properties, target.is_running));
+    checks.push(check_disk_space(target.server_path));
+    checks.push(check_memory(target.memory_mb));
+
+    let can_start = checks.iter().all(|check| check.status != CheckStatus::Fail);
+
+    DiagnosticReport {
+        server_name: target.server_name.to_string(),
+        checks,
+        can_start,
+    }
+}
+
+fn read_properties(server_path: &Path) -> Result<ServerProperties, String> {
+    let content = fs::read_to_string(server_path.join("server.properties"))
+        .map_err(|e| e.to_string())?;
+    ServerProperties::from_properties_string(&content).map_err(|e| e.to_string())
+}
+
+fn check_java(java_command: Option<&str>, minecraft_version: &str) -> DiagnosticCheck {
+    let required = required_java_version(minecraft_version);
+
+    // Starting the server downloads it, so there is nothing for the user to fix
+    let Some(java_command) = java_command else {
+        return DiagnosticCheck::new(
+            "java",
+            CheckStatus::Warn,
+            format!("The bundled Java {} runtime is not installed yet; it is downloaded on first start", required),
+        );
+    };
+
+    let java = find_java(java_command);
+    match java.version {
+        Some(installed) if installed >= required => DiagnosticCheck::new(
+            "java",
+            CheckStatus::Pass,
+            format!(
+                "Java {} ({}) found at {} (Minecraft {} needs Java {}+)",
+                installed,
+                java.vendor.as_deref().unwrap_or("unknown vendor"),
+                java.path,
+                minecraft_version,
+                required
+            ),
+        ),
+        Some(installed) => DiagnosticCheck::new(
+            "java",
+            CheckStatus::Fail,
+            format!("Java {} found but Minecraft {} needs Java {} or newer", installed, minecraft_version, required),
+        ),
+        None => DiagnosticCheck::new(
+            "java",
+            CheckStatus::Fail,
+            if java_command == "java" {
+                format!("Java was not found in PATH. Install Java {} or newer", required)
+            } else {
+                format!("Java could not be run from {}. Install Java {} or newer", java_command, required)
+            },
+        ),
+    }
+}
+
+fn check_launch_files(target: &DiagnosticTarget) -> DiagnosticCheck {
+    let strategy = get_strategy(&target.loader);
+
+    match strategy.build_start_command(target.server_path, 1024, 512) {
+        Ok(_) => DiagnosticCheck::new("launch_files", CheckStatus::Pass, "Server JAR or run script found"),
+        Err(e) => DiagnosticCheck::new(
+            "launch_files",
+            CheckStatus::Fail,
+            format!("Cannot build the {:?} start command: {}", target.loader, e),
+        ),
+    }
+}
+
+fn check_eula(server_path: &Path) -> DiagnosticCheck {
+    let accepted = fs::read_to_string(server_path.join("eula.txt"))
+        .map(|content| {
+            content
+                .lines()
+                .any(|line| line.trim().eq_ignore_ascii_case("eula=true"))
+        })
+        .unwrap_or(false);
+
+    if accepted {
+        DiagnosticCheck::new("eula", CheckStatus::Pass, "EULA accepted")
+    } else {
+        DiagnosticCheck::new("eula", CheckStatus::Fail, "eula.txt is missing or does not contain eula=true")
+    }
+}
+
+fn check_properties(server_path: &Path, properties: &Result<ServerProperties, String>) -> DiagnosticCheck {
+    let properties_path = server_path.join("server.properties");
+
+    if let Err(e) = properties {
+        return DiagnosticCheck::new(
+            "server_properties",
+            CheckStatus::Fail,
+            format!("server.properties could not be read: {}", e),
+        );
+    }
+
+    // The parser falls back to defaults for bad values, so look for malformed lines explicitly
+    let content = fs::read_to_string(&properties_path).unwrap_or_default();
+    let malformed: Vec<String> = content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#') && !line.contains('='))
+        .map(str::to_string)
+        .collect();
+
+    let bad_numbers: Vec<String> = content
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .filter(|(key, value)| {
+            matches!(key.trim(), "server-port" | "rcon.port" | "query.port")
+                && value.trim().parse::<u16>().is_err()
+        })
+        .map(|(key, _)| key.trim().to_string())
+        .collect();
+
+    if !bad_numbers.is_empty() {
+        DiagnosticCheck::new(
+            "server_properties",
+            CheckStatus::Fail,
+            format!("Invalid port values for: {}", bad_numbers.join(", ")),
+        )
+    } else if !malformed.is_empty() {
+        DiagnosticCheck::new(
+            "server_properties",
+            CheckStatus::Warn,
+            format!("{} malformed line(s) will be ignored: {}", malformed.len(), malformed.join(" | ")),
+        )
+    } else {
+        DiagnosticCheck::new("server_properties", CheckStatus::Pass, "server.properties parsed successfully")
+    }
+}
+
+fn check_ports(properties: &Result<ServerProperties, String>, is_running: bool) -> Vec<DiagnosticCheck> {
+    let properties = match properties {
+        Ok(properties) => properties,
+        Err(_) => return Vec::new(),
+    };
+
+    // (property, port, is_udp)
+    let mut ports = vec![("server-port", properties.server_port, false)];
+    if properties.enable_rcon {
+        ports.push(("rcon.port", properties.rcon_port, false));
+    }
+    if properties.enable_query {
+        // Query runs over UDP, so it may share the game's TCP port number
+        ports.push(("query.port", properties.query_port, true));
+    }
+
+    ports
+        .into_iter()
+        .map(|(key, port, is_udp)| {
+            let name = format!("port_{}", key);
+            let is_free = is_port_available(port, is_udp);
+
+            if is_running {
+                DiagnosticCheck::new(&name, CheckStatus::Pass, format!("Port {} is in use by this running server", port))
+            } else if is_free {
+                DiagnosticCheck::new(&name, CheckStatus::Pass, format!("Port {} ({}) is free", port, key))
+            } else {
+                DiagnosticCheck::new(
+                    &name,
+                    CheckStatus::Fail,
+                    format!("Port {} ({}) is already in use by another program", port, key),
+                )
+            }
+        })
+        .collect()
+}
+
+/// True when nothing else is bound to `port` on any interface
+pub fn is_port_available(port: u16, udp: bool) -> bool {
+    if udp {
+        UdpSocket::bind(("0.0.0.0", port)).is_ok()
+    } else {
+        TcpListener::bind(("0.0.0.0", port)).is_ok()
+    }
+}
+
+fn check_disk_space(server_path: &Path) -> DiagnosticCheck {
+    match available_space(server_path) {
+        Some(free) if free < MIN_FREE_SPACE_BYTES => DiagnosticCheck::new(
+            "disk_space",
+            CheckStatus::Fail,
+            format!("Only {} free; at least {} is needed", format_bytes(free), format_bytes(MIN_FREE_SPACE_BYTES)),
+        ),
+        Some(free) if free < LOW_FREE_SPACE_BYTES => DiagnosticCheck::new(
+            "disk_space",
+            CheckStatus::Warn,
+            format!("Only {} free; the world may run out of room", format_bytes(free)),
+        ),
+        Some(free) => DiagnosticCheck::new("disk_space", CheckStatus::Pass, format!("{} free", format_bytes(free))),
+        None => DiagnosticCheck::new("disk_space", CheckStatus::Warn, "Could not determine free disk space"),
+    }
+}
+
+fn check_memory(memory_mb: u32) -> DiagnosticCheck {
+    use sysinfo::System;
+
+    let mut system = System::new();
+    system.refresh_memory();
+    let total_mb = system.total_memory() / (1024 * 1024);
+
+    if total_mb == 0 {
+        return DiagnosticCheck::new("memory", CheckStatus::Warn, "Could not detect system memory");
+    }
+
+    let memory_mb = memory_mb as u64;
+    if memory_mb > total_mb {
+        DiagnosticCheck::new(
+            "memory",
+            CheckStatus::Fail,
+            format!("{} MB allocated but the system only has {} MB", memory_mb, total_mb),
+        )
+    } else if memory_mb * 4 > total_mb * 3 {
+        DiagnosticCheck::new(
+            "memory",
+            CheckStatus::Warn,
+            format!("{} MB allocated leaves little of the system's {} MB for the OS", memory_mb, total_mb),
+        )
+    } else if memory_mb < 1024 {
+        DiagnosticCheck::new(
+            "memory",
+            CheckStatus::Warn,
+            format!("{} MB is below the 1024 MB most servers need", memory_mb),
+        )
+    } else {
+        DiagnosticCheck::new(
+            "memory",
+            CheckStatus::Pass,
+            format!("{} MB allocated of {} MB system memory", memory_mb, total_mb),
+        )
+    }
+}
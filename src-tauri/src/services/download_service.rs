@@ -4,6 +4,8 @@ use std::path::PathBuf;
 use anyhow::{Result, anyhow};
 use crate::models::version::LoaderType;
 use crate::util::JarCacheManager;
+use crate::services::http_client::get_json;
+use crate::services::resumable_download::download_to_file;
 
 pub struct DownloadService {
     client: Client,
@@ -32,35 +34,29 @@ impl DownloadService {
         
         // Check if JAR is cached first
         if self.jar_cache.is_jar_cached(&loader, &minecraft_version, loader_version_ref) {
-            println!("JAR found in cache, copying to server: {:?}", server_path);
+            log::debug!("JAR found in cache, copying to server: {:?}", server_path);
             return self.jar_cache.copy_cached_jar_to_server(&loader, &minecraft_version, loader_version_ref, &server_path);
         }
 
-        println!("JAR not in cache, downloading...");
+        log::debug!("JAR not in cache, downloading...");
         
         let download_url = self.get_download_url(&loader, &minecraft_version, &loader_version).await?;
         let jar_name = self.get_jar_filename(&loader, &minecraft_version, &loader_version);
 
-        println!("Downloading {} from: {}", jar_name, download_url);
+        log::info!("Downloading {} from: {}", jar_name, download_url);
 
-        // Download the JAR file
-        let response = self.client.get(&download_url).send().await?;
-        
-        if !response.status().is_success() {
-            return Err(anyhow!("Failed to download JAR: HTTP {}", response.status()));
-        }
-
-        let bytes = response.bytes().await?;
-
-        // Cache the JAR first
-        println!("Caching downloaded JAR...");
-        self.jar_cache.cache_jar(&loader, &minecraft_version, loader_version_ref, &bytes)?;
+        // Download straight into the cache through a .part file that is resumed on retry
+        let cached_path = self.jar_cache.prepare_cache_entry(&loader, &minecraft_version, loader_version_ref)?;
+        download_to_file(&self.client, &download_url, &cached_path, None)
+            .await
+            .map_err(|e| anyhow!("Failed to download JAR: {}", e))?;
+        self.jar_cache.record_checksum(&cached_path);
 
         // Then copy it to the server directory
-        println!("Copying cached JAR to server: {:?}", server_path);
+        log::debug!("Copying cached JAR to server: {:?}", server_path);
         let jar_path = self.jar_cache.copy_cached_jar_to_server(&loader, &minecraft_version, loader_version_ref, &server_path)?;
 
-        println!("Successfully downloaded and cached JAR: {:?}", jar_path);
+        log::info!("Successfully downloaded and cached JAR: {:?}", jar_path);
         Ok(jar_path)
     }
 
@@ -97,13 +93,16 @@ impl DownloadService {
                     .ok_or_else(|| anyhow!("Quilt loader version is required"))?;
                 self.get_quilt_download_url(minecraft_version, loader_ver).await
             }
+            LoaderType::Spigot => {
+                Err(anyhow!("Spigot has no download; it is built locally with BuildTools"))
+            }
         }
     }
 
     async fn get_vanilla_download_url(&self, minecraft_version: &str) -> Result<String> {
         // Get version manifest
         let manifest_url = "https://launchermeta.mojang.com/mc/game/version_manifest.json";
-        let manifest: serde_json::Value = self.client.get(manifest_url).send().await?.json().await?;
+        let manifest: serde_json::Value = get_json(&self.client, manifest_url).await?;
         
         // Find the specific version
         let versions = manifest["versions"].as_array()
@@ -117,7 +116,7 @@ impl DownloadService {
             .ok_or_else(|| anyhow!("Version URL not found"))?;
         
         // Get version details
-        let version_details: serde_json::Value = self.client.get(version_url).send().await?.json().await?;
+        let version_details: serde_json::Value = get_json(&self.client, version_url).await?;
         
         // Get server JAR URL
         let server_url = version_details["downloads"]["server"]["url"].as_str()
@@ -179,7 +178,7 @@ impl DownloadService {
     async fn get_paper_download_url(&self, minecraft_version: &str) -> Result<String> {
         // Get latest build for the version
         let builds_url = format!("https://api.papermc.io/v2/projects/paper/versions/{}/builds", minecraft_version);
-        let builds_response: serde_json::Value = self.client.get(&builds_url).send().await?.json().await?;
+        let builds_response: serde_json::Value = get_json(&self.client, &builds_url).await?;
         
         let builds = builds_response["builds"].as_array()
             .ok_or_else(|| anyhow!("No builds found for Paper version {}", minecraft_version))?;
@@ -221,7 +220,7 @@ impl DownloadService {
             minecraft_version, actual_loader_version
         );
         
-        println!("Quilt server profile URL: {}", profile_url);
+        log::debug!("Quilt server profile URL: {}", profile_url);
         
         // Return the profile URL - we'll download the JSON and process it during setup
         Ok(profile_url)
@@ -273,6 +272,7 @@ impl DownloadService {
                 // Quilt downloads the server profile JSON first
                 "quilt-server-profile.json".to_string()
             },
+            LoaderType::Spigot => format!("spigot-{}.jar", minecraft_version),
         }
     }
 }
\ No newline at end of file
@@ -5,6 +5,7 @@ use std::path::PathBuf;
 use std::fs;
 use chrono::Utc;
 use crate::services::mod_loader_strategy::ModLoaderStrategy;
+use crate::services::http_client::{get_json, HttpClient};
 use crate::models::version::{LoaderType, VersionResponse, MinecraftVersion, VersionType, FabricVersions, FabricLoaderVersion};
 use crate::util::JarCacheManager;
 
@@ -13,14 +14,14 @@ pub struct FabricStrategy;
 
 #[async_trait]
 impl ModLoaderStrategy for FabricStrategy {
-    async fn get_versions(&self, client: &Client, minecraft_version: Option<String>) -> Result<VersionResponse> {
+    async fn get_versions(&self, client: &dyn HttpClient, minecraft_version: Option<String>) -> Result<VersionResponse> {
         // Get game versions
         let game_url = "https://meta.fabricmc.net/v2/versions";
-        let game_response: FabricVersions = client.get(game_url).send().await?.json().await?;
+        let game_response: FabricVersions = get_json(client, game_url).await?;
 
         // Get loader versions
         let loader_url = "https://meta.fabricmc.net/v2/versions/loader";
-        let loader_response: Vec<FabricLoaderVersion> = client.get(loader_url).send().await?.json().await?;
+        let loader_response: Vec<FabricLoaderVersion> = get_json(client, loader_url).await?;
 
         let mut versions = Vec::new();
         
@@ -134,8 +135,12 @@ impl ModLoaderStrategy for FabricStrategy {
         };
         format!("fabric-server-mc.{}-loader.{}-launcher.1.0.3.jar", minecraft_version, clean_version)
     }
+
+    fn is_loader_file(&self, file_name: &str) -> bool {
+        (file_name.starts_with("fabric-server-") && file_name.ends_with(".jar")) || file_name == ".fabric"
+    }
     
-    async fn setup_server(&self, _client: &Client, server_path: &PathBuf, minecraft_version: &str, loader_version: &str) -> Result<()> {
+    async fn setup_server(&self, _client: &Client, _java_command: &str, server_path: &PathBuf, minecraft_version: &str, loader_version: &str) -> Result<()> {
         let clean_version = if loader_version.starts_with("fabric-") {
             let without_prefix = loader_version.strip_prefix("fabric-").unwrap_or(loader_version);
             if let Some(dash_pos) = without_prefix.find('-') {
@@ -154,14 +159,14 @@ impl ModLoaderStrategy for FabricStrategy {
             return Err(anyhow!("Fabric server launcher not found: {:?}", server_jar));
         }
 
-        println!("Fabric server launcher ready: {:?}", server_jar);
+        log::info!("Fabric server launcher ready: {:?}", server_jar);
         Ok(())
     }
     
-    fn build_start_command(&self, server_path: &PathBuf, memory_gb: u32, min_memory_gb: u32) -> Result<Vec<String>> {
+    fn build_start_command(&self, server_path: &PathBuf, memory_mb: u32, min_memory_mb: u32) -> Result<Vec<String>> {
         let mut args = vec![
-            format!("-Xmx{}G", memory_gb),
-            format!("-Xms{}G", min_memory_gb),
+            format!("-Xmx{}M", memory_mb),
+            format!("-Xms{}M", min_memory_mb),
             "-jar".to_string(),
         ];
 
@@ -178,4 +183,49 @@ impl ModLoaderStrategy for FabricStrategy {
         }
         Err(anyhow!("Fabric server launcher JAR not found"))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::http_client::MockHttpClient;
+
+    const GAME: &str = r#"{
+        "game": [
+            { "version": "1.21.1", "stable": true },
+            { "version": "24w33a", "stable": false },
+            { "version": "1.21", "stable": true }
+        ],
+        "mappings": []
+    }"#;
+
+    const LOADERS: &str = r#"[
+        { "separator": ".", "build": 2, "maven": "net.fabricmc:fabric-loader:0.16.2", "version": "0.16.2", "stable": true },
+        { "separator": ".", "build": 1, "maven": "net.fabricmc:fabric-loader:0.16.1", "version": "0.16.1", "stable": false }
+    ]"#;
+
+    fn client() -> MockHttpClient {
+        MockHttpClient::new()
+            .with("https://meta.fabricmc.net/v2/versions", GAME)
+            .with("https://meta.fabricmc.net/v2/versions/loader", LOADERS)
+    }
+
+    #[tokio::test]
+    async fn pairs_the_stable_loader_with_stable_game_versions() {
+        let response = FabricStrategy.get_versions(&client(), None).await.unwrap();
+
+        let ids: Vec<_> = response.versions.iter().map(|v| v.id.as_str()).collect();
+        assert_eq!(ids, ["fabric-0.16.2-1.21.1", "fabric-0.16.2-1.21"]);
+        assert_eq!(response.latest.unwrap().id, "fabric-0.16.2-1.21.1");
+    }
+
+    #[tokio::test]
+    async fn lists_every_loader_for_a_game_version() {
+        let response = FabricStrategy.get_versions(&client(), Some("1.21".to_string())).await.unwrap();
+
+        let ids: Vec<_> = response.versions.iter().map(|v| v.id.as_str()).collect();
+        assert_eq!(ids, ["fabric-0.16.2-1.21", "fabric-0.16.1-1.21"]);
+        assert!(response.versions.iter().all(|v| v.minecraft_version.as_deref() == Some("1.21")));
+        assert_eq!(response.recommended.unwrap().id, "fabric-0.16.2-1.21");
+    }
+}
@@ -1,13 +1,16 @@
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use reqwest::Client;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
-use std::process::Command;
 use std::collections::HashMap;
+use std::cmp::Ordering;
 use chrono::Utc;
-use crate::services::mod_loader_strategy::ModLoaderStrategy;
+use crate::services::resumable_download::{fetch_maven_sha1, Checksum};
+use crate::services::mod_loader_strategy::{run_installer, ModLoaderStrategy, write_user_jvm_args};
+use crate::services::http_client::HttpClient;
 use crate::models::version::{LoaderType, VersionResponse, MinecraftVersion, VersionType};
+use crate::services::world_info::compare_release_versions;
 use crate::util::JarCacheManager;
 
 /// Forge strategy
@@ -15,9 +18,9 @@ pub struct ForgeStrategy;
 
 #[async_trait]
 impl ModLoaderStrategy for ForgeStrategy {
-    async fn get_versions(&self, client: &Client, minecraft_version: Option<String>) -> Result<VersionResponse> {
+    async fn get_versions(&self, client: &dyn HttpClient, minecraft_version: Option<String>) -> Result<VersionResponse> {
         let url = "https://maven.minecraftforge.net/net/minecraftforge/forge/maven-metadata.xml";
-        let response = client.get(url).send().await?.text().await?;
+        let response = client.get_text(url).await?;
 
         // Parse XML manually (simple approach for this case)
         let mut versions = Vec::new();
@@ -94,11 +97,7 @@ impl ModLoaderStrategy for ForgeStrategy {
     }
     
     async fn get_download_url(&self, _client: &Client, minecraft_version: &str, loader_version: &str) -> Result<String> {
-        let clean_version = if loader_version.starts_with("forge-") {
-            loader_version.strip_prefix("forge-").unwrap_or(loader_version)
-        } else {
-            loader_version
-        };
+        let clean_version = clean_forge_version(loader_version);
         
         Ok(format!(
             "https://maven.minecraftforge.net/net/minecraftforge/forge/{}/forge-{}-installer.jar",
@@ -106,6 +105,10 @@ impl ModLoaderStrategy for ForgeStrategy {
         ))
     }
     
+    async fn get_checksum(&self, client: &Client, download_url: &str, _minecraft_version: &str, _loader_version: &str) -> Result<Option<Checksum>> {
+        fetch_maven_sha1(client, download_url).await
+    }
+    
     fn get_filename(&self, _minecraft_version: &str, loader_version: &str) -> String {
         if loader_version.starts_with("forge-") {
             format!("{}-installer.jar", loader_version)
@@ -113,8 +116,13 @@ impl ModLoaderStrategy for ForgeStrategy {
             format!("forge-{}-installer.jar", loader_version)
         }
     }
+
+    fn is_loader_file(&self, file_name: &str) -> bool {
+        (file_name.contains("forge") && !file_name.contains("neoforge") && file_name.ends_with(".jar"))
+            || matches!(file_name, "run.sh" | "run.bat" | "libraries")
+    }
     
-    async fn setup_server(&self, _client: &Client, server_path: &PathBuf, _minecraft_version: &str, loader_version: &str) -> Result<()> {
+    async fn setup_server(&self, _client: &Client, java_command: &str, server_path: &PathBuf, _minecraft_version: &str, loader_version: &str) -> Result<()> {
         let installer_name = if loader_version.starts_with("forge-") {
             format!("{}-installer.jar", loader_version)
         } else {
@@ -127,101 +135,242 @@ impl ModLoaderStrategy for ForgeStrategy {
         }
 
         // Check if server is already installed
-        let run_script = server_path.join("run.sh");
-        let server_jar = if loader_version.starts_with("forge-") {
-            server_path.join(format!("{}-server.jar", loader_version))
-        } else {
-            server_path.join(format!("forge-{}-server.jar", loader_version))
-        };
-        
-        if run_script.exists() || server_jar.exists() {
-            println!("Forge server already installed");
+        let forge_version = clean_forge_version(loader_version);
+        let layout = ForgeLayout::for_version(forge_version);
+        let installed = (layout == ForgeLayout::RunScript && has_run_script(server_path))
+            || layout.launch_jars(forge_version).iter().any(|jar| server_path.join(jar).exists());
+
+        if installed {
+            log::debug!("Forge server already installed ({:?} layout)", layout);
             return Ok(());
         }
 
-        println!("Installing Forge server...");
+        log::info!("Installing Forge server...");
         
         let installer_filename = installer_path.file_name()
             .ok_or_else(|| anyhow!("Invalid installer filename"))?
             .to_str()
             .ok_or_else(|| anyhow!("Invalid installer filename encoding"))?;
         
-        let output = Command::new("java")
-            .args(&[
-                "-jar", 
-                installer_filename,
-                "--installServer"
-            ])
-            .current_dir(server_path)
-            .output()
-            .map_err(|e| {
-                if e.kind() == std::io::ErrorKind::NotFound {
-                    anyhow!("Java is not installed or not found in PATH. Please install Java to run Minecraft servers.")
-                } else {
-                    anyhow!("Failed to execute Java: {}", e)
-                }
-            })?;
-        
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow!("Forge installation failed: {}", error));
-        }
+        run_installer("Forge", java_command, server_path, installer_filename).await?;
 
-        println!("Forge server installed successfully");
+        log::info!("Forge server installed successfully");
         Ok(())
     }
     
-    fn build_start_command(&self, server_path: &PathBuf, memory_gb: u32, _min_memory_gb: u32) -> Result<Vec<String>> {
-        // Check OS and use appropriate script
-        let (script_path, script_command) = if cfg!(windows) {
-            (server_path.join("run.bat"), "run.bat".to_string())
-        } else {
-            (server_path.join("run.sh"), "./run.sh".to_string())
-        };
-        
-        if script_path.exists() {
-            println!("Using {} script for {}", script_command, if cfg!(windows) { "Windows" } else { "Unix" });
-            
-            if cfg!(windows) {
-                return Ok(vec!["cmd".to_string(), "/c".to_string(), script_command]);
-            } else {
-                // For Unix, ensure the script is executable
-                #[cfg(unix)]
-                {
-                    use std::os::unix::fs::PermissionsExt;
-                    if let Ok(metadata) = script_path.metadata() {
-                        let permissions = metadata.permissions();
-                        if permissions.mode() & 0o111 == 0 {
-                            let mut new_permissions = permissions.clone();
-                            new_permissions.set_mode(permissions.mode() | 0o755);
-                            let _ = std::fs::set_permissions(&script_path, new_permissions);
-                            println!("Made {} executable", script_command);
-                        }
-                    }
-                }
-                
-                return Ok(vec!["bash".to_string(), script_command]);
+    fn prepare_launch(&self, server_path: &Path, memory_mb: u32, min_memory_mb: u32) -> Result<()> {
+        // run.sh/run.bat take their heap flags from user_jvm_args.txt
+        let uses_run_script = server_path.join("run.sh").exists() || server_path.join("run.bat").exists();
+        if uses_run_script {
+            write_user_jvm_args(server_path, &format!("-Xmx{}M", memory_mb), &format!("-Xms{}M", min_memory_mb))?;
+        }
+        Ok(())
+    }
+
+    fn build_start_command(&self, server_path: &PathBuf, memory_mb: u32, min_memory_mb: u32) -> Result<Vec<String>> {
+        let forge_version = installed_forge_version(server_path);
+        let layout = forge_version
+            .as_deref()
+            .map(ForgeLayout::for_version)
+            .unwrap_or(ForgeLayout::RunScript);
+
+        if layout == ForgeLayout::RunScript {
+            if let Some(command) = run_script_command(server_path) {
+                return Ok(command);
             }
+            log::info!("No run script found, falling back to direct JAR execution");
         }
-        
-        println!("No run script found, falling back to direct JAR execution");
-        
-        // Find forge server JAR
-        let entries = fs::read_dir(server_path)?;
-        for entry in entries {
-            let entry = entry?;
-            let file_name = entry.file_name().to_string_lossy().to_string();
-            if file_name.contains("forge") && file_name.ends_with("server.jar") {
-                let args = vec![
-                    format!("-Xmx{}G", memory_gb),
-                    format!("-Xms{}G", memory_gb / 2),
-                    "-jar".to_string(),
-                    file_name,
-                    "nogui".to_string(),
-                ];
-                return Ok(args);
+
+        let expected_jar = forge_version
+            .map(|version| layout.launch_jars(&version))
+            .unwrap_or_default()
+            .into_iter()
+            .find(|jar| server_path.join(jar).exists());
+
+        // Without the installer we can't tell the version, so take any Forge jar that isn't it
+        let jar = match expected_jar {
+            Some(jar) => jar,
+            None => fs::read_dir(server_path)?
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .find(|name| name.starts_with("forge-") && name.ends_with(".jar") && !name.ends_with("-installer.jar"))
+                .ok_or_else(|| anyhow!("Forge server JAR not found"))?,
+        };
+
+        log::debug!("Launching Forge directly from {} ({:?} layout)", jar, layout);
+        Ok(vec![
+            format!("-Xmx{}M", memory_mb),
+            format!("-Xms{}M", min_memory_mb),
+            "-jar".to_string(),
+            jar,
+            "nogui".to_string(),
+        ])
+    }
+}
+
+/// How the Forge installer lays out a server; it changed twice over the years
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ForgeLayout {
+    /// Up to 1.12.2: forge-<ver>-universal.jar (forge-<ver>.jar on late 1.12.2 builds), launched directly
+    Universal,
+    /// 1.13-1.16.5: forge-<ver>.jar, launched directly
+    ServerJar,
+    /// 1.17+: run.sh/run.bat passing the args files under libraries/ to java
+    RunScript,
+}
+
+impl ForgeLayout {
+    fn for_version(forge_version: &str) -> Self {
+        let minecraft_version = clean_forge_version(forge_version).split('-').next().unwrap_or_default();
+        let at_least = |v: &str| {
+            // Unknown formats are treated as current
+            compare_release_versions(minecraft_version, v).map(|o| o != Ordering::Less).unwrap_or(true)
+        };
+        if at_least("1.17") {
+            ForgeLayout::RunScript
+        } else if at_least("1.13") {
+            ForgeLayout::ServerJar
+        } else {
+            ForgeLayout::Universal
+        }
+    }
+
+    /// Jars the installer leaves behind that can be run with `java -jar`, most specific first
+    fn launch_jars(self, forge_version: &str) -> Vec<String> {
+        let version = clean_forge_version(forge_version);
+        match self {
+            ForgeLayout::Universal => vec![format!("forge-{}-universal.jar", version), format!("forge-{}.jar", version)],
+            ForgeLayout::ServerJar => vec![format!("forge-{}.jar", version)],
+            // 1.20.3+ ships a shim jar; a few early 1.17 builds shipped a server jar
+            ForgeLayout::RunScript => vec![format!("forge-{}-shim.jar", version), format!("forge-{}-server.jar", version)],
+        }
+    }
+}
+
+/// "forge-1.20.1-47.2.0" -> "1.20.1-47.2.0"
+fn clean_forge_version(loader_version: &str) -> &str {
+    loader_version.strip_prefix("forge-").unwrap_or(loader_version)
+}
+
+/// The Forge version of the installer kept in the server folder
+fn installed_forge_version(server_path: &Path) -> Option<String> {
+    fs::read_dir(server_path)
+        .ok()?
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            name.strip_prefix("forge-")?.strip_suffix("-installer.jar").map(str::to_string)
+        })
+        .max()
+}
+
+fn has_run_script(server_path: &Path) -> bool {
+    server_path.join("run.sh").exists() || server_path.join("run.bat").exists()
+}
+
+/// The command running the platform's run script, if the installer created one
+fn run_script_command(server_path: &Path) -> Option<Vec<String>> {
+    if cfg!(windows) {
+        if server_path.join("run.bat").exists() {
+            log::debug!("Using run.bat script for Windows");
+            return Some(vec!["cmd".to_string(), "/c".to_string(), "run.bat".to_string()]);
+        }
+        return None;
+    }
+
+    let script_path = server_path.join("run.sh");
+    if !script_path.exists() {
+        return None;
+    }
+    log::debug!("Using ./run.sh script for Unix");
+
+    // Ensure the script is executable
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Ok(metadata) = script_path.metadata() {
+            let permissions = metadata.permissions();
+            if permissions.mode() & 0o111 == 0 {
+                let mut new_permissions = permissions.clone();
+                new_permissions.set_mode(permissions.mode() | 0o755);
+                let _ = std::fs::set_permissions(&script_path, new_permissions);
+                log::debug!("Made ./run.sh executable");
             }
         }
-        Err(anyhow!("Forge server JAR not found"))
     }
-}
\ No newline at end of file
+
+    Some(vec!["bash".to_string(), "./run.sh".to_string()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::http_client::MockHttpClient;
+
+    const METADATA: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<metadata>
+  <groupId>net.minecraftforge</groupId>
+  <artifactId>forge</artifactId>
+  <versioning>
+    <latest>1.20.4-49.1.0</latest>
+    <release>1.20.4-49.1.0</release>
+    <versions>
+      <version>1.20.1-47.2.0</version>
+      <version>1.20.1-47.3.0</version>
+      <version>1.20.4-49.0.1</version>
+      <version>1.20.4-49.1.0</version>
+    </versions>
+  </versioning>
+</metadata>"#;
+
+    fn client() -> MockHttpClient {
+        MockHttpClient::new()
+            .with("https://maven.minecraftforge.net/net/minecraftforge/forge/maven-metadata.xml", METADATA)
+    }
+
+    #[tokio::test]
+    async fn lists_one_entry_per_minecraft_version() {
+        let response = ForgeStrategy.get_versions(&client(), None).await.unwrap();
+
+        let mc_versions: Vec<_> = response.versions.iter().filter_map(|v| v.minecraft_version.as_deref()).collect();
+        assert_eq!(mc_versions, ["1.20.4", "1.20.1"]);
+        assert!(response.versions.iter().all(|v| v.id.starts_with("forge-")));
+    }
+
+    #[tokio::test]
+    async fn lists_every_build_for_a_minecraft_version() {
+        let response = ForgeStrategy.get_versions(&client(), Some("1.20.1".to_string())).await.unwrap();
+
+        let mut ids: Vec<_> = response.versions.iter().map(|v| v.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, ["forge-1.20.1-47.2.0", "forge-1.20.1-47.3.0"]);
+    }
+
+    #[test]
+    fn picks_the_layout_from_the_minecraft_version() {
+        assert_eq!(ForgeLayout::for_version("1.7.10-10.13.4.1614-1.7.10"), ForgeLayout::Universal);
+        assert_eq!(ForgeLayout::for_version("forge-1.12.2-14.23.5.2860"), ForgeLayout::Universal);
+        assert_eq!(ForgeLayout::for_version("1.16.5-36.2.39"), ForgeLayout::ServerJar);
+        assert_eq!(ForgeLayout::for_version("1.20.1-47.2.0"), ForgeLayout::RunScript);
+    }
+
+    #[test]
+    fn universal_layout_prefers_the_universal_jar() {
+        let jars = ForgeLayout::Universal.launch_jars("forge-1.12.2-14.23.5.2860");
+        assert_eq!(jars, ["forge-1.12.2-14.23.5.2860-universal.jar", "forge-1.12.2-14.23.5.2860.jar"]);
+    }
+
+    #[test]
+    fn launches_old_forge_from_its_jar() {
+        let dir = std::env::temp_dir().join(format!("allay-forge-layout-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["forge-1.12.2-14.23.5.2860-installer.jar", "forge-1.12.2-14.23.5.2860.jar", "minecraft_server.1.12.2.jar"] {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+
+        let command = ForgeStrategy.build_start_command(&dir, 4096, 1024);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(command.unwrap(), ["-Xmx4096M", "-Xms1024M", "-jar", "forge-1.12.2-14.23.5.2860.jar", "nogui"]);
+    }
+}
@@ -0,0 +1,188 @@
+use super::rcon_commands::strip_formatting_codes;
+use super::world_info::compare_release_versions;
+use serde::Serialize;
+use std::cmp::Ordering;
+
+/// Value type of a gamerule, so the UI can pick a toggle or a number field
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GameruleKind {
+    Bool,
+    Int,
+}
+
+/// A vanilla gamerule and the first release that has it
+#[derive(Debug, Clone, Serialize)]
+pub struct GameruleInfo {
+    pub name: &'static str,
+    pub kind: GameruleKind,
+    pub default: &'static str,
+    pub since: &'static str,
+}
+
+/// A gamerule value as reported by the server
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum GameruleValue {
+    Bool(bool),
+    Int(i64),
+}
+
+const fn rule(name: &'static str, kind: GameruleKind, default: &'static str, since: &'static str) -> GameruleInfo {
+    GameruleInfo { name, kind, default, since }
+}
+
+use GameruleKind::{Bool, Int};
+
+/// Vanilla Java Edition gamerules, oldest first
+const KNOWN_GAMERULES: &[GameruleInfo] = &[
+    rule("commandBlockOutput", Bool, "true", "1.4.2"),
+    rule("doFireTick", Bool, "true", "1.4.2"),
+    rule("doMobLoot", Bool, "true", "1.4.2"),
+    rule("doMobSpawning", Bool, "true", "1.4.2"),
+    rule("doTileDrops", Bool, "true", "1.4.2"),
+    rule("keepInventory", Bool, "false", "1.4.2"),
+    rule("mobGriefing", Bool, "true", "1.4.2"),
+    rule("doDaylightCycle", Bool, "true", "1.6.1"),
+    rule("naturalRegeneration", Bool, "true", "1.6.1"),
+    rule("logAdminCommands", Bool, "true", "1.8"),
+    rule("randomTickSpeed", Int, "3", "1.8"),
+    rule("reducedDebugInfo", Bool, "false", "1.8"),
+    rule("sendCommandFeedback", Bool, "true", "1.8"),
+    rule("showDeathMessages", Bool, "true", "1.8"),
+    rule("doEntityDrops", Bool, "true", "1.8.1"),
+    rule("spectatorsGenerateChunks", Bool, "true", "1.8.1"),
+    rule("disableElytraMovementCheck", Bool, "false", "1.9"),
+    rule("spawnRadius", Int, "10", "1.9"),
+    rule("doWeatherCycle", Bool, "true", "1.11"),
+    rule("maxEntityCramming", Int, "24", "1.11"),
+    rule("announceAdvancements", Bool, "true", "1.12"),
+    rule("doLimitedCrafting", Bool, "false", "1.12"),
+    rule("maxCommandChainLength", Int, "65536", "1.12"),
+    rule("disableRaids", Bool, "false", "1.14.3"),
+    rule("doImmediateRespawn", Bool, "false", "1.15"),
+    rule("doInsomnia", Bool, "true", "1.15"),
+    rule("drowningDamage", Bool, "true", "1.15"),
+    rule("fallDamage", Bool, "true", "1.15"),
+    rule("fireDamage", Bool, "true", "1.15"),
+    rule("doPatrolSpawning", Bool, "true", "1.15.2"),
+    rule("doTraderSpawning", Bool, "true", "1.15.2"),
+    rule("forgiveDeadPlayers", Bool, "true", "1.16"),
+    rule("universalAnger", Bool, "false", "1.16"),
+    rule("freezeDamage", Bool, "true", "1.17"),
+    rule("playersSleepingPercentage", Int, "100", "1.17"),
+    rule("doWardenSpawning", Bool, "true", "1.19"),
+    rule("blockExplosionDropDecay", Bool, "true", "1.19.3"),
+    rule("globalSoundEvents", Bool, "true", "1.19.3"),
+    rule("lavaSourceConversion", Bool, "false", "1.19.3"),
+    rule("mobExplosionDropDecay", Bool, "true", "1.19.3"),
+    rule("snowAccumulationHeight", Int, "1", "1.19.3"),
+    rule("tntExplosionDropDecay", Bool, "false", "1.19.3"),
+    rule("waterSourceConversion", Bool, "true", "1.19.3"),
+    rule("commandModificationBlockLimit", Int, "32768", "1.19.4"),
+    rule("doVinesSpread", Bool, "true", "1.19.4"),
+    rule("enderPearlsVanishOnDeath", Bool, "true", "1.20.2"),
+    rule("maxCommandForkCount", Int, "65536", "1.20.3"),
+    rule("playersNetherPortalCreativeDelay", Int, "1", "1.20.3"),
+    rule("playersNetherPortalDefaultDelay", Int, "80", "1.20.3"),
+    rule("projectilesCanBreakBlocks", Bool, "true", "1.20.3"),
+    rule("spawnChunkRadius", Int, "2", "1.20.5"),
+];
+
+fn known_rule(name: &str) -> Option<&'static GameruleInfo> {
+    KNOWN_GAMERULES.iter().find(|info| info.name == name)
+}
+
+fn available_in(info: &GameruleInfo, minecraft_version: &str) -> bool {
+    // Snapshots and unknown formats are treated as current
+    compare_release_versions(minecraft_version, info.since)
+        .map(|o| o != Ordering::Less)
+        .unwrap_or(true)
+}
+
+/// The vanilla gamerules a server of `minecraft_version` has
+pub fn list_gamerules(minecraft_version: &str) -> Vec<GameruleInfo> {
+    KNOWN_GAMERULES
+        .iter()
+        .filter(|info| available_in(info, minecraft_version))
+        .cloned()
+        .collect()
+}
+
+/// Checks a rule name before it goes into a command. Unknown names are allowed since mods
+/// and plugins add their own rules, but vanilla rules newer than the server are rejected.
+pub fn check_rule(rule: &str, minecraft_version: &str) -> Result<(), String> {
+    if rule.is_empty() || !rule.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | ':')) {
+        return Err(format!("'{}' is not a valid gamerule name", rule));
+    }
+    match known_rule(rule) {
+        Some(info) if !available_in(info, minecraft_version) => Err(format!(
+            "{} was added in Minecraft {}; this server runs {}",
+            rule, info.since, minecraft_version
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Types a raw value by the rule's known kind, or by its shape for rules Allay doesn't know
+pub fn typed_value(rule: &str, raw: &str) -> Result<GameruleValue, String> {
+    let raw = raw.trim();
+    let as_bool = || match raw {
+        "true" => Ok(GameruleValue::Bool(true)),
+        "false" => Ok(GameruleValue::Bool(false)),
+        _ => Err(format!("{} expects true or false, got '{}'", rule, raw)),
+    };
+    let as_int = || {
+        raw.parse()
+            .map(GameruleValue::Int)
+            .map_err(|_| format!("{} expects a whole number, got '{}'", rule, raw))
+    };
+    match known_rule(rule).map(|info| info.kind) {
+        Some(Bool) => as_bool(),
+        Some(Int) => as_int(),
+        None => as_bool().or_else(|_| as_int()).map_err(|_| format!("{} has an unsupported value '{}'", rule, raw)),
+    }
+}
+
+/// The value in a `gamerule` reply:
+/// "Gamerule keepInventory is currently set to: false" / "... is now set to: true" (1.13+),
+/// "keepInventory = false" and "Game rule keepInventory has been updated to true" (older)
+pub fn parse_gamerule_response(rule: &str, response: &str) -> Option<String> {
+    let text = strip_formatting_codes(response);
+    let text = text.trim();
+    if let Some((_, value)) = text.split_once("set to:") {
+        return Some(value.trim().to_string());
+    }
+    if let Some((_, value)) = text.split_once("has been updated to") {
+        return Some(value.trim().to_string());
+    }
+    text.strip_prefix(rule)?
+        .trim_start()
+        .strip_prefix('=')
+        .map(|value| value.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_gamerule_replies() {
+        let some = |value: &str| Some(value.to_string());
+        let cases = [
+            ("Gamerule keepInventory is currently set to: false", some("false")),
+            ("Gamerule keepInventory is now set to: true", some("true")),
+            ("§eGamerule keepInventory is now set to: true\n", some("true")),
+            ("Game rule keepInventory has been updated to true", some("true")),
+            ("keepInventory = false", some("false")),
+            ("keepInventory=true", some("true")),
+            ("keepInventoryX = true", None),
+            ("doDaylightCycle = true", None),
+            ("No game rule called 'keepInventory' is available", None),
+            ("", None),
+        ];
+        for (reply, expected) in cases {
+            assert_eq!(parse_gamerule_response("keepInventory", reply), expected, "{}", reply);
+        }
+    }
+}
@@ -78,7 +78,7 @@ impl HeartbeatManager {
         let logger = match RconLogger::new(server_name.clone()) {
             Ok(logger) => logger,
             Err(e) => {
-                println!("Failed to create RCON logger for {}: {}", server_name, e);
+                log::warn!("Failed to create RCON logger for {}: {}", server_name, e);
                 return;
             }
         };
@@ -0,0 +1,166 @@
+use crate::models::DashboardSnapshot;
+use async_trait::async_trait;
+use axum::extract::{Path, Request, State};
+use axum::http::{header, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+
+/// Server operations exposed over HTTP, implemented on top of the Tauri command layer
+#[async_trait]
+pub trait ServerController: Send + Sync {
+    async fn list_servers(&self) -> Result<Vec<ApiServerSummary>, String>;
+    async fn server_status(&self, server_name: &str) -> Result<ApiServerSummary, String>;
+    async fn start_server(&self, server_name: &str) -> Result<String, String>;
+    async fn stop_server(&self, server_name: &str) -> Result<String, String>;
+    async fn execute_command(&self, server_name: &str, command: &str) -> Result<String, String>;
+    async fn dashboard_snapshot(&self) -> Result<DashboardSnapshot, String>;
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ApiServerSummary {
+    pub name: String,
+    pub version: String,
+    pub mod_loader: String,
+    pub is_running: bool,
+    pub status: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommandRequest {
+    command: String,
+}
+
+#[derive(Clone)]
+struct ApiState {
+    controller: Arc<dyn ServerController>,
+    token: Arc<String>,
+}
+
+/// Handle to a running API server; dropping it leaves the server running, call `shutdown` to stop it
+pub struct HttpApiHandle {
+    pub address: SocketAddr,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl HttpApiHandle {
+    pub fn shutdown(mut self) {
+        if let Some(sender) = self.shutdown.take() {
+            let _ = sender.send(());
+        }
+    }
+}
+
+/// Binds the API on `address` and serves it on the tokio runtime until shut down.
+/// Every request must carry `Authorization: Bearer <token>`.
+pub async fn start_http_api(
+    address: SocketAddr,
+    token: String,
+    controller: Arc<dyn ServerController>,
+) -> anyhow::Result<HttpApiHandle> {
+    let state = ApiState {
+        controller,
+        token: Arc::new(token),
+    };
+
+    let router = Router::new()
+        .route("/api/dashboard", get(dashboard_snapshot))
+        .route("/api/servers", get(list_servers))
+        .route("/api/servers/{name}", get(server_status))
+        .route("/api/servers/{name}/start", post(start_server))
+        .route("/api/servers/{name}/stop", post(stop_server))
+        .route("/api/servers/{name}/command", post(execute_command))
+        .layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .with_state(state);
+
+    let listener = tokio::net::TcpListener::bind(address).await?;
+    let address = listener.local_addr()?;
+    let (shutdown_sender, shutdown_receiver) = oneshot::channel::<()>();
+
+    tokio::spawn(async move {
+        let server = axum::serve(listener, router).with_graceful_shutdown(async {
+            let _ = shutdown_receiver.await;
+        });
+        if let Err(e) = server.await {
+            log::error!("HTTP API stopped with an error: {}", e);
+        }
+    });
+
+    log::info!("HTTP API listening on http://{}", address);
+    Ok(HttpApiHandle {
+        address,
+        shutdown: Some(shutdown_sender),
+    })
+}
+
+async fn require_token(State(state): State<ApiState>, request: Request, next: Next) -> Response {
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match provided {
+        Some(token) if constant_time_eq(token.as_bytes(), state.token.as_bytes()) => next.run(request).await,
+        _ => error_response(StatusCode::UNAUTHORIZED, "Missing or invalid API token"),
+    }
+}
+
+/// Compares tokens without leaking the matching prefix length through timing
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn error_response(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({ "error": message }))).into_response()
+}
+
+fn result_response<T: Serialize>(result: Result<T, String>) -> Response {
+    match result {
+        Ok(value) => Json(value).into_response(),
+        Err(e) => error_response(StatusCode::BAD_REQUEST, &e),
+    }
+}
+
+async fn dashboard_snapshot(State(state): State<ApiState>) -> Response {
+    result_response(state.controller.dashboard_snapshot().await)
+}
+
+async fn list_servers(State(state): State<ApiState>) -> Response {
+    result_response(state.controller.list_servers().await)
+}
+
+async fn server_status(State(state): State<ApiState>, Path(name): Path<String>) -> Response {
+    match state.controller.server_status(&name).await {
+        Ok(summary) => Json(summary).into_response(),
+        Err(e) => error_response(StatusCode::NOT_FOUND, &e),
+    }
+}
+
+async fn start_server(State(state): State<ApiState>, Path(name): Path<String>) -> Response {
+    result_response(state.controller.start_server(&name).await.map(|message| json!({ "message": message })))
+}
+
+async fn stop_server(State(state): State<ApiState>, Path(name): Path<String>) -> Response {
+    result_response(state.controller.stop_server(&name).await.map(|message| json!({ "message": message })))
+}
+
+async fn execute_command(
+    State(state): State<ApiState>,
+    Path(name): Path<String>,
+    Json(request): Json<CommandRequest>,
+) -> Response {
+    result_response(
+        state
+            .controller
+            .execute_command(&name, &request.command)
+            .await
+            .map(|response| json!({ "response": response })),
+    )
+}
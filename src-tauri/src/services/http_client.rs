@@ -0,0 +1,70 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use crate::services::rate_limiter;
+
+/// The HTTP surface the version lookups need, so their parsing can be tested against
+/// captured API responses instead of live endpoints
+#[async_trait]
+pub trait HttpClient: Send + Sync {
+    /// GETs `url` and returns the body; non-success statuses are errors
+    async fn get_text(&self, url: &str) -> Result<String>;
+
+    /// GETs `url` and returns the raw body, for images and other binary responses
+    async fn get_bytes(&self, url: &str) -> Result<Vec<u8>>;
+}
+
+#[async_trait]
+impl HttpClient for reqwest::Client {
+    async fn get_text(&self, url: &str) -> Result<String> {
+        Ok(rate_limiter::send(self.get(url)).await?.error_for_status()?.text().await?)
+    }
+
+    async fn get_bytes(&self, url: &str) -> Result<Vec<u8>> {
+        Ok(rate_limiter::send(self.get(url)).await?.error_for_status()?.bytes().await?.to_vec())
+    }
+}
+
+/// GETs `url` and deserializes the JSON body
+pub async fn get_json<T: DeserializeOwned>(client: &dyn HttpClient, url: &str) -> Result<T> {
+    let body = client.get_text(url).await?;
+    Ok(serde_json::from_str(&body)?)
+}
+
+/// Serves fixed bodies by URL; any other URL fails like a 404 would
+#[cfg(test)]
+#[derive(Default)]
+pub struct MockHttpClient {
+    responses: std::collections::HashMap<String, Vec<u8>>,
+}
+
+#[cfg(test)]
+impl MockHttpClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(self, url: &str, body: &str) -> Self {
+        self.with_bytes(url, body.as_bytes())
+    }
+
+    pub fn with_bytes(mut self, url: &str, body: &[u8]) -> Self {
+        self.responses.insert(url.to_string(), body.to_vec());
+        self
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl HttpClient for MockHttpClient {
+    async fn get_text(&self, url: &str) -> Result<String> {
+        Ok(String::from_utf8(self.get_bytes(url).await?)?)
+    }
+
+    async fn get_bytes(&self, url: &str) -> Result<Vec<u8>> {
+        self.responses
+            .get(url)
+            .cloned()
+            .ok_or_else(|| anyhow::anyhow!("HTTP 404 Not Found for {}", url))
+    }
+}
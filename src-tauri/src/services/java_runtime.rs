@@ -0,0 +1,427 @@
+use crate::services::http_client::get_json;
+use crate::services::resumable_download::{download_to_file, Checksum};
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::path::{Component, Path, PathBuf};
+use std::process::Command;
+
+/// Bundled runtimes live in storage/runtimes/<major version>
+pub const RUNTIMES_DIR: &str = "storage/runtimes";
+const ADOPTIUM_API: &str = "https://api.adoptium.net/v3";
+
+#[derive(Debug, Deserialize)]
+struct AdoptiumRelease {
+    binary: AdoptiumBinary,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdoptiumBinary {
+    package: AdoptiumPackage,
+}
+
+#[derive(Debug, Deserialize)]
+struct AdoptiumPackage {
+    name: String,
+    link: String,
+    checksum: String,
+}
+
+/// What `<java> -version` reported, or `found: false` when it couldn't be run
+#[derive(Debug, Clone, Serialize)]
+pub struct JavaInstallation {
+    pub found: bool,
+    /// Major version (8, 17, 21, ...)
+    pub version: Option<u32>,
+    /// The full version string, e.g. "17.0.2"
+    pub full_version: Option<String>,
+    pub vendor: Option<String>,
+    /// The executable that was run, resolved through PATH when possible
+    pub path: String,
+}
+
+/// Runs `<java_command> -version` without starting anything else and describes the runtime
+pub fn check_java(java_command: &str) -> JavaInstallation {
+    let path = resolve_executable(java_command)
+        .map(|path| path.to_string_lossy().to_string())
+        .unwrap_or_else(|| java_command.to_string());
+
+    let output = match Command::new(java_command).arg("-version").output() {
+        Ok(output) => output,
+        Err(e) => {
+            log::debug!("Could not run {} -version: {}", java_command, e);
+            return JavaInstallation {
+                found: false,
+                version: None,
+                full_version: None,
+                vendor: None,
+                path,
+            };
+        }
+    };
+
+    // `java -version` prints to stderr, but some distributions use stdout
+    let text = format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stderr),
+        String::from_utf8_lossy(&output.stdout)
+    );
+
+    let version = parse_java_major_version(&text);
+    JavaInstallation {
+        found: version.is_some(),
+        version,
+        full_version: parse_full_version(&text),
+        vendor: parse_java_vendor(&text),
+        path,
+    }
+}
+
+/// Finds `command` the way the OS would: as given if it contains a path, otherwise in PATH
+fn resolve_executable(command: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(command);
+    if path.components().count() > 1 {
+        return path.is_file().then_some(path);
+    }
+
+    let names: Vec<String> = if cfg!(windows) && !command.to_lowercase().ends_with(".exe") {
+        vec![format!("{}.exe", command), command.to_string()]
+    } else {
+        vec![command.to_string()]
+    };
+
+    std::env::split_paths(&std::env::var_os("PATH")?)
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .find(|candidate| candidate.is_file())
+}
+
+/// The quoted version of `java -version` output, e.g. `openjdk version "17.0.2" 2022-01-18` -> 17.0.2
+fn parse_full_version(output: &str) -> Option<String> {
+    let version_regex = regex::Regex::new(r#"version "([^"]+)""#).ok()?;
+    Some(version_regex.captures(output)?.get(1)?.as_str().to_string())
+}
+
+/// Names the distribution from the runtime line of `java -version`,
+/// e.g. `OpenJDK Runtime Environment Temurin-17.0.2+8` -> Eclipse Temurin
+pub fn parse_java_vendor(output: &str) -> Option<String> {
+    const VENDORS: [(&str, &str); 13] = [
+        ("Temurin", "Eclipse Temurin"),
+        ("Corretto", "Amazon Corretto"),
+        ("Zulu", "Azul Zulu"),
+        ("Microsoft", "Microsoft"),
+        ("GraalVM", "GraalVM"),
+        ("Semeru", "IBM Semeru"),
+        ("OpenJ9", "Eclipse OpenJ9"),
+        ("Red_Hat", "Red Hat"),
+        ("JBR", "JetBrains"),
+        ("BellSoft", "BellSoft Liberica"),
+        ("SapMachine", "SapMachine"),
+        ("Homebrew", "Homebrew"),
+        ("Java(TM)", "Oracle"),
+    ];
+
+    VENDORS
+        .iter()
+        .find(|(marker, _)| output.contains(marker))
+        .map(|(_, vendor)| vendor.to_string())
+        .or_else(|| output.contains("OpenJDK").then(|| "OpenJDK".to_string()))
+}
+
+/// Extracts the major version from `java -version` output,
+/// e.g. `openjdk version "17.0.2"` -> 17 and `java version "1.8.0_381"` -> 8
+pub fn parse_java_major_version(output: &str) -> Option<u32> {
+    let version = parse_full_version(output)?;
+    let mut parts = version.split(['.', '_', '-', '+']);
+    let first: u32 = parts.next()?.parse().ok()?;
+    if first == 1 {
+        // Legacy "1.x" scheme used up to Java 8
+        parts.next()?.parse().ok()
+    } else {
+        Some(first)
+    }
+}
+
+/// The Java major version a failed program says it needs, from an `UnsupportedClassVersionError`
+/// (class file version 61.0 -> Java 17) or a plain "requires Java 17" message
+pub fn parse_required_java_version(output: &str) -> Option<u32> {
+    let class_version_regex =
+        regex::Regex::new(r"(?:class file version|Unsupported major\.minor version) (\d+)(?:\.0)?").ok()?;
+    // Class file versions count up from 45 for Java 1.1
+    let from_class_version = class_version_regex
+        .captures_iter(output)
+        .filter_map(|captures| captures[1].parse::<u32>().ok())
+        .filter(|class_version| *class_version > 44)
+        .map(|class_version| class_version - 44)
+        .max();
+
+    let message_regex = regex::Regex::new(r"(?i)(?:requires|needs|need) (?:at least )?Java (\d+)").ok()?;
+    let from_message = message_regex
+        .captures_iter(output)
+        .filter_map(|captures| captures[1].parse::<u32>().ok())
+        .max();
+
+    from_class_version.max(from_message)
+}
+
+/// Minimum Java major version required to run a given Minecraft version
+pub fn required_java_version(minecraft_version: &str) -> u32 {
+    let parts: Vec<u32> = minecraft_version
+        .split('.')
+        .map_while(|part| part.parse().ok())
+        .collect();
+
+    match parts.as_slice() {
+        [1, minor, rest @ ..] => {
+            let patch = rest.first().copied().unwrap_or(0);
+            match *minor {
+                0..=16 => 8,
+                17 => 16,
+                18 | 19 => 17,
+                20 if patch <= 4 => 17,
+                _ => 21,
+            }
+        }
+        // Year-based releases (26.1 and later)
+        [major, ..] if *major >= 26 => 25,
+        // Snapshots and unknown formats: assume a current release
+        _ => 21,
+    }
+}
+
+/// The java executable of the bundled runtime for `major_version`, if it is installed
+pub fn bundled_java_path(major_version: u32) -> Option<PathBuf> {
+    bundled_java_in(Path::new(RUNTIMES_DIR), major_version)
+}
+
+/// The path is made absolute because servers and installers are spawned from their own folder,
+/// where a relative one would resolve against the wrong directory
+fn bundled_java_in(runtimes: &Path, major_version: u32) -> Option<PathBuf> {
+    find_java_binary(&std::path::absolute(runtimes.join(major_version.to_string())).ok()?)
+}
+
+/// Returns the bundled Java `major_version` runtime, downloading the latest Eclipse Temurin JRE
+/// for this platform first if it isn't installed yet
+pub async fn get_or_install_jre(client: &Client, major_version: u32) -> Result<PathBuf> {
+    if let Some(java) = bundled_java_path(major_version) {
+        return Ok(java);
+    }
+
+    let (os, arch) = adoptium_platform()?;
+    let url = format!(
+        "{}/assets/latest/{}/hotspot?architecture={}&image_type=jre&os={}&vendor=eclipse",
+        ADOPTIUM_API, major_version, arch, os
+    );
+    let releases: Vec<AdoptiumRelease> = get_json(client, &url).await?;
+    let package = releases
+        .into_iter()
+        .next()
+        .map(|release| release.binary.package)
+        .ok_or_else(|| anyhow!("No Java {} runtime is published for {} {}", major_version, os, arch))?;
+
+    log::info!("Installing bundled Java {} from {}", major_version, package.link);
+
+    let runtimes = std::path::absolute(RUNTIMES_DIR)?;
+    let archive = runtimes.join(&package.name);
+    download_to_file(client, &package.link, &archive, Some(&Checksum::Sha256(package.checksum))).await?;
+
+    // Unpack beside the target and move it into place only once it's known to be usable
+    let target = runtimes.join(major_version.to_string());
+    let staging = runtimes.join(format!("{}.installing", major_version));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::create_dir_all(&staging)?;
+
+    let unpacked = extract_archive(&archive, &staging).and_then(|_| {
+        find_java_binary(&staging).ok_or_else(|| anyhow!("{} contains no java executable", package.name))
+    });
+    let _ = fs::remove_file(&archive);
+    if let Err(e) = unpacked {
+        let _ = fs::remove_dir_all(&staging);
+        return Err(e);
+    }
+
+    if target.exists() {
+        fs::remove_dir_all(&target)?;
+    }
+    fs::rename(&staging, &target)?;
+
+    let java = find_java_binary(&target).ok_or_else(|| anyhow!("Java {} runtime disappeared after install", major_version))?;
+    log::info!("Bundled Java {} installed at {:?}", major_version, java);
+    Ok(java)
+}
+
+/// Adoptium's names for the current OS and CPU architecture
+fn adoptium_platform() -> Result<(&'static str, &'static str)> {
+    let os = match std::env::consts::OS {
+        "linux" => "linux",
+        "macos" => "mac",
+        "windows" => "windows",
+        other => return Err(anyhow!("No bundled Java is available for {}", other)),
+    };
+    let arch = match std::env::consts::ARCH {
+        "x86_64" => "x64",
+        "aarch64" => "aarch64",
+        "x86" => "x32",
+        "arm" => "arm",
+        other => return Err(anyhow!("No bundled Java is available for {} CPUs", other)),
+    };
+    Ok((os, arch))
+}
+
+/// Unpacks a Temurin archive: .zip on Windows, .tar.gz elsewhere. The system tar is used for
+/// the latter because it restores the executable bits and symlinks the runtime depends on.
+fn extract_archive(archive: &Path, destination: &Path) -> Result<()> {
+    let name = archive.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+
+    if name.ends_with(".zip") {
+        let mut zip = zip::ZipArchive::new(File::open(archive)?)?;
+        for index in 0..zip.len() {
+            let mut entry = zip.by_index(index)?;
+            let relative = entry
+                .enclosed_name()
+                .filter(|path| path.components().all(|c| matches!(c, Component::Normal(_))))
+                .ok_or_else(|| anyhow!("Archive contains an unsafe path: {}", entry.name()))?;
+
+            let target = destination.join(relative);
+            if entry.is_dir() {
+                fs::create_dir_all(&target)?;
+            } else {
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                std::io::copy(&mut entry, &mut File::create(&target)?)?;
+            }
+        }
+        Ok(())
+    } else if name.ends_with(".tar.gz") {
+        let output = Command::new("tar")
+            .arg("-xzf")
+            .arg(archive)
+            .arg("-C")
+            .arg(destination)
+            .output()
+            .map_err(|e| anyhow!("Could not run tar to unpack the Java runtime: {}", e))?;
+        if !output.status.success() {
+            return Err(anyhow!("tar failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        Ok(())
+    } else {
+        Err(anyhow!("Unsupported Java runtime archive: {}", name))
+    }
+}
+
+/// Finds bin/java inside an unpacked runtime; archives nest it one level down
+/// (jdk-21.0.2+13-jre/bin) and on macOS a few more (.../Contents/Home/bin)
+fn find_java_binary(root: &Path) -> Option<PathBuf> {
+    let executable = if cfg!(windows) { "java.exe" } else { "java" };
+    let mut dirs = vec![root.to_path_buf()];
+
+    for _ in 0..4 {
+        if let Some(java) = dirs.iter().map(|dir| dir.join("bin").join(executable)).find(|java| java.is_file()) {
+            return Some(java);
+        }
+        dirs = dirs
+            .iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flat_map(|entries| entries.flatten().map(|entry| entry.path()))
+            .filter(|path| path.is_dir())
+            .collect();
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_java_version_a_program_requires() {
+        let class_version_error = "Exception in thread \"main\" java.lang.UnsupportedClassVersionError: \
+            net/minecraftforge/installer/SimpleInstaller has been compiled by a more recent version of the \
+            Java Runtime (class file version 61.0), this version of the Java Runtime only recognizes class \
+            file versions up to 52.0";
+        assert_eq!(parse_required_java_version(class_version_error), Some(17));
+        assert_eq!(parse_required_java_version("Unsupported major.minor version 52.0"), Some(8));
+        assert_eq!(parse_required_java_version("This installer requires at least Java 21"), Some(21));
+        assert_eq!(parse_required_java_version("Minecraft 1.20.5 needs Java 21 to run"), Some(21));
+        // The highest of several hints wins
+        assert_eq!(parse_required_java_version("requires Java 17\n(class file version 65.0)"), Some(21));
+        assert_eq!(parse_required_java_version("Downloading libraries... done"), None);
+    }
+
+    const TEMURIN_17: &str = "openjdk version \"17.0.2\" 2022-01-18\n\
+        OpenJDK Runtime Environment Temurin-17.0.2+8 (build 17.0.2+8)\n\
+        OpenJDK 64-Bit Server VM Temurin-17.0.2+8 (build 17.0.2+8, mixed mode, sharing)";
+    const ORACLE_8: &str = "java version \"1.8.0_381\"\n\
+        Java(TM) SE Runtime Environment (build 1.8.0_381-b09)\n\
+        Java HotSpot(TM) 64-Bit Server VM (build 25.381-b09, mixed mode)";
+
+    #[test]
+    fn reads_full_and_major_versions() {
+        assert_eq!(parse_full_version(TEMURIN_17).as_deref(), Some("17.0.2"));
+        assert_eq!(parse_java_major_version(TEMURIN_17), Some(17));
+        assert_eq!(parse_full_version(ORACLE_8).as_deref(), Some("1.8.0_381"));
+        assert_eq!(parse_java_major_version(ORACLE_8), Some(8));
+        assert_eq!(parse_java_major_version("openjdk version \"21\" 2023-09-19"), Some(21));
+        assert_eq!(parse_java_major_version("openjdk version \"23-ea\" 2024-09-17"), Some(23));
+        assert_eq!(parse_full_version("bash: java: command not found"), None);
+        assert_eq!(parse_java_major_version("openjdk version \"unknown\""), None);
+    }
+
+    #[test]
+    fn names_the_distribution() {
+        assert_eq!(parse_java_vendor(TEMURIN_17).as_deref(), Some("Eclipse Temurin"));
+        assert_eq!(parse_java_vendor(ORACLE_8).as_deref(), Some("Oracle"));
+        assert_eq!(
+            parse_java_vendor("OpenJDK Runtime Environment Corretto-21.0.1.12.1 (build 21.0.1+12-LTS)").as_deref(),
+            Some("Amazon Corretto")
+        );
+        assert_eq!(parse_java_vendor("OpenJDK Runtime Environment (build 17.0.9+9-Ubuntu)").as_deref(), Some("OpenJDK"));
+        assert_eq!(parse_java_vendor("something else entirely"), None);
+    }
+
+    #[test]
+    fn resolves_paths_and_commands() {
+        let dir = std::env::temp_dir().join(format!("allay-java-resolve-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let java = dir.join("java");
+        fs::write(&java, "").unwrap();
+
+        let resolved = resolve_executable(&java.to_string_lossy());
+        let missing = resolve_executable(&dir.join("missing").to_string_lossy());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(resolved, Some(java));
+        assert_eq!(missing, None);
+        assert_eq!(resolve_executable("allay-no-such-command"), None);
+        #[cfg(unix)]
+        assert!(resolve_executable("sh").is_some_and(|path| path.is_absolute()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn bundled_java_runs_from_a_server_folder() {
+        use std::os::unix::fs::PermissionsExt;
+
+        // Relative like RUNTIMES_DIR, to check the path survives a different current_dir
+        let runtimes = PathBuf::from(format!("allay-runtimes-test-{}", std::process::id()));
+        let bin = runtimes.join("21").join("jdk-21.0.4+7-jre").join("bin");
+        fs::create_dir_all(&bin).unwrap();
+        fs::write(bin.join("java"), "#!/bin/sh\necho bundled\n").unwrap();
+        fs::set_permissions(bin.join("java"), fs::Permissions::from_mode(0o755)).unwrap();
+        let server = std::env::temp_dir().join(format!("allay-java-server-{}", std::process::id()));
+        fs::create_dir_all(&server).unwrap();
+
+        let java = bundled_java_in(&runtimes, 21).unwrap();
+        let output = Command::new(&java).current_dir(&server).output();
+        let missing = bundled_java_in(&runtimes, 17);
+        fs::remove_dir_all(&runtimes).unwrap();
+        fs::remove_dir_all(&server).unwrap();
+
+        assert!(java.is_absolute());
+        assert_eq!(String::from_utf8_lossy(&output.unwrap().stdout).trim(), "bundled");
+        assert_eq!(missing, None);
+    }
+}
@@ -0,0 +1,259 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use crate::models::version::LoaderType;
+use crate::services::paper_strategy::PaperStrategy;
+use crate::services::world_info::{read_world_version, world_folder};
+
+/// How much of a detection rests on file names that spell out the versions
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DetectionConfidence {
+    /// The loader's own files name the loader and its versions
+    High,
+    /// The loader is clear, but a version is missing or was inferred
+    Medium,
+    /// Only indirect hints, e.g. a bare server.jar
+    Low,
+}
+
+/// The loader and versions an existing server directory appears to run
+#[derive(Debug, Clone, Serialize)]
+pub struct DetectedLoader {
+    pub loader: LoaderType,
+    pub minecraft_version: Option<String>,
+    /// Loader version, Paper build, or "craftbukkit" for CraftBukkit; None for Vanilla and Spigot
+    pub loader_version: Option<String>,
+    pub confidence: DetectionConfidence,
+}
+
+impl DetectedLoader {
+    fn new(loader: LoaderType, minecraft_version: Option<String>, loader_version: Option<String>) -> Self {
+        let needs_loader_version = !matches!(loader, LoaderType::Vanilla | LoaderType::Spigot);
+        let confidence = if minecraft_version.is_none() || (needs_loader_version && loader_version.is_none()) {
+            DetectionConfidence::Medium
+        } else {
+            DetectionConfidence::High
+        };
+        Self { loader, minecraft_version, loader_version, confidence }
+    }
+}
+
+/// "fabric-server-mc.1.20.4-loader.0.15.11-launcher.1.0.3.jar" -> ("1.20.4", "0.15.11")
+fn parse_fabric_jar(file_name: &str) -> Option<(String, String)> {
+    let rest = file_name.strip_prefix("fabric-server-mc.")?.strip_suffix(".jar")?;
+    let (minecraft_version, rest) = rest.split_once("-loader.")?;
+    let loader_version = rest.split("-launcher.").next()?;
+    Some((minecraft_version.to_string(), loader_version.to_string()))
+}
+
+/// "paper-1.20.4-496.jar" -> ("1.20.4", Some(496)); "paper-1.20.4.jar" -> ("1.20.4", None)
+fn parse_paper_jar(file_name: &str) -> Option<(String, Option<u32>)> {
+    let rest = file_name.strip_prefix("paper-")?.strip_suffix(".jar")?;
+    match rest.rsplit_once('-') {
+        Some((minecraft_version, build)) if build.chars().all(|c| c.is_ascii_digit()) => {
+            Some((minecraft_version.to_string(), build.parse().ok()))
+        }
+        _ => Some((rest.to_string(), None)),
+    }
+}
+
+/// "1.20.1-47.2.0" from forge-1.20.1-47.2.0-installer.jar, -universal.jar, -shim.jar or the
+/// plain server jar of old versions
+fn parse_forge_jar(file_name: &str) -> Option<(String, String)> {
+    let rest = file_name.strip_prefix("forge-")?.strip_suffix(".jar")?;
+    let rest = ["-installer", "-universal", "-shim", "-server"]
+        .iter()
+        .find_map(|suffix| rest.strip_suffix(suffix))
+        .unwrap_or(rest);
+    split_forge_version(rest)
+}
+
+/// "1.20.1-47.2.0" -> ("1.20.1", "47.2.0")
+fn split_forge_version(version: &str) -> Option<(String, String)> {
+    let (minecraft_version, forge_version) = version.split_once('-')?;
+    let looks_like_version = |part: &str| part.starts_with(|c: char| c.is_ascii_digit());
+    (looks_like_version(minecraft_version) && looks_like_version(forge_version))
+        .then(|| (minecraft_version.to_string(), forge_version.to_string()))
+}
+
+/// NeoForge versions follow Minecraft's: 20.4.237 is for 1.20.4 and 21.0.167 for 1.21
+fn neoforge_minecraft_version(neoforge_version: &str) -> Option<String> {
+    let mut parts = neoforge_version.split('.');
+    let major: u32 = parts.next()?.parse().ok()?;
+    let minor: u32 = parts.next()?.parse().ok()?;
+    Some(if minor == 0 { format!("1.{}", major) } else { format!("1.{}.{}", major, minor) })
+}
+
+/// Version folders of a library under libraries/, e.g. libraries/net/neoforged/neoforge/<version>
+fn library_versions(server_path: &Path, group_path: &str) -> Vec<String> {
+    let mut versions: Vec<String> = fs::read_dir(server_path.join("libraries").join(group_path))
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    versions.sort();
+    versions
+}
+
+/// The (Minecraft, loader) versions named by a Quilt server profile, whose id is
+/// "quilt-loader-<loader>-<minecraft>" and which inherits from the Minecraft version
+fn read_quilt_profile(profile_path: &Path) -> (Option<String>, Option<String>) {
+    let Some(profile) = fs::read_to_string(profile_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+    else {
+        return (None, None);
+    };
+
+    let minecraft_version = profile["inheritsFrom"].as_str().map(str::to_string);
+    let loader_version = profile["id"].as_str().and_then(|id| {
+        let rest = id.strip_prefix("quilt-loader-")?;
+        let loader = match &minecraft_version {
+            Some(minecraft) => rest.strip_suffix(&format!("-{}", minecraft))?,
+            None => rest.split('-').next()?,
+        };
+        Some(loader.to_string())
+    });
+    (minecraft_version, loader_version)
+}
+
+/// Guesses the loader from the files in the directory, most specific first
+fn detect_from_files(server_path: &Path, file_names: &[String]) -> Option<DetectedLoader> {
+    let has = |name: &str| file_names.iter().any(|file| file == name);
+    let has_run_script = has("run.sh") || has("run.bat");
+
+    if let Some((minecraft, loader)) = file_names.iter().find_map(|name| parse_fabric_jar(name)) {
+        return Some(DetectedLoader::new(LoaderType::Fabric, Some(minecraft), Some(loader)));
+    }
+
+    if has("quilt-server-profile.json") {
+        let (minecraft, loader) = read_quilt_profile(&server_path.join("quilt-server-profile.json"));
+        return Some(DetectedLoader::new(LoaderType::Quilt, minecraft, loader));
+    }
+
+    // NeoForge before Forge: both keep a run script and a libraries folder
+    let neoforge_version = file_names
+        .iter()
+        .find_map(|name| name.strip_prefix("neoforge-")?.strip_suffix("-installer.jar").map(str::to_string))
+        .or_else(|| library_versions(server_path, "net/neoforged/neoforge").pop());
+    if let Some(version) = neoforge_version {
+        let mut detected = DetectedLoader::new(LoaderType::NeoForge, neoforge_minecraft_version(&version), Some(version));
+        if !has_run_script {
+            detected.confidence = DetectionConfidence::Medium;
+        }
+        return Some(detected);
+    }
+
+    let forge_version = file_names
+        .iter()
+        .find_map(|name| parse_forge_jar(name))
+        .or_else(|| library_versions(server_path, "net/minecraftforge/forge").pop().and_then(|v| split_forge_version(&v)));
+    if let Some((minecraft, forge)) = forge_version {
+        return Some(DetectedLoader::new(LoaderType::Forge, Some(minecraft), Some(forge)));
+    }
+    if has_run_script && has("user_jvm_args.txt") {
+        return Some(DetectedLoader {
+            loader: LoaderType::Forge,
+            minecraft_version: None,
+            loader_version: None,
+            confidence: DetectionConfidence::Low,
+        });
+    }
+
+    if let Some((minecraft, build)) = file_names.iter().find_map(|name| parse_paper_jar(name)) {
+        // Unpinned jars don't name their build, but Paper records it in version_history.json
+        let build = build.or_else(|| PaperStrategy::installed_build(server_path, ""));
+        return Some(DetectedLoader::new(LoaderType::Paper, Some(minecraft), build.map(|b| b.to_string())));
+    }
+
+    for (prefix, loader_version) in [("spigot-", None), ("craftbukkit-", Some("craftbukkit"))] {
+        if let Some(minecraft) = file_names.iter().find_map(|name| name.strip_prefix(prefix)?.strip_suffix(".jar")) {
+            let mut detected = DetectedLoader::new(LoaderType::Spigot, Some(minecraft.to_string()), None);
+            detected.loader_version = loader_version.map(str::to_string);
+            return Some(detected);
+        }
+    }
+
+    if let Some(minecraft) = file_names.iter().find_map(|name| name.strip_prefix("server-")?.strip_suffix(".jar")) {
+        return Some(DetectedLoader::new(LoaderType::Vanilla, Some(minecraft.to_string()), None));
+    }
+    if has("server.jar") || has("minecraft_server.jar") {
+        return Some(DetectedLoader {
+            loader: LoaderType::Vanilla,
+            minecraft_version: None,
+            loader_version: None,
+            confidence: DetectionConfidence::Low,
+        });
+    }
+
+    None
+}
+
+/// Inspects a server directory and guesses which loader and versions it runs. Returns None
+/// when nothing in it looks like a Minecraft server. A Minecraft version the files don't name
+/// is read from the world, if one was generated.
+pub fn detect_server_loader(server_path: &Path) -> Result<Option<DetectedLoader>> {
+    if !server_path.is_dir() {
+        return Err(anyhow!("{:?} is not a directory", server_path));
+    }
+
+    let file_names: Vec<String> = fs::read_dir(server_path)?
+        .flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+
+    let mut detected = detect_from_files(server_path, &file_names);
+    if let Some(detected) = detected.as_mut().filter(|detected| detected.minecraft_version.is_none()) {
+        detected.minecraft_version = read_world_version(&world_folder(server_path)).ok().and_then(|version| version.name);
+    }
+    Ok(detected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_loader_jar_names() {
+        assert_eq!(
+            parse_fabric_jar("fabric-server-mc.1.20.4-loader.0.15.11-launcher.1.0.3.jar"),
+            Some(("1.20.4".to_string(), "0.15.11".to_string()))
+        );
+        assert_eq!(parse_paper_jar("paper-1.20.4-496.jar"), Some(("1.20.4".to_string(), Some(496))));
+        assert_eq!(parse_paper_jar("paper-1.21.jar"), Some(("1.21".to_string(), None)));
+        assert_eq!(
+            parse_forge_jar("forge-1.20.1-47.2.0-installer.jar"),
+            Some(("1.20.1".to_string(), "47.2.0".to_string()))
+        );
+        assert_eq!(parse_forge_jar("forge-installer.jar"), None);
+        assert_eq!(neoforge_minecraft_version("20.4.237").as_deref(), Some("1.20.4"));
+        assert_eq!(neoforge_minecraft_version("21.0.167-beta").as_deref(), Some("1.21"));
+    }
+
+    #[test]
+    fn prefers_the_most_specific_loader() {
+        let detect = |files: &[&str]| {
+            let files: Vec<String> = files.iter().map(|file| file.to_string()).collect();
+            detect_from_files(Path::new("does-not-exist"), &files)
+        };
+
+        // Fabric servers also keep the vanilla server jar
+        let fabric = detect(&["server.jar", "fabric-server-mc.1.20.1-loader.0.14.21-launcher.1.0.3.jar"]).unwrap();
+        assert_eq!(fabric.loader, LoaderType::Fabric);
+        assert_eq!(fabric.confidence, DetectionConfidence::High);
+
+        let neoforge = detect(&["run.sh", "neoforge-20.4.237-installer.jar"]).unwrap();
+        assert_eq!((neoforge.loader, neoforge.minecraft_version.as_deref()), (LoaderType::NeoForge, Some("1.20.4")));
+
+        let vanilla = detect(&["server.jar", "eula.txt"]).unwrap();
+        assert_eq!((vanilla.loader, vanilla.confidence), (LoaderType::Vanilla, DetectionConfidence::Low));
+
+        assert!(detect(&["notes.txt"]).is_none());
+    }
+}
@@ -1,20 +1,60 @@
 pub mod version_service;
 pub mod version_manager;
+pub mod update_check;
 pub mod mod_loader_strategy;
 pub mod unified_server_service;
+pub mod resumable_download;
+pub mod http_client;
+pub mod rate_limiter;
 
 // RCON services
 pub mod rcon_service;
 pub mod rcon_manager;
 pub mod heartbeat_manager;
 pub mod rcon_global;
+pub mod rcon_commands;
+pub mod gamerules;
 
 // Query service
 pub mod query_service;
 
+// Port management
+pub mod port_manager;
+
+// Diagnostics
+pub mod java_runtime;
+pub mod diagnostics;
+pub mod crash_reports;
+
+// Mods and plugins
+pub mod modrinth;
+pub mod mod_installer;
+
+// Notifications
+pub mod notification_service;
+
+// Import/export
+pub mod server_bundle;
+pub mod loader_detection;
+pub mod world_info;
+pub mod world_preset;
+pub mod backup_scheduler;
+pub mod server_templates;
+
+// File browser
+pub mod server_files;
+pub mod server_icon;
+pub mod whitelist;
+pub mod player_profile;
+
+// Local automation API
+#[cfg(feature = "http-api")]
+pub mod http_api;
+
 // Server monitoring services
 pub mod server_monitor;
 pub mod simple_rcon_monitor;
+pub mod console_log;
 
 // Individual mod loader strategies
 pub mod vanilla_strategy;
@@ -22,4 +62,6 @@ pub mod fabric_strategy;
 pub mod forge_strategy;
 pub mod neoforge_strategy;
 pub mod paper_strategy;
-pub mod quilt_strategy;
\ No newline at end of file
+pub mod quilt_strategy;
+pub mod spigot_strategy;
+
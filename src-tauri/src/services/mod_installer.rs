@@ -0,0 +1,84 @@
+use crate::models::version::LoaderType;
+use crate::services::modrinth;
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Modrinth project id of Fabric API
+const FABRIC_API_PROJECT: &str = "P7dR8mSH";
+
+/// Folder a loader reads mods or plugins from
+pub fn addon_folder(loader: &LoaderType) -> &'static str {
+    match loader {
+        LoaderType::Paper | LoaderType::Spigot => "plugins",
+        _ => "mods",
+    }
+}
+
+/// Copies a mod or plugin JAR into the server's mods/plugins folder and returns its new path
+pub fn install_mod_from_path(server_path: &Path, loader: &LoaderType, source: &Path) -> Result<PathBuf> {
+    let is_jar = source
+        .extension()
+        .map(|ext| ext.eq_ignore_ascii_case("jar"))
+        .unwrap_or(false);
+    if !source.is_file() || !is_jar {
+        return Err(anyhow!("{:?} is not a JAR file", source));
+    }
+
+    let dest_dir = server_path.join(addon_folder(loader));
+    fs::create_dir_all(&dest_dir)?;
+    let dest = dest_dir.join(source.file_name().ok_or_else(|| anyhow!("Invalid file name {:?}", source))?);
+    fs::copy(source, &dest)?;
+
+    log::info!("Installed {:?} into {:?}", source.file_name().unwrap_or_default(), dest_dir);
+    Ok(dest)
+}
+
+/// True if the JAR's fabric.mod.json lists Fabric API ("fabric-api", or "fabric" before 0.42) as a dependency
+pub fn requires_fabric_api(jar_path: &Path) -> bool {
+    let read_manifest = || -> Result<serde_json::Value> {
+        let mut archive = zip::ZipArchive::new(fs::File::open(jar_path)?)?;
+        let mut content = String::new();
+        archive.by_name("fabric.mod.json")?.read_to_string(&mut content)?;
+        Ok(serde_json::from_str(&content)?)
+    };
+
+    match read_manifest() {
+        Ok(manifest) => manifest
+            .get("depends")
+            .and_then(|depends| depends.as_object())
+            .map(|depends| depends.contains_key("fabric-api") || depends.contains_key("fabric"))
+            .unwrap_or(false),
+        Err(_) => false,
+    }
+}
+
+fn has_fabric_api(mods_dir: &Path) -> bool {
+    fs::read_dir(mods_dir)
+        .map(|entries| {
+            entries.flatten().any(|entry| {
+                let name = entry.file_name().to_string_lossy().to_lowercase();
+                name.starts_with("fabric-api") && name.ends_with(".jar")
+            })
+        })
+        .unwrap_or(false)
+}
+
+/// Downloads the newest Fabric API build for `minecraft_version` into mods/ unless one is
+/// already installed. Returns the installed file, or None if Fabric API was already present.
+pub async fn ensure_fabric_api(client: &Client, server_path: &Path, minecraft_version: &str) -> Result<Option<PathBuf>> {
+    let mods_dir = server_path.join("mods");
+    if has_fabric_api(&mods_dir) {
+        return Ok(None);
+    }
+
+    let versions = modrinth::get_project_versions(client, FABRIC_API_PROJECT, &["fabric"], minecraft_version).await?;
+    let file = versions
+        .first()
+        .and_then(|version| version.primary_file())
+        .ok_or_else(|| anyhow!("No Fabric API release found for Minecraft {}", minecraft_version))?;
+
+    modrinth::download_file(client, file, &mods_dir).await.map(Some)
+}
@@ -1,9 +1,20 @@
 use anyhow::{anyhow, Result};
 use async_trait::async_trait;
 use reqwest::Client;
-use std::path::PathBuf;
+use std::fs;
+use std::path::{Path, PathBuf};
 use crate::models::version::{LoaderType, VersionResponse};
 use crate::util::JarCacheManager;
+use crate::services::resumable_download::{download_to_file, Checksum};
+use crate::services::http_client::HttpClient;
+use crate::services::world_info::compare_release_versions;
+use crate::services::java_runtime::{check_java, parse_required_java_version};
+use serde::Serialize;
+use lazy_static::lazy_static;
+use std::process::Stdio;
+use std::sync::Mutex;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::sync::mpsc::UnboundedSender;
 
 // Import all strategy implementations
 use crate::services::vanilla_strategy::VanillaStrategy;
@@ -12,69 +23,191 @@ use crate::services::forge_strategy::ForgeStrategy;
 use crate::services::neoforge_strategy::NeoForgeStrategy;
 use crate::services::paper_strategy::PaperStrategy;
 use crate::services::quilt_strategy::QuiltStrategy;
+use crate::services::spigot_strategy::SpigotStrategy;
+
+/// Smallest heap we hand to the JVM, regardless of configuration
+pub const MIN_HEAP_MB: u32 = 512;
+
+/// What a strategy needs to fetch (or build) a server JAR into the shared cache
+pub struct DownloadContext<'a> {
+    pub client: &'a Client,
+    pub jar_cache: &'a JarCacheManager,
+    /// Java used by strategies that compile the JAR locally (Spigot's BuildTools)
+    pub java_command: &'a str,
+}
+
+/// Converts the configured memory into (-Xmx, -Xms) sizes in megabytes.
+/// The initial heap is half the maximum, rounded down to a 64 MB step, and both respect MIN_HEAP_MB.
+pub fn heap_sizes_mb(memory_mb: u32) -> (u32, u32) {
+    let max_mb = memory_mb.max(MIN_HEAP_MB);
+    let min_mb = (max_mb / 2 / 64 * 64).clamp(MIN_HEAP_MB.min(max_mb), max_mb);
+    (max_mb, min_mb)
+}
+
+/// The heap a configured memory amount actually turns into at launch
+#[derive(Debug, Clone, Serialize)]
+pub struct EffectiveMemory {
+    /// The stored setting
+    pub requested_mb: u32,
+    /// -Xmx passed to the JVM
+    pub max_heap_mb: u32,
+    /// -Xms passed to the JVM
+    pub initial_heap_mb: u32,
+    /// Set when the setting isn't used as-is
+    pub warning: Option<String>,
+}
+
+/// What `heap_sizes_mb` makes of `memory_mb`, with a warning when the two differ
+pub fn effective_memory(memory_mb: u32) -> EffectiveMemory {
+    let (max_heap_mb, initial_heap_mb) = heap_sizes_mb(memory_mb);
+    let warning = (max_heap_mb != memory_mb).then(|| {
+        format!(
+            "{} MB is below the {} MB minimum heap; the server will start with {} MB",
+            memory_mb, MIN_HEAP_MB, max_heap_mb
+        )
+    });
+    EffectiveMemory {
+        requested_mb: memory_mb,
+        max_heap_mb,
+        initial_heap_mb,
+        warning,
+    }
+}
+
+/// Memory bounds suggested for a new server, for the creation slider
+#[derive(Debug, Clone, Serialize)]
+pub struct MemoryRecommendation {
+    pub min_mb: u32,
+    pub recommended_mb: u32,
+    pub max_mb: u32,
+    /// Set when the machine can't fit the ideal amount and the values were capped
+    pub warning: Option<String>,
+}
+
+/// Rough heap needs from the loader's baseline, players and installed mods or plugins,
+/// capped so the OS keeps at least 2 GB (or a quarter of `system_mb`, whichever is more)
+pub fn recommend_memory_mb(
+    loader: &LoaderType,
+    minecraft_version: &str,
+    expected_players: u32,
+    mod_count: u32,
+    system_mb: u64,
+) -> MemoryRecommendation {
+    let (base_mb, per_player_mb, per_mod_mb) = match loader {
+        LoaderType::Vanilla => (1024, 64, 0),
+        // Plugins are much lighter than mods
+        LoaderType::Paper | LoaderType::Spigot => (1024, 48, 16),
+        LoaderType::Fabric | LoaderType::Quilt => (1536, 96, 32),
+        LoaderType::Forge | LoaderType::NeoForge => (2560, 128, 48),
+    };
+    // 1.18 doubled the world height, which shows up in chunk memory
+    let tall_world_mb = match compare_release_versions(minecraft_version, "1.18") {
+        Some(std::cmp::Ordering::Less) => 0,
+        _ => 512,
+    };
+
+    let round_up = |mb: u32, step: u32| mb.div_ceil(step) * step;
+    let ideal_mb = round_up(
+        base_mb + tall_world_mb + per_player_mb * expected_players + per_mod_mb * mod_count,
+        512,
+    );
+
+    let reserved_mb = (system_mb / 4).max(2048);
+    let usable_mb = system_mb.saturating_sub(reserved_mb).min(u32::MAX as u64) as u32;
+    let usable_mb = (usable_mb / 256 * 256).max(MIN_HEAP_MB);
+
+    let recommended_mb = ideal_mb.min(usable_mb);
+    let min_mb = round_up(recommended_mb * 3 / 5, 256).clamp(MIN_HEAP_MB, recommended_mb);
+    let max_mb = (ideal_mb * 2).min(usable_mb).max(recommended_mb);
+    let warning = (recommended_mb < ideal_mb).then(|| {
+        format!(
+            "This server would ideally use {} MB, but only {} MB can be spared on this machine",
+            ideal_mb, usable_mb
+        )
+    });
+
+    MemoryRecommendation { min_mb, recommended_mb, max_mb, warning }
+}
 
 /// Strategy trait for mod-loader-specific operations
 #[async_trait]
 pub trait ModLoaderStrategy: Send + Sync {
     /// Get versions for this mod loader
-    async fn get_versions(&self, client: &Client, minecraft_version: Option<String>) -> Result<VersionResponse>;
+    async fn get_versions(&self, client: &dyn HttpClient, minecraft_version: Option<String>) -> Result<VersionResponse>;
     
     /// Get the download URL for this mod loader
     async fn get_download_url(&self, client: &Client, minecraft_version: &str, loader_version: &str) -> Result<String>;
     
+    /// Checksum the download source publishes for the file at `download_url`, if any
+    async fn get_checksum(&self, _client: &Client, _download_url: &str, _minecraft_version: &str, _loader_version: &str) -> Result<Option<Checksum>> {
+        Ok(None)
+    }
+    
     /// Get the filename for the downloaded file
     fn get_filename(&self, minecraft_version: &str, loader_version: &str) -> String;
+
+    /// Whether a top-level entry of the server directory belongs to this loader's installation
+    fn is_loader_file(&self, file_name: &str) -> bool;
     
-    /// Setup/install the mod loader in the server directory
-    async fn setup_server(&self, client: &Client, server_path: &PathBuf, minecraft_version: &str, loader_version: &str) -> Result<()>;
+    /// Setup/install the mod loader in the server directory; installers run on `java_command`
+    async fn setup_server(&self, client: &Client, java_command: &str, server_path: &PathBuf, minecraft_version: &str, loader_version: &str) -> Result<()>;
     
     /// Build the start command for this mod loader
-    fn build_start_command(&self, server_path: &PathBuf, memory_gb: u32, min_memory_gb: u32) -> Result<Vec<String>>;
+    fn build_start_command(&self, server_path: &PathBuf, memory_mb: u32, min_memory_mb: u32) -> Result<Vec<String>>;
+
+    /// Reconciles launch files with the configured memory before every start.
+    /// Only needed by loaders whose run scripts read JVM flags from a file instead of the command line.
+    fn prepare_launch(&self, _server_path: &Path, _memory_mb: u32, _min_memory_mb: u32) -> Result<()> {
+        Ok(())
+    }
 
     /// Default implementation for downloading and caching JAR files
     /// Can be overridden by strategies that need special handling
     async fn download_server_jar(
         &self, 
-        client: &Client, 
-        jar_cache: &JarCacheManager,
+        context: &DownloadContext<'_>,
         minecraft_version: &str, 
         loader_version: &str, 
         server_path: &PathBuf,
         loader_type: &LoaderType
     ) -> Result<PathBuf> {
+        let (client, jar_cache) = (context.client, context.jar_cache);
         let loader_version_opt = if loader_version.is_empty() { None } else { Some(loader_version) };
         
         // Check if JAR is cached first
         if jar_cache.is_jar_cached(loader_type, minecraft_version, loader_version_opt) {
-            println!("{:?} JAR found in cache, copying to server: {:?}", loader_type, server_path);
+            log::debug!("{:?} JAR found in cache, copying to server: {:?}", loader_type, server_path);
             return jar_cache.copy_cached_jar_to_server(loader_type, minecraft_version, loader_version_opt, server_path);
         }
 
-        println!("{:?} JAR not in cache, downloading...", loader_type);
+        log::debug!("{:?} JAR not in cache, downloading...", loader_type);
         
         let download_url = self.get_download_url(client, minecraft_version, loader_version).await?;
         let jar_name = self.get_filename(minecraft_version, loader_version);
 
-        println!("Downloading {} from: {}", jar_name, download_url);
-
-        // Download the JAR file
-        let response = client.get(&download_url).send().await?;
-        
-        if !response.status().is_success() {
-            return Err(anyhow!("Failed to download {:?} JAR: HTTP {}", loader_type, response.status()));
-        }
+        log::info!("Downloading {} from: {}", jar_name, download_url);
 
-        let bytes = response.bytes().await?;
+        // A missing checksum only skips verification; it must not block the download
+        let checksum = match self.get_checksum(client, &download_url, minecraft_version, loader_version).await {
+            Ok(checksum) => checksum,
+            Err(e) => {
+                log::warn!("Could not fetch the checksum for {}: {}", jar_name, e);
+                None
+            }
+        };
 
-        // Cache the JAR first
-        println!("Caching downloaded {:?} JAR...", loader_type);
-        jar_cache.cache_jar(loader_type, minecraft_version, loader_version_opt, &bytes)?;
+        // Download straight into the cache through a .part file that is promoted once verified
+        let cached_path = jar_cache.prepare_cache_entry(loader_type, minecraft_version, loader_version_opt)?;
+        download_to_file(client, &download_url, &cached_path, checksum.as_ref())
+            .await
+            .map_err(|e| anyhow!("Failed to download {:?} JAR: {}", loader_type, e))?;
+        jar_cache.record_checksum(&cached_path);
 
         // Then copy it to the server directory
-        println!("Copying cached {:?} JAR to server: {:?}", loader_type, server_path);
+        log::debug!("Copying cached {:?} JAR to server: {:?}", loader_type, server_path);
         let jar_path = jar_cache.copy_cached_jar_to_server(loader_type, minecraft_version, loader_version_opt, server_path)?;
 
-        println!("Successfully downloaded and cached {:?} JAR: {:?}", loader_type, jar_path);
+        log::info!("Successfully downloaded and cached {:?} JAR: {:?}", loader_type, jar_path);
         Ok(jar_path)
     }
 }
@@ -88,5 +221,155 @@ pub fn get_strategy(loader_type: &LoaderType) -> Box<dyn ModLoaderStrategy> {
         LoaderType::NeoForge => Box::new(NeoForgeStrategy),
         LoaderType::Paper => Box::new(PaperStrategy),
         LoaderType::Quilt => Box::new(QuiltStrategy),
+        LoaderType::Spigot => Box::new(SpigotStrategy),
+    }
+}
+
+/// Writes the heap flags into user_jvm_args.txt (read by the Forge/NeoForge run scripts),
+/// replacing existing -Xmx/-Xms lines and keeping any other user-provided arguments
+pub fn write_user_jvm_args(server_path: &Path, max_memory_arg: &str, min_memory_arg: &str) -> std::io::Result<()> {
+    let jvm_args_path = server_path.join("user_jvm_args.txt");
+    let content = fs::read_to_string(&jvm_args_path).unwrap_or_default();
+
+    let mut lines: Vec<String> = content
+        .lines()
+        .filter(|line| {
+            let trimmed = line.trim();
+            !trimmed.starts_with("-Xmx") && !trimmed.starts_with("-Xms")
+        })
+        .map(str::to_string)
+        .collect();
+    lines.push(max_memory_arg.to_string());
+    lines.push(min_memory_arg.to_string());
+
+    fs::write(&jvm_args_path, lines.join("\n") + "\n")
+}
+
+lazy_static! {
+    /// Receives installer output while a Forge/NeoForge install runs; set once the app is up
+    static ref INSTALL_PROGRESS_SENDER: Mutex<Option<UnboundedSender<InstallProgressEvent>>> = Mutex::new(None);
+}
+
+/// One line of installer output, sent as an "install-progress" event
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallProgressEvent {
+    pub server_name: String,
+    pub loader: String,
+    pub line: String,
+}
+
+pub fn set_install_progress_sender(sender: UnboundedSender<InstallProgressEvent>) {
+    *INSTALL_PROGRESS_SENDER.lock().unwrap() = Some(sender);
+}
+
+/// Runs `<java_command> -jar <installer> --installServer` in the server folder, passing each
+/// line the installer prints on to the progress channel. Installs download dozens of libraries
+/// and take minutes, so this is what shows the user something is happening.
+pub async fn run_installer(loader_name: &str, java_command: &str, server_path: &Path, installer_filename: &str) -> Result<()> {
+    let mut child = tokio::process::Command::new(java_command)
+        .args(["-jar", installer_filename, "--installServer"])
+        .current_dir(server_path)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound {
+                anyhow!("Java is not installed or not found in PATH. Please install Java to run Minecraft servers.")
+            } else {
+                anyhow!("Failed to execute Java: {}", e)
+            }
+        })?;
+
+    let stdout = child.stdout.take().ok_or_else(|| anyhow!("Installer output is not available"))?;
+    let mut stderr = child.stderr.take().ok_or_else(|| anyhow!("Installer output is not available"))?;
+    // Drained separately so a chatty stderr can't fill its pipe and stall the installer
+    let stderr_task = tokio::spawn(async move {
+        let mut buffer = Vec::new();
+        let _ = stderr.read_to_end(&mut buffer).await;
+        buffer
+    });
+
+    let server_name = server_path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let sender = INSTALL_PROGRESS_SENDER.lock().unwrap().clone();
+    let mut captured_stdout = Vec::new();
+    let mut reader = BufReader::new(stdout);
+    let mut raw_line = Vec::new();
+    // Installers print paths and library names in the system code page, which need not be UTF-8
+    while reader.read_until(b'\n', &mut raw_line).await? > 0 {
+        if let Some(sender) = &sender {
+            let line = String::from_utf8_lossy(&raw_line);
+            let _ = sender.send(InstallProgressEvent {
+                server_name: server_name.clone(),
+                loader: loader_name.to_string(),
+                line: line.trim_end_matches(['\r', '\n']).to_string(),
+            });
+        }
+        captured_stdout.append(&mut raw_line);
+    }
+
+    let output = std::process::Output {
+        status: child.wait().await?,
+        stdout: captured_stdout,
+        stderr: stderr_task.await.unwrap_or_default(),
+    };
+    if !output.status.success() {
+        return Err(installer_error(loader_name, java_command, &output));
     }
-}
\ No newline at end of file
+    Ok(())
+}
+
+/// The error for a failed Forge/NeoForge installer run on `java_command`. When the installer was
+/// refused by an old Java the user is told which version to get rather than shown the raw stack trace.
+pub fn installer_error(loader_name: &str, java_command: &str, output: &std::process::Output) -> anyhow::Error {
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    let Some(required) = parse_required_java_version(&format!("{}\n{}", stdout, stderr)) else {
+        return anyhow!("{} installation failed: {}", loader_name, stderr);
+    };
+
+    let found = match check_java(java_command).version {
+        Some(version) => format!(" (found Java {})", version),
+        None => String::new(),
+    };
+    log::warn!("{} installer needs Java {}{}: {}", loader_name, required, found, stderr.trim());
+    // The bundled runtime is picked by Minecraft version, so it can only help when not in use yet
+    let advice = if java_command == "java" {
+        format!("install Java {} or newer, or enable the bundled Java runtime in the settings", required)
+    } else {
+        format!("install Java {} or newer and disable the bundled Java runtime in the settings", required)
+    };
+    anyhow!("The {} installer for this version needs Java {}{}; {}", loader_name, required, found, advice)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recommends_memory_by_loader_and_load() {
+        let vanilla = recommend_memory_mb(&LoaderType::Vanilla, "1.20.4", 0, 0, 16384);
+        assert_eq!((vanilla.min_mb, vanilla.recommended_mb, vanilla.max_mb), (1024, 1536, 3072));
+        assert!(vanilla.warning.is_none());
+
+        // Worlds before 1.18 are half as tall
+        let old = recommend_memory_mb(&LoaderType::Vanilla, "1.12.2", 0, 0, 16384);
+        assert_eq!(old.recommended_mb, 1024);
+
+        let modded = recommend_memory_mb(&LoaderType::Forge, "1.20.1", 10, 100, 16384);
+        assert_eq!((modded.min_mb, modded.recommended_mb, modded.max_mb), (5632, 9216, 12288));
+        assert!(modded.warning.is_none());
+    }
+
+    #[test]
+    fn caps_memory_to_what_the_machine_can_spare() {
+        let capped = recommend_memory_mb(&LoaderType::Forge, "1.20.1", 10, 100, 8192);
+        assert_eq!((capped.min_mb, capped.recommended_mb, capped.max_mb), (3840, 6144, 6144));
+        assert!(capped.warning.is_some());
+
+        let tiny = recommend_memory_mb(&LoaderType::Vanilla, "1.20.4", 0, 0, 2048);
+        assert_eq!((tiny.min_mb, tiny.recommended_mb, tiny.max_mb), (MIN_HEAP_MB, MIN_HEAP_MB, MIN_HEAP_MB));
+        assert!(tiny.warning.is_some());
+    }
+}
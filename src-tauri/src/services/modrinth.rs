@@ -0,0 +1,230 @@
+use crate::models::version::LoaderType;
+use crate::services::mod_installer::addon_folder;
+use crate::services::resumable_download::{download_with_headers, Checksum};
+use anyhow::{anyhow, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+const MODRINTH_API: &str = "https://api.modrinth.com/v2";
+/// Modrinth asks API consumers to identify themselves
+const USER_AGENT: &str = concat!("D4ffi/allay-app/", env!("CARGO_PKG_VERSION"));
+
+/// Search result entry from the Modrinth /search endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModrinthProject {
+    pub project_id: String,
+    pub slug: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub author: String,
+    pub project_type: String,
+    #[serde(default)]
+    pub downloads: u64,
+    #[serde(default)]
+    pub icon_url: Option<String>,
+    /// Loaders and categories; Modrinth mixes both into this list
+    #[serde(default)]
+    pub categories: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    hits: Vec<ModrinthProject>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModrinthVersion {
+    pub id: String,
+    pub project_id: String,
+    pub name: String,
+    pub version_number: String,
+    #[serde(default)]
+    pub game_versions: Vec<String>,
+    #[serde(default)]
+    pub loaders: Vec<String>,
+    pub files: Vec<ModrinthFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModrinthFile {
+    pub url: String,
+    pub filename: String,
+    #[serde(default)]
+    pub primary: bool,
+    pub size: u64,
+    pub hashes: ModrinthHashes,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModrinthHashes {
+    pub sha512: String,
+    #[serde(default)]
+    pub sha1: Option<String>,
+}
+
+impl ModrinthVersion {
+    /// The file Modrinth marks as primary, or the first one if none is marked
+    pub fn primary_file(&self) -> Option<&ModrinthFile> {
+        self.files.iter().find(|file| file.primary).or_else(|| self.files.first())
+    }
+}
+
+/// Modrinth loader names whose files run on `loader`; Paper and Spigot run Bukkit plugins
+/// and Quilt runs most Fabric mods. Vanilla has none since it loads neither.
+pub fn compatible_loaders(loader: &LoaderType) -> &'static [&'static str] {
+    match loader {
+        LoaderType::Vanilla => &[],
+        LoaderType::Fabric => &["fabric"],
+        LoaderType::Quilt => &["quilt", "fabric"],
+        LoaderType::Forge => &["forge"],
+        LoaderType::NeoForge => &["neoforge"],
+        LoaderType::Paper => &["paper", "spigot", "bukkit"],
+        LoaderType::Spigot => &["spigot", "bukkit"],
+    }
+}
+
+/// Searches mods (or plugins for Paper and Spigot) that support `loader` on `minecraft_version`
+pub async fn search_projects(
+    client: &Client,
+    query: &str,
+    loader: &LoaderType,
+    minecraft_version: &str,
+) -> Result<Vec<ModrinthProject>> {
+    let loaders = compatible_loaders(loader);
+    if loaders.is_empty() {
+        return Err(anyhow!("{:?} servers do not load mods or plugins", loader));
+    }
+
+    // Facets are AND-ed across inner lists and OR-ed within one
+    let project_type = if matches!(loader, LoaderType::Paper | LoaderType::Spigot) { "plugin" } else { "mod" };
+    let facets = serde_json::to_string(&vec![
+        loaders.iter().map(|name| format!("categories:{}", name)).collect::<Vec<_>>(),
+        vec![format!("versions:{}", minecraft_version)],
+        vec![format!("project_type:{}", project_type)],
+    ])?;
+
+    let response = client
+        .get(format!("{}/search", MODRINTH_API))
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .query(&[("query", query), ("facets", facets.as_str()), ("limit", "20")])
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("Modrinth search failed: HTTP {}", response.status()));
+    }
+
+    let results: SearchResponse = response.json().await?;
+    Ok(results.hits)
+}
+
+/// Fetches a single project version by id
+pub async fn get_version(client: &Client, version_id: &str) -> Result<ModrinthVersion> {
+    let response = client
+        .get(format!("{}/version/{}", MODRINTH_API, version_id))
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("Modrinth returned HTTP {} for version {}", response.status(), version_id));
+    }
+
+    Ok(response.json().await?)
+}
+
+/// Lists the versions of a project that support one of `loaders` on `minecraft_version`, newest first
+pub async fn get_project_versions(
+    client: &Client,
+    project_id: &str,
+    loaders: &[&str],
+    minecraft_version: &str,
+) -> Result<Vec<ModrinthVersion>> {
+    let url = format!("{}/project/{}/version", MODRINTH_API, project_id);
+    let loaders = serde_json::to_string(loaders)?;
+    let game_versions = serde_json::to_string(&[minecraft_version])?;
+
+    let response = client
+        .get(&url)
+        .header(reqwest::header::USER_AGENT, USER_AGENT)
+        .query(&[("loaders", loaders), ("game_versions", game_versions)])
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("Modrinth returned HTTP {} for project {}", response.status(), project_id));
+    }
+
+    Ok(response.json().await?)
+}
+
+/// Downloads a Modrinth file into `dest_dir`, checking its sha512 before the file becomes visible.
+/// Returns the path of the installed file.
+pub async fn download_file(client: &Client, file: &ModrinthFile, dest_dir: &Path) -> Result<PathBuf> {
+    let filename = Path::new(&file.filename)
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid file name '{}'", file.filename))?;
+
+    let dest = dest_dir.join(filename);
+    let checksum = Checksum::Sha512(file.hashes.sha512.clone());
+    let mut headers = reqwest::header::HeaderMap::new();
+    headers.insert(reqwest::header::USER_AGENT, reqwest::header::HeaderValue::from_static(USER_AGENT));
+    download_with_headers(client, &file.url, headers, &dest, Some(&checksum))
+        .await
+        .map_err(|e| anyhow!("Failed to download {}: {}", file.filename, e))?;
+
+    log::info!("Installed {} ({} bytes) into {:?}", file.filename, file.size, dest_dir);
+    Ok(dest)
+}
+
+/// Installs a project into the server's mods/plugins folder. With `version_id` that exact
+/// version is used after checking it belongs to the project and fits the server; otherwise
+/// the newest compatible one.
+pub async fn install_project(
+    client: &Client,
+    server_path: &Path,
+    loader: &LoaderType,
+    minecraft_version: &str,
+    project_id: &str,
+    version_id: Option<&str>,
+) -> Result<PathBuf> {
+    let loaders = compatible_loaders(loader);
+    if loaders.is_empty() {
+        return Err(anyhow!("{:?} servers do not load mods or plugins", loader));
+    }
+
+    let version = match version_id {
+        Some(version_id) => {
+            let version = get_version(client, version_id).await?;
+            if version.project_id != project_id {
+                return Err(anyhow!(
+                    "Version {} belongs to project {}, not {}",
+                    version_id, version.project_id, project_id
+                ));
+            }
+            if !version.loaders.iter().any(|name| loaders.contains(&name.as_str())) {
+                return Err(anyhow!(
+                    "{} supports {} but the server runs {:?}",
+                    version.name, version.loaders.join(", "), loader
+                ));
+            }
+            if !version.game_versions.iter().any(|v| v == minecraft_version) {
+                return Err(anyhow!("{} does not support Minecraft {}", version.name, minecraft_version));
+            }
+            version
+        }
+        None => get_project_versions(client, project_id, loaders, minecraft_version)
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!(
+                "Project {} has no release for {:?} on Minecraft {}",
+                project_id, loader, minecraft_version
+            ))?,
+    };
+
+    let file = version
+        .primary_file()
+        .ok_or_else(|| anyhow!("{} has no downloadable files", version.name))?;
+    download_file(client, file, &server_path.join(addon_folder(loader))).await
+}
@@ -1,11 +1,12 @@
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use reqwest::Client;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
-use std::process::Command;
 use chrono::Utc;
-use crate::services::mod_loader_strategy::ModLoaderStrategy;
+use crate::services::resumable_download::{fetch_maven_sha1, Checksum};
+use crate::services::mod_loader_strategy::{run_installer, ModLoaderStrategy, write_user_jvm_args};
+use crate::services::http_client::{get_json, HttpClient};
 use crate::models::version::{LoaderType, VersionResponse, MinecraftVersion, VersionType, NeoForgeVersions};
 use crate::util::JarCacheManager;
 
@@ -14,9 +15,9 @@ pub struct NeoForgeStrategy;
 
 #[async_trait]
 impl ModLoaderStrategy for NeoForgeStrategy {
-    async fn get_versions(&self, client: &Client, minecraft_version: Option<String>) -> Result<VersionResponse> {
+    async fn get_versions(&self, client: &dyn HttpClient, minecraft_version: Option<String>) -> Result<VersionResponse> {
         let url = "https://maven.neoforged.net/api/maven/versions/releases/net/neoforged/neoforge";
-        let response: NeoForgeVersions = client.get(url).send().await?.json().await?;
+        let response: NeoForgeVersions = get_json(client, url).await?;
 
         let mut versions = Vec::new();
         
@@ -146,6 +147,10 @@ impl ModLoaderStrategy for NeoForgeStrategy {
         ))
     }
     
+    async fn get_checksum(&self, client: &Client, download_url: &str, _minecraft_version: &str, _loader_version: &str) -> Result<Option<Checksum>> {
+        fetch_maven_sha1(client, download_url).await
+    }
+    
     fn get_filename(&self, _minecraft_version: &str, loader_version: &str) -> String {
         if loader_version.starts_with("neoforge-") {
             format!("{}-installer.jar", loader_version)
@@ -153,8 +158,13 @@ impl ModLoaderStrategy for NeoForgeStrategy {
             format!("neoforge-{}-installer.jar", loader_version)
         }
     }
+
+    fn is_loader_file(&self, file_name: &str) -> bool {
+        (file_name.contains("neoforge") && file_name.ends_with(".jar"))
+            || matches!(file_name, "run.sh" | "run.bat" | "libraries")
+    }
     
-    async fn setup_server(&self, _client: &Client, server_path: &PathBuf, _minecraft_version: &str, loader_version: &str) -> Result<()> {
+    async fn setup_server(&self, _client: &Client, java_command: &str, server_path: &PathBuf, _minecraft_version: &str, loader_version: &str) -> Result<()> {
         let installer_name = if loader_version.starts_with("neoforge-") {
             format!("{}-installer.jar", loader_version)
         } else {
@@ -175,43 +185,33 @@ impl ModLoaderStrategy for NeoForgeStrategy {
         };
         
         if run_script.exists() || server_jar.exists() {
-            println!("NeoForge server already installed");
+            log::debug!("NeoForge server already installed");
             return Ok(());
         }
 
-        println!("Installing NeoForge server...");
+        log::info!("Installing NeoForge server...");
         
         let installer_filename = installer_path.file_name()
             .ok_or_else(|| anyhow!("Invalid installer filename"))?
             .to_str()
             .ok_or_else(|| anyhow!("Invalid installer filename encoding"))?;
 
-        let output = Command::new("java")
-            .args(&[
-                "-jar", 
-                installer_filename,
-                "--installServer"
-            ])
-            .current_dir(server_path)
-            .output()
-            .map_err(|e| {
-                if e.kind() == std::io::ErrorKind::NotFound {
-                    anyhow!("Java is not installed or not found in PATH. Please install Java to run Minecraft servers.")
-                } else {
-                    anyhow!("Failed to execute Java: {}", e)
-                }
-            })?;
+        run_installer("NeoForge", java_command, server_path, installer_filename).await?;
 
-        if !output.status.success() {
-            let error = String::from_utf8_lossy(&output.stderr);
-            return Err(anyhow!("NeoForge installation failed: {}", error));
-        }
-
-        println!("NeoForge server installed successfully");
+        log::info!("NeoForge server installed successfully");
         Ok(())
     }
     
-    fn build_start_command(&self, server_path: &PathBuf, memory_gb: u32, _min_memory_gb: u32) -> Result<Vec<String>> {
+    fn prepare_launch(&self, server_path: &Path, memory_mb: u32, min_memory_mb: u32) -> Result<()> {
+        // run.sh/run.bat take their heap flags from user_jvm_args.txt
+        let uses_run_script = server_path.join("run.sh").exists() || server_path.join("run.bat").exists();
+        if uses_run_script {
+            write_user_jvm_args(server_path, &format!("-Xmx{}M", memory_mb), &format!("-Xms{}M", min_memory_mb))?;
+        }
+        Ok(())
+    }
+
+    fn build_start_command(&self, server_path: &PathBuf, memory_mb: u32, min_memory_mb: u32) -> Result<Vec<String>> {
         // Check OS and use appropriate script
         let (script_path, script_command) = if cfg!(windows) {
             (server_path.join("run.bat"), "run.bat".to_string())
@@ -220,7 +220,7 @@ impl ModLoaderStrategy for NeoForgeStrategy {
         };
         
         if script_path.exists() {
-            println!("Using {} script for {}", script_command, if cfg!(windows) { "Windows" } else { "Unix" });
+            log::debug!("Using {} script for {}", script_command, if cfg!(windows) { "Windows" } else { "Unix" });
             
             if cfg!(windows) {
                 return Ok(vec!["cmd".to_string(), "/c".to_string(), script_command]);
@@ -235,7 +235,7 @@ impl ModLoaderStrategy for NeoForgeStrategy {
                             let mut new_permissions = permissions.clone();
                             new_permissions.set_mode(permissions.mode() | 0o755);
                             let _ = std::fs::set_permissions(&script_path, new_permissions);
-                            println!("Made {} executable", script_command);
+                            log::debug!("Made {} executable", script_command);
                         }
                     }
                 }
@@ -244,7 +244,7 @@ impl ModLoaderStrategy for NeoForgeStrategy {
             }
         }
         
-        println!("No run script found, falling back to direct JAR execution");
+        log::info!("No run script found, falling back to direct JAR execution");
         
         // Find neoforge server JAR
         let entries = fs::read_dir(server_path)?;
@@ -253,8 +253,8 @@ impl ModLoaderStrategy for NeoForgeStrategy {
             let file_name = entry.file_name().to_string_lossy().to_string();
             if file_name.contains("neoforge") && file_name.ends_with("server.jar") {
                 let args = vec![
-                    format!("-Xmx{}G", memory_gb),
-                    format!("-Xms{}G", memory_gb / 2),
+                    format!("-Xmx{}M", memory_mb),
+                    format!("-Xms{}M", min_memory_mb),
                     "-jar".to_string(),
                     file_name,
                     "nogui".to_string(),
@@ -264,4 +264,38 @@ impl ModLoaderStrategy for NeoForgeStrategy {
         }
         Err(anyhow!("NeoForge server JAR not found"))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::http_client::MockHttpClient;
+
+    const VERSIONS: &str = r#"{
+        "isSnapshot": false,
+        "versions": ["20.2.86", "20.4.80-beta", "20.4.237", "21.0.0-alpha.1.21.20240613", "21.1.1"]
+    }"#;
+
+    fn client() -> MockHttpClient {
+        MockHttpClient::new()
+            .with("https://maven.neoforged.net/api/maven/versions/releases/net/neoforged/neoforge", VERSIONS)
+    }
+
+    #[tokio::test]
+    async fn sorts_newest_first_and_skips_alphas() {
+        let response = NeoForgeStrategy.get_versions(&client(), None).await.unwrap();
+
+        let ids: Vec<_> = response.versions.iter().map(|v| v.id.as_str()).collect();
+        assert_eq!(ids, ["neoforge-21.1.1", "neoforge-20.4.237", "neoforge-20.4.80-beta", "neoforge-20.2.86"]);
+        assert_eq!(response.versions[0].minecraft_version.as_deref(), Some("1.21"));
+    }
+
+    #[tokio::test]
+    async fn filters_by_minecraft_version() {
+        let response = NeoForgeStrategy.get_versions(&client(), Some("1.20.4".to_string())).await.unwrap();
+
+        let ids: Vec<_> = response.versions.iter().map(|v| v.id.as_str()).collect();
+        assert_eq!(ids, ["neoforge-20.4.237", "neoforge-20.4.80-beta"]);
+        assert_eq!(response.latest.unwrap().id, "neoforge-20.4.237");
+    }
+}
@@ -0,0 +1,119 @@
+use crate::util::{AppSettingsManager, WebhookConfig};
+use reqwest::Client;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Status changes are held this long so a server that flaps online/offline sends one message
+const DEBOUNCE: Duration = Duration::from_secs(10);
+/// A slow webhook endpoint is abandoned after this long
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub const DEFAULT_WEBHOOK_TEMPLATE: &str = "**{server}** is now {status}";
+
+#[derive(Default)]
+struct NotificationState {
+    /// Incremented on every change so stale debounced sends can detect they were superseded
+    generation: u64,
+    /// Last status actually delivered, to skip flaps that end where they started
+    last_sent: Option<String>,
+}
+
+lazy_static::lazy_static! {
+    static ref CLIENT: Client = Client::builder()
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+        .unwrap_or_default();
+    static ref STATES: Mutex<HashMap<String, NotificationState>> = Mutex::new(HashMap::new());
+}
+
+/// Queues a webhook notification for a server status change ("online", "offline", "crashed", ...).
+/// Returns immediately; delivery happens on a background task after the debounce window.
+pub fn notify_status_change(server_name: &str, status: &str) {
+    let webhook = match load_webhook(server_name) {
+        Some(webhook) if webhook.events.iter().any(|event| event == status) => webhook,
+        _ => return,
+    };
+
+    let generation = {
+        let mut states = match STATES.lock() {
+            Ok(states) => states,
+            Err(_) => return,
+        };
+        let state = states.entry(server_name.to_string()).or_default();
+        state.generation += 1;
+        state.generation
+    };
+
+    let server_name = server_name.to_string();
+    let status = status.to_string();
+
+    tokio::spawn(async move {
+        tokio::time::sleep(DEBOUNCE).await;
+
+        {
+            let mut states = match STATES.lock() {
+                Ok(states) => states,
+                Err(_) => return,
+            };
+            let state = states.entry(server_name.clone()).or_default();
+            if state.generation != generation || state.last_sent.as_deref() == Some(status.as_str()) {
+                return;
+            }
+            state.last_sent = Some(status.clone());
+        }
+
+        if let Err(e) = send_webhook(&webhook, &server_name, &status).await {
+            log::warn!("Webhook for {} failed: {}", server_name, e);
+        }
+    });
+}
+
+/// Sends a notification right away, bypassing the debounce; used to test a webhook from the UI
+pub async fn send_test_notification(server_name: &str) -> Result<(), String> {
+    let webhook = load_webhook(server_name)
+        .ok_or_else(|| format!("No webhook configured for server '{}'", server_name))?;
+    send_webhook(&webhook, server_name, "test").await
+}
+
+/// Renders a message template, replacing {server}, {status} and {time}
+pub fn render_template(template: &str, server_name: &str, status: &str) -> String {
+    template
+        .replace("{server}", server_name)
+        .replace("{status}", status)
+        .replace("{time}", &chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string())
+}
+
+fn load_webhook(server_name: &str) -> Option<WebhookConfig> {
+    let settings = AppSettingsManager::new(PathBuf::from("storage/app_settings.json"))
+        .load_settings()
+        .ok()?;
+    settings.webhooks.get(server_name).cloned()
+}
+
+async fn send_webhook(webhook: &WebhookConfig, server_name: &str, status: &str) -> Result<(), String> {
+    let template = webhook.template.as_deref().unwrap_or(DEFAULT_WEBHOOK_TEMPLATE);
+    // "content" is what Discord displays; the other fields are for generic webhook consumers
+    let payload = json!({
+        "content": render_template(template, server_name, status),
+        "server": server_name,
+        "status": status,
+        "timestamp": chrono::Utc::now().to_rfc3339(),
+    });
+
+    let response = CLIENT
+        .post(&webhook.url)
+        .json(&payload)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.status().is_success() {
+        log::debug!("Webhook delivered for {} ({})", server_name, status);
+        Ok(())
+    } else {
+        Err(format!("HTTP {}", response.status()))
+    }
+}
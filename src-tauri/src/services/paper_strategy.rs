@@ -1,34 +1,120 @@
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use reqwest::Client;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
 use chrono::Utc;
+use serde::Serialize;
+use crate::services::resumable_download::Checksum;
 use crate::services::mod_loader_strategy::ModLoaderStrategy;
-use crate::models::version::{LoaderType, VersionResponse, MinecraftVersion, VersionType, PaperProject};
+use crate::services::http_client::{get_json, HttpClient};
+use crate::models::version::{LoaderType, VersionResponse, MinecraftVersion, VersionType, PaperProject, PaperBuild, PaperBuilds};
 use crate::util::JarCacheManager;
 
 /// Paper strategy
 pub struct PaperStrategy;
 
+/// The installed Paper build of a server compared with the newest one available
+#[derive(Debug, Clone, Serialize)]
+pub struct PaperUpdateInfo {
+    /// None when the server runs an unpinned build and hasn't recorded it yet
+    pub installed_build: Option<u32>,
+    pub latest_build: u32,
+    pub latest_experimental: bool,
+    pub update_available: bool,
+}
+
+impl PaperStrategy {
+    /// The build a Paper loader version pins, if any. Paper instances store the build number
+    /// as their loader version; older instances store "", "none" or "paper-<mc>", meaning latest.
+    pub fn pinned_build(loader_version: &str) -> Option<u32> {
+        loader_version.trim().parse().ok()
+    }
+    
+    /// All builds for a Minecraft version, oldest first, including their channel and changes
+    pub async fn get_builds(client: &dyn HttpClient, minecraft_version: &str) -> Result<Vec<PaperBuild>> {
+        let builds_url = format!("https://api.papermc.io/v2/projects/paper/versions/{}/builds", minecraft_version);
+        let builds: PaperBuilds = get_json(client, &builds_url)
+            .await
+            .map_err(|e| anyhow!("No builds found for Paper version {}: {}", minecraft_version, e))?;
+        Ok(builds.builds)
+    }
+    
+    /// The build installed in `server_path`: the pinned build, or else the one Paper wrote to
+    /// version_history.json on its last start ("git-Paper-196 (MC: 1.20.1)" or
+    /// "1.21.1-123-abc1234 (MC: 1.21.1)")
+    pub fn installed_build(server_path: &Path, loader_version: &str) -> Option<u32> {
+        if let Some(build) = Self::pinned_build(loader_version) {
+            return Some(build);
+        }
+
+        let content = fs::read_to_string(server_path.join("version_history.json")).ok()?;
+        let history: serde_json::Value = serde_json::from_str(&content).ok()?;
+        let current = history["currentVersion"].as_str()?.split(" (MC").next()?;
+        match current.split_once("Paper-") {
+            Some((_, build)) => build.parse().ok(),
+            None => current.split('-').nth(1)?.parse().ok(),
+        }
+    }
+
+    /// Compares the installed build with the newest stable build, or the newest experimental
+    /// one for versions that have no stable builds yet
+    pub async fn check_for_update(
+        client: &dyn HttpClient,
+        server_path: &Path,
+        minecraft_version: &str,
+        loader_version: &str,
+    ) -> Result<PaperUpdateInfo> {
+        let builds = Self::get_builds(client, minecraft_version).await?;
+        let latest = builds
+            .iter()
+            .rev()
+            .find(|build| !build.is_experimental())
+            .or_else(|| builds.last())
+            .ok_or_else(|| anyhow!("No builds available for Paper version {}", minecraft_version))?;
+
+        let installed_build = Self::installed_build(server_path, loader_version);
+        Ok(PaperUpdateInfo {
+            installed_build,
+            latest_build: latest.build,
+            latest_experimental: latest.is_experimental(),
+            update_available: installed_build.map(|build| latest.build > build).unwrap_or(true),
+        })
+    }
+    
+    /// Changes and channel of a single build
+    pub async fn get_build(client: &dyn HttpClient, minecraft_version: &str, build: u32) -> Result<PaperBuild> {
+        let build_url = format!("https://api.papermc.io/v2/projects/paper/versions/{}/builds/{}", minecraft_version, build);
+        get_json(client, &build_url)
+            .await
+            .map_err(|e| anyhow!("Paper build {} for {} not found: {}", build, minecraft_version, e))
+    }
+}
+
 #[async_trait]
 impl ModLoaderStrategy for PaperStrategy {
-    async fn get_versions(&self, client: &Client, minecraft_version: Option<String>) -> Result<VersionResponse> {
+    async fn get_versions(&self, client: &dyn HttpClient, minecraft_version: Option<String>) -> Result<VersionResponse> {
         let url = "https://api.papermc.io/v2/projects/paper";
-        let response: PaperProject = client.get(url).send().await?.json().await?;
+        let response: PaperProject = get_json(client, url).await?;
 
         let mut versions = Vec::new();
         
         if let Some(target_mc_version) = minecraft_version {
             // Filter for specific MC version
             if response.versions.contains(&target_mc_version) {
+                // Paper ships new Minecraft versions on the experimental channel first; flag those
+                let latest_build = Self::get_builds(client, &target_mc_version).await
+                    .ok()
+                    .and_then(|builds| builds.into_iter().last());
+                let experimental = latest_build.as_ref().map(PaperBuild::is_experimental).unwrap_or(false);
+                
                 let minecraft_version_obj = MinecraftVersion {
                     id: format!("paper-{}", target_mc_version),
-                    version_type: VersionType::Release,
+                    version_type: if experimental { VersionType::Beta } else { VersionType::Release },
                     loader: LoaderType::Paper,
-                    release_time: Utc::now(),
+                    release_time: latest_build.map(|build| build.time).unwrap_or_else(Utc::now),
                     latest: true,
-                    recommended: true,
+                    recommended: !experimental,
                     minecraft_version: Some(target_mc_version.clone()),
                 };
                 versions.push(minecraft_version_obj);
@@ -59,21 +145,26 @@ impl ModLoaderStrategy for PaperStrategy {
         })
     }
     
-    async fn get_download_url(&self, client: &Client, minecraft_version: &str, _loader_version: &str) -> Result<String> {
-        // Get latest build for the version
+    async fn get_download_url(&self, client: &Client, minecraft_version: &str, loader_version: &str) -> Result<String> {
+        // Use the pinned build if there is one, otherwise the latest build for the version
         let builds_url = format!("https://api.papermc.io/v2/projects/paper/versions/{}/builds", minecraft_version);
         let builds_response: serde_json::Value = client.get(&builds_url).send().await?.json().await?;
         
         let builds = builds_response["builds"].as_array()
             .ok_or_else(|| anyhow!("No builds found for Paper version {}", minecraft_version))?;
         
-        let latest_build = builds.last()
-            .ok_or_else(|| anyhow!("No builds available for Paper version {}", minecraft_version))?;
+        let selected_build = match Self::pinned_build(loader_version) {
+            Some(pinned) => builds.iter()
+                .find(|build| build["build"].as_u64() == Some(pinned as u64))
+                .ok_or_else(|| anyhow!("Paper build {} does not exist for {}", pinned, minecraft_version))?,
+            None => builds.last()
+                .ok_or_else(|| anyhow!("No builds available for Paper version {}", minecraft_version))?,
+        };
         
-        let build_number = latest_build["build"].as_u64()
+        let build_number = selected_build["build"].as_u64()
             .ok_or_else(|| anyhow!("Invalid build number"))?;
         
-        let jar_name = latest_build["downloads"]["application"]["name"].as_str()
+        let jar_name = selected_build["downloads"]["application"]["name"].as_str()
             .ok_or_else(|| anyhow!("JAR name not found"))?;
         
         Ok(format!(
@@ -82,26 +173,52 @@ impl ModLoaderStrategy for PaperStrategy {
         ))
     }
     
-    fn get_filename(&self, minecraft_version: &str, _loader_version: &str) -> String {
-        format!("paper-{}.jar", minecraft_version)
+    async fn get_checksum(&self, client: &Client, download_url: &str, minecraft_version: &str, _loader_version: &str) -> Result<Option<Checksum>> {
+        // The download URL pins the build; look up that build's SHA-256
+        let build_number = download_url
+            .split("/builds/")
+            .nth(1)
+            .and_then(|rest| rest.split('/').next())
+            .and_then(|build| build.parse::<u64>().ok())
+            .ok_or_else(|| anyhow!("Unexpected Paper download URL: {}", download_url))?;
+        
+        let builds_url = format!("https://api.papermc.io/v2/projects/paper/versions/{}/builds", minecraft_version);
+        let builds_response: serde_json::Value = client.get(&builds_url).send().await?.json().await?;
+        
+        Ok(builds_response["builds"].as_array()
+            .and_then(|builds| builds.iter().find(|build| build["build"].as_u64() == Some(build_number)))
+            .and_then(|build| build["downloads"]["application"]["sha256"].as_str())
+            .map(|sha256| Checksum::Sha256(sha256.to_string())))
     }
     
-    async fn setup_server(&self, _client: &Client, server_path: &PathBuf, minecraft_version: &str, _loader_version: &str) -> Result<()> {
-        let jar_name = format!("paper-{}.jar", minecraft_version);
+    fn get_filename(&self, minecraft_version: &str, loader_version: &str) -> String {
+        match Self::pinned_build(loader_version) {
+            Some(build) => format!("paper-{}-{}.jar", minecraft_version, build),
+            None => format!("paper-{}.jar", minecraft_version),
+        }
+    }
+
+    fn is_loader_file(&self, file_name: &str) -> bool {
+        (file_name.starts_with("paper-") && file_name.ends_with(".jar"))
+            || matches!(file_name, "cache" | "libraries" | "versions")
+    }
+    
+    async fn setup_server(&self, _client: &Client, _java_command: &str, server_path: &PathBuf, minecraft_version: &str, loader_version: &str) -> Result<()> {
+        let jar_name = self.get_filename(minecraft_version, loader_version);
         let jar_path = server_path.join(&jar_name);
         
         if !jar_path.exists() {
             return Err(anyhow!("Paper JAR not found: {:?}", jar_path));
         }
 
-        println!("Paper server ready: {:?}", jar_path);
+        log::info!("Paper server ready: {:?}", jar_path);
         Ok(())
     }
     
-    fn build_start_command(&self, server_path: &PathBuf, memory_gb: u32, min_memory_gb: u32) -> Result<Vec<String>> {
+    fn build_start_command(&self, server_path: &PathBuf, memory_mb: u32, min_memory_mb: u32) -> Result<Vec<String>> {
         let mut args = vec![
-            format!("-Xmx{}G", memory_gb),
-            format!("-Xms{}G", min_memory_gb),
+            format!("-Xmx{}M", memory_mb),
+            format!("-Xms{}M", min_memory_mb),
             "-jar".to_string(),
         ];
 
@@ -118,4 +235,90 @@ impl ModLoaderStrategy for PaperStrategy {
         }
         Err(anyhow!("Paper server JAR not found"))
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::http_client::MockHttpClient;
+
+    const PROJECT: &str = r#"{
+        "project_id": "paper",
+        "project_name": "Paper",
+        "version_groups": ["1.20", "1.21"],
+        "versions": ["1.20.4", "1.21", "1.21.1"]
+    }"#;
+
+    const BUILDS: &str = r#"{
+        "project_id": "paper",
+        "project_name": "Paper",
+        "version": "1.21.1",
+        "builds": [
+            {
+                "build": 1, "time": "2024-08-10T10:00:00.000Z", "channel": "experimental", "promoted": false,
+                "changes": [{ "commit": "abc123", "summary": "Update to 1.21.1", "message": "Update to 1.21.1\n" }],
+                "downloads": { "application": { "name": "paper-1.21.1-1.jar", "sha256": "00ff" } }
+            }
+        ]
+    }"#;
+
+    fn client() -> MockHttpClient {
+        MockHttpClient::new()
+            .with("https://api.papermc.io/v2/projects/paper", PROJECT)
+            .with("https://api.papermc.io/v2/projects/paper/versions/1.21.1/builds", BUILDS)
+    }
+
+    #[tokio::test]
+    async fn lists_minecraft_versions_newest_first() {
+        let response = PaperStrategy.get_versions(&client(), None).await.unwrap();
+
+        let ids: Vec<_> = response.versions.iter().map(|v| v.id.as_str()).collect();
+        assert_eq!(ids, ["paper-1.21.1", "paper-1.21", "paper-1.20.4"]);
+        assert_eq!(response.latest.unwrap().id, "paper-1.21.1");
+    }
+
+    #[tokio::test]
+    async fn flags_versions_whose_latest_build_is_experimental() {
+        let response = PaperStrategy.get_versions(&client(), Some("1.21.1".to_string())).await.unwrap();
+
+        let version = &response.versions[0];
+        assert!(matches!(version.version_type, VersionType::Beta));
+        assert!(!version.recommended);
+    }
+
+    #[tokio::test]
+    async fn parses_build_changes() {
+        let builds = PaperStrategy::get_builds(&client(), "1.21.1").await.unwrap();
+
+        assert_eq!(builds.len(), 1);
+        assert!(builds[0].is_experimental());
+        assert_eq!(builds[0].changes[0].summary, "Update to 1.21.1");
+        assert_eq!(builds[0].downloads["application"].name, "paper-1.21.1-1.jar");
+    }
+
+    #[test]
+    fn reads_the_installed_build() {
+        let server_path = std::env::temp_dir().join(format!("allay-paper-build-{}", std::process::id()));
+        fs::create_dir_all(&server_path).unwrap();
+        let history = |current: &str| {
+            fs::write(server_path.join("version_history.json"), format!(r#"{{"currentVersion": "{}"}}"#, current)).unwrap();
+        };
+
+        // Nothing recorded yet
+        assert_eq!(PaperStrategy::installed_build(&server_path, "latest"), None);
+
+        history("git-Paper-196 (MC: 1.20.1)");
+        assert_eq!(PaperStrategy::installed_build(&server_path, ""), Some(196));
+        history("1.21.1-123-abc1234 (MC: 1.21.1)");
+        assert_eq!(PaperStrategy::installed_build(&server_path, "paper-1.21.1"), Some(123));
+        // A pinned build wins over whatever last ran
+        assert_eq!(PaperStrategy::installed_build(&server_path, " 130 "), Some(130));
+
+        history("git-Paper-unknown (MC: 1.20.1)");
+        assert_eq!(PaperStrategy::installed_build(&server_path, "none"), None);
+        fs::write(server_path.join("version_history.json"), "not json").unwrap();
+        assert_eq!(PaperStrategy::installed_build(&server_path, "none"), None);
+
+        let _ = fs::remove_dir_all(&server_path);
+    }
+}
@@ -0,0 +1,370 @@
+use crate::services::http_client::{get_json, HttpClient};
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use image::imageops::{self, FilterType};
+use image::{DynamicImage, ImageFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+const CACHE_DIR: &str = "storage/player_cache";
+const UUID_CACHE_FILE: &str = "uuids.json";
+const HEADS_DIR: &str = "heads";
+/// Skins rarely change; a cached head is refetched once it is a day old
+const HEAD_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+/// A name freed by a rename can be claimed by another account, so resolved names are
+/// looked up again after a week
+const UUID_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// Heads are upscaled from the skin's 8x8 face so they stay sharp in the UI
+const HEAD_SIZE: u32 = 64;
+
+/// A Minecraft account with its face for player lists
+#[derive(Debug, Clone, Serialize)]
+pub struct PlayerProfile {
+    pub uuid: String,
+    /// Current name of the account, which may differ in case or entirely from the one looked up
+    pub name: String,
+    /// Face with the hat layer as a base64-encoded PNG; None if the skin couldn't be fetched
+    pub head: Option<String>,
+}
+
+/// A name resolved to its account, trusted for UUID_TTL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedUuid {
+    uuid: String,
+    name: String,
+    resolved_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+struct MojangProfile {
+    id: String,
+    name: String,
+}
+
+/// A profile from the session server, with the skin in its base64 "textures" property
+#[derive(Deserialize)]
+struct SessionProfile {
+    name: String,
+    #[serde(default)]
+    properties: Vec<ProfileProperty>,
+}
+
+#[derive(Deserialize)]
+struct ProfileProperty {
+    name: String,
+    value: String,
+}
+
+/// "069a79f444e94726a5befca90e38aaf5" -> "069a79f4-44e9-4726-a5be-fca90e38aaf5"
+pub fn hyphenate_uuid(id: &str) -> Result<String> {
+    if id.len() != 32 || !id.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(anyhow!("'{}' is not a UUID", id));
+    }
+    Ok(format!("{}-{}-{}-{}-{}", &id[0..8], &id[8..12], &id[12..16], &id[16..20], &id[20..32]))
+}
+
+lazy_static::lazy_static! {
+    /// Serializes read-modify-write cycles on the UUID cache, which also share one temp file
+    static ref UUID_CACHE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Cached UUIDs by lowercase name
+fn load_uuid_cache(cache_dir: &Path) -> HashMap<String, CachedUuid> {
+    fs::read_to_string(cache_dir.join(UUID_CACHE_FILE))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn save_uuid_cache(cache_dir: &Path, cache: &HashMap<String, CachedUuid>) -> Result<()> {
+    fs::create_dir_all(cache_dir)?;
+    let temp_path = cache_dir.join(format!("{}.tmp", UUID_CACHE_FILE));
+    fs::write(&temp_path, serde_json::to_string_pretty(cache)?)?;
+    fs::rename(&temp_path, cache_dir.join(UUID_CACHE_FILE))?;
+    Ok(())
+}
+
+/// Caches `uuid` under its current name. An account that was renamed drops its old name, which
+/// may since belong to someone else.
+fn remember_uuid(cache_dir: &Path, uuid: &str, name: &str) {
+    let _lock = UUID_CACHE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut cache = load_uuid_cache(cache_dir);
+    cache.retain(|cached_name, entry| entry.uuid != uuid || cached_name.eq_ignore_ascii_case(name));
+    cache.insert(
+        name.to_lowercase(),
+        CachedUuid { uuid: uuid.to_string(), name: name.to_string(), resolved_at: Utc::now() },
+    );
+    if let Err(e) = save_uuid_cache(cache_dir, &cache) {
+        log::warn!("Failed to save the player UUID cache: {}", e);
+    }
+}
+
+/// Resolves a player name to the account's (UUID, current name), asking Mojang only for names
+/// that aren't cached yet
+pub async fn lookup_uuid(client: &dyn HttpClient, name: &str) -> Result<(String, String)> {
+    resolve_uuid(client, Path::new(CACHE_DIR), name).await
+}
+
+/// The cached entry for `name` if it hasn't expired yet
+fn cached_uuid(cache_dir: &Path, name: &str) -> Option<CachedUuid> {
+    let cached = {
+        let _lock = UUID_CACHE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        load_uuid_cache(cache_dir).remove(&name.to_lowercase())?
+    };
+    let age = (Utc::now() - cached.resolved_at).to_std().unwrap_or_default();
+    (age < UUID_TTL).then_some(cached)
+}
+
+async fn resolve_uuid(client: &dyn HttpClient, cache_dir: &Path, name: &str) -> Result<(String, String)> {
+    if let Some(cached) = cached_uuid(cache_dir, name) {
+        return Ok((cached.uuid, cached.name));
+    }
+
+    let url = format!("https://api.mojang.com/users/profiles/minecraft/{}", name);
+    let profile: MojangProfile = get_json(client, &url)
+        .await
+        .map_err(|e| anyhow!("Could not find a Minecraft account named {}: {}", name, e))?;
+    let uuid = hyphenate_uuid(&profile.id)?;
+    remember_uuid(cache_dir, &uuid, &profile.name);
+    Ok((uuid, profile.name))
+}
+
+/// The 8x8 face with the hat layer on top, scaled up to HEAD_SIZE. Both layers sit in the
+/// same place in 64x64 and legacy 64x32 skins.
+fn render_head(skin: &DynamicImage) -> Result<Vec<u8>> {
+    if skin.width() < 64 || skin.height() < 16 {
+        return Err(anyhow!("Skin is {}x{}, too small to hold a head", skin.width(), skin.height()));
+    }
+    let mut face = skin.crop_imm(8, 8, 8, 8).to_rgba8();
+    let hat = skin.crop_imm(40, 8, 8, 8).to_rgba8();
+    imageops::overlay(&mut face, &hat, 0, 0);
+
+    let head = imageops::resize(&face, HEAD_SIZE, HEAD_SIZE, FilterType::Nearest);
+    let mut png = Vec::new();
+    head.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok(png)
+}
+
+/// Fetches the current skin of `uuid` and renders its head. Also returns the account's current
+/// name, which is how renames are noticed.
+async fn fetch_head(client: &dyn HttpClient, uuid: &str) -> Result<(Vec<u8>, String)> {
+    let url = format!("https://sessionserver.mojang.com/session/minecraft/profile/{}", uuid.replace('-', ""));
+    let profile: SessionProfile = get_json(client, &url).await?;
+
+    let textures = profile
+        .properties
+        .iter()
+        .find(|property| property.name == "textures")
+        .ok_or_else(|| anyhow!("{} has no textures", profile.name))?;
+    let textures: serde_json::Value =
+        serde_json::from_slice(&base64::engine::general_purpose::STANDARD.decode(&textures.value)?)?;
+    // Accounts on a default skin have no SKIN entry
+    let skin_url = textures["textures"]["SKIN"]["url"]
+        .as_str()
+        .ok_or_else(|| anyhow!("{} uses a default skin", profile.name))?;
+
+    let skin_bytes = client.get_bytes(skin_url).await?;
+    let skin = image::load_from_memory(&skin_bytes)?;
+    Ok((render_head(&skin)?, profile.name))
+}
+
+fn is_fresh(path: &Path) -> bool {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age < HEAD_TTL)
+}
+
+/// A player's UUID and head. The head is cached for a day; when Mojang can't be reached or is
+/// rate limiting, an older cached head is returned instead, or none at all.
+pub async fn get_player_profile(client: &dyn HttpClient, name: &str) -> Result<PlayerProfile> {
+    load_profile(client, Path::new(CACHE_DIR), name).await
+}
+
+async fn load_profile(client: &dyn HttpClient, cache_dir: &Path, name: &str) -> Result<PlayerProfile> {
+    let name = name.trim();
+    if !(1..=16).contains(&name.len()) || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(anyhow!("'{}' is not a valid player name", name));
+    }
+
+    let (uuid, mut current_name) = resolve_uuid(client, cache_dir, name).await?;
+    let head_path = cache_dir.join(HEADS_DIR).join(format!("{}.png", uuid));
+
+    let head = if is_fresh(&head_path) {
+        fs::read(&head_path).ok()
+    } else {
+        match fetch_head(client, &uuid).await {
+            Ok((png, name)) => {
+                if name != current_name {
+                    remember_uuid(cache_dir, &uuid, &name);
+                    current_name = name;
+                }
+                if let Err(e) = fs::create_dir_all(cache_dir.join(HEADS_DIR)).and_then(|_| fs::write(&head_path, &png)) {
+                    log::warn!("Failed to cache the head of {}: {}", current_name, e);
+                }
+                Some(png)
+            }
+            Err(e) => {
+                log::warn!("Could not fetch the skin of {}: {}", current_name, e);
+                fs::read(&head_path).ok()
+            }
+        }
+    };
+
+    Ok(PlayerProfile {
+        uuid,
+        name: current_name,
+        head: head.map(|png| base64::engine::general_purpose::STANDARD.encode(png)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::http_client::MockHttpClient;
+    use image::{Rgba, RgbaImage};
+
+    const NOTCH: &str = "069a79f444e94726a5befca90e38aaf5";
+    const NOTCH_UUID: &str = "069a79f4-44e9-4726-a5be-fca90e38aaf5";
+    const SKIN_URL: &str = "http://textures.minecraft.net/texture/notch";
+
+    fn temp_cache(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("allay-player-cache-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn mojang_url(name: &str) -> String {
+        format!("https://api.mojang.com/users/profiles/minecraft/{}", name)
+    }
+
+    /// A skin with a red face and one blue hat pixel over its top-left corner
+    fn skin_png() -> Vec<u8> {
+        let mut skin = RgbaImage::new(64, 64);
+        for x in 8..16 {
+            for y in 8..16 {
+                skin.put_pixel(x, y, Rgba([255, 0, 0, 255]));
+            }
+        }
+        skin.put_pixel(40, 8, Rgba([0, 0, 255, 255]));
+        let mut png = Vec::new();
+        skin.write_to(&mut Cursor::new(&mut png), ImageFormat::Png).unwrap();
+        png
+    }
+
+    fn session_profile(name: &str) -> String {
+        let textures = format!(r#"{{"textures":{{"SKIN":{{"url":"{}"}}}}}}"#, SKIN_URL);
+        format!(
+            r#"{{"id":"{}","name":"{}","properties":[{{"name":"textures","value":"{}"}}]}}"#,
+            NOTCH,
+            name,
+            base64::engine::general_purpose::STANDARD.encode(textures)
+        )
+    }
+
+    #[test]
+    fn hyphenates_only_uuids() {
+        assert_eq!(hyphenate_uuid(NOTCH).unwrap(), NOTCH_UUID);
+        assert!(hyphenate_uuid("069a79f4").is_err());
+        assert!(hyphenate_uuid(&NOTCH.replace('f', "g")).is_err());
+    }
+
+    #[tokio::test]
+    async fn caches_resolved_names() {
+        let cache_dir = temp_cache("resolve");
+        let client = MockHttpClient::new().with(&mojang_url("notch"), &format!(r#"{{"id":"{}","name":"Notch"}}"#, NOTCH));
+
+        let resolved = resolve_uuid(&client, &cache_dir, "notch").await.unwrap();
+        assert_eq!(resolved, (NOTCH_UUID.to_string(), "Notch".to_string()));
+        // Served from the cache, whatever the case of the name
+        let cached = resolve_uuid(&MockHttpClient::new(), &cache_dir, "NOTCH").await.unwrap();
+        assert_eq!(cached, resolved);
+
+        let _ = fs::remove_dir_all(&cache_dir);
+    }
+
+    #[tokio::test]
+    async fn looks_expired_names_up_again() {
+        let cache_dir = temp_cache("expired");
+        let stale = CachedUuid {
+            uuid: "00000000-0000-0000-0000-000000000000".to_string(),
+            name: "Notch".to_string(),
+            resolved_at: Utc::now() - chrono::Duration::days(8),
+        };
+        save_uuid_cache(&cache_dir, &HashMap::from([("notch".to_string(), stale)])).unwrap();
+
+        assert!(resolve_uuid(&MockHttpClient::new(), &cache_dir, "Notch").await.is_err());
+        let client = MockHttpClient::new().with(&mojang_url("Notch"), &format!(r#"{{"id":"{}","name":"Notch"}}"#, NOTCH));
+        assert_eq!(resolve_uuid(&client, &cache_dir, "Notch").await.unwrap().0, NOTCH_UUID);
+
+        let _ = fs::remove_dir_all(&cache_dir);
+    }
+
+    #[test]
+    fn renamed_accounts_drop_their_old_name() {
+        let cache_dir = temp_cache("rename");
+        remember_uuid(&cache_dir, NOTCH_UUID, "Notch");
+        remember_uuid(&cache_dir, "other", "Jeb_");
+        remember_uuid(&cache_dir, NOTCH_UUID, "Notch2");
+
+        let cache = load_uuid_cache(&cache_dir);
+        let mut names: Vec<_> = cache.keys().map(String::as_str).collect();
+        names.sort();
+        assert_eq!(names, ["jeb_", "notch2"]);
+        assert!(!cache_dir.join(format!("{}.tmp", UUID_CACHE_FILE)).exists());
+
+        let _ = fs::remove_dir_all(&cache_dir);
+    }
+
+    #[test]
+    fn renders_the_face_under_the_hat() {
+        let head = image::load_from_memory(&render_head(&image::load_from_memory(&skin_png()).unwrap()).unwrap()).unwrap();
+        assert_eq!((head.width(), head.height()), (HEAD_SIZE, HEAD_SIZE));
+
+        let head = head.to_rgba8();
+        // Each skin pixel becomes an 8x8 block
+        assert_eq!(head.get_pixel(0, 0), &Rgba([0, 0, 255, 255]));
+        assert_eq!(head.get_pixel(8, 0), &Rgba([255, 0, 0, 255]));
+        assert_eq!(head.get_pixel(63, 63), &Rgba([255, 0, 0, 255]));
+
+        assert!(render_head(&DynamicImage::new_rgba8(32, 32)).is_err());
+    }
+
+    #[tokio::test]
+    async fn loads_profiles_and_notices_renames() {
+        let cache_dir = temp_cache("profile");
+        let client = MockHttpClient::new()
+            .with(&mojang_url("Notch"), &format!(r#"{{"id":"{}","name":"Notch"}}"#, NOTCH))
+            .with(&format!("https://sessionserver.mojang.com/session/minecraft/profile/{}", NOTCH), &session_profile("Notch2"))
+            .with_bytes(SKIN_URL, &skin_png());
+
+        let profile = load_profile(&client, &cache_dir, " Notch ").await.unwrap();
+        assert_eq!(profile.uuid, NOTCH_UUID);
+        assert_eq!(profile.name, "Notch2");
+        assert!(profile.head.is_some());
+        assert!(cache_dir.join(HEADS_DIR).join(format!("{}.png", NOTCH_UUID)).exists());
+        assert_eq!(cached_uuid(&cache_dir, "notch2").unwrap().uuid, NOTCH_UUID);
+        assert!(cached_uuid(&cache_dir, "notch").is_none());
+
+        // The fresh head is reused without asking Mojang again
+        let cached = load_profile(&MockHttpClient::new(), &cache_dir, "notch2").await.unwrap();
+        assert_eq!(cached.head, profile.head);
+
+        let _ = fs::remove_dir_all(&cache_dir);
+    }
+
+    #[tokio::test]
+    async fn rejects_invalid_names() {
+        let cache_dir = temp_cache("invalid");
+        for name in ["", "has space", "seventeen_chars_x", "dash-name"] {
+            assert!(load_profile(&MockHttpClient::new(), &cache_dir, name).await.is_err(), "{}", name);
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use crate::util::{ServerFileManager, ServerProperties, ServerPropertiesManager};
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+/// Ports below this need elevated privileges on most systems
+pub const MIN_SERVER_PORT: u16 = 1024;
+
+/// Port settings of server.properties that Allay manages
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PortKey {
+    Server,
+    Rcon,
+    Query,
+}
+
+impl PortKey {
+    pub fn property_name(&self) -> &'static str {
+        match self {
+            PortKey::Server => "server-port",
+            PortKey::Rcon => "rcon.port",
+            PortKey::Query => "query.port",
+        }
+    }
+
+    /// Query runs over UDP, the game and RCON over TCP
+    fn is_udp(&self) -> bool {
+        matches!(self, PortKey::Query)
+    }
+}
+
+/// Ports a server binds while running, with the property each one comes from
+fn bound_ports(properties: &ServerProperties) -> Vec<(PortKey, u16)> {
+    let mut ports = vec![(PortKey::Server, properties.server_port)];
+    if properties.enable_rcon {
+        ports.push((PortKey::Rcon, properties.rcon_port));
+    }
+    if properties.enable_query {
+        ports.push((PortKey::Query, properties.query_port));
+    }
+    ports
+}
+
+/// The ports one managed server is configured with
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerPorts {
+    pub server_name: String,
+    pub running: bool,
+    pub server_port: Option<u16>,
+    pub rcon_port: Option<u16>,
+    pub rcon_enabled: bool,
+    pub query_port: Option<u16>,
+    pub query_enabled: bool,
+    /// Why the ports couldn't be read, e.g. a missing server.properties
+    pub error: Option<String>,
+}
+
+/// One port bound by more than one server on the same protocol
+#[derive(Debug, Clone, Serialize)]
+pub struct PortCollision {
+    pub port: u16,
+    pub protocol: String,
+    /// "<server> (<property>)" for each server binding the port
+    pub used_by: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PortMap {
+    pub servers: Vec<ServerPorts>,
+    pub collisions: Vec<PortCollision>,
+}
+
+/// The ports of every managed server and the ones they would fight over. Only ports a server
+/// actually binds (RCON and Query when enabled) count towards collisions.
+pub fn get_port_map(storage_root: &Path, running: &HashSet<String>) -> Result<PortMap> {
+    let file_manager = ServerFileManager::new(storage_root.join("server_config.json"));
+    let instances = file_manager.get_all_instances().map_err(|e| anyhow!(e))?;
+
+    let mut servers = Vec::new();
+    let mut users: BTreeMap<(u16, bool), Vec<String>> = BTreeMap::new();
+    for instance in &instances {
+        let mut entry = ServerPorts {
+            server_name: instance.name.clone(),
+            running: running.contains(&instance.name),
+            server_port: None,
+            rcon_port: None,
+            rcon_enabled: false,
+            query_port: None,
+            query_enabled: false,
+            error: None,
+        };
+
+        match load_properties(&storage_root.join(&instance.name)) {
+            Ok(properties) => {
+                entry.server_port = Some(properties.server_port);
+                entry.rcon_port = Some(properties.rcon_port);
+                entry.rcon_enabled = properties.enable_rcon;
+                entry.query_port = Some(properties.query_port);
+                entry.query_enabled = properties.enable_query;
+                for (key, port) in bound_ports(&properties) {
+                    users
+                        .entry((port, key.is_udp()))
+                        .or_default()
+                        .push(format!("{} ({})", instance.name, key.property_name()));
+                }
+            }
+            Err(e) => entry.error = Some(e.to_string()),
+        }
+        servers.push(entry);
+    }
+
+    let collisions = users
+        .into_iter()
+        .filter(|(_, used_by)| used_by.len() > 1)
+        .map(|((port, is_udp), used_by)| PortCollision {
+            port,
+            protocol: if is_udp { "udp" } else { "tcp" }.to_string(),
+            used_by,
+        })
+        .collect();
+
+    Ok(PortMap { servers, collisions })
+}
+
+/// Validates that `port` can be used for `key` on `server_name`: in range, not colliding with
+/// the server's own other ports, and not used by another managed server on the same protocol
+pub fn check_port_change(server_name: &str, key: PortKey, port: u16, storage_root: &Path) -> Result<()> {
+    if port < MIN_SERVER_PORT {
+        return Err(anyhow!("Port {} is out of range; use {}-65535", port, MIN_SERVER_PORT));
+    }
+
+    let properties = load_properties(&storage_root.join(server_name))?;
+    for (other_key, other_port) in bound_ports(&properties) {
+        if other_key != key && other_port == port && other_key.is_udp() == key.is_udp() {
+            return Err(anyhow!(
+                "Port {} is already used by {} on this server",
+                port,
+                other_key.property_name()
+            ));
+        }
+    }
+
+    let file_manager = ServerFileManager::new(storage_root.join("server_config.json"));
+    let instances = file_manager.get_all_instances().map_err(|e| anyhow!(e))?;
+    for instance in instances.iter().filter(|instance| instance.name != server_name) {
+        let other = match load_properties(&storage_root.join(&instance.name)) {
+            Ok(other) => other,
+            Err(_) => continue,
+        };
+        for (other_key, other_port) in bound_ports(&other) {
+            if other_port == port && other_key.is_udp() == key.is_udp() {
+                return Err(anyhow!(
+                    "Port {} is already used by {} of server '{}'",
+                    port,
+                    other_key.property_name(),
+                    instance.name
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks and writes a port to server.properties. Returns true if the value changed.
+pub fn set_port(server_name: &str, key: PortKey, port: u16, storage_root: &Path) -> Result<bool> {
+    check_port_change(server_name, key, port, storage_root)?;
+
+    let properties_path = storage_root.join(server_name).join("server.properties");
+    let manager = ServerPropertiesManager::new(properties_path);
+    let mut properties = manager.load_properties()?;
+
+    let field = match key {
+        PortKey::Server => &mut properties.server_port,
+        PortKey::Rcon => &mut properties.rcon_port,
+        PortKey::Query => &mut properties.query_port,
+    };
+    if *field == port {
+        return Ok(false);
+    }
+    *field = port;
+
+    manager.save_properties(&properties)?;
+    log::info!("Set {} of {} to {}", key.property_name(), server_name, port);
+    Ok(true)
+}
+
+fn load_properties(server_path: &Path) -> Result<ServerProperties> {
+    let properties_path = server_path.join("server.properties");
+    if !properties_path.exists() {
+        return Err(anyhow!("server.properties not found for {:?}", server_path));
+    }
+    Ok(ServerPropertiesManager::new(properties_path).load_properties()?)
+}
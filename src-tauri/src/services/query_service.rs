@@ -2,6 +2,7 @@ use crate::models::query::{QueryResponse, QueryConfig};
 use std::io::{self, Write, Read};
 use std::net::{UdpSocket, ToSocketAddrs};
 use std::time::Duration;
+use crate::util::format_socket_address;
 
 pub struct QueryService {
     config: QueryConfig,
@@ -19,12 +20,15 @@ impl QueryService {
     /// Performs a basic server list ping to check if server is online
     /// This is simpler than full query protocol and works for most servers
     pub async fn ping_server(&self) -> QueryResponse {
-        let address = format!("{}:{}", self.config.host, self.config.port);
+        let address = format_socket_address(&self.config.host, self.config.port);
         let timeout = self.config.timeout_ms;
+        let retries = self.config.retries;
         
         // Run blocking operation in a separate thread
         let result = tokio::task::spawn_blocking(move || {
-            Self::perform_basic_ping_blocking(&address, timeout)
+            Self::retry_blocking(retries, timeout, |attempt_timeout| {
+                Self::perform_basic_ping_blocking(&address, attempt_timeout)
+            })
         }).await;
         
         match result {
@@ -34,11 +38,32 @@ impl QueryService {
         }
     }
 
+    /// Performs a full-stat query (players, plugins, map) without falling back to a TCP ping
+    /// This requires enable-query=true in server.properties
+    pub async fn query_full_stats(&self) -> QueryResponse {
+        let address = format_socket_address(&self.config.host, self.config.port);
+        let timeout = self.config.timeout_ms;
+        let retries = self.config.retries;
+        
+        let result = tokio::task::spawn_blocking(move || {
+            Self::retry_blocking(retries, timeout, |attempt_timeout| {
+                Self::perform_query_blocking(&address, attempt_timeout)
+            })
+        }).await;
+        
+        match result {
+            Ok(Ok(response)) => response,
+            Ok(Err(e)) => QueryResponse::offline(format!("Query failed (is enable-query=true?): {}", e)),
+            Err(e) => QueryResponse::offline(format!("Task failed: {}", e)),
+        }
+    }
+
     /// Performs a more detailed query using the Minecraft Query protocol
     /// This requires enable-query=true in server.properties
     pub async fn query_server(&self) -> QueryResponse {
-        let address = format!("{}:{}", self.config.host, self.config.port);
+        let address = format_socket_address(&self.config.host, self.config.port);
         let timeout = self.config.timeout_ms;
+        let retries = self.config.retries;
         
         // Clone for potential fallback use
         let address_clone = address.clone();
@@ -46,7 +71,9 @@ impl QueryService {
         
         // Run a blocking operation in a separate thread
         let result = tokio::task::spawn_blocking(move || {
-            Self::perform_query_blocking(&address, timeout)
+            Self::retry_blocking(retries, timeout, |attempt_timeout| {
+                Self::perform_query_blocking(&address, attempt_timeout)
+            })
         }).await;
         
         match result {
@@ -54,7 +81,9 @@ impl QueryService {
             Ok(Err(e)) => {
                 // Fallback to basic ping if a query fails
                 let ping_result = tokio::task::spawn_blocking(move || {
-                    Self::perform_basic_ping_blocking(&address_clone, timeout_clone)
+                    Self::retry_blocking(retries, timeout_clone, |attempt_timeout| {
+                        Self::perform_basic_ping_blocking(&address_clone, attempt_timeout)
+                    })
                 }).await;
                 
                 match ping_result {
@@ -66,6 +95,25 @@ impl QueryService {
         }
     }
 
+    /// Runs a blocking probe up to `retries + 1` times, splitting the timeout budget between attempts
+    fn retry_blocking<F>(retries: u32, timeout_ms: u64, mut probe: F) -> Result<QueryResponse, Box<dyn std::error::Error + Send + Sync>>
+    where
+        F: FnMut(u64) -> Result<QueryResponse, Box<dyn std::error::Error + Send + Sync>>,
+    {
+        let attempts = retries.saturating_add(1);
+        let attempt_timeout = std::cmp::max(1, timeout_ms / attempts as u64);
+        
+        let mut last_error = None;
+        for _ in 0..attempts {
+            match probe(attempt_timeout) {
+                Ok(response) => return Ok(response),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        
+        Err(last_error.unwrap_or_else(|| "No query attempts were made".into()))
+    }
+
     fn perform_basic_ping_blocking(address: &str, timeout_ms: u64) -> Result<QueryResponse, Box<dyn std::error::Error + Send + Sync>> {
         // Use a TCP connection attempt as a basic "ping"
         // This is the most reliable way to check if a Minecraft server is accepting connections
@@ -84,37 +132,56 @@ impl QueryService {
     }
 
     fn perform_query_blocking(address: &str, timeout_ms: u64) -> Result<QueryResponse, Box<dyn std::error::Error + Send + Sync>> {
-        // Minecraft Query Protocol implementation
+        // Minecraft Query Protocol (GameSpy4) full stat request
         // This is more complex but provides detailed information
         
-        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        let target_addr = address.to_socket_addrs()?.next().ok_or("Invalid address")?;
+
+        // The local socket has to be of the same family as the server's address
+        let socket = UdpSocket::bind(if target_addr.is_ipv6() { "[::]:0" } else { "0.0.0.0:0" })?;
         socket.set_read_timeout(Some(Duration::from_millis(timeout_ms)))?;
         socket.set_write_timeout(Some(Duration::from_millis(timeout_ms)))?;
 
-        let target_addr = address.to_socket_addrs()?.next().ok_or("Invalid address")?;
+        // Only the lower 4 bits of each byte are used by the server
+        let session_id = rand::random::<i32>() & 0x0F0F0F0F;
 
+        // Challenge tokens expire every 30 seconds, so a stale token makes the server
+        // silently drop the stat request; redo the handshake once before giving up
+        let mut last_error: Box<dyn std::error::Error + Send + Sync> = "Query failed".into();
+        for _ in 0..2 {
+            match Self::full_stat_exchange(&socket, target_addr, session_id) {
+                Ok(response) => return Ok(response),
+                Err(e) => last_error = e,
+            }
+        }
+
+        Err(last_error)
+    }
+
+    fn full_stat_exchange(
+        socket: &UdpSocket,
+        target_addr: std::net::SocketAddr,
+        session_id: i32,
+    ) -> Result<QueryResponse, Box<dyn std::error::Error + Send + Sync>> {
         // Step 1: Handshake
-        let session_id = 1u32;
         let handshake_packet = Self::create_handshake_packet(session_id);
         socket.send_to(&handshake_packet, target_addr)?;
 
         // Read handshake response
-        let mut buffer = [0u8; 1024];
+        let mut buffer = [0u8; 4096];
         let (size, _) = socket.recv_from(&mut buffer)?;
-        let challenge_token = Self::parse_handshake_response(&buffer[..size])?;
+        let challenge_token = Self::parse_handshake_response(&buffer[..size], session_id)?;
 
-        // Step 2: Query
-        let query_packet = Self::create_query_packet(session_id, challenge_token);
+        // Step 2: Full stat query
+        let query_packet = Self::create_full_stat_packet(session_id, challenge_token);
         socket.send_to(&query_packet, target_addr)?;
 
         // Read query response
         let (size, _) = socket.recv_from(&mut buffer)?;
-        let response = Self::parse_query_response(&buffer[..size])?;
-
-        Ok(response)
+        Self::parse_full_stat_response(&buffer[..size], session_id)
     }
 
-    fn create_handshake_packet(session_id: u32) -> Vec<u8> {
+    fn create_handshake_packet(session_id: i32) -> Vec<u8> {
         let mut packet = Vec::new();
         packet.extend_from_slice(&[0xFE, 0xFD]); // Magic
         packet.push(0x09); // Handshake type
@@ -122,65 +189,112 @@ impl QueryService {
         packet
     }
 
-    fn parse_handshake_response(data: &[u8]) -> Result<u32, Box<dyn std::error::Error + Send + Sync>> {
-        if data.len() < 5 {
+    fn parse_handshake_response(data: &[u8], session_id: i32) -> Result<i32, Box<dyn std::error::Error + Send + Sync>> {
+        if data.len() < 6 || data[0] != 0x09 {
             return Err("Invalid handshake response".into());
         }
+        if data[1..5] != session_id.to_be_bytes() {
+            return Err("Handshake response for a different session".into());
+        }
         
-        // Skip header and session ID, extract challenge token
-        let token_str = std::str::from_utf8(&data[5..data.len()-1])?;
-        let token = token_str.parse::<u32>()?;
+        // The token is a null-terminated decimal string that may be negative
+        let token_bytes = &data[5..];
+        let token_end = token_bytes.iter().position(|&b| b == 0).unwrap_or(token_bytes.len());
+        let token_str = std::str::from_utf8(&token_bytes[..token_end])?;
+        let token = token_str.trim().parse::<i32>()?;
         Ok(token)
     }
 
-    fn create_query_packet(session_id: u32, challenge_token: u32) -> Vec<u8> {
+    fn create_full_stat_packet(session_id: i32, challenge_token: i32) -> Vec<u8> {
         let mut packet = Vec::new();
         packet.extend_from_slice(&[0xFE, 0xFD]); // Magic
-        packet.push(0x00); // Query type
+        packet.push(0x00); // Stat type
         packet.extend_from_slice(&session_id.to_be_bytes());
         packet.extend_from_slice(&challenge_token.to_be_bytes());
+        packet.extend_from_slice(&[0x00, 0x00, 0x00, 0x00]); // Padding requests the full stat
         packet
     }
 
-    fn parse_query_response(data: &[u8]) -> Result<QueryResponse, Box<dyn std::error::Error + Send + Sync>> {
-        if data.len() < 5 {
+    fn parse_full_stat_response(data: &[u8], session_id: i32) -> Result<QueryResponse, Box<dyn std::error::Error + Send + Sync>> {
+        // Type (1) + session id (4) + "splitnum\0\x80\0" padding (11)
+        const HEADER_LEN: usize = 16;
+        // "\x01player_\0\0" padding between the K-V section and the player list
+        const PLAYER_SECTION_PADDING: usize = 10;
+
+        if data.len() < HEADER_LEN || data[0] != 0x00 {
             return Err("Invalid query response".into());
         }
+        if data[1..5] != session_id.to_be_bytes() {
+            return Err("Query response for a different session".into());
+        }
 
-        // Skip header
-        let mut offset = 5;
-        
-        // Parse K-V pairs
+        let mut offset = HEADER_LEN;
+        let next_string = |offset: &mut usize| -> Option<String> {
+            if *offset >= data.len() {
+                return None;
+            }
+            let end = data[*offset..].iter().position(|&b| b == 0)? + *offset;
+            let value = String::from_utf8_lossy(&data[*offset..end]).to_string();
+            *offset = end + 1;
+            Some(value)
+        };
+
+        // Parse K-V pairs, terminated by an empty key
         let mut motd = String::new();
         let mut players_online = 0u32;
         let mut players_max = 0u32;
         let mut version = String::new();
+        let mut plugins = Vec::new();
+        let mut map = None;
+        let mut game_type = None;
+
+        while let Some(key) = next_string(&mut offset) {
+            if key.is_empty() {
+                break;
+            }
+            let value = next_string(&mut offset).unwrap_or_default();
 
-        while offset < data.len() {
-            // Find null-terminated key
-            let key_end = data[offset..].iter().position(|&b| b == 0).unwrap_or(0) + offset;
-            if key_end >= data.len() { break; }
-            
-            let key = std::str::from_utf8(&data[offset..key_end])?;
-            offset = key_end + 1;
-
-            // Find null-terminated value
-            let value_end = data[offset..].iter().position(|&b| b == 0).unwrap_or(0) + offset;
-            if value_end >= data.len() { break; }
-            
-            let value = std::str::from_utf8(&data[offset..value_end])?;
-            offset = value_end + 1;
-
-            match key {
-                "hostname" => motd = value.to_string(),
+            match key.as_str() {
+                "hostname" => motd = value,
                 "numplayers" => players_online = value.parse().unwrap_or(0),
                 "maxplayers" => players_max = value.parse().unwrap_or(0),
-                "version" => version = value.to_string(),
+                "version" => version = value,
+                "map" => map = Some(value),
+                "gametype" => game_type = Some(value),
+                "plugins" => plugins = Self::parse_plugins(&value),
                 _ => {} // Ignore other fields
             }
         }
 
-        Ok(QueryResponse::online(players_online, players_max, motd, version))
+        // Player names, terminated by an empty name
+        let mut players = Vec::new();
+        offset += PLAYER_SECTION_PADDING;
+        while let Some(player) = next_string(&mut offset) {
+            if player.is_empty() {
+                break;
+            }
+            players.push(player);
+        }
+
+        let mut response = QueryResponse::online(players_online, players_max, motd, version);
+        response.players = players;
+        response.plugins = plugins;
+        response.map = map;
+        response.game_type = game_type;
+        Ok(response)
+    }
+
+    /// Parses the plugins value, formatted as "<server mod>: <plugin>; <plugin>"
+    fn parse_plugins(value: &str) -> Vec<String> {
+        let list = match value.split_once(':') {
+            Some((_, list)) => list,
+            None => return Vec::new(),
+        };
+
+        list.split(';')
+            .map(|plugin| plugin.trim().to_string())
+            .filter(|plugin| !plugin.is_empty())
+            .collect()
     }
 }
 
@@ -4,9 +4,11 @@ use reqwest::Client;
 use std::path::PathBuf;
 use std::fs;
 use chrono::Utc;
-use crate::services::mod_loader_strategy::ModLoaderStrategy;
+use crate::services::rate_limiter;
+use crate::services::resumable_download::{download_to_file, fetch_maven_sha1};
+use crate::services::mod_loader_strategy::{DownloadContext, ModLoaderStrategy};
+use crate::services::http_client::{get_json, HttpClient};
 use crate::models::version::{LoaderType, VersionResponse, MinecraftVersion, VersionType, QuiltVersions};
-use crate::util::JarCacheManager;
 
 /// Quilt strategy
 pub struct QuiltStrategy;
@@ -50,50 +52,46 @@ struct QuiltLibrary {
 
 #[async_trait]
 impl ModLoaderStrategy for QuiltStrategy {
-    async fn get_versions(&self, client: &Client, minecraft_version: Option<String>) -> Result<VersionResponse> {
+    async fn get_versions(&self, client: &dyn HttpClient, minecraft_version: Option<String>) -> Result<VersionResponse> {
         let mut versions = Vec::new();
 
         if let Some(target_mc_version) = minecraft_version {
             // Get all available loader versions
             let loader_url = "https://meta.quiltmc.org/v3/versions/loader";
 
-            match client.get(loader_url).send().await {
-                Ok(response) => {
-                    if response.status().is_success() {
-                        let loader_response: Vec<QuiltLoaderVersion> = response.json().await?;
-
-                        // Filter out beta versions (versions containing "beta" or "alpha")
-                        let stable_loader_versions: Vec<_> = loader_response
-                            .iter()
-                            .filter(|v| !v.version.to_lowercase().contains("beta") && !v.version.to_lowercase().contains("alpha"))
-                            .collect();
-
-                        // Create versions for each stable loader version
-                        for (i, loader) in stable_loader_versions.iter().enumerate() {
-                            let version_id = format!("quilt-{}-{}", loader.version, target_mc_version);
-                            let minecraft_version_obj = MinecraftVersion {
-                                id: version_id,
-                                version_type: VersionType::Release,
-                                loader: LoaderType::Quilt,
-                                release_time: Utc::now(),
-                                latest: i == 0,
-                                recommended: i == 0,
-                                minecraft_version: Some(target_mc_version.clone()),
-                            };
-                            versions.push(minecraft_version_obj);
-                        }
-                    } else {
-                        println!("Failed to get Quilt loader versions: HTTP {}", response.status());
+            match client.get_text(loader_url).await {
+                Ok(body) => {
+                    let loader_response: Vec<QuiltLoaderVersion> = serde_json::from_str(&body)?;
+
+                    // Filter out beta versions (versions containing "beta" or "alpha")
+                    let stable_loader_versions: Vec<_> = loader_response
+                        .iter()
+                        .filter(|v| !v.version.to_lowercase().contains("beta") && !v.version.to_lowercase().contains("alpha"))
+                        .collect();
+
+                    // Create versions for each stable loader version
+                    for (i, loader) in stable_loader_versions.iter().enumerate() {
+                        let version_id = format!("quilt-{}-{}", loader.version, target_mc_version);
+                        let minecraft_version_obj = MinecraftVersion {
+                            id: version_id,
+                            version_type: VersionType::Release,
+                            loader: LoaderType::Quilt,
+                            release_time: Utc::now(),
+                            latest: i == 0,
+                            recommended: i == 0,
+                            minecraft_version: Some(target_mc_version.clone()),
+                        };
+                        versions.push(minecraft_version_obj);
                     }
                 }
                 Err(e) => {
-                    println!("Error fetching Quilt loader versions: {}", e);
+                    log::warn!("Error fetching Quilt loader versions: {}", e);
                 }
             }
         } else {
             // Get game versions using v3 API
             let base_url = "https://meta.quiltmc.org/v3/versions";
-            let response: QuiltVersions = client.get(base_url).send().await?.json().await?;
+            let response: QuiltVersions = get_json(client, base_url).await?;
 
             // Return only stable game versions (like Fabric does)
             let stable_game_versions: Vec<_> = response
@@ -129,46 +127,40 @@ impl ModLoaderStrategy for QuiltStrategy {
     // Custom implementation for Quilt since it downloads JSON profiles, not JARs
     async fn download_server_jar(
         &self,
-        client: &Client,
-        jar_cache: &JarCacheManager,
+        context: &DownloadContext<'_>,
         minecraft_version: &str,
         loader_version: &str,
         server_path: &PathBuf,
         loader_type: &LoaderType
     ) -> Result<PathBuf> {
+        let (client, jar_cache) = (context.client, context.jar_cache);
         let loader_version_opt = if loader_version.is_empty() { None } else { Some(loader_version) };
 
         // Check if profile JSON is cached first
         if jar_cache.is_jar_cached(loader_type, minecraft_version, loader_version_opt) {
-            println!("Quilt profile found in cache, copying to server: {:?}", server_path);
+            log::debug!("Quilt profile found in cache, copying to server: {:?}", server_path);
             return jar_cache.copy_cached_jar_to_server(loader_type, minecraft_version, loader_version_opt, server_path);
         }
 
-        println!("Quilt profile not in cache, downloading...");
+        log::debug!("Quilt profile not in cache, downloading...");
 
         let download_url = self.get_download_url(client, minecraft_version, loader_version).await?;
         let profile_name = self.get_filename(minecraft_version, loader_version);
 
-        println!("Downloading {} from: {}", profile_name, download_url);
+        log::info!("Downloading {} from: {}", profile_name, download_url);
 
-        // Download the profile JSON
-        let response = client.get(&download_url).send().await?;
-
-        if !response.status().is_success() {
-            return Err(anyhow!("Failed to download Quilt profile: HTTP {}", response.status()));
-        }
-
-        let bytes = response.bytes().await?;
-
-        // Cache the profile first
-        println!("Caching downloaded Quilt profile...");
-        jar_cache.cache_jar(loader_type, minecraft_version, loader_version_opt, &bytes)?;
+        // Download the profile JSON into the cache through a .part file
+        let cached_path = jar_cache.prepare_cache_entry(loader_type, minecraft_version, loader_version_opt)?;
+        download_to_file(client, &download_url, &cached_path, None)
+            .await
+            .map_err(|e| anyhow!("Failed to download Quilt profile: {}", e))?;
+        jar_cache.record_checksum(&cached_path);
 
         // Then copy it to the server directory
-        println!("Copying cached Quilt profile to server: {:?}", server_path);
+        log::debug!("Copying cached Quilt profile to server: {:?}", server_path);
         let profile_path = jar_cache.copy_cached_jar_to_server(loader_type, minecraft_version, loader_version_opt, server_path)?;
 
-        println!("Successfully downloaded and cached Quilt profile: {:?}", profile_path);
+        log::info!("Successfully downloaded and cached Quilt profile: {:?}", profile_path);
         Ok(profile_path)
     }
 
@@ -196,7 +188,11 @@ impl ModLoaderStrategy for QuiltStrategy {
         "quilt-server-profile.json".to_string()
     }
 
-    async fn setup_server(&self, client: &Client, server_path: &PathBuf, minecraft_version: &str, _loader_version: &str) -> Result<()> {
+    fn is_loader_file(&self, file_name: &str) -> bool {
+        matches!(file_name, "quilt-server-profile.json" | "server.jar" | "libraries" | ".quilt")
+    }
+
+    async fn setup_server(&self, client: &Client, _java_command: &str, server_path: &PathBuf, minecraft_version: &str, _loader_version: &str) -> Result<()> {
         let profile_json = server_path.join("quilt-server-profile.json");
         if !profile_json.exists() {
             return Err(anyhow!("Quilt server profile not found: {:?}", profile_json));
@@ -205,11 +201,11 @@ impl ModLoaderStrategy for QuiltStrategy {
         // Check if libraries are already downloaded
         let libraries_dir = server_path.join("libraries");
         if libraries_dir.exists() && self.check_vanilla_jar_exists(server_path) {
-            println!("Quilt server libraries and vanilla JAR already installed");
+            log::debug!("Quilt server libraries and vanilla JAR already installed");
             return Ok(());
         }
 
-        println!("Installing Quilt server libraries...");
+        log::info!("Installing Quilt server libraries...");
 
         // Read and parse the profile JSON
         let profile_content = fs::read_to_string(&profile_json)?;
@@ -226,19 +222,17 @@ impl ModLoaderStrategy for QuiltStrategy {
         // Download vanilla server JAR if needed
         let vanilla_jar = server_path.join("server.jar");
         if !vanilla_jar.exists() {
-            println!("Downloading vanilla Minecraft server for Quilt...");
+            log::info!("Downloading vanilla Minecraft server for Quilt...");
             let vanilla_url = self.get_vanilla_server_url(client, minecraft_version).await?;
-            let response = client.get(&vanilla_url).send().await?;
-            let bytes = response.bytes().await?;
-            fs::write(&vanilla_jar, &bytes)?;
-            println!("Vanilla server JAR downloaded: {:?}", vanilla_jar);
+            download_to_file(client, &vanilla_url, &vanilla_jar, None).await?;
+            log::info!("Vanilla server JAR downloaded: {:?}", vanilla_jar);
         }
 
-        println!("Quilt server setup completed");
+        log::info!("Quilt server setup completed");
         Ok(())
     }
 
-    fn build_start_command(&self, server_path: &PathBuf, memory_gb: u32, min_memory_gb: u32) -> Result<Vec<String>> {
+    fn build_start_command(&self, server_path: &PathBuf, memory_mb: u32, min_memory_mb: u32) -> Result<Vec<String>> {
         // Read Quilt profile to get mainClass and libraries
         let profile_json = server_path.join("quilt-server-profile.json");
         if !profile_json.exists() {
@@ -272,8 +266,8 @@ impl ModLoaderStrategy for QuiltStrategy {
 
         // Build the complete command
         let args = vec![
-            format!("-Xmx{}G", memory_gb),
-            format!("-Xms{}G", min_memory_gb),
+            format!("-Xmx{}M", memory_mb),
+            format!("-Xms{}M", min_memory_mb),
             "-cp".to_string(),
             classpath_str,
             main_class.to_string(),
@@ -309,28 +303,25 @@ impl QuiltStrategy {
 
         // Skip if already exists
         if jar_path.exists() {
-            println!("Library already exists: {}", jar_name);
+            log::debug!("Library already exists: {}", jar_name);
             return Ok(());
         }
 
-        println!("Downloading library: {} from {}", jar_name, download_url);
-
-        let response = client.get(&download_url).send().await?;
-        if !response.status().is_success() {
-            return Err(anyhow!("Failed to download library {}: HTTP {}", name, response.status()));
-        }
+        log::debug!("Downloading library: {} from {}", jar_name, download_url);
 
-        let bytes = response.bytes().await?;
-        fs::write(&jar_path, &bytes)?;
+        let checksum = fetch_maven_sha1(client, &download_url).await.unwrap_or(None);
+        download_to_file(client, &download_url, &jar_path, checksum.as_ref())
+            .await
+            .map_err(|e| anyhow!("Failed to download library {}: {}", name, e))?;
 
-        println!("Downloaded library: {:?}", jar_path);
+        log::debug!("Downloaded library: {:?}", jar_path);
         Ok(())
     }
 
     async fn get_vanilla_server_url(&self, client: &Client, minecraft_version: &str) -> Result<String> {
         // Get version manifest
         let manifest_url = "https://launchermeta.mojang.com/mc/game/version_manifest.json";
-        let manifest: serde_json::Value = client.get(manifest_url).send().await?.json().await?;
+        let manifest: serde_json::Value = rate_limiter::send(client.get(manifest_url)).await?.json().await?;
 
         // Find the specific version
         let versions = manifest["versions"].as_array()
@@ -344,7 +335,7 @@ impl QuiltStrategy {
             .ok_or_else(|| anyhow!("Version URL not found"))?;
 
         // Get version details
-        let version_details: serde_json::Value = client.get(version_url).send().await?.json().await?;
+        let version_details: serde_json::Value = rate_limiter::send(client.get(version_url)).await?.json().await?;
 
         // Get server JAR URL
         let server_url = version_details["downloads"]["server"]["url"].as_str()
@@ -0,0 +1,228 @@
+use anyhow::Result;
+use lazy_static::lazy_static;
+use reqwest::header::RETRY_AFTER;
+use reqwest::{RequestBuilder, Response, StatusCode};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Minimum spacing between two requests to the same host
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_millis(250);
+/// Consecutive failures after which a host is left alone for a while
+const FAILURES_BEFORE_OPEN: u32 = 3;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(30);
+const MAX_BACKOFF: Duration = Duration::from_secs(600);
+
+/// Returned instead of sending a request while a host is backing off
+#[derive(Debug, Clone)]
+pub struct TooManyRequests {
+    pub host: String,
+    pub retry_after: Duration,
+}
+
+impl std::fmt::Display for TooManyRequests {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Too many requests: {} is unavailable or rate limiting, retry in {}s",
+            self.host,
+            self.retry_after.as_secs().max(1)
+        )
+    }
+}
+
+impl std::error::Error for TooManyRequests {}
+
+#[derive(Debug, Default)]
+struct HostState {
+    next_request_at: Option<Instant>,
+    consecutive_failures: u32,
+    blocked_until: Option<Instant>,
+}
+
+lazy_static! {
+    static ref HOSTS: Mutex<HashMap<String, HostState>> = Mutex::new(HashMap::new());
+}
+
+/// Whether an error (anywhere in its chain) came from a host that is backing off
+pub fn is_too_many_requests(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| cause.is::<TooManyRequests>())
+}
+
+/// Sends `request` once its host allows it: requests to one host are spaced out, and a host
+/// that answered 429 or kept failing is skipped with a `TooManyRequests` error until its
+/// backoff expires. Every outbound call to the version and download APIs goes through here.
+pub async fn send(request: RequestBuilder) -> Result<Response> {
+    let (client, request) = request.build_split();
+    let request = request?;
+    let host = request.url().host_str().unwrap_or_default().to_string();
+
+    let wait = reserve_slot(&host)?;
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+
+    match client.execute(request).await {
+        Ok(response) => {
+            record_status(&host, &response);
+            Ok(response)
+        }
+        Err(e) => {
+            // Timeouts and refused connections mean the host is struggling, too
+            record_failure(&host, None);
+            Err(e.into())
+        }
+    }
+}
+
+/// Claims the next request slot for `host` and returns how long to wait for it
+fn reserve_slot(host: &str) -> Result<Duration> {
+    let mut hosts = HOSTS.lock().unwrap();
+    let state = hosts.entry(host.to_string()).or_default();
+    let now = Instant::now();
+
+    if let Some(until) = state.blocked_until {
+        if until > now {
+            return Err(TooManyRequests {
+                host: host.to_string(),
+                retry_after: until - now,
+            }.into());
+        }
+        state.blocked_until = None;
+    }
+
+    let slot = state.next_request_at.filter(|at| *at > now).unwrap_or(now);
+    state.next_request_at = Some(slot + MIN_REQUEST_INTERVAL);
+    Ok(slot - now)
+}
+
+fn record_status(host: &str, response: &Response) {
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .map(Duration::from_secs);
+    record_response(host, response.status(), retry_after);
+}
+
+fn record_response(host: &str, status: StatusCode, retry_after: Option<Duration>) {
+    if status == StatusCode::TOO_MANY_REQUESTS {
+        record_failure(host, Some(retry_after.unwrap_or(INITIAL_BACKOFF)));
+    } else if status.is_server_error() {
+        record_failure(host, None);
+    } else {
+        // Client errors such as 404 are about the request, not the host's health
+        let mut hosts = HOSTS.lock().unwrap();
+        if let Some(state) = hosts.get_mut(host) {
+            state.consecutive_failures = 0;
+        }
+    }
+}
+
+/// Counts a failure against `host`. A 429 blocks it right away (for `block_for`); other
+/// failures only do after several in a row, with the backoff doubling each time.
+fn record_failure(host: &str, block_for: Option<Duration>) {
+    let mut hosts = HOSTS.lock().unwrap();
+    let state = hosts.entry(host.to_string()).or_default();
+    state.consecutive_failures += 1;
+
+    let backoff = match block_for {
+        Some(duration) => Some(duration.min(MAX_BACKOFF)),
+        None if state.consecutive_failures >= FAILURES_BEFORE_OPEN => {
+            let doublings = (state.consecutive_failures - FAILURES_BEFORE_OPEN).min(5);
+            Some((INITIAL_BACKOFF * 2u32.pow(doublings)).min(MAX_BACKOFF))
+        }
+        None => None,
+    };
+
+    if let Some(backoff) = backoff {
+        log::warn!(
+            "Backing off from {} for {}s after {} failed request(s)",
+            host, backoff.as_secs(), state.consecutive_failures
+        );
+        state.blocked_until = Some(Instant::now() + backoff);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pretends the host's backoff ran out
+    fn expire_backoff(host: &str) {
+        HOSTS.lock().unwrap().get_mut(host).unwrap().blocked_until = Some(Instant::now());
+    }
+
+    fn backoff_of(host: &str) -> Duration {
+        let until = HOSTS.lock().unwrap()[host].blocked_until.unwrap();
+        until.saturating_duration_since(Instant::now())
+    }
+
+    fn is_blocked(host: &str) -> bool {
+        reserve_slot(host).is_err_and(|e| is_too_many_requests(&e))
+    }
+
+    #[test]
+    fn spaces_out_requests_to_one_host() {
+        let host = "spacing.test";
+        assert_eq!(reserve_slot(host).unwrap(), Duration::ZERO);
+        let wait = reserve_slot(host).unwrap();
+        assert!(wait > Duration::ZERO && wait <= MIN_REQUEST_INTERVAL);
+        assert_eq!(reserve_slot("other.test").unwrap(), Duration::ZERO);
+    }
+
+    #[test]
+    fn opens_after_repeated_failures() {
+        let host = "failing.test";
+        for _ in 1..FAILURES_BEFORE_OPEN {
+            record_response(host, StatusCode::BAD_GATEWAY, None);
+            assert!(!is_blocked(host));
+        }
+        record_failure(host, None);
+        assert!(is_blocked(host));
+        assert!(backoff_of(host) > INITIAL_BACKOFF - Duration::from_secs(1));
+
+        // 404s are the request's fault; the host answered, so they never open it
+        let missing = "missing.test";
+        for _ in 0..FAILURES_BEFORE_OPEN {
+            record_response(missing, StatusCode::NOT_FOUND, None);
+        }
+        assert!(!is_blocked(missing));
+    }
+
+    #[test]
+    fn rate_limits_open_right_away_for_the_requested_time() {
+        let host = "limited.test";
+        record_response(host, StatusCode::TOO_MANY_REQUESTS, Some(Duration::from_secs(90)));
+        assert!(is_blocked(host));
+        assert!(backoff_of(host) > Duration::from_secs(89));
+
+        let capped = "capped.test";
+        record_response(capped, StatusCode::TOO_MANY_REQUESTS, Some(Duration::from_secs(86_400)));
+        assert!(backoff_of(capped) <= MAX_BACKOFF);
+    }
+
+    #[test]
+    fn lets_one_request_through_after_the_cool_down() {
+        let host = "recovering.test";
+        for _ in 0..FAILURES_BEFORE_OPEN {
+            record_failure(host, None);
+        }
+        assert!(is_blocked(host));
+
+        expire_backoff(host);
+        assert!(reserve_slot(host).is_ok());
+        // Still failing: open again, for twice as long
+        record_failure(host, None);
+        assert!(is_blocked(host));
+        assert!(backoff_of(host) > INITIAL_BACKOFF * 2 - Duration::from_secs(1));
+
+        expire_backoff(host);
+        assert!(reserve_slot(host).is_ok());
+        // A success closes the breaker, so the next failure is counted from scratch
+        record_response(host, StatusCode::OK, None);
+        record_failure(host, None);
+        assert!(!is_blocked(host));
+    }
+}
@@ -0,0 +1,662 @@
+use super::rcon_service::RconError;
+use super::world_info::compare_release_versions;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// Reply prefixes (lowercase) that vanilla, Paper and Spigot use for rejected commands
+const ERROR_PREFIXES: &[&str] = &[
+    "unknown command",
+    "unknown or incomplete command",
+    "incorrect argument for command",
+    // Brigadier's parse errors, e.g. "Expected whitespace to end one argument, but found trailing data"
+    "expected whitespace",
+    "expected integer",
+    "expected float",
+    "expected double",
+    "expected boolean",
+    "invalid integer",
+    "invalid float",
+    "invalid double",
+    "invalid boolean",
+    "invalid name or uuid",
+    "no player was found",
+    "no entity was found",
+    "no targets matched",
+    "that player cannot be found",
+    "that player does not exist",
+    "player not found",
+    "can't find player",
+    "you do not have permission",
+    "i'm sorry, but you do not have permission",
+    "an internal error occurred",
+    "usage: /",
+    "error:",
+];
+
+/// Parsed reply of the vanilla `list` command
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayerList {
+    pub online: u32,
+    pub max: u32,
+    pub players: Vec<String>,
+}
+
+/// Parses the `list` response in the modern, pre-1.13 and EssentialsX formats:
+/// "There are 2 of a max of 20 players online: Steve, Alex"
+/// "There are 2/20 players online:\nSteve, Alex"
+/// "There are 2 out of maximum 20 players online.\nadmins: Steve\ndefault: [AFK]Alex"
+pub fn parse_list_response(response: &str) -> Option<PlayerList> {
+    let text = strip_formatting_codes(response);
+    let rest = text.trim().strip_prefix("There are ")?;
+
+    // The counts end at the first colon or line break, whichever comes first
+    let split_at = rest.find([':', '\n']).unwrap_or(rest.len());
+    let (counts, players) = rest.split_at(split_at);
+    let players = players.strip_prefix(':').unwrap_or(players);
+    let numbers: Vec<u32> = counts
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .filter_map(|part| part.parse().ok())
+        .collect();
+
+    let (online, max) = match numbers.as_slice() {
+        [online, max, ..] => (*online, *max),
+        _ => return None,
+    };
+
+    let players = players
+        .split([',', '\n'])
+        .filter_map(normalize_player_name)
+        .collect();
+
+    Some(PlayerList { online, max, players })
+}
+
+/// Extracts the bare player name from a roster entry, dropping group labels
+/// ("admins: Steve"), bracketed tags ("[AFK]Steve") and prefixes like "~" nicknames
+fn normalize_player_name(entry: &str) -> Option<String> {
+    let entry = entry.rsplit(':').next().unwrap_or(entry);
+
+    let mut name = String::new();
+    let mut depth = 0u32;
+    for c in entry.chars() {
+        match c {
+            '[' | '(' => depth += 1,
+            ']' | ')' => depth = depth.saturating_sub(1),
+            _ if depth == 0 => name.push(c),
+            _ => {}
+        }
+    }
+
+    // Keep the last word; Floodgate prefixes Bedrock players with '.' so allow it
+    let name = name.split_whitespace().last()?.trim_start_matches('~');
+    let valid = !name.is_empty()
+        && name.len() <= 32
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '*');
+    valid.then(|| name.to_string())
+}
+
+/// Strips § codes from a raw reply and turns recognised game errors into `CommandRejected`,
+/// so a delivered-but-failed command can be told apart from a successful one
+pub fn normalize_response(raw: &str) -> Result<String, RconError> {
+    let text = strip_formatting_codes(raw).trim().to_string();
+    let lowercase = text.to_lowercase();
+    if ERROR_PREFIXES.iter().any(|prefix| lowercase.starts_with(prefix)) {
+        return Err(RconError::CommandRejected(text));
+    }
+    Ok(text)
+}
+
+/// Points (seconds remaining) at which a shutdown countdown of `total_secs` is announced,
+/// in descending order: the start, then familiar marks such as 1 minute, 30s and the last 5s
+pub fn countdown_marks(total_secs: u32) -> Vec<u32> {
+    const MARKS: [u32; 12] = [600, 300, 180, 120, 60, 30, 10, 5, 4, 3, 2, 1];
+    if total_secs == 0 {
+        return Vec::new();
+    }
+    std::iter::once(total_secs)
+        .chain(MARKS.into_iter().filter(|mark| *mark < total_secs))
+        .collect()
+}
+
+/// Chat broadcast for one countdown mark, e.g. "say Server restarting in 2 minutes"
+pub fn countdown_message(action: &str, remaining_secs: u32) -> String {
+    let (amount, unit) = if remaining_secs >= 60 && remaining_secs.is_multiple_of(60) {
+        (remaining_secs / 60, "minute")
+    } else {
+        (remaining_secs, "second")
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+    format!("say Server {} in {} {}{}", action, amount, unit, plural)
+}
+
+/// Largest absolute X/Z the game accepts in commands
+pub const MAX_HORIZONTAL_COORDINATE: i32 = 29_999_984;
+/// Widest world border the game allows
+pub const MAX_WORLD_BORDER_DIAMETER: f64 = 59_999_968.0;
+/// Build height limits a data pack can stretch the world to
+const MIN_Y: i32 = -2032;
+const MAX_Y: i32 = 2031;
+
+/// World spawn confirmed by the server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpawnPoint {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+/// World border confirmed by the server
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorldBorder {
+    pub diameter: f64,
+    pub center_x: f64,
+    pub center_z: f64,
+}
+
+/// `setworldspawn` for the given block, rejecting coordinates outside the world
+pub fn setworldspawn_command(x: i32, y: i32, z: i32) -> Result<String, String> {
+    check_horizontal("x", x as f64)?;
+    check_horizontal("z", z as f64)?;
+    if !(MIN_Y..=MAX_Y).contains(&y) {
+        return Err(format!("y must be between {} and {}", MIN_Y, MAX_Y));
+    }
+    Ok(format!("setworldspawn {} {} {}", x, y, z))
+}
+
+/// `worldborder center` followed by `worldborder set`, in the order they should run
+pub fn worldborder_commands(diameter: f64, center_x: f64, center_z: f64) -> Result<[String; 2], String> {
+    if !(1.0..=MAX_WORLD_BORDER_DIAMETER).contains(&diameter) {
+        return Err(format!("Diameter must be between 1 and {}", MAX_WORLD_BORDER_DIAMETER));
+    }
+    check_horizontal("Center x", center_x)?;
+    check_horizontal("Center z", center_z)?;
+    Ok([
+        format!("worldborder center {} {}", center_x, center_z),
+        format!("worldborder set {}", diameter),
+    ])
+}
+
+fn check_horizontal(name: &str, value: f64) -> Result<(), String> {
+    if !value.is_finite() || value.abs() > MAX_HORIZONTAL_COORDINATE as f64 {
+        return Err(format!("{} must be between -{} and {}", name, MAX_HORIZONTAL_COORDINATE, MAX_HORIZONTAL_COORDINATE));
+    }
+    Ok(())
+}
+
+/// Parses "Set the world spawn point to 10, 64, -20 [0.0]" (1.16+) and
+/// "Set the world spawn point to (10, 64, -20)" (older)
+pub fn parse_spawn_response(response: &str) -> Option<SpawnPoint> {
+    let text = strip_formatting_codes(response);
+    let rest = text.trim().strip_prefix("Set the world spawn point to")?;
+    match parse_numbers(rest).as_slice() {
+        [x, y, z, ..] => Some(SpawnPoint { x: *x as i32, y: *y as i32, z: *z as i32 }),
+        _ => None,
+    }
+}
+
+/// Reads the diameter from "Set the world border to 1000.0 block(s) wide" (or the older
+/// "Set world border to 1000 blocks wide"); "Nothing changed" replies mean it already was `requested`
+pub fn parse_worldborder_set_response(response: &str, requested: f64) -> Option<f64> {
+    let text = strip_formatting_codes(response);
+    let text = text.trim();
+    if text.starts_with("Nothing changed") {
+        return Some(requested);
+    }
+    if !text.starts_with("Set the world border to") && !text.starts_with("Set world border to") {
+        return None;
+    }
+    parse_numbers(text).first().copied()
+}
+
+/// Reads the center from "Set the center of the world border to 0.00, 0.00" (or the older
+/// "Set world border center to 0,0"); "Nothing changed" replies mean it already was `requested`
+pub fn parse_worldborder_center_response(response: &str, requested: (f64, f64)) -> Option<(f64, f64)> {
+    let text = strip_formatting_codes(response);
+    let text = text.trim();
+    if text.starts_with("Nothing changed") {
+        return Some(requested);
+    }
+    if !text.starts_with("Set the center of the world border to") && !text.starts_with("Set world border center to") {
+        return None;
+    }
+    match parse_numbers(text).as_slice() {
+        [x, z, ..] => Some((*x, *z)),
+        _ => None,
+    }
+}
+
+/// Weather that `weather` can set
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Weather {
+    Clear,
+    Rain,
+    Thunder,
+}
+
+impl Weather {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "clear" | "sun" | "sunny" => Ok(Weather::Clear),
+            "rain" | "rainy" => Ok(Weather::Rain),
+            "thunder" | "storm" | "thunderstorm" => Ok(Weather::Thunder),
+            other => Err(format!("Unknown weather '{}'; use clear, rain or thunder", other)),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            Weather::Clear => "clear",
+            Weather::Rain => "rain",
+            Weather::Thunder => "thunder",
+        }
+    }
+}
+
+/// Day time in ticks for a friendly name (day, noon, night, midnight) or a tick count.
+/// Names map to ticks because servers before 1.13 only know "day" and "night".
+pub fn time_ticks(value: &str) -> Result<u32, String> {
+    match value.trim().to_lowercase().as_str() {
+        "day" | "morning" => Ok(1000),
+        "noon" => Ok(6000),
+        "sunset" | "evening" => Ok(12000),
+        "night" => Ok(13000),
+        "midnight" => Ok(18000),
+        "sunrise" => Ok(23000),
+        other => other
+            .parse()
+            .map_err(|_| format!("Unknown time '{}'; use day, noon, night, midnight or a tick count", value.trim())),
+    }
+}
+
+/// `weather` with an optional duration in seconds. 1.20.5 changed the duration to ticks unless
+/// it has a unit, so newer servers get an explicit "s".
+pub fn weather_command(weather: Weather, duration_secs: Option<u32>, minecraft_version: &str) -> String {
+    match duration_secs {
+        None => format!("weather {}", weather.as_str()),
+        Some(secs) => {
+            // Snapshots and unknown formats are treated as current
+            let unit = compare_release_versions(minecraft_version, "1.20.5")
+                .map(|o| o != Ordering::Less)
+                .unwrap_or(true);
+            format!("weather {} {}{}", weather.as_str(), secs, if unit { "s" } else { "" })
+        }
+    }
+}
+
+/// Where a world seed was read from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeedSource {
+    /// The seed the running world was generated with, from the "seed" command
+    Rcon,
+    /// level-seed from server.properties: only what was configured, and empty for a random seed
+    Properties,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorldSeed {
+    /// The numeric seed; None for an empty or text level-seed
+    pub seed: Option<i64>,
+    /// level-seed as written, since text seeds are hashed by the game rather than parsed
+    pub configured: Option<String>,
+    pub source: SeedSource,
+}
+
+/// Reads the seed from "Seed: [-4172144997902289642]" (1.13+, where the brackets make it
+/// copyable in chat) and "Seed: -4172144997902289642" (older)
+pub fn parse_seed_response(response: &str) -> Option<i64> {
+    let text = strip_formatting_codes(response);
+    let rest = text.trim().strip_prefix("Seed:")?;
+    let rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == '[');
+    let end = rest
+        .char_indices()
+        .find(|(i, c)| !(c.is_ascii_digit() || (*i == 0 && *c == '-')))
+        .map_or(rest.len(), |(i, _)| i);
+    rest[..end].parse().ok()
+}
+
+/// Reads the ticks from "Set the time to 13000"
+pub fn parse_time_response(response: &str) -> Option<u32> {
+    let text = strip_formatting_codes(response);
+    let rest = text.trim().strip_prefix("Set the time to")?;
+    parse_numbers(rest).first().map(|ticks| *ticks as u32)
+}
+
+/// Reads "Set the weather to rain and thunder" (1.13+) and "Changing to rain and thunder" (older)
+pub fn parse_weather_response(response: &str) -> Option<Weather> {
+    let text = strip_formatting_codes(response).to_lowercase();
+    let text = text.trim();
+    if !text.starts_with("set the weather to") && !text.starts_with("changing to") {
+        return None;
+    }
+    if text.contains("thunder") {
+        Some(Weather::Thunder)
+    } else if text.contains("rain") {
+        Some(Weather::Rain)
+    } else if text.contains("clear") {
+        Some(Weather::Clear)
+    } else {
+        None
+    }
+}
+
+/// Largest stack count `give` accepts (100 stacks of 64)
+pub const MAX_GIVE_COUNT: u32 = 6400;
+
+/// Outcome of an admin action, split by the failures the UI handles differently
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ActionResult {
+    Success { message: String },
+    PlayerNotFound { message: String },
+    InvalidItem { message: String },
+    Rejected { message: String },
+}
+
+/// Accepts Java usernames and Floodgate's '.'-prefixed Bedrock names; selectors are refused
+/// so a wrapper can't act on every player at once
+pub fn check_player_name(player: &str) -> Result<(), String> {
+    let name = player.strip_prefix('.').unwrap_or(player);
+    let valid = (1..=16).contains(&name.len()) && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!("'{}' is not a valid player name", player));
+    }
+    Ok(())
+}
+
+/// `tp` for one player to absolute coordinates
+pub fn teleport_command(player: &str, x: f64, y: f64, z: f64) -> Result<String, String> {
+    check_player_name(player)?;
+    check_horizontal("x", x)?;
+    check_horizontal("z", z)?;
+    if !y.is_finite() || y.abs() > 20_000_000.0 {
+        return Err("y must be between -20000000 and 20000000".to_string());
+    }
+    Ok(format!("tp {} {} {} {}", player, x, y, z))
+}
+
+/// `give` for one player. The item is an id such as "diamond" or "minecraft:diamond_sword",
+/// optionally followed by [components] or {nbt}.
+pub fn give_command(player: &str, item: &str, count: u32) -> Result<String, String> {
+    check_player_name(player)?;
+    if !(1..=MAX_GIVE_COUNT).contains(&count) {
+        return Err(format!("Count must be between 1 and {}", MAX_GIVE_COUNT));
+    }
+
+    let item = item.trim();
+    let id_end = item.find(['[', '{']).unwrap_or(item.len());
+    let (id, data) = item.split_at(id_end);
+    let (namespace, path) = id.split_once(':').unwrap_or(("minecraft", id));
+    let valid_part = |part: &str, extra: &[char]| {
+        !part.is_empty()
+            && part.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '_' | '-' | '.') || extra.contains(&c))
+    };
+    if !valid_part(namespace, &[]) || !valid_part(path, &['/']) || data.contains(['\n', '\r']) {
+        return Err(format!("'{}' is not a valid item id", item));
+    }
+    Ok(format!("give {} {} {}", player, item, count))
+}
+
+/// Sorts a reply into success or a specific failure. Replies starting with one of
+/// `success_prefixes` succeed; anything else, including `CommandRejected`, is classified
+/// from its text. Transport errors are passed through.
+pub fn classify_action_response(
+    result: Result<String, RconError>,
+    success_prefixes: &[&str],
+) -> Result<ActionResult, RconError> {
+    let text = match result {
+        Ok(text) if success_prefixes.iter().any(|prefix| text.starts_with(prefix)) => {
+            return Ok(ActionResult::Success { message: text });
+        }
+        Ok(text) | Err(RconError::CommandRejected(text)) => text,
+        Err(e) => return Err(e),
+    };
+
+    let lowercase = text.to_lowercase();
+    let player_missing = ["no player was found", "no entity was found", "that player cannot be found", "player not found", "can't find player"];
+    let item_invalid = ["unknown item", "there is no such item", "invalid item"];
+    Ok(if player_missing.iter().any(|p| lowercase.contains(p)) {
+        ActionResult::PlayerNotFound { message: text }
+    } else if item_invalid.iter().any(|p| lowercase.contains(p)) {
+        ActionResult::InvalidItem { message: text }
+    } else {
+        ActionResult::Rejected { message: text }
+    })
+}
+
+/// Every number in a reply, in order, e.g. "to 10, 64, -20 [0.0]" -> [10, 64, -20, 0]
+fn parse_numbers(text: &str) -> Vec<f64> {
+    text.split(|c: char| !(c.is_ascii_digit() || c == '-' || c == '.'))
+        .map(|part| part.trim_end_matches('.'))
+        .filter(|part| part.chars().any(|c| c.is_ascii_digit()))
+        .filter_map(|part| part.parse().ok())
+        .collect()
+}
+
+/// Removes Minecraft § color/format codes that Paper and Spigot add to command output
+pub fn strip_formatting_codes(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            chars.next();
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_game_errors() {
+        for reply in [
+            "Unknown or incomplete command, see below for error",
+            "§cNo player was found",
+            "Expected whitespace to end one argument, but found trailing data",
+            "Invalid integer 'ten'",
+            "Incorrect argument for command",
+            "Usage: /whitelist <add|remove> <player>",
+        ] {
+            assert!(matches!(normalize_response(reply), Err(RconError::CommandRejected(_))), "{}", reply);
+        }
+    }
+
+    #[test]
+    fn passes_replies_that_only_start_like_errors() {
+        for reply in ["Expected restart in 10 minutes", "Invalidated 3 chunks", "Made Steve a server operator"] {
+            assert_eq!(normalize_response(reply).unwrap(), reply);
+        }
+        assert_eq!(normalize_response("  §aSaved the game\n").unwrap(), "Saved the game");
+    }
+
+    #[test]
+    fn parses_spawn_replies() {
+        let spawn = |x, y, z| Some(SpawnPoint { x, y, z });
+        let cases = [
+            ("Set the world spawn point to 10, 64, -20 [0.0]", spawn(10, 64, -20)),
+            ("Set the world spawn point to (10, 64, -20)", spawn(10, 64, -20)),
+            ("§eSet the world spawn point to -5, -60, 7 [90.0]\n", spawn(-5, -60, 7)),
+            ("Set the world spawn point to 10, 64", None),
+            ("Unknown command", None),
+            ("", None),
+        ];
+        for (reply, expected) in cases {
+            assert_eq!(parse_spawn_response(reply), expected, "{}", reply);
+        }
+    }
+
+    #[test]
+    fn parses_worldborder_set_replies() {
+        let cases = [
+            ("Set the world border to 1000.0 block(s) wide", Some(1000.0)),
+            ("Set world border to 1000 blocks wide", Some(1000.0)),
+            ("Set the world border to 59999968.0 block(s) wide", Some(59999968.0)),
+            ("Nothing changed. The world border is already that size", Some(500.0)),
+            ("Set the world border to wide", None),
+            ("Unknown or incomplete command, see below for error", None),
+        ];
+        for (reply, expected) in cases {
+            assert_eq!(parse_worldborder_set_response(reply, 500.0), expected, "{}", reply);
+        }
+    }
+
+    #[test]
+    fn parses_worldborder_center_replies() {
+        let cases = [
+            ("Set the center of the world border to 0.00, 0.00", Some((0.0, 0.0))),
+            ("Set the center of the world border to -120.50, 300.25", Some((-120.5, 300.25))),
+            ("Set world border center to 10,-20", Some((10.0, -20.0))),
+            ("Nothing changed. The world border is already centered there", Some((1.0, 2.0))),
+            ("Set the center of the world border to 5.00", None),
+            ("Incorrect argument for command", None),
+        ];
+        for (reply, expected) in cases {
+            assert_eq!(parse_worldborder_center_response(reply, (1.0, 2.0)), expected, "{}", reply);
+        }
+    }
+
+    #[test]
+    fn parses_time_replies() {
+        let cases = [
+            ("Set the time to 13000", Some(13000)),
+            ("§7Set the time to 1000\n", Some(1000)),
+            ("Set the time to 0", Some(0)),
+            ("Set the time to", None),
+            ("The time is 6000", None),
+            ("Unknown command", None),
+        ];
+        for (reply, expected) in cases {
+            assert_eq!(parse_time_response(reply), expected, "{}", reply);
+        }
+    }
+
+    #[test]
+    fn parses_weather_replies() {
+        let cases = [
+            ("Set the weather to clear", Some(Weather::Clear)),
+            ("Set the weather to rain", Some(Weather::Rain)),
+            ("Set the weather to rain & thunder", Some(Weather::Thunder)),
+            ("§eChanging to rain and thunder", Some(Weather::Thunder)),
+            ("Changing to clear weather", Some(Weather::Clear)),
+            ("Set the weather to snow", None),
+            ("It is raining", None),
+            ("", None),
+        ];
+        for (reply, expected) in cases {
+            assert_eq!(parse_weather_response(reply), expected, "{}", reply);
+        }
+    }
+
+    #[test]
+    fn classifies_action_replies() {
+        let rejected = |text: &str| Err(RconError::CommandRejected(text.to_string()));
+        let cases = [
+            (Ok("Gave 1 [Diamond] to Steve".to_string()), "success"),
+            (Ok("Given [Diamond] * 1 to Steve".to_string()), "success"),
+            (rejected("No player was found"), "player_not_found"),
+            (Ok("That player cannot be found".to_string()), "player_not_found"),
+            (rejected("Unknown item 'minecraft:dimond'"), "invalid_item"),
+            (Ok("There is no such item with name 4000".to_string()), "invalid_item"),
+            (Ok("Unknown command".to_string()), "rejected"),
+            (rejected("gave 1 [Diamond] to Steve"), "rejected"),
+        ];
+        for (result, expected) in cases {
+            let reply = match &result {
+                Ok(text) | Err(RconError::CommandRejected(text)) => text.clone(),
+                Err(e) => e.to_string(),
+            };
+            let (status, message) = match classify_action_response(result, &["Gave ", "Given "]).unwrap() {
+                ActionResult::Success { message } => ("success", message),
+                ActionResult::PlayerNotFound { message } => ("player_not_found", message),
+                ActionResult::InvalidItem { message } => ("invalid_item", message),
+                ActionResult::Rejected { message } => ("rejected", message),
+            };
+            assert_eq!(status, expected, "{}", reply);
+            assert_eq!(message, reply);
+        }
+    }
+
+    #[test]
+    fn passes_transport_errors_through() {
+        assert!(matches!(
+            classify_action_response(Err(RconError::NetworkTimeout), &["Gave "]),
+            Err(RconError::NetworkTimeout)
+        ));
+        assert!(matches!(
+            classify_action_response(Err(RconError::CommandFailed("broken pipe".to_string())), &["Gave "]),
+            Err(RconError::CommandFailed(_))
+        ));
+    }
+
+    #[test]
+    fn parses_seed_replies() {
+        let cases = [
+            ("Seed: [-123]", Some(-123)),
+            ("Seed: 123", Some(123)),
+            ("Seed: [-4172144997902289642]", Some(-4172144997902289642)),
+            ("§aSeed: §r[§a4172144997902289642§r]\n", Some(4172144997902289642)),
+            ("Seed: []", None),
+            ("Seed: abc", None),
+            ("Seed: 99999999999999999999", None),
+            ("Unknown command", None),
+            ("", None),
+        ];
+        for (reply, expected) in cases {
+            assert_eq!(parse_seed_response(reply), expected, "{}", reply);
+        }
+    }
+
+    #[test]
+    fn parses_list_replies() {
+        let list = |online, max, players: &[&str]| {
+            Some(PlayerList { online, max, players: players.iter().map(|p| p.to_string()).collect() })
+        };
+        let cases = [
+            ("There are 2 of a max of 20 players online: Steve, Alex", list(2, 20, &["Steve", "Alex"])),
+            ("There are 0 of a max of 20 players online: ", list(0, 20, &[])),
+            ("There are 2/20 players online:\nSteve, Alex", list(2, 20, &["Steve", "Alex"])),
+            (
+                "There are 2 out of maximum 20 players online.\nadmins: Steve\ndefault: [AFK]Alex",
+                list(2, 20, &["Steve", "Alex"]),
+            ),
+            ("§6There are §c1§6 out of maximum §c10§6 players online.\n§6default§r: §fNotch", list(1, 10, &["Notch"])),
+            ("There are 1 of a max of 20 players online: .BedrockPlayer", list(1, 20, &[".BedrockPlayer"])),
+            ("There are players online", None),
+            ("There are 3 players online: Steve", None),
+            ("Unknown command", None),
+            ("", None),
+        ];
+        for (reply, expected) in cases {
+            assert_eq!(parse_list_response(reply), expected, "{}", reply);
+        }
+    }
+
+    #[test]
+    fn normalizes_player_names() {
+        let cases = [
+            ("Steve", Some("Steve")),
+            (" Alex ", Some("Alex")),
+            ("admins: Steve", Some("Steve")),
+            ("[AFK]Alex", Some("Alex")),
+            ("[Admin] [AFK] Notch", Some("Notch")),
+            ("(Mod)jeb_", Some("jeb_")),
+            ("~Nick", Some("Nick")),
+            ("Owner Dinnerbone", Some("Dinnerbone")),
+            (".BedrockPlayer", Some(".BedrockPlayer")),
+            ("*Hidden", Some("*Hidden")),
+            ("[AFK]", None),
+            ("", None),
+            ("Steve!", None),
+            ("ThisNameIsFarTooLongToBeAPlayerName", None),
+        ];
+        for (entry, expected) in cases {
+            assert_eq!(normalize_player_name(entry).as_deref(), expected, "{}", entry);
+        }
+    }
+}
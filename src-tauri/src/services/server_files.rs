@@ -0,0 +1,114 @@
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+/// Largest file the in-app editor reads or writes; region files and logs can be gigabytes
+pub const MAX_TEXT_FILE_BYTES: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub name: String,
+    pub is_dir: bool,
+    pub size: u64,
+    pub modified: Option<DateTime<Utc>>,
+}
+
+/// Resolves `relative_path` inside `server_path`, rejecting anything that escapes it
+/// (absolute paths, `..`, or symlinks pointing outside). The target itself may not exist
+/// yet, but its parent directory must.
+pub fn resolve_server_path(server_path: &Path, relative_path: &str) -> Result<PathBuf> {
+    let root = server_path
+        .canonicalize()
+        .map_err(|e| anyhow!("Server directory {:?} is not accessible: {}", server_path, e))?;
+
+    let relative = Path::new(relative_path.trim());
+    if relative
+        .components()
+        .any(|component| !matches!(component, Component::Normal(_) | Component::CurDir))
+    {
+        return Err(anyhow!("Path '{}' is outside the server directory", relative_path));
+    }
+
+    let candidate = root.join(relative);
+    let resolved = if candidate.exists() {
+        candidate.canonicalize()?
+    } else {
+        let parent = candidate
+            .parent()
+            .ok_or_else(|| anyhow!("Invalid path '{}'", relative_path))?
+            .canonicalize()
+            .map_err(|_| anyhow!("Folder for '{}' does not exist", relative_path))?;
+        parent.join(candidate.file_name().unwrap_or_default())
+    };
+
+    if !resolved.starts_with(&root) {
+        return Err(anyhow!("Path '{}' is outside the server directory", relative_path));
+    }
+    Ok(resolved)
+}
+
+/// Lists a folder of the server directory, folders first and then by name
+pub fn list_files(server_path: &Path, relative_path: &str) -> Result<Vec<FileEntry>> {
+    let dir = resolve_server_path(server_path, relative_path)?;
+    if !dir.is_dir() {
+        return Err(anyhow!("'{}' is not a folder", relative_path));
+    }
+
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(&dir)?.flatten() {
+        let metadata = match entry.metadata() {
+            Ok(metadata) => metadata,
+            Err(_) => continue,
+        };
+        entries.push(FileEntry {
+            name: entry.file_name().to_string_lossy().to_string(),
+            is_dir: metadata.is_dir(),
+            size: if metadata.is_dir() { 0 } else { metadata.len() },
+            modified: metadata.modified().ok().map(DateTime::<Utc>::from),
+        });
+    }
+
+    entries.sort_by(|a, b| b.is_dir.cmp(&a.is_dir).then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase())));
+    Ok(entries)
+}
+
+/// Reads a small UTF-8 text file from the server directory
+pub fn read_text_file(server_path: &Path, relative_path: &str) -> Result<String> {
+    let path = resolve_server_path(server_path, relative_path)?;
+    let metadata = fs::metadata(&path).map_err(|_| anyhow!("File '{}' not found", relative_path))?;
+    if !metadata.is_file() {
+        return Err(anyhow!("'{}' is not a file", relative_path));
+    }
+    if metadata.len() > MAX_TEXT_FILE_BYTES {
+        return Err(anyhow!(
+            "'{}' is {} bytes; only files up to {} bytes can be opened",
+            relative_path, metadata.len(), MAX_TEXT_FILE_BYTES
+        ));
+    }
+
+    String::from_utf8(fs::read(&path)?).map_err(|_| anyhow!("'{}' is not a text file", relative_path))
+}
+
+/// Replaces (or creates) a small text file in the server directory
+pub fn write_text_file(server_path: &Path, relative_path: &str, content: &str) -> Result<()> {
+    if content.len() as u64 > MAX_TEXT_FILE_BYTES {
+        return Err(anyhow!("Content exceeds the {} byte limit", MAX_TEXT_FILE_BYTES));
+    }
+
+    let path = resolve_server_path(server_path, relative_path)?;
+    if path.is_dir() {
+        return Err(anyhow!("'{}' is a folder", relative_path));
+    }
+
+    // Write beside the target and rename so a crash never leaves a half-written config
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".allay-tmp");
+    let temp_path = path.with_file_name(temp_name);
+    fs::write(&temp_path, content)?;
+    fs::rename(&temp_path, &path)?;
+
+    log::info!("Wrote {} bytes to {:?}", content.len(), path);
+    Ok(())
+}
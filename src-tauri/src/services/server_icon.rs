@@ -0,0 +1,85 @@
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use image::imageops::FilterType;
+use image::{GenericImageView, ImageFormat, ImageReader};
+use serde::Serialize;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+/// The multiplayer list only shows icons of exactly this size
+const ICON_SIZE: u32 = 64;
+const ICON_FILE: &str = "server-icon.png";
+
+/// Outcome of installing a server icon
+#[derive(Debug, Clone, Serialize)]
+pub struct ServerIconResult {
+    pub path: PathBuf,
+    /// Original dimensions of the source image
+    pub original_width: u32,
+    pub original_height: u32,
+    /// True when the source was not already a 64x64 image and had to be scaled
+    pub resized: bool,
+}
+
+/// Loads any supported image, scales it to 64x64 and writes it as the server's server-icon.png.
+/// Non-square images are center-cropped first so the icon isn't stretched.
+pub fn set_server_icon(server_path: &Path, source: &Path) -> Result<ServerIconResult> {
+    let image = ImageReader::open(source)
+        .map_err(|e| anyhow!("Cannot open {:?}: {}", source, e))?
+        .with_guessed_format()?
+        .decode()
+        .map_err(|e| anyhow!("{:?} is not a supported image: {}", source, e))?;
+
+    let (width, height) = image.dimensions();
+    let resized = width != ICON_SIZE || height != ICON_SIZE;
+    let icon = if resized {
+        let side = width.min(height);
+        image
+            .crop_imm((width - side) / 2, (height - side) / 2, side, side)
+            .resize_exact(ICON_SIZE, ICON_SIZE, FilterType::Lanczos3)
+    } else {
+        image
+    };
+
+    let path = server_path.join(ICON_FILE);
+    icon.to_rgba8()
+        .save_with_format(&path, ImageFormat::Png)
+        .map_err(|e| anyhow!("Failed to write {:?}: {}", path, e))?;
+
+    log::info!("Installed server icon from {:?} ({}x{}, resized: {})", source, width, height, resized);
+    Ok(ServerIconResult {
+        path,
+        original_width: width,
+        original_height: height,
+        resized,
+    })
+}
+
+/// The current icon as base64-encoded PNG, or None if the server has no icon
+pub fn get_server_icon(server_path: &Path) -> Result<Option<String>> {
+    let path = server_path.join(ICON_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+
+    let bytes = fs::read(&path)?;
+    // The game silently ignores icons it can't decode, so surface that here instead
+    if image::guess_format(&bytes).ok() != Some(ImageFormat::Png) {
+        return Err(anyhow!("{} is not a PNG image", ICON_FILE));
+    }
+    image::load(Cursor::new(&bytes), ImageFormat::Png)
+        .map_err(|e| anyhow!("{} is corrupt: {}", ICON_FILE, e))?;
+
+    Ok(Some(base64::engine::general_purpose::STANDARD.encode(bytes)))
+}
+
+/// Deletes server-icon.png; returns false if there was none
+pub fn remove_server_icon(server_path: &Path) -> Result<bool> {
+    let path = server_path.join(ICON_FILE);
+    if !path.exists() {
+        return Ok(false);
+    }
+    fs::remove_file(&path)?;
+    Ok(true)
+}
@@ -1,6 +1,7 @@
 use crate::models::query::{QueryResponse, QueryConfig};
 use crate::services::query_service::QueryService;
 use crate::services::rcon_manager::RconManager;
+use crate::util::server_connect_host;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime};
@@ -64,6 +65,7 @@ pub struct ServerMonitor {
     rcon_manager: Arc<Mutex<RconManager>>,
     monitoring_task: Option<tokio::task::JoinHandle<()>>,
     app_handle: Option<AppHandle>,
+    interval: Duration,
 }
 
 impl ServerMonitor {
@@ -73,6 +75,7 @@ impl ServerMonitor {
             rcon_manager,
             monitoring_task: None,
             app_handle: None,
+            interval: Duration::from_secs(20),
         }
     }
 
@@ -81,18 +84,29 @@ impl ServerMonitor {
         self.app_handle = Some(app_handle);
     }
 
+    /// Change the polling interval, restarting the background task if it is running
+    pub fn set_interval(&mut self, secs: u64) {
+        let secs = crate::util::clamp_monitor_interval(secs);
+        self.interval = Duration::from_secs(secs);
+
+        if self.monitoring_task.is_some() {
+            self.stop_background_monitoring();
+            self.start_background_monitoring();
+        }
+    }
+
     /// Start monitoring a server
     pub async fn start_monitoring(&self, server_name: String, port: u16) {
         let mut servers = self.servers.write().await;
         servers.insert(server_name.clone(), ServerMonitorState::new(port));
-        println!("Started monitoring server: {} on port {}", server_name, port);
+        log::debug!("Started monitoring server: {} on port {}", server_name, port);
     }
 
     /// Stop monitoring a server
     pub async fn stop_monitoring(&self, server_name: &str) {
         let mut servers = self.servers.write().await;
         servers.remove(server_name);
-        println!("Stopped monitoring server: {}", server_name);
+        log::debug!("Stopped monitoring server: {}", server_name);
     }
 
     /// Update server status manually (for start/stop commands)
@@ -105,10 +119,10 @@ impl ServerMonitor {
                 server_state.last_status_change = Instant::now();
                 server_state.consecutive_failures = 0;
                 server_state.consecutive_successes = 0;
-                println!("🔄 Manual update server {} status: {:?} → {:?}", server_name, old_status, status);
+                log::debug!("Manual update server {} status: {:?} → {:?}", server_name, old_status, status);
             }
         } else {
-            println!("⚠️  Tried to update status for unknown server: {}", server_name);
+            log::warn!("Tried to update status for unknown server: {}", server_name);
         }
     }
 
@@ -128,9 +142,9 @@ impl ServerMonitor {
             .collect();
         
         if !statuses.is_empty() {
-            println!("📊 Event-driven status summary: {} servers monitored", statuses.len());
+            log::debug!("Event-driven status summary: {} servers monitored", statuses.len());
             for (name, status) in &statuses {
-                println!("• {}: {:?}", name, status);
+                log::debug!("{}: {:?}", name, status);
             }
         }
         
@@ -154,7 +168,7 @@ impl ServerMonitor {
             self.monitoring_task.is_some()
         );
         
-        println!("{}", diagnostic);
+        log::debug!("{}", diagnostic);
         diagnostic
     }
 
@@ -162,20 +176,21 @@ impl ServerMonitor {
     pub fn start_background_monitoring(&mut self) {
         // Prevent multiple monitoring tasks
         if self.monitoring_task.is_some() {
-            println!("⚠️ Monitoring task already running, skipping duplicate start");
+            log::debug!("Monitoring task already running, skipping duplicate start");
             return;
         }
         
-        println!("🚀 ✅ Starting PURE EVENT-DRIVEN monitoring (no frontend polling)");
+        log::debug!("Starting PURE EVENT-DRIVEN monitoring (no frontend polling)");
 
         let servers = Arc::clone(&self.servers);
         let rcon_manager = Arc::clone(&self.rcon_manager);
         let app_handle = self.app_handle.clone();
+        let period = self.interval;
 
         let task = tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(20)); // Check every 20 seconds (less aggressive)
+            let mut interval = tokio::time::interval(period);
 
-            println!("🔍 Background monitoring thread started (20s intervals)");
+            log::debug!("Background monitoring thread started ({}s intervals)", period.as_secs());
             loop {
                 interval.tick().await;
                 
@@ -192,7 +207,7 @@ impl ServerMonitor {
                     unsafe {
                         CYCLE_COUNT += 1;
                         if CYCLE_COUNT % 3 == 1 {
-                            println!("🔍 Monitoring cycle #{} for {} servers (20s interval)", CYCLE_COUNT, server_count);
+                            log::trace!("Monitoring cycle #{} for {} servers ({}s interval)", CYCLE_COUNT, server_count, period.as_secs());
                         }
                     }
                     Self::monitor_cycle(Arc::clone(&servers), Arc::clone(&rcon_manager), app_handle.clone()).await;
@@ -201,7 +216,7 @@ impl ServerMonitor {
                     unsafe {
                         CYCLE_COUNT += 1;
                         if CYCLE_COUNT % 6 == 0 { // Every 2 minutes when no servers
-                            println!("⏳ No servers to monitor yet");
+                            log::trace!("No servers to monitor yet");
                         }
                     }
                 }
@@ -209,14 +224,14 @@ impl ServerMonitor {
         });
 
         self.monitoring_task = Some(task);
-        println!("🎯 Background server monitoring started");
+        log::debug!("Background server monitoring started");
     }
 
     /// Stop the background monitoring task
     pub fn stop_background_monitoring(&mut self) {
         if let Some(task) = self.monitoring_task.take() {
             task.abort();
-            println!("Background server monitoring stopped");
+            log::debug!("Background server monitoring stopped");
         }
     }
 
@@ -281,7 +296,7 @@ impl ServerMonitor {
                     
                     if time_since_last_change >= min_change_interval {
                         let old_status = server_state.status;
-                        println!("🚀 Monitor detected stable change for {}: {:?} → {:?} (after {:.1}s, failures: {}, successes: {})", 
+                        log::debug!("Monitor detected stable change for {}: {:?} → {:?} (after {:.1}s, failures: {}, successes: {})", 
                                  server_name, old_status, new_status, time_since_last_change.as_secs_f32(),
                                  server_state.consecutive_failures, server_state.consecutive_successes);
                         
@@ -299,12 +314,12 @@ impl ServerMonitor {
                             
                             match app.emit("server-status-changed", &event) {
                                 Ok(_) => {
-                                    println!("📡 ✅ Event SUCCESS: {} {:?} → {:?} (pure event-driven)", 
+                                    log::debug!("Event SUCCESS: {} {:?} → {:?} (pure event-driven)", 
                                            server_name, old_status, new_status);
                                 },
                                 Err(e) => {
-                                    println!("⚠️ ❌ CRITICAL EVENT FAILURE: {} - {}", server_name, e);
-                                    println!("🛠️ Frontend will NOT update without events! server={}, old={:?}, new={:?}", 
+                                    log::error!("CRITICAL EVENT FAILURE: {} - {}", server_name, e);
+                                    log::warn!("Frontend will NOT update without events! server={}, old={:?}, new={:?}", 
                                            server_name, old_status, new_status);
                                 }
                             }
@@ -323,7 +338,7 @@ impl ServerMonitor {
                         }
                     } else {
                         let time_remaining = min_change_interval.saturating_sub(time_since_last_change);
-                        println!("🕰️ Suppressing status change for {} ({}s remaining for stability)", 
+                        log::trace!("Suppressing status change for {} ({}s remaining for stability)", 
                                 server_name, time_remaining.as_secs());
                     }
                 }
@@ -352,16 +367,17 @@ impl ServerMonitor {
             // RCON is connected = server is very likely online (RCON is more reliable than Query)
             if current_status != ServerMonitorStatus::Online {
                 // RCON is reliable enough to immediately mark as online
-                println!("✅ {} confirmed online via RCON", server_name);
+                log::info!("{} confirmed online via RCON", server_name);
             }
             return (ServerMonitorStatus::Online, true); // Success - RCON is trustworthy
         }
 
         // Priority 2: Check Query protocol
         let query_config = QueryConfig {
-            host: "127.0.0.1".to_string(),
+            host: server_connect_host(server_name),
             port,
-            timeout_ms: 2000, // 2 second timeout for monitoring
+            timeout_ms: 2000, // 2 second budget for monitoring
+            retries: 2, // Dropped UDP packets shouldn't flap the server offline
         };
 
         let query_service = QueryService::new(query_config);
@@ -372,10 +388,10 @@ impl ServerMonitor {
             // For conservative approach: require 2 consecutive successes to mark as online
             if current_status != ServerMonitorStatus::Online {
                 if consecutive_successes >= 1 { // Require 2 total successes (previous + this one)
-                    println!("✅ {} confirmed online via Query (after {} successes)", server_name, consecutive_successes + 1);
+                    log::info!("{} confirmed online via Query (after {} successes)", server_name, consecutive_successes + 1);
                     return (ServerMonitorStatus::Online, true);
                 } else {
-                    println!("🔍 {} responding to Query, but waiting for confirmation", server_name);
+                    log::trace!("{} responding to Query, but waiting for confirmation", server_name);
                     return (current_status, true); // Success but don't change state yet
                 }
             } else {
@@ -387,7 +403,7 @@ impl ServerMonitor {
         // Both RCON and Query failed - this is a failure
         // Only log offline status when it changes to avoid spam
         if current_status == ServerMonitorStatus::Online || current_status == ServerMonitorStatus::Starting {
-            println!("❌ {} connection failed - RCON: {}, Query: {} (failures: {})", 
+            log::debug!("{} connection failed - RCON: {}, Query: {} (failures: {})", 
                      server_name, rcon_connected, query_response.online, consecutive_failures + 1);
         }
         
@@ -397,7 +413,7 @@ impl ServerMonitor {
                 // Keep as starting - servers take time to fully initialize
                 // Only transition to offline after many consecutive failures (60+ seconds)
                 if consecutive_failures >= 3 { // 3 * 20s cycles = 60 seconds
-                    println!("⏰ {} failed to start after {} attempts (60s), marking offline", server_name, consecutive_failures + 1);
+                    log::warn!("{} failed to start after {} attempts (60s), marking offline", server_name, consecutive_failures + 1);
                     ServerMonitorStatus::Offline
                 } else {
                     ServerMonitorStatus::Starting
@@ -411,7 +427,7 @@ impl ServerMonitor {
                 // VERY CONSERVATIVE: Require multiple consecutive failures before marking offline
                 // This prevents flapping due to temporary network issues
                 if consecutive_failures >= 3 { // 60+ seconds of failures (3 * 20s cycles)
-                    println!("⬇️ {} going offline after {} consecutive failures (60s)", server_name, consecutive_failures + 1);
+                    log::info!("{} going offline after {} consecutive failures (60s)", server_name, consecutive_failures + 1);
                     ServerMonitorStatus::Offline
                 } else {
                     // Stay online, this might just be a temporary hiccup
@@ -0,0 +1,141 @@
+use crate::util::{ServerInstance, ServerProperties};
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Templates live in storage/templates/<name>.json
+pub const TEMPLATES_DIR: &str = "storage/templates";
+
+/// Properties that identify one particular server; copying them would make the new server
+/// clash with the original, so templates never capture them
+const SERVER_SPECIFIC_PROPERTIES: [&str; 4] = ["server-port", "rcon.port", "rcon.password", "query.port"];
+
+/// The reusable setup of a server: what it runs, how it is launched and its game settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServerTemplate {
+    pub name: String,
+    pub minecraft_version: String,
+    pub mod_loader: String,
+    pub mod_loader_version: String,
+    pub memory_mb: u32,
+    #[serde(default)]
+    pub env_vars: HashMap<String, String>,
+    #[serde(default = "default_nogui")]
+    pub nogui: bool,
+    /// server.properties values applied to servers created from the template
+    #[serde(default)]
+    pub properties: BTreeMap<String, String>,
+    pub created_at: DateTime<Utc>,
+    /// The server the template was saved from
+    pub source_server: Option<String>,
+}
+
+fn default_nogui() -> bool {
+    true
+}
+
+impl ServerTemplate {
+    /// Captures a server's setup. `property_keys` limits which properties are kept;
+    /// by default every property except the server-specific ports and password is.
+    pub fn from_server(
+        template_name: &str,
+        instance: &ServerInstance,
+        properties: &ServerProperties,
+        property_keys: Option<&[String]>,
+    ) -> Self {
+        let properties = properties
+            .to_key_values()
+            .into_iter()
+            .filter(|(key, _)| match property_keys {
+                Some(keys) => keys.iter().any(|selected| selected == key),
+                None => !SERVER_SPECIFIC_PROPERTIES.contains(key),
+            })
+            .map(|(key, value)| (key.to_string(), value))
+            .collect();
+
+        Self {
+            name: template_name.to_string(),
+            minecraft_version: instance.version.clone(),
+            mod_loader: instance.mod_loader.clone(),
+            mod_loader_version: instance.mod_loader_version.clone(),
+            memory_mb: instance.memory_mb,
+            env_vars: instance.env_vars.clone(),
+            nogui: instance.nogui,
+            properties,
+            created_at: Utc::now(),
+            source_server: Some(instance.name.clone()),
+        }
+    }
+}
+
+/// Template names become file names, so keep them to a safe character set
+pub fn validate_template_name(name: &str) -> Result<()> {
+    let valid = !name.trim().is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ' ' | '.'))
+        && !name.starts_with('.');
+    if !valid {
+        return Err(anyhow!(
+            "Invalid template name '{}': use up to 64 letters, digits, spaces, '.', '-' or '_'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+fn template_path(templates_dir: &Path, name: &str) -> PathBuf {
+    templates_dir.join(format!("{}.json", name))
+}
+
+/// Writes the template, replacing an existing one of the same name
+pub fn save_template(templates_dir: &Path, template: &ServerTemplate) -> Result<PathBuf> {
+    validate_template_name(&template.name)?;
+    fs::create_dir_all(templates_dir)?;
+
+    let path = template_path(templates_dir, &template.name);
+    fs::write(&path, serde_json::to_string_pretty(template)?)?;
+    log::info!("Saved server template {:?}", path);
+    Ok(path)
+}
+
+pub fn load_template(templates_dir: &Path, name: &str) -> Result<ServerTemplate> {
+    validate_template_name(name)?;
+    let path = template_path(templates_dir, name);
+    let content = fs::read_to_string(&path).map_err(|_| anyhow!("Template '{}' not found", name))?;
+    serde_json::from_str(&content).map_err(|e| anyhow!("Template '{}' is corrupt: {}", name, e))
+}
+
+/// Every readable template, sorted by name. Unreadable files are skipped with a warning.
+pub fn list_templates(templates_dir: &Path) -> Result<Vec<ServerTemplate>> {
+    if !templates_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut templates: Vec<ServerTemplate> = fs::read_dir(templates_dir)?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| {
+            let template = fs::read_to_string(&path)
+                .map_err(anyhow::Error::from)
+                .and_then(|content| Ok(serde_json::from_str::<ServerTemplate>(&content)?));
+            match template {
+                Ok(template) => Some(template),
+                Err(e) => {
+                    log::warn!("Skipping unreadable template {:?}: {}", path, e);
+                    None
+                }
+            }
+        })
+        .collect();
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(templates)
+}
+
+pub fn delete_template(templates_dir: &Path, name: &str) -> Result<()> {
+    validate_template_name(name)?;
+    fs::remove_file(template_path(templates_dir, name)).map_err(|_| anyhow!("Template '{}' not found", name))
+}
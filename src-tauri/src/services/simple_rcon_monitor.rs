@@ -1,15 +1,32 @@
+use crate::services::rcon_commands::parse_list_response;
+use crate::services::notification_service::notify_status_change;
 use crate::services::rcon_manager::RconManager;
-use std::collections::HashMap;
+use crate::util::ServerFileManager;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime};
-use tokio::sync::{Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::{mpsc, Mutex, RwLock};
 use serde::{Serialize, Deserialize};
 use tauri::{AppHandle, Emitter};
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum ServerStatus {
     Offline,
+    Starting,
     Online,
+    Stopping,
+}
+
+impl ServerStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ServerStatus::Offline => "offline",
+            ServerStatus::Starting => "starting",
+            ServerStatus::Online => "online",
+            ServerStatus::Stopping => "stopping",
+        }
+    }
 }
 
 #[derive(Clone, Serialize)]
@@ -20,11 +37,36 @@ pub struct ServerStatusEvent {
     pub timestamp: u64,
 }
 
+/// Emitted as "server-idle-shutdown" right before an empty server is stopped
+#[derive(Clone, Serialize)]
+pub struct IdleShutdownEvent {
+    pub server_name: String,
+    pub idle_minutes: u32,
+    pub timestamp: u64,
+}
+
+/// Emitted as "player-joined" / "player-left" when the roster of a server changes
+#[derive(Clone, Serialize)]
+pub struct PlayerEvent {
+    pub server_name: String,
+    pub player_name: String,
+    pub timestamp: u64,
+}
+
+/// Time after a server comes online during which an empty server is not considered idle
+const IDLE_GRACE_PERIOD: Duration = Duration::from_secs(5 * 60);
+
 #[derive(Debug, Clone)]
 pub struct ServerState {
     pub status: ServerStatus,
     pub is_connecting: bool,
     pub last_connection_attempt: Option<std::time::Instant>,
+    /// When the server was last seen coming online
+    pub online_since: Option<Instant>,
+    /// When the player count was first seen at zero (after the grace period)
+    pub empty_since: Option<Instant>,
+    /// Players seen in the last `list` poll; None until the first poll after coming online
+    pub roster: Option<HashSet<String>>,
 }
 
 impl ServerState {
@@ -33,6 +75,9 @@ impl ServerState {
             status: ServerStatus::Offline,
             is_connecting: false,
             last_connection_attempt: None,
+            online_since: None,
+            empty_since: None,
+            roster: None,
         }
     }
 }
@@ -42,6 +87,11 @@ pub struct SimpleRconMonitor {
     rcon_manager: Arc<Mutex<RconManager>>,
     monitoring_task: Option<tokio::task::JoinHandle<()>>,
     app_handle: Option<AppHandle>,
+    interval: Duration,
+    /// Receives the names of servers that should be stopped for being idle
+    idle_shutdown_sender: Option<mpsc::UnboundedSender<String>>,
+    /// Receives the names of servers that went offline without being stopped
+    offline_sender: Option<mpsc::UnboundedSender<String>>,
 }
 
 impl SimpleRconMonitor {
@@ -51,6 +101,9 @@ impl SimpleRconMonitor {
             rcon_manager,
             monitoring_task: None,
             app_handle: None,
+            interval: Duration::from_secs(15),
+            idle_shutdown_sender: None,
+            offline_sender: None,
         }
     }
 
@@ -59,11 +112,43 @@ impl SimpleRconMonitor {
         self.app_handle = Some(app_handle);
     }
 
+    /// Set the channel that performs idle shutdowns; without it idle servers are only logged
+    pub fn set_idle_shutdown_sender(&mut self, sender: mpsc::UnboundedSender<String>) {
+        self.idle_shutdown_sender = Some(sender);
+    }
+
+    /// Set the channel told about servers that went offline while nobody stopped them,
+    /// so their process can be checked for a crash
+    pub fn set_offline_sender(&mut self, sender: mpsc::UnboundedSender<String>) {
+        self.offline_sender = Some(sender);
+    }
+
+    /// Get the current polling interval
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Change the polling interval, restarting the background task if it is running
+    pub fn set_interval(&mut self, secs: u64) {
+        let secs = crate::util::clamp_monitor_interval(secs);
+        if self.interval == Duration::from_secs(secs) {
+            return;
+        }
+
+        self.interval = Duration::from_secs(secs);
+        log::debug!("RCON monitor interval set to {}s", secs);
+
+        if self.monitoring_task.is_some() {
+            self.stop_background_monitoring();
+            self.start_background_monitoring();
+        }
+    }
+
     /// Start monitoring a server
     pub async fn start_monitoring(&self, server_name: String) {
         let mut servers = self.servers.write().await;
         servers.insert(server_name.clone(), ServerState::new());
-        println!("🎯 Started RCON monitoring for server: {}", server_name);
+        log::debug!("Started RCON monitoring for server: {}", server_name);
     }
 
     /// Stop monitoring a server
@@ -78,7 +163,7 @@ impl SimpleRconMonitor {
             let _ = self.disconnect_rcon(server_name).await;
         }
         
-        println!("🛑 Stopped RCON monitoring for server: {}", server_name);
+        log::debug!("Stopped RCON monitoring for server: {}", server_name);
     }
 
     /// Update server status manually (when user starts/stops server)
@@ -93,6 +178,19 @@ impl SimpleRconMonitor {
         }
     }
 
+    /// Puts a server the idle check marked Stopping back to Online after its stop failed.
+    /// The idle timer restarts, so it is tried again once the server has been empty that long.
+    pub async fn cancel_idle_shutdown(&self, server_name: &str) {
+        let mut servers = self.servers.write().await;
+        if let Some(server_state) = servers.get_mut(server_name) {
+            server_state.empty_since = None;
+            if server_state.status == ServerStatus::Stopping {
+                server_state.status = ServerStatus::Online;
+                self.emit_status_change(server_name, ServerStatus::Stopping, ServerStatus::Online).await;
+            }
+        }
+    }
+
     /// Get current server status
     pub async fn get_server_status(&self, server_name: &str) -> ServerStatus {
         let servers = self.servers.read().await;
@@ -115,18 +213,22 @@ impl SimpleRconMonitor {
             return;
         }
 
-        println!("🚀 Starting simple RCON-based monitoring (15s intervals)");
+        log::debug!("Starting simple RCON-based monitoring ({}s intervals)", self.interval.as_secs());
 
         let servers = Arc::clone(&self.servers);
         let rcon_manager = Arc::clone(&self.rcon_manager);
         let app_handle = self.app_handle.clone();
+        let idle_shutdown_sender = self.idle_shutdown_sender.clone();
+        let offline_sender = self.offline_sender.clone();
+        let period = self.interval;
 
         let task = tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(15));
+            let mut interval = tokio::time::interval(period);
             
             loop {
                 interval.tick().await;
-                Self::monitor_cycle(Arc::clone(&servers), Arc::clone(&rcon_manager), app_handle.clone()).await;
+                Self::monitor_cycle(Arc::clone(&servers), Arc::clone(&rcon_manager), app_handle.clone(), offline_sender.clone(), period).await;
+                Self::player_cycle(Arc::clone(&servers), Arc::clone(&rcon_manager), app_handle.clone(), idle_shutdown_sender.clone()).await;
             }
         });
 
@@ -145,6 +247,8 @@ impl SimpleRconMonitor {
         servers: Arc<RwLock<HashMap<String, ServerState>>>,
         rcon_manager: Arc<Mutex<RconManager>>,
         app_handle: Option<AppHandle>,
+        offline_sender: Option<mpsc::UnboundedSender<String>>,
+        period: Duration,
     ) {
         let server_list: Vec<String> = {
             let servers_read = servers.read().await;
@@ -156,12 +260,12 @@ impl SimpleRconMonitor {
                 let servers_read = servers.read().await;
                 if let Some(state) = servers_read.get(&server_name) {
                     // Only try to connect if:
-                    // 1. Currently offline AND not already connecting
-                    // 2. OR last attempt was more than 15 seconds ago (in case of failure)
-                    state.status == ServerStatus::Offline && 
+                    // 1. Currently offline or starting AND not already connecting
+                    // 2. OR last attempt was at least one polling period ago (in case of failure)
+                    matches!(state.status, ServerStatus::Offline | ServerStatus::Starting) && 
                     !state.is_connecting &&
                     (state.last_connection_attempt.is_none() || 
-                     state.last_connection_attempt.unwrap().elapsed() >= Duration::from_secs(15))
+                     state.last_connection_attempt.unwrap().elapsed() >= period)
                 } else {
                     false
                 }
@@ -190,6 +294,8 @@ impl SimpleRconMonitor {
                             if state.status != ServerStatus::Online {
                                 let old_status = state.status;
                                 state.status = ServerStatus::Online;
+                                state.online_since = Some(Instant::now());
+                                state.empty_since = None;
                                 
                                 // Emit event
                                 if let Some(ref app) = app_handle {
@@ -204,11 +310,12 @@ impl SimpleRconMonitor {
                                     };
                                     
                                     if let Err(e) = app.emit("server-status-changed", &event) {
-                                        println!("⚠️ Failed to emit status event: {}", e);
+                                        log::warn!("Failed to emit status event: {}", e);
                                     }
                                 }
                                 
-                                println!("✅ {} now online via RCON", server_name);
+                                notify_status_change(&server_name, ServerStatus::Online.as_str());
+                                log::info!("{} now online via RCON", server_name);
                             }
                         },
                         Err(_) => {
@@ -237,6 +344,9 @@ impl SimpleRconMonitor {
                         // Server was online but RCON disconnected
                         let old_status = state.status;
                         state.status = ServerStatus::Offline;
+                        state.online_since = None;
+                        state.empty_since = None;
+                        state.roster = None;
                         
                         // Emit event
                         if let Some(ref app) = app_handle {
@@ -251,17 +361,186 @@ impl SimpleRconMonitor {
                             };
                             
                             if let Err(e) = app.emit("server-status-changed", &event) {
-                                println!("⚠️ Failed to emit status event: {}", e);
+                                log::warn!("Failed to emit status event: {}", e);
                             }
                         }
                         
-                        println!("❌ {} went offline (RCON disconnected)", server_name);
+                        notify_status_change(&server_name, ServerStatus::Offline.as_str());
+                        log::info!("{} went offline (RCON disconnected)", server_name);
+                        if let Some(ref sender) = offline_sender {
+                            let _ = sender.send(server_name.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Polls `list` on every online server to emit player join/leave events, and
+    /// requests a stop once a server with idle shutdown has been empty for longer than its limit
+    async fn player_cycle(
+        servers: Arc<RwLock<HashMap<String, ServerState>>>,
+        rcon_manager: Arc<Mutex<RconManager>>,
+        app_handle: Option<AppHandle>,
+        idle_shutdown_sender: Option<mpsc::UnboundedSender<String>>,
+    ) {
+        let online: Vec<String> = {
+            let servers_read = servers.read().await;
+            servers_read
+                .iter()
+                .filter(|(_, state)| state.status == ServerStatus::Online)
+                .map(|(name, _)| name.clone())
+                .collect()
+        };
+        if online.is_empty() {
+            return;
+        }
+
+        let file_manager = ServerFileManager::new(PathBuf::from("storage/server_config.json"));
+
+        for server_name in online {
+            let player_list = {
+                let rcon = rcon_manager.lock().await;
+                rcon.execute_command(&server_name, "list")
+                    .ok()
+                    .and_then(|response| parse_list_response(&response))
+            };
+            let player_list = match player_list {
+                Some(list) => list,
+                None => continue,
+            };
+            let player_count = player_list.online;
+
+            Self::update_roster(&servers, &app_handle, &server_name, player_list.players).await;
+
+            let idle_minutes = match file_manager.get_instance(&server_name) {
+                Ok(Some(instance)) => match instance.idle_shutdown_minutes {
+                    Some(minutes) if minutes > 0 => minutes,
+                    _ => continue,
+                },
+                _ => continue,
+            };
+
+            let should_stop = {
+                let mut servers_write = servers.write().await;
+                let state = match servers_write.get_mut(&server_name) {
+                    Some(state) => state,
+                    None => continue,
+                };
+                let online_since = *state.online_since.get_or_insert_with(Instant::now);
+
+                if player_count > 0 || online_since.elapsed() < IDLE_GRACE_PERIOD {
+                    state.empty_since = None;
+                    false
+                } else {
+                    let empty_since = *state.empty_since.get_or_insert_with(Instant::now);
+                    let idle = empty_since.elapsed() >= Duration::from_secs(idle_minutes as u64 * 60);
+                    if idle {
+                        state.status = ServerStatus::Stopping;
+                    }
+                    idle
+                }
+            };
+
+            if !should_stop {
+                continue;
+            }
+
+            log::info!("{} has been empty for {} minutes, stopping", server_name, idle_minutes);
+            notify_status_change(&server_name, ServerStatus::Stopping.as_str());
+
+            if let Some(ref app) = app_handle {
+                let event = IdleShutdownEvent {
+                    server_name: server_name.clone(),
+                    idle_minutes,
+                    timestamp: SystemTime::now()
+                        .duration_since(SystemTime::UNIX_EPOCH)
+                        .unwrap_or_default()
+                        .as_millis() as u64,
+                };
+                if let Err(e) = app.emit("server-idle-shutdown", &event) {
+                    log::warn!("Failed to emit idle shutdown event: {}", e);
+                }
+                let status_event = ServerStatusEvent {
+                    server_name: server_name.clone(),
+                    old_status: ServerStatus::Online,
+                    new_status: ServerStatus::Stopping,
+                    timestamp: event.timestamp,
+                };
+                if let Err(e) = app.emit("server-status-changed", &status_event) {
+                    log::warn!("Failed to emit status event: {}", e);
+                }
+            }
+
+            match idle_shutdown_sender {
+                Some(ref sender) => {
+                    let _ = sender.send(server_name);
+                }
+                None => log::warn!("Idle shutdown requested for {} but no handler is configured", server_name),
+            }
+        }
+    }
+
+    /// Diffs the new roster against the last one and emits "player-joined"/"player-left".
+    /// The first roster after a server comes online is only recorded, not announced.
+    async fn update_roster(
+        servers: &Arc<RwLock<HashMap<String, ServerState>>>,
+        app_handle: &Option<AppHandle>,
+        server_name: &str,
+        players: Vec<String>,
+    ) {
+        let current: HashSet<String> = players.into_iter().collect();
+
+        let (joined, left) = {
+            let mut servers_write = servers.write().await;
+            let state = match servers_write.get_mut(server_name) {
+                Some(state) => state,
+                None => return,
+            };
+            let previous = state.roster.replace(current.clone());
+            match previous {
+                Some(previous) => (
+                    current.difference(&previous).cloned().collect::<Vec<_>>(),
+                    previous.difference(&current).cloned().collect::<Vec<_>>(),
+                ),
+                None => return,
+            }
+        };
+
+        let timestamp = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+
+        for (event_name, names) in [("player-joined", joined), ("player-left", left)] {
+            for player_name in names {
+                log::info!("{} {} {}", player_name, if event_name == "player-joined" { "joined" } else { "left" }, server_name);
+                if let Some(ref app) = app_handle {
+                    let event = PlayerEvent {
+                        server_name: server_name.to_string(),
+                        player_name,
+                        timestamp,
+                    };
+                    if let Err(e) = app.emit(event_name, &event) {
+                        log::warn!("Failed to emit {} event: {}", event_name, e);
                     }
                 }
             }
         }
     }
 
+    /// Last known player names of a server, if its roster has been read since it came online
+    pub async fn get_roster(&self, server_name: &str) -> Option<Vec<String>> {
+        let servers = self.servers.read().await;
+        servers.get(server_name)
+            .and_then(|state| state.roster.as_ref())
+            .map(|roster| {
+                let mut players: Vec<String> = roster.iter().cloned().collect();
+                players.sort();
+                players
+            })
+    }
+
     /// Attempt to connect to a server via RCON
     async fn attempt_rcon_connection(
         server_name: &str,
@@ -269,15 +548,9 @@ impl SimpleRconMonitor {
     ) -> Result<(), String> {
         let rcon = rcon_manager.lock().await;
         
-        // Setup RCON with default settings (need to import RconConfig)
+        // Refresh the config each attempt so property or override changes are picked up
         use crate::services::rcon_manager::RconConfig;
-        let config = RconConfig {
-            host: "127.0.0.1".to_string(),
-            port: 25575,
-            password: "minecraft".to_string(),
-        };
-        
-        rcon.add_server(server_name.to_string(), config);
+        rcon.add_server(server_name.to_string(), RconConfig::for_server(server_name));
         
         // Try to connect
         match rcon.connect(server_name) {
@@ -295,6 +568,8 @@ impl SimpleRconMonitor {
 
     /// Emit status change event
     async fn emit_status_change(&self, server_name: &str, old_status: ServerStatus, new_status: ServerStatus) {
+        notify_status_change(server_name, new_status.as_str());
+        
         if let Some(ref app) = self.app_handle {
             let event = ServerStatusEvent {
                 server_name: server_name.to_string(),
@@ -307,7 +582,7 @@ impl SimpleRconMonitor {
             };
             
             if let Err(e) = app.emit("server-status-changed", &event) {
-                println!("⚠️ Failed to emit status event: {}", e);
+                log::warn!("Failed to emit status event: {}", e);
             }
         }
     }
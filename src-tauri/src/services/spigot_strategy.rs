@@ -0,0 +1,233 @@
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use reqwest::Client;
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+use std::fs;
+use chrono::Utc;
+use crate::services::mod_loader_strategy::{installer_error, DownloadContext, ModLoaderStrategy};
+use crate::services::http_client::HttpClient;
+use crate::services::resumable_download::download_to_file;
+use crate::models::version::{LoaderType, VersionResponse, MinecraftVersion, VersionType};
+
+/// One <version>.json per Minecraft version BuildTools can build
+const VERSIONS_URL: &str = "https://hub.spigotmc.org/versions/";
+const BUILD_TOOLS_URL: &str =
+    "https://hub.spigotmc.org/jenkins/job/BuildTools/lastSuccessfulBuild/artifact/target/BuildTools.jar";
+/// BuildTools clones and compiles here; keeping the checkouts makes later builds much faster
+const BUILD_DIR: &str = "storage/version_cache/buildtools";
+
+/// Spigot and CraftBukkit, which can't be downloaded and are compiled locally with BuildTools.
+/// The loader version picks the flavour: "craftbukkit", or anything else for Spigot.
+pub struct SpigotStrategy;
+
+impl SpigotStrategy {
+    /// Which server BuildTools should compile for a loader version
+    pub fn variant(loader_version: &str) -> &'static str {
+        if loader_version.trim().eq_ignore_ascii_case("craftbukkit") {
+            "craftbukkit"
+        } else {
+            "spigot"
+        }
+    }
+
+    /// Runs BuildTools for `minecraft_version`, writing <variant>-<version>.jar into `output_dir`.
+    /// This compiles the server from source and takes several minutes on a first build.
+    async fn run_build_tools(client: &Client, java_command: &str, minecraft_version: &str, variant: &str, output_dir: &Path) -> Result<()> {
+        let build_dir = Path::new(BUILD_DIR);
+        let build_tools = build_dir.join("BuildTools.jar");
+        // Always fetch the newest BuildTools; old ones can't build new versions
+        download_to_file(client, BUILD_TOOLS_URL, &build_tools, None)
+            .await
+            .map_err(|e| anyhow!("Failed to download BuildTools: {}", e))?;
+
+        let output_dir = std::path::absolute(output_dir)?;
+        log::info!("Compiling {} {} with BuildTools, this can take several minutes", variant, minecraft_version);
+        let output = tokio::process::Command::new(java_command)
+            .arg("-jar")
+            .arg("BuildTools.jar")
+            .args(["--rev", minecraft_version, "--compile", variant, "--output-dir"])
+            .arg(&output_dir)
+            .current_dir(build_dir)
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| {
+                if e.kind() == std::io::ErrorKind::NotFound {
+                    anyhow!("Java is not installed or not found in PATH. Please install Java to run Minecraft servers.")
+                } else {
+                    anyhow!("Failed to execute Java: {}", e)
+                }
+            })?;
+
+        if !output.status.success() {
+            return Err(installer_error("BuildTools", java_command, &output));
+        }
+        Ok(())
+    }
+}
+
+/// Release versions in the hub's directory listing ("1.20.4.json"), newest first
+fn parse_version_listing(listing: &str) -> Vec<String> {
+    let Ok(link_regex) = regex::Regex::new(r#"href="(\d+\.\d+(?:\.\d+)?)\.json""#) else {
+        return Vec::new();
+    };
+    let mut versions: Vec<String> = link_regex
+        .captures_iter(listing)
+        .map(|captures| captures[1].to_string())
+        .collect();
+    versions.sort_by_key(|version| {
+        Reverse(version.split('.').map(|part| part.parse::<u32>().unwrap_or(0)).collect::<Vec<_>>())
+    });
+    versions.dedup();
+    versions
+}
+
+#[async_trait]
+impl ModLoaderStrategy for SpigotStrategy {
+    async fn get_versions(&self, client: &dyn HttpClient, minecraft_version: Option<String>) -> Result<VersionResponse> {
+        let listing = client.get_text(VERSIONS_URL).await?;
+
+        let versions: Vec<MinecraftVersion> = parse_version_listing(&listing)
+            .into_iter()
+            .filter(|version| minecraft_version.as_ref().is_none_or(|target| target == version))
+            .enumerate()
+            .map(|(i, version)| MinecraftVersion {
+                id: format!("spigot-{}", version),
+                version_type: VersionType::Release,
+                loader: LoaderType::Spigot,
+                release_time: Utc::now(),
+                latest: i == 0,
+                recommended: i == 0,
+                minecraft_version: Some(version),
+            })
+            .collect();
+
+        let latest = versions.first().cloned();
+        let recommended = versions.first().cloned();
+
+        Ok(VersionResponse {
+            latest,
+            recommended,
+            versions,
+        })
+    }
+
+    async fn get_download_url(&self, _client: &Client, _minecraft_version: &str, _loader_version: &str) -> Result<String> {
+        Ok(BUILD_TOOLS_URL.to_string())
+    }
+
+    fn get_filename(&self, minecraft_version: &str, loader_version: &str) -> String {
+        format!("{}-{}.jar", Self::variant(loader_version), minecraft_version)
+    }
+
+    fn is_loader_file(&self, file_name: &str) -> bool {
+        // bukkit.yml and spigot.yml are the user's configuration, which Paper reads too
+        (file_name.starts_with("spigot-") || file_name.starts_with("craftbukkit-")) && file_name.ends_with(".jar")
+    }
+
+    // Compiled with BuildTools instead of downloaded; the output is the same for a given
+    // version, so it is cached like a downloaded JAR
+    async fn download_server_jar(
+        &self,
+        context: &DownloadContext<'_>,
+        minecraft_version: &str,
+        loader_version: &str,
+        server_path: &PathBuf,
+        loader_type: &LoaderType
+    ) -> Result<PathBuf> {
+        let jar_cache = context.jar_cache;
+        let loader_version_opt = if loader_version.is_empty() { None } else { Some(loader_version) };
+
+        if jar_cache.is_jar_cached(loader_type, minecraft_version, loader_version_opt) {
+            log::debug!("Spigot JAR found in cache, copying to server: {:?}", server_path);
+            return jar_cache.copy_cached_jar_to_server(loader_type, minecraft_version, loader_version_opt, server_path);
+        }
+
+        let cached_path = jar_cache.prepare_cache_entry(loader_type, minecraft_version, loader_version_opt)?;
+        // BuildTools writes its output gradually and may be killed (cancelled setup, app closed)
+        // halfway; build next to the cache entry and move the JAR in only once it is done
+        let staging_dir = cached_path
+            .parent()
+            .ok_or_else(|| anyhow!("Invalid cache path {:?}", cached_path))?
+            .join(".build");
+        if staging_dir.exists() {
+            fs::remove_dir_all(&staging_dir)?;
+        }
+        fs::create_dir_all(&staging_dir)?;
+        fs::create_dir_all(BUILD_DIR)?;
+        Self::run_build_tools(context.client, context.java_command, minecraft_version, Self::variant(loader_version), &staging_dir).await?;
+
+        let jar_name = self.get_filename(minecraft_version, loader_version);
+        let built_jar = staging_dir.join(&jar_name);
+        if !built_jar.exists() {
+            let _ = fs::remove_dir_all(&staging_dir);
+            return Err(anyhow!("BuildTools finished but {} was not produced", jar_name));
+        }
+        fs::rename(&built_jar, &cached_path)?;
+        let _ = fs::remove_dir_all(&staging_dir);
+        jar_cache.record_checksum(&cached_path);
+
+        let jar_path = jar_cache.copy_cached_jar_to_server(loader_type, minecraft_version, loader_version_opt, server_path)?;
+        log::info!("Successfully built and cached Spigot JAR: {:?}", jar_path);
+        Ok(jar_path)
+    }
+
+    async fn setup_server(&self, _client: &Client, _java_command: &str, server_path: &PathBuf, minecraft_version: &str, loader_version: &str) -> Result<()> {
+        let jar_name = self.get_filename(minecraft_version, loader_version);
+        let jar_path = server_path.join(&jar_name);
+
+        if !jar_path.exists() {
+            return Err(anyhow!("Spigot JAR not found: {:?}", jar_path));
+        }
+
+        log::info!("Spigot server ready: {:?}", jar_path);
+        Ok(())
+    }
+
+    fn build_start_command(&self, server_path: &PathBuf, memory_mb: u32, min_memory_mb: u32) -> Result<Vec<String>> {
+        let jar = fs::read_dir(server_path)?
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .find(|name| (name.starts_with("spigot-") || name.starts_with("craftbukkit-")) && name.ends_with(".jar"))
+            .ok_or_else(|| anyhow!("Spigot server JAR not found"))?;
+
+        Ok(vec![
+            format!("-Xmx{}M", memory_mb),
+            format!("-Xms{}M", min_memory_mb),
+            "-jar".to_string(),
+            jar,
+            "nogui".to_string(),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::http_client::MockHttpClient;
+
+    const LISTING: &str = r#"<html><body><pre>
+        <a href="1.8.8.json">1.8.8.json</a>
+        <a href="1.20.4.json">1.20.4.json</a>
+        <a href="1.21.json">1.21.json</a>
+        <a href="1234.json">1234.json</a>
+        <a href="1.21-pre1.json">1.21-pre1.json</a>
+    </pre></body></html>"#;
+
+    #[tokio::test]
+    async fn lists_release_versions_newest_first() {
+        let client = MockHttpClient::new().with(VERSIONS_URL, LISTING);
+        let response = SpigotStrategy.get_versions(&client, None).await.unwrap();
+
+        let ids: Vec<_> = response.versions.iter().map(|v| v.id.as_str()).collect();
+        assert_eq!(ids, ["spigot-1.21", "spigot-1.20.4", "spigot-1.8.8"]);
+        assert_eq!(response.latest.unwrap().id, "spigot-1.21");
+    }
+
+    #[test]
+    fn builds_craftbukkit_only_when_asked() {
+        assert_eq!(SpigotStrategy.get_filename("1.20.4", ""), "spigot-1.20.4.jar");
+        assert_eq!(SpigotStrategy.get_filename("1.20.4", "CraftBukkit"), "craftbukkit-1.20.4.jar");
+    }
+}
@@ -1,32 +1,156 @@
 use reqwest::Client;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Child, Stdio};
+use std::time::{Duration, Instant};
 use anyhow::{Result, anyhow};
 use crate::models::version::LoaderType;
-use crate::services::mod_loader_strategy::{get_strategy, ModLoaderStrategy};
-use crate::util::{JarCacheManager, ServerPropertiesManager, ServerProperties};
-use std::collections::HashMap;
+use crate::services::mod_loader_strategy::{get_strategy, DownloadContext, ModLoaderStrategy};
+use crate::services::mod_loader_strategy::heap_sizes_mb;
+use crate::services::console_log::{capture_output, ConsoleLog};
+use crate::services::paper_strategy::PaperStrategy;
+use crate::services::rcon_manager::generate_strong_rcon_password;
+use crate::services::world_info::{compare_release_versions, read_world_version, world_folder};
+use crate::util::{JarCacheManager, ServerFileManager, ServerPropertiesManager, ServerProperties, ProcessStateManager, TrackedProcess, ensure_free_space, DOWNLOAD_SPACE_BYTES, SETUP_SPACE_BYTES};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use rand::Rng;
+use sysinfo::{Pid, ProcessStatus, System};
+use tokio::sync::{Mutex, Notify};
+use lazy_static::lazy_static;
+use std::future::Future;
 
-pub struct UnifiedServerService {
+/// Tolerance when matching a persisted start time against the live process
+const START_TIME_TOLERANCE_SECS: u64 = 5;
+
+/// Files loader installers leave behind that no strategy owns as a loader file
+const LOADER_LEFTOVERS: &[&str] = &[
+    "user_jvm_args.txt",
+    "installer.log",
+    "fabric-server-launcher.properties",
+    ".mixin.out",
+];
+
+/// Handshake next state of a client joining, as opposed to 1 for a server list ping
+const LOGIN_NEXT_STATE: i32 = 2;
+/// A handshake is a few dozen bytes; anything much longer is not a Minecraft client
+const MAX_HANDSHAKE_LEN: usize = 1024;
+/// How long the wake listener waits for a connection's handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(3);
+
+lazy_static! {
+    /// Cancels the download or setup running for a server. Kept outside the service because
+    /// setups run on a ServerInstaller, away from the service.
+    static ref SETUP_CANCELLATIONS: std::sync::Mutex<HashMap<String, Arc<Notify>>> = std::sync::Mutex::new(HashMap::new());
+}
+
+/// Asks the download or setup running for `server_name` to stop; false if none is running
+pub fn cancel_setup(server_name: &str) -> bool {
+    match SETUP_CANCELLATIONS.lock().unwrap().get(server_name) {
+        Some(cancel) => {
+            cancel.notify_one();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Runs `step` until it finishes or cancel_setup is called for the server. Cancelling drops
+/// the step, which aborts its downloads and kills the installer (spawned with kill_on_drop).
+async fn cancellable<T>(server_name: &str, step: impl Future<Output = Result<T>>) -> Result<T> {
+    let cancel = Arc::new(Notify::new());
+    SETUP_CANCELLATIONS.lock().unwrap().insert(server_name.to_string(), Arc::clone(&cancel));
+    let result = tokio::select! {
+        result = step => result,
+        _ = cancel.notified() => Err(anyhow!("Setup of {} was cancelled", server_name)),
+    };
+    SETUP_CANCELLATIONS.lock().unwrap().remove(server_name);
+    result
+}
+
+/// A running server process
+enum ServerProcess {
+    /// Started in this session; we own stdin and can wait on it
+    Owned(Child),
+    /// Re-attached after an app restart; only the PID is known, so stop goes through RCON
+    Attached(u32),
+    /// Started in this session in its own process group with no stdio, so it outlives Allay;
+    /// we can reap it but stop still goes through RCON
+    Detached(Child),
+}
+
+impl ServerProcess {
+    fn pid(&self) -> u32 {
+        match self {
+            ServerProcess::Owned(child) | ServerProcess::Detached(child) => child.id(),
+            ServerProcess::Attached(pid) => *pid,
+        }
+    }
+}
+
+/// Result of re-attaching to servers that were running when Allay last closed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReattachReport {
+    pub reattached: Vec<String>,
+    /// Servers whose process is gone, most likely because they crashed or were killed
+    pub crashed: Vec<String>,
+}
+
+/// Per-server settings that shape how its process is launched
+#[derive(Debug, Clone)]
+pub struct LaunchOptions {
+    pub memory_mb: u32,
+    /// Run in its own process group without console pipes (see `start_server`)
+    pub detached: bool,
+    /// Added to the inherited environment of the launched process
+    pub env_vars: HashMap<String, String>,
+    /// The Java executable, "java" for the one in PATH
+    pub java_command: String,
+    /// False opens the server GUI; only honored when the server JAR is launched directly
+    pub nogui: bool,
+}
+
+/// The command Allay would run to start a server, resolved without launching anything
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchCommand {
+    /// Program followed by its arguments
+    pub argv: Vec<String>,
+    pub working_dir: String,
+    /// True when a Forge/NeoForge run script is used instead of the server JAR; heap flags
+    /// then come from user_jvm_args.txt rather than argv
+    pub uses_run_script: bool,
+}
+
+/// What repair_server found missing or broken and put back
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RepairReport {
+    pub fixed: Vec<String>,
+}
+
+/// Downloads and installs server files. Holds none of the running-server state, so a clone
+/// can create a server, which may take minutes, without keeping the service locked.
+#[derive(Clone)]
+pub struct ServerInstaller {
     client: Client,
     jar_cache: JarCacheManager,
-    running_servers: Arc<Mutex<HashMap<String, Child>>>,
+    /// Runs loader installers, BuildTools and first-run setups; "java" for the one in PATH
+    java_command: String,
 }
 
-impl UnifiedServerService {
-    pub fn new() -> Result<Self> {
-        let cache_dir = PathBuf::from("storage/version_cache");
-        let jar_cache = JarCacheManager::new(cache_dir)?;
-        
-        Ok(Self {
-            client: Client::new(),
-            jar_cache,
-            running_servers: Arc::new(Mutex::new(HashMap::new())),
-        })
+impl ServerInstaller {
+    /// The same installer running Java from `java_command`, which should be the Java the
+    /// server will be launched with
+    pub fn with_java_command(mut self, java_command: &str) -> Self {
+        self.java_command = java_command.to_string();
+        self
+    }
+
+    fn download_context(&self) -> DownloadContext<'_> {
+        DownloadContext {
+            client: &self.client,
+            jar_cache: &self.jar_cache,
+            java_command: &self.java_command,
+        }
     }
 
     /// Downloads or retrieves server JAR from cache using the strategy pattern
@@ -37,27 +161,38 @@ impl UnifiedServerService {
         loader_version: Option<String>,
         server_path: PathBuf,
     ) -> Result<PathBuf> {
+        // Fail early instead of leaving a half-written JAR on a full disk
+        ensure_free_space(Path::new("storage"), DOWNLOAD_SPACE_BYTES)?;
+        
         // Get the appropriate strategy
         let strategy = get_strategy(&loader);
         
         // For loaders that require loader_version, validate it exists
         let loader_version_str = match loader {
-            LoaderType::Vanilla | LoaderType::Paper => "".to_string(), // These don't need loader version
+            LoaderType::Vanilla => "".to_string(), // Vanilla doesn't need a loader version
+            // Paper's loader version is an optional pinned build; anything else means latest
+            LoaderType::Paper => paper_build_version(loader_version.as_deref()),
+            // Spigot's loader version only picks Spigot or CraftBukkit
+            LoaderType::Spigot => loader_version.clone().unwrap_or_default(),
             _ => {
                 loader_version.clone()
                     .ok_or_else(|| anyhow!("{:?} requires a loader version", loader))?
             }
         };
 
-        // Delegate download to the strategy
-        strategy.download_server_jar(
-            &self.client,
-            &self.jar_cache,
+        // A second creation of the same version waits here and then finds the JAR cached
+        let loader_version_opt = Some(loader_version_str.as_str()).filter(|v| !v.is_empty());
+        let _cache_entry = self.jar_cache.lock_entry(&loader, &minecraft_version, loader_version_opt).await;
+        
+        // Delegate download to the strategy; the server folder is named after the server
+        let server_name = server_path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        cancellable(&server_name, strategy.download_server_jar(
+            &self.download_context(),
             &minecraft_version,
             &loader_version_str,
             &server_path,
             &loader
-        ).await
+        )).await
     }
 
     /// Sets up server using the strategy pattern
@@ -69,21 +204,26 @@ impl UnifiedServerService {
         loader_version: Option<&str>,
         server_path: &PathBuf,
     ) -> Result<()> {
-        println!("=== Setting up server: {} with loader: {:?} ===", server_name, loader);
-        println!("Minecraft version: {}", minecraft_version);
-        println!("Loader version: {:?}", loader_version);
-        println!("Server path: {:?}", server_path);
+        log::info!("=== Setting up server: {} with loader: {:?} ===", server_name, loader);
+        log::debug!("Minecraft version: {}", minecraft_version);
+        log::debug!("Loader version: {:?}", loader_version);
+        log::debug!("Server path: {:?}", server_path);
+        
+        // Loader installers and world generation need room to work with
+        ensure_free_space(Path::new("storage"), SETUP_SPACE_BYTES)?;
         
         // Create server directory if it doesn't exist
         fs::create_dir_all(server_path)?;
-        println!("Server directory created/verified");
+        log::debug!("Server directory created/verified");
         
         // Get the appropriate strategy
         let strategy = get_strategy(&loader);
         
         // For loaders that require loader_version, validate it exists
         let loader_version_str = match loader {
-            LoaderType::Vanilla | LoaderType::Paper => "".to_string(),
+            LoaderType::Vanilla => "".to_string(),
+            LoaderType::Paper => paper_build_version(loader_version),
+            LoaderType::Spigot => loader_version.unwrap_or_default().to_string(),
             _ => {
                 loader_version
                     .ok_or_else(|| anyhow!("{:?} requires a loader version", loader))?
@@ -91,70 +231,534 @@ impl UnifiedServerService {
             }
         };
 
-        println!("Starting {:?} server setup...", loader);
-        strategy.setup_server(&self.client, server_path, minecraft_version, &loader_version_str).await?;
-        println!("{:?} server setup completed", loader);
+        log::info!("Starting {:?} server setup...", loader);
+        cancellable(server_name, strategy.setup_server(&self.client, &self.java_command, server_path, minecraft_version, &loader_version_str)).await?;
+        log::info!("{:?} server setup completed", loader);
         
         // Generate common server files
-        println!("Generating EULA file...");
+        log::info!("Generating EULA file...");
         self.generate_eula_file(server_path)?;
-        println!("Generating server properties...");
+        log::info!("Generating server properties...");
         self.generate_server_properties(server_path, server_name)?;
         
-        println!("=== Server setup completed for: {} ===", server_name);
+        log::info!("=== Server setup completed for: {} ===", server_name);
+        Ok(())
+    }
+
+    /// Puts back what an interrupted setup or a stray delete left missing: the loader JAR (from
+    /// the cache when possible), the loader installation, eula.txt and server.properties.
+    /// A readable server.properties is never overwritten.
+    pub async fn repair_server(
+        &self,
+        server_name: &str,
+        loader: LoaderType,
+        minecraft_version: &str,
+        loader_version: Option<&str>,
+        server_path: &PathBuf,
+        setup_finished: bool,
+    ) -> Result<RepairReport> {
+        log::info!("Repairing server: {}", server_name);
+        ensure_free_space(Path::new("storage"), SETUP_SPACE_BYTES)?;
+        fs::create_dir_all(server_path)?;
+
+        let mut report = RepairReport::default();
+        let strategy = get_strategy(&loader);
+        let loader_version_str = match loader {
+            LoaderType::Vanilla => "".to_string(),
+            LoaderType::Paper => paper_build_version(loader_version),
+            LoaderType::Spigot => loader_version.unwrap_or_default().to_string(),
+            _ => {
+                loader_version
+                    .ok_or_else(|| anyhow!("{:?} requires a loader version", loader))?
+                    .to_string()
+            }
+        };
+
+        let jar_name = self.jar_cache.get_jar_filename(
+            &loader,
+            minecraft_version,
+            Some(loader_version_str.as_str()).filter(|v| !v.is_empty()),
+        );
+        if !server_path.join(&jar_name).exists() {
+            self.download_server_jar(
+                loader.clone(),
+                minecraft_version.to_string(),
+                loader_version.map(str::to_string),
+                server_path.clone(),
+            ).await?;
+            report.fixed.push(format!("Restored {}", jar_name));
+        }
+
+        // Setup is idempotent, so only the files it brings back tell us whether it fixed anything
+        let loader_files = |path: &Path| -> Vec<String> {
+            fs::read_dir(path)
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .map(|entry| entry.file_name().to_string_lossy().to_string())
+                        .filter(|name| strategy.is_loader_file(name))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let before = loader_files(server_path);
+        strategy.setup_server(&self.client, &self.java_command, server_path, minecraft_version, &loader_version_str).await?;
+        let mut reinstalled: Vec<String> = loader_files(server_path)
+            .into_iter()
+            .filter(|name| !before.contains(name))
+            .collect();
+        reinstalled.sort();
+        if !reinstalled.is_empty() {
+            report.fixed.push(format!("Reinstalled {:?} files: {}", loader, reinstalled.join(", ")));
+        } else if !setup_finished {
+            report.fixed.push(format!("Finished the interrupted {:?} setup", loader));
+        }
+
+        let eula_accepted = fs::read_to_string(server_path.join("eula.txt"))
+            .map(|content| content.lines().any(|line| line.trim() == "eula=true"))
+            .unwrap_or(false);
+        if !eula_accepted {
+            self.generate_eula_file(server_path)?;
+            report.fixed.push("Regenerated eula.txt".to_string());
+        }
+
+        let properties_path = server_path.join("server.properties");
+        let properties_readable = fs::read_to_string(&properties_path)
+            .map(|content| {
+                content.lines().any(|line| {
+                    let line = line.trim();
+                    !line.starts_with('#') && line.contains('=')
+                })
+            })
+            .unwrap_or(false);
+        if !properties_readable {
+            if properties_path.exists() {
+                fs::rename(&properties_path, server_path.join("server.properties.broken"))?;
+                report.fixed.push("Moved the unreadable server.properties to server.properties.broken".to_string());
+            }
+            self.generate_server_properties(server_path, server_name)?;
+            report.fixed.push("Regenerated server.properties".to_string());
+        }
+
+        log::info!("Repaired server {}: {} fix(es)", server_name, report.fixed.len());
+        Ok(report)
+    }
+
+    /// Switches an existing, stopped server to a different mod loader, keeping the world and server.properties.
+    /// Returns warnings about migration directions that may not be fully compatible with the world.
+    pub async fn migrate_loader(
+        &self,
+        server_name: &str,
+        server_path: &PathBuf,
+        current_loader: LoaderType,
+        new_loader: LoaderType,
+        minecraft_version: &str,
+        new_loader_version: Option<&str>,
+    ) -> Result<Vec<String>> {
+        if current_loader == new_loader {
+            return Err(anyhow!("Server {} already runs {:?}", server_name, new_loader));
+        }
+
+        let loader_version_str = match new_loader {
+            LoaderType::Vanilla | LoaderType::Paper => "".to_string(),
+            LoaderType::Spigot => new_loader_version.unwrap_or_default().to_string(),
+            _ => new_loader_version
+                .ok_or_else(|| anyhow!("{:?} requires a loader version", new_loader))?
+                .to_string(),
+        };
+
+        if !loader_version_matches_minecraft(&new_loader, &loader_version_str, minecraft_version) {
+            return Err(anyhow!(
+                "{:?} version {} does not target Minecraft {}, which this world was created with",
+                new_loader, loader_version_str, minecraft_version
+            ));
+        }
+
+        let mut warnings = migration_warnings(server_path, &current_loader, &new_loader);
+        if let Some(warning) = world_downgrade_warning(server_path, minecraft_version) {
+            warnings.push(warning);
+        }
+        for warning in &warnings {
+            log::warn!("Migration warning for {}: {}", server_name, warning);
+        }
+
+        log::info!("=== Migrating server {} from {:?} to {:?} ===", server_name, current_loader, new_loader);
+
+        ensure_free_space(Path::new("storage"), DOWNLOAD_SPACE_BYTES + SETUP_SPACE_BYTES)?;
+
+        // Download into a staging folder first so a failed download leaves the server untouched
+        let staging_path = server_path.join(".allay-migration");
+        if staging_path.exists() {
+            fs::remove_dir_all(&staging_path)?;
+        }
+        fs::create_dir_all(&staging_path)?;
+
+        let new_strategy = get_strategy(&new_loader);
+        if let Err(e) = new_strategy.download_server_jar(
+            &self.download_context(),
+            minecraft_version,
+            &loader_version_str,
+            &staging_path,
+            &new_loader,
+        ).await {
+            let _ = fs::remove_dir_all(&staging_path);
+            return Err(anyhow!("Failed to download {:?} server files: {}", new_loader, e));
+        }
+
+        // Move the old loader's files aside rather than deleting them, so a failed setup can put
+        // the server back the way it was
+        let previous_path = server_path.join(".allay-previous-loader");
+        if previous_path.exists() {
+            fs::remove_dir_all(&previous_path)?;
+        }
+        fs::create_dir_all(&previous_path)?;
+        let old_strategy = get_strategy(&current_loader);
+        for entry in fs::read_dir(server_path)? {
+            let entry = entry?;
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if old_strategy.is_loader_file(&file_name) {
+                fs::rename(entry.path(), previous_path.join(&file_name))?;
+            }
+        }
+
+        // Move the staged files into place
+        for entry in fs::read_dir(&staging_path)? {
+            let entry = entry?;
+            fs::rename(entry.path(), server_path.join(entry.file_name()))?;
+        }
+        fs::remove_dir_all(&staging_path)?;
+
+        let setup = new_strategy.setup_server(&self.client, &self.java_command, server_path, minecraft_version, &loader_version_str).await;
+        if let Err(e) = setup {
+            if let Err(restore_error) = restore_previous_loader(server_path, &previous_path, new_strategy.as_ref()) {
+                log::error!("Failed to restore the previous loader of {}: {}", server_name, restore_error);
+                return Err(anyhow!(
+                    "{:?} setup failed: {}. Restoring the previous loader also failed ({}); its files are in {:?}",
+                    new_loader, e, restore_error, previous_path
+                ));
+            }
+            return Err(anyhow!("{:?} setup failed, the server was left on {:?}: {}", new_loader, current_loader, e));
+        }
+        for entry in fs::read_dir(&previous_path)?.flatten() {
+            log::info!("Removed old loader file: {}", entry.file_name().to_string_lossy());
+        }
+        fs::remove_dir_all(&previous_path)?;
+
+        // The world and server.properties are kept as-is; only make sure the EULA is still accepted
+        if !server_path.join("eula.txt").exists() {
+            self.generate_eula_file(server_path)?;
+        }
+
+        log::info!("=== Migration completed for: {} ===", server_name);
+        Ok(warnings)
+    }
+
+    /// Uninstalls the server's mod loader and runs it on the vanilla server JAR of the same
+    /// Minecraft version. The world, server.properties and the mods/plugins folders are kept;
+    /// vanilla simply ignores the latter.
+    pub async fn revert_to_vanilla(
+        &self,
+        server_name: &str,
+        server_path: &PathBuf,
+        current_loader: LoaderType,
+        minecraft_version: &str,
+    ) -> Result<Vec<String>> {
+        if current_loader == LoaderType::Vanilla {
+            return Err(anyhow!("Server {} already runs vanilla", server_name));
+        }
+
+        let mut warnings = Vec::new();
+        if matches!(current_loader, LoaderType::Fabric | LoaderType::Forge | LoaderType::NeoForge | LoaderType::Quilt) {
+            // Without scanning the world there is no telling which modded content it holds, so
+            // always assume the worst for modded loaders
+            warnings.push(format!(
+                "This world was played on {:?}; any modded blocks, items and entities in it will be \
+                 deleted when vanilla loads the chunks they are in. Back up the world first if you \
+                 may want to go back",
+                current_loader
+            ));
+        }
+
+        warnings.extend(self.migrate_loader(
+            server_name,
+            server_path,
+            current_loader,
+            LoaderType::Vanilla,
+            minecraft_version,
+            None,
+        ).await?);
+
+        for name in LOADER_LEFTOVERS {
+            let path = server_path.join(name);
+            if path.is_file() {
+                fs::remove_file(&path)?;
+                log::info!("Removed leftover loader file: {}", name);
+            }
+        }
+
+        // Forge/NeoForge installers log next to themselves as <installer>.jar.log
+        for entry in fs::read_dir(server_path)?.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            if file_name.ends_with("-self.jar.log") {
+                fs::remove_file(entry.path())?;
+                log::info!("Removed leftover loader file: {}", file_name);
+            }
+        }
+
+        Ok(warnings)
+    }
+
+    fn generate_eula_file(&self, server_path: &PathBuf) -> Result<()> {
+        let eula_path = server_path.join("eula.txt");
+        let eula_content = "# EULA accepted automatically by Allay\neula=true\n";
+        fs::write(eula_path, eula_content)?;
+        log::info!("Generated eula.txt");
+        Ok(())
+    }
+
+    fn generate_server_properties(&self, server_path: &PathBuf, server_name: &str) -> Result<()> {
+        let properties_path = server_path.join("server.properties");
+        
+        // Always generate/overwrite server.properties to ensure correct configuration
+        if properties_path.exists() {
+            log::info!("server.properties exists, forcing regeneration with Allay configuration");
+        } else {
+            log::info!("Creating new server.properties with Allay configuration");
+        }
+
+        let properties_manager = ServerPropertiesManager::new(properties_path);
+        
+        // Generate random RCON password
+        let rcon_password = generate_strong_rcon_password(16);
+        
+        // Create default properties and customize for Allay
+        let mut properties = ServerProperties::default();
+        properties.motd = format!("A Minecraft Server manage with §bAllay");
+        properties.level_name = "world".to_string();
+        properties.gamemode = "survival".to_string();
+        properties.difficulty = "easy".to_string();
+        properties.max_players = 20;
+        properties.online_mode = true;
+        properties.pvp = true;
+        properties.spawn_protection = 16;
+        properties.enable_command_block = true;
+        properties.white_list = false;
+        properties.server_port = 25565;
+        
+        // Enable RCON and Query by default with generated password
+        properties.enable_rcon = true;
+        properties.rcon_port = 25575;
+        properties.rcon_password = rcon_password;
+        properties.enable_query = true;
+        properties.query_port = 25565;
+        
+        properties_manager.save_properties(&properties).map_err(|e| anyhow!("Failed to save server.properties: {}", e))?;
+        log::info!("Generated server.properties with RCON enabled");
+        Ok(())
+    }
+}
+
+/// Keeps a server from being started until dropped; see UnifiedServerService::begin_maintenance
+pub struct MaintenanceGuard {
+    server_name: String,
+    maintenance: Arc<std::sync::Mutex<HashSet<String>>>,
+}
+
+impl Drop for MaintenanceGuard {
+    fn drop(&mut self) {
+        self.maintenance.lock().unwrap().remove(&self.server_name);
+    }
+}
+
+pub struct UnifiedServerService {
+    installer: ServerInstaller,
+    running_servers: Arc<Mutex<HashMap<String, ServerProcess>>>,
+    /// Stopped servers whose files are being restored or replaced without the service lock held
+    maintenance: Arc<std::sync::Mutex<HashSet<String>>>,
+    process_state: ProcessStateManager,
+    /// Placeholder listeners holding the game port of stopped servers with wake-on-demand
+    wake_listeners: Mutex<HashMap<String, tokio::task::JoinHandle<()>>>,
+    /// Kept between calls so CPU usage can be measured over the time since the last sample
+    usage_sampler: std::sync::Mutex<System>,
+}
+
+impl UnifiedServerService {
+    pub fn new() -> Result<Self> {
+        let cache_dir = PathBuf::from("storage/version_cache");
+        let jar_cache = JarCacheManager::new(cache_dir)?;
+        
+        Ok(Self {
+            installer: ServerInstaller { client: Client::new(), jar_cache, java_command: "java".to_string() },
+            running_servers: Arc::new(Mutex::new(HashMap::new())),
+            maintenance: Arc::new(std::sync::Mutex::new(HashSet::new())),
+            process_state: ProcessStateManager::new(PathBuf::from("storage/running_servers.json")),
+            wake_listeners: Mutex::new(HashMap::new()),
+            usage_sampler: std::sync::Mutex::new(System::new()),
+        })
+    }
+
+    /// A handle for downloads and setups that don't need the service to stay locked
+    pub fn installer(&self) -> ServerInstaller {
+        self.installer.clone()
+    }
+
+    /// Reserves a stopped server for long work on its files, such as restoring a backup, so the
+    /// work can run after the service lock is released. The server can't be started until the
+    /// guard is dropped. None when the server is running or already reserved.
+    pub async fn begin_maintenance(&self, server_name: &str) -> Option<MaintenanceGuard> {
+        if self.is_server_running(server_name).await {
+            return None;
+        }
+        if !self.maintenance.lock().unwrap().insert(server_name.to_string()) {
+            return None;
+        }
+        Some(MaintenanceGuard {
+            server_name: server_name.to_string(),
+            maintenance: Arc::clone(&self.maintenance),
+        })
+    }
+
+    /// Binds the game port of a stopped server and sends its name on `wake_sender` at the
+    /// first player trying to join. Server list pings and other connections are dropped
+    /// without waking it. The port is released before the name is sent, so the server can
+    /// bind it when started.
+    pub async fn arm_wake_listener(
+        &self,
+        server_name: &str,
+        port: u16,
+        wake_sender: tokio::sync::mpsc::UnboundedSender<String>,
+    ) -> Result<()> {
+        self.disarm_wake_listener(server_name).await;
+
+        let listener = tokio::net::TcpListener::bind(("0.0.0.0", port)).await
+            .map_err(|e| anyhow!("Failed to listen on port {} for {}: {}", port, server_name, e))?;
+
+        let name = server_name.to_string();
+        let task = tokio::spawn(async move {
+            loop {
+                let (mut socket, address) = match listener.accept().await {
+                    Ok(connection) => connection,
+                    Err(e) => {
+                        log::warn!("Wake listener for {} failed: {}", name, e);
+                        return;
+                    }
+                };
+                let next_state = tokio::time::timeout(HANDSHAKE_TIMEOUT, read_handshake_next_state(&mut socket))
+                    .await
+                    .ok()
+                    .flatten();
+                // The socket is dropped either way; a joining client has to reconnect once the server is up
+                if next_state == Some(LOGIN_NEXT_STATE) {
+                    log::info!("Login from {} to sleeping server {}, waking it", address, name);
+                    break;
+                }
+                log::debug!("Ignoring a connection from {} to sleeping server {} that is not a login", address, name);
+            }
+            drop(listener);
+            let _ = wake_sender.send(name);
+        });
+
+        self.wake_listeners.lock().await.insert(server_name.to_string(), task);
+        log::info!("Wake listener armed for {} on port {}", server_name, port);
         Ok(())
     }
 
-    /// Starts a server using the strategy pattern
-    pub async fn start_server(&self, server_name: &str, server_path: &PathBuf, loader: LoaderType, memory_mb: u32) -> Result<()> {
+    /// Stops the wake listener of a server, if any, and waits until its port is released
+    pub async fn disarm_wake_listener(&self, server_name: &str) {
+        let task = self.wake_listeners.lock().await.remove(server_name);
+        if let Some(task) = task {
+            task.abort();
+            let _ = task.await;
+            log::debug!("Wake listener disarmed for {}", server_name);
+        }
+    }
+
+    /// Whether a wake listener is currently holding the port of a server
+    pub async fn is_wake_listener_armed(&self, server_name: &str) -> bool {
+        self.wake_listeners.lock().await
+            .get(server_name)
+            .map(|task| !task.is_finished())
+            .unwrap_or(false)
+    }
+
+    /// Starts a server using the strategy pattern. A `detached` server runs in its own process
+    /// group (session on Unix) without pipes to Allay, so it keeps running if Allay is closed
+    /// or killed. The trade-off is that Allay cannot write to its console: it is stopped via
+    /// RCON, or killed by PID, and picked up again by `reattach_servers` on the next launch.
+    pub async fn start_server(
+        &self,
+        server_name: &str,
+        server_path: &PathBuf,
+        loader: LoaderType,
+        options: &LaunchOptions,
+    ) -> Result<()> {
+        let (memory_mb, detached) = (options.memory_mb, options.detached);
+        let (env_vars, java_command) = (&options.env_vars, options.java_command.as_str());
+        
+        if self.maintenance.lock().unwrap().contains(server_name) {
+            return Err(anyhow!("Server {} can't start while its files are being restored or updated", server_name));
+        }
+        
+        // Hand the game port back from the wake listener before the server tries to bind it
+        self.disarm_wake_listener(server_name).await;
+        
         let mut servers = self.running_servers.lock().await;
         
+        // A re-attached process may have exited since; its slot can be reused
+        if let Some(ServerProcess::Attached(pid)) = servers.get(server_name) {
+            if !is_pid_alive(*pid) {
+                servers.remove(server_name);
+            }
+        }
+        
         if servers.contains_key(server_name) {
             return Err(anyhow!("Server {} is already running", server_name));
         }
 
         let strategy = get_strategy(&loader);
         
-        // Convert MB to GB for JVM args, ensure minimum 1GB
-        let memory_gb = std::cmp::max(1, memory_mb / 1024);
-        let min_memory_gb = std::cmp::max(1, memory_gb / 2); // Half of max memory for initial heap
+        // Heap flags are passed in MB so the configured memory is honored exactly
+        let (max_memory_mb, min_memory_mb) = heap_sizes_mb(memory_mb);
         
-        let command_args = strategy.build_start_command(server_path, memory_gb, min_memory_gb)?;
+        // The configured memory is authoritative at every start, including script-based loaders
+        strategy.prepare_launch(server_path, max_memory_mb, min_memory_mb)?;
+        let command_args = strategy.build_start_command(server_path, max_memory_mb, min_memory_mb)?;
         
-        println!("Starting server: {} with command: {:?}", server_name, command_args);
+        log::info!("Starting server: {} with command: {:?}", server_name, command_args);
         
-        // Determine the command and arguments based on the first element
-        let (command, args) = if command_args.len() > 0 {
-            let first_arg = &command_args[0];
-            
-            if first_arg == "cmd" || first_arg == "bash" || first_arg == "./run.sh" || first_arg.ends_with(".sh") || first_arg.ends_with(".bat") {
-                // This is a script command, use the first argument as the command
-                if first_arg == "cmd" || first_arg == "bash" {
-                    // Windows: cmd /c run.bat or Unix: bash ./run.sh
-                    (first_arg.clone(), command_args[1..].to_vec())
-                } else {
-                    // Direct script execution: ./run.sh
-                    (first_arg.clone(), command_args[1..].to_vec())
-                }
-            } else {
-                // This is a Java command
-                ("java".to_string(), command_args)
-            }
+        let (command, args) = split_launch_command(command_args, java_command)?;
+        let args = apply_gui_mode(&command, java_command, args, options.nogui);
+        
+        let mut process_command = Command::new(&command);
+        process_command.args(&args).current_dir(server_path).envs(env_vars);
+        if let Some(path) = path_with_java(java_command, env_vars) {
+            // Run scripts call plain `java`, so put the chosen runtime first in their PATH
+            process_command.env("PATH", path);
+        }
+        let console_log = ConsoleLog::create(server_path)
+            .map_err(|e| log::warn!("Console output of {} will not be saved: {}", server_name, e))
+            .ok();
+        if detached {
+            // Nothing would drain pipes once Allay exits, so output goes straight to the console
+            // log (without rotation) or nowhere
+            let console_file = console_log.as_ref().and_then(|log| log.file().try_clone().ok());
+            let stderr_file = console_file.as_ref().and_then(|file| file.try_clone().ok());
+            process_command
+                .stdin(Stdio::null())
+                .stdout(console_file.map(Stdio::from).unwrap_or_else(Stdio::null))
+                .stderr(stderr_file.map(Stdio::from).unwrap_or_else(Stdio::null));
+            detach_from_parent(&mut process_command);
         } else {
-            return Err(anyhow!("No command arguments provided"));
-        };
+            process_command.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped());
+        }
         
-        let child = Command::new(&command)
-            .args(&args)
-            .current_dir(server_path)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
+        let mut child = process_command
             .spawn()
             .map_err(|e| {
                 if e.kind() == std::io::ErrorKind::NotFound {
                     match command.as_str() {
                         "java" => anyhow!("Java is not installed or not found in PATH. Please install Java to run Minecraft servers."),
+                        _ if command == java_command => anyhow!("Java was not found at {}", java_command),
                         "bash" => anyhow!("Bash is not available or not found in PATH. Please install bash or use a different shell."),
                         "cmd" => anyhow!("Command Prompt (cmd) is not available. This should not happen on Windows."),
                         _ => anyhow!("{} is not available or not found in PATH. Error: {}", command, e)
@@ -164,42 +768,229 @@ impl UnifiedServerService {
                 }
             })?;
 
-        servers.insert(server_name.to_string(), child);
-        println!("Server {} started successfully", server_name);
+        if !detached {
+            match console_log {
+                Some(console_log) => capture_output(console_log, server_name, child.stdout.take(), child.stderr.take()),
+                None => {
+                    // Still drain the pipes; a server blocked on a full pipe stops ticking
+                    capture_output_to_sink(child.stdout.take());
+                    capture_output_to_sink(child.stderr.take());
+                }
+            }
+        }
+
+        let pid = child.id();
+        if let Err(e) = self.process_state.track_process(TrackedProcess {
+            server_name: server_name.to_string(),
+            pid,
+            started_at: process_start_time(pid).unwrap_or_else(|| chrono::Utc::now().timestamp() as u64),
+        }) {
+            log::warn!("Failed to persist process state for {}: {}", server_name, e);
+        }
+
+        if detached {
+            servers.insert(server_name.to_string(), ServerProcess::Detached(child));
+            log::info!("Server {} started detached (pid {})", server_name, pid);
+        } else {
+            servers.insert(server_name.to_string(), ServerProcess::Owned(child));
+            log::info!("Server {} started successfully (pid {})", server_name, pid);
+        }
         
         Ok(())
     }
 
+    /// Resolves the exact command `start_server` would run, for display and debugging
+    pub fn resolve_launch_command(
+        &self,
+        server_path: &PathBuf,
+        loader: LoaderType,
+        memory_mb: u32,
+        java_command: &str,
+        nogui: bool,
+    ) -> Result<LaunchCommand> {
+        let strategy = get_strategy(&loader);
+        let (max_memory_mb, min_memory_mb) = heap_sizes_mb(memory_mb);
+        let command_args = strategy.build_start_command(server_path, max_memory_mb, min_memory_mb)?;
+        let (command, args) = split_launch_command(command_args, java_command)?;
+        let args = apply_gui_mode(&command, java_command, args, nogui);
+        let uses_run_script = command != java_command;
+
+        let mut argv = vec![command];
+        argv.extend(args);
+        Ok(LaunchCommand {
+            argv,
+            working_dir: server_path.to_string_lossy().to_string(),
+            uses_run_script,
+        })
+    }
+
     /// Stops a running server
     pub async fn stop_server(&self, server_name: &str) -> Result<()> {
+        self.stop_server_and_wait(server_name, Duration::from_secs(60)).await
+    }
+
+    /// Sends the stop commands to a running server and waits until the process has actually exited,
+    /// killing it if it does not shut down within the timeout
+    pub async fn stop_server_and_wait(&self, server_name: &str, timeout: Duration) -> Result<()> {
+        // Take the process out of the map so the lock isn't held while waiting
+        let process = {
+            let mut servers = self.running_servers.lock().await;
+            servers.remove(server_name)
+                .ok_or_else(|| anyhow!("Server {} is not running", server_name))?
+        };
+        let _ = self.process_state.untrack_process(server_name);
+
+        let mut child = match process {
+            ServerProcess::Owned(child) => child,
+            ServerProcess::Attached(pid) => return stop_attached_process(server_name, pid, timeout).await,
+            ServerProcess::Detached(mut child) => {
+                let result = stop_attached_process(server_name, child.id(), timeout).await;
+                let _ = child.wait();
+                return result;
+            }
+        };
+
+        // Send the stop sequence to the server console
+        if let Some(stdin) = child.stdin.as_mut() {
+            use std::io::Write;
+            for command in stop_commands(server_name) {
+                if let Err(e) = writeln!(stdin, "{}", command) {
+                    log::warn!("Failed to send '{}' to {}: {}", command, server_name, e);
+                    break;
+                }
+            }
+        }
+
+        // Wait for graceful shutdown
+        let started = Instant::now();
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    log::info!("Server {} stopped gracefully ({})", server_name, status);
+                    return Ok(());
+                }
+                Ok(None) if started.elapsed() < timeout => {
+                    tokio::time::sleep(Duration::from_millis(500)).await;
+                }
+                _ => {
+                    log::warn!("Force killing server {}", server_name);
+                    child.kill()?;
+                    let _ = child.wait();
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    /// Forcefully kills a running server without attempting a graceful shutdown
+    pub async fn kill_server(&self, server_name: &str) -> Result<()> {
         let mut servers = self.running_servers.lock().await;
         
-        if let Some(mut child) = servers.remove(server_name) {
-            // Send stop command to server
-            if let Some(stdin) = child.stdin.as_mut() {
-                use std::io::Write;
-                writeln!(stdin, "stop")?;
+        match servers.remove(server_name) {
+            Some(ServerProcess::Owned(mut child)) | Some(ServerProcess::Detached(mut child)) => {
+                child.kill()?;
+                // Reap the process so it doesn't linger as a zombie
+                let _ = child.wait();
             }
-            
-            // Wait for graceful shutdown
-            match child.wait() {
-                Ok(_) => println!("Server {} stopped gracefully", server_name),
-                Err(_) => {
-                    println!("Force killing server {}", server_name);
-                    child.kill()?;
+            Some(ServerProcess::Attached(pid)) => {
+                if !kill_pid(pid) && is_pid_alive(pid) {
+                    return Err(anyhow!("Failed to kill server {} (pid {})", server_name, pid));
                 }
             }
-            
-            Ok(())
-        } else {
-            Err(anyhow!("Server {} is not running", server_name))
+            None => return Err(anyhow!("Server {} is not running", server_name)),
         }
+
+        let _ = self.process_state.untrack_process(server_name);
+        log::warn!("Server {} was force killed", server_name);
+        Ok(())
     }
 
     /// Check if a server is running
     pub async fn is_server_running(&self, server_name: &str) -> bool {
         let servers = self.running_servers.lock().await;
-        servers.contains_key(server_name)
+        match servers.get(server_name) {
+            Some(ServerProcess::Owned(_)) | Some(ServerProcess::Detached(_)) => true,
+            Some(ServerProcess::Attached(pid)) => is_pid_alive(*pid),
+            None => false,
+        }
+    }
+
+    /// Forgets a server whose process has exited on its own. Returns None while it is still
+    /// running (or isn't tracked), otherwise whether the exit looks like a crash. Stops take the
+    /// process out of the map before waiting on it, so an exit found here was never requested.
+    pub async fn reap_exited_server(&self, server_name: &str) -> Option<bool> {
+        let mut servers = self.running_servers.lock().await;
+        let crashed = match servers.get_mut(server_name)? {
+            ServerProcess::Owned(child) | ServerProcess::Detached(child) => !child.try_wait().ok()??.success(),
+            // The exit code of a process we didn't start can't be read
+            ServerProcess::Attached(pid) if !is_pid_alive(*pid) => true,
+            ServerProcess::Attached(_) => return None,
+        };
+        servers.remove(server_name);
+        drop(servers);
+        let _ = self.process_state.untrack_process(server_name);
+        Some(crashed)
+    }
+
+    /// Seconds since the server process started, or None if it isn't running. The OS start
+    /// time of the PID is the source of truth, so this stays correct across a re-attach.
+    pub async fn get_uptime(&self, server_name: &str) -> Option<u64> {
+        let pid = self.running_servers.lock().await.get(server_name)?.pid();
+        let started_at = process_start_time(pid)?;
+        Some((chrono::Utc::now().timestamp() as u64).saturating_sub(started_at))
+    }
+
+    /// CPU (percent of one core, since the previous call) and resident memory of the server
+    /// process, or None if it isn't running
+    pub async fn get_resource_usage(&self, server_name: &str) -> Option<(f32, u64)> {
+        let pid = Pid::from_u32(self.running_servers.lock().await.get(server_name)?.pid());
+        let mut system = self.usage_sampler.lock().ok()?;
+        if !system.refresh_process(pid) {
+            return None;
+        }
+        system.process(pid).map(|process| (process.cpu_usage(), process.memory()))
+    }
+
+    /// Re-registers servers that were still running when Allay last closed.
+    /// Processes that are gone (or whose PID now belongs to another program) are reported as crashed.
+    pub async fn reattach_servers(&self) -> ReattachReport {
+        let mut report = ReattachReport::default();
+        let tracked = match self.process_state.load_processes() {
+            Ok(tracked) => tracked,
+            Err(e) => {
+                log::warn!("Failed to load process state: {}", e);
+                return report;
+            }
+        };
+
+        let mut servers = self.running_servers.lock().await;
+        let mut still_running = Vec::new();
+
+        for process in tracked {
+            if servers.contains_key(&process.server_name) {
+                continue;
+            }
+
+            let same_process = process_start_time(process.pid)
+                .map(|started| started.abs_diff(process.started_at) <= START_TIME_TOLERANCE_SECS)
+                .unwrap_or(false);
+
+            if same_process {
+                log::info!("Re-attached to {} (pid {})", process.server_name, process.pid);
+                servers.insert(process.server_name.clone(), ServerProcess::Attached(process.pid));
+                report.reattached.push(process.server_name.clone());
+                still_running.push(process);
+            } else {
+                log::warn!("{} is no longer running (pid {}), marking as crashed", process.server_name, process.pid);
+                report.crashed.push(process.server_name);
+            }
+        }
+
+        if let Err(e) = self.process_state.save_processes(&still_running) {
+            log::warn!("Failed to save process state: {}", e);
+        }
+
+        report
     }
 
     /// Get list of all running servers
@@ -208,67 +999,339 @@ impl UnifiedServerService {
         servers.keys().cloned().collect()
     }
 
-    fn generate_eula_file(&self, server_path: &PathBuf) -> Result<()> {
-        let eula_path = server_path.join("eula.txt");
-        let eula_content = "# EULA accepted automatically by Allay\neula=true\n";
-        fs::write(eula_path, eula_content)?;
-        println!("Generated eula.txt");
-        Ok(())
+}
+
+/// Checks that a loader version targets the given Minecraft version, when the version string encodes it
+fn loader_version_matches_minecraft(loader: &LoaderType, loader_version: &str, minecraft_version: &str) -> bool {
+    match loader {
+        LoaderType::Forge => {
+            let clean_version = loader_version.strip_prefix("forge-").unwrap_or(loader_version);
+            clean_version.split('-').next() == Some(minecraft_version)
+        }
+        LoaderType::NeoForge => {
+            let clean_version = loader_version.strip_prefix("neoforge-").unwrap_or(loader_version);
+            if clean_version.starts_with("1.") {
+                // Early NeoForge releases use the Forge "<mc>-<version>" scheme
+                return clean_version.split('-').next() == Some(minecraft_version);
+            }
+
+            // "20.4.237" targets 1.20.4, "21.0.10" targets 1.21
+            let parts: Vec<&str> = clean_version.split('.').collect();
+            if parts.len() < 2 {
+                return true;
+            }
+            let expected = if parts[1] == "0" {
+                format!("1.{}", parts[0])
+            } else {
+                format!("1.{}.{}", parts[0], parts[1])
+            };
+            expected == minecraft_version
+        }
+        // Fabric and Quilt loader versions are independent of the Minecraft version
+        _ => true,
     }
+}
 
-    /// Generates a random RCON password with format "allay_XXXX" (avoiding # to prevent escape issues)
-    fn generate_rcon_password(&self) -> String {
-        let mut rng = rand::thread_rng();
-        let mut password = String::from("allay_");
-        
-        // Generate 4 random characters (alphanumeric only to avoid escape issues)
-        let chars = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
-        for _ in 0..4 {
-            let idx = rng.gen_range(0..chars.len());
-            password.push(chars.chars().nth(idx).unwrap());
+/// Describes migration directions that can lose data or leave the world in an unexpected state
+pub fn migration_warnings(server_path: &Path, current_loader: &LoaderType, new_loader: &LoaderType) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    let dir_has_files = |name: &str| {
+        fs::read_dir(server_path.join(name))
+            .map(|mut entries| entries.next().is_some())
+            .unwrap_or(false)
+    };
+
+    let is_modded = |loader: &LoaderType| {
+        matches!(loader, LoaderType::Fabric | LoaderType::Forge | LoaderType::NeoForge | LoaderType::Quilt)
+    };
+
+    // Paper and Spigot share the Bukkit world layout and plugin folder
+    let is_bukkit = |loader: &LoaderType| matches!(loader, LoaderType::Paper | LoaderType::Spigot);
+
+    if current_loader == new_loader {
+        return warnings;
+    }
+
+    if is_bukkit(current_loader) && !is_bukkit(new_loader) {
+        warnings.push(format!(
+            "{:?} stores the Nether and End in separate world_nether/world_the_end folders; \
+             other loaders will not read them and will generate new dimensions instead",
+            current_loader
+        ));
+        if dir_has_files("plugins") {
+            warnings.push("Installed plugins will not be loaded by the new loader".to_string());
         }
-        
-        password
     }
 
-    fn generate_server_properties(&self, server_path: &PathBuf, server_name: &str) -> Result<()> {
-        let properties_path = server_path.join("server.properties");
-        
-        // Always generate/overwrite server.properties to ensure correct configuration
-        if properties_path.exists() {
-            println!("server.properties exists, forcing regeneration with Allay configuration");
+    if is_bukkit(new_loader) && !is_bukkit(current_loader) {
+        warnings.push(format!(
+            "{:?} will split the Nether and End into separate folders on first start; \
+             this conversion cannot be undone automatically",
+            new_loader
+        ));
+    }
+
+    if is_modded(current_loader) && dir_has_files("mods") {
+        let mods_survive = matches!(
+            (current_loader, new_loader),
+            (LoaderType::Fabric, LoaderType::Quilt) | (LoaderType::Forge, LoaderType::NeoForge)
+        );
+        if mods_survive {
+            warnings.push("Some installed mods may not be compatible with the new loader".to_string());
         } else {
-            println!("Creating new server.properties with Allay configuration");
+            warnings.push(
+                "Blocks, items and entities added by installed mods will be removed from the world".to_string()
+            );
         }
+    }
 
-        let properties_manager = ServerPropertiesManager::new(properties_path);
-        
-        // Generate random RCON password
-        let rcon_password = self.generate_rcon_password();
-        
-        // Create default properties and customize for Allay
-        let mut properties = ServerProperties::default();
-        properties.motd = format!("A Minecraft Server manage with §bAllay");
-        properties.level_name = "world".to_string();
-        properties.gamemode = "survival".to_string();
-        properties.difficulty = "easy".to_string();
-        properties.max_players = 20;
-        properties.online_mode = true;
-        properties.pvp = true;
-        properties.spawn_protection = 16;
-        properties.enable_command_block = true;
-        properties.white_list = false;
-        properties.server_port = 25565;
-        
-        // Enable RCON and Query by default with generated password
-        properties.enable_rcon = true;
-        properties.rcon_port = 25575;
-        properties.rcon_password = rcon_password.clone();
-        properties.enable_query = true;
-        properties.query_port = 25565;
-        
-        properties_manager.save_properties(&properties).map_err(|e| anyhow!("Failed to save server.properties: {}", e))?;
-        println!("Generated server.properties with RCON enabled (password: {})", rcon_password);
-        Ok(())
+    warnings
+}
+
+/// Splits strategy output into the program to run and its arguments: run scripts are launched
+/// through their interpreter, everything else is a list of arguments for `java_command`
+fn split_launch_command(command_args: Vec<String>, java_command: &str) -> Result<(String, Vec<String>)> {
+    let first_arg = command_args.first().ok_or_else(|| anyhow!("No command arguments provided"))?;
+
+    if first_arg == "cmd" || first_arg == "bash" || first_arg.ends_with(".sh") || first_arg.ends_with(".bat") {
+        // Windows: cmd /c run.bat, Unix: bash ./run.sh, or a script executed directly
+        Ok((first_arg.clone(), command_args[1..].to_vec()))
+    } else {
+        Ok((java_command.to_string(), command_args))
+    }
+}
+
+fn capture_output_to_sink(output: Option<impl std::io::Read + Send + 'static>) {
+    if let Some(mut output) = output {
+        std::thread::spawn(move || std::io::copy(&mut output, &mut std::io::sink()));
+    }
+}
+
+/// Strategies always pass "nogui"; drop it when the GUI is wanted. Run scripts build their own
+/// java command line, so they are left alone.
+fn apply_gui_mode(command: &str, java_command: &str, args: Vec<String>, nogui: bool) -> Vec<String> {
+    if nogui || command != java_command {
+        return args;
+    }
+    args.into_iter().filter(|arg| arg != "nogui").collect()
+}
+
+/// PATH for the server process with the folder of `java_command` in front, or None when the
+/// server uses the `java` already in PATH
+fn path_with_java(java_command: &str, env_vars: &HashMap<String, String>) -> Option<std::ffi::OsString> {
+    let java_dir = Path::new(java_command).parent().filter(|dir| !dir.as_os_str().is_empty())?;
+    let current = env_vars
+        .get("PATH")
+        .map(std::ffi::OsString::from)
+        .or_else(|| std::env::var_os("PATH"))
+        .unwrap_or_default();
+
+    let mut dirs = vec![java_dir.to_path_buf()];
+    dirs.extend(std::env::split_paths(&current));
+    std::env::join_paths(dirs).ok()
+}
+
+/// Warns when the world was last saved by a newer Minecraft version than the one being installed
+fn world_downgrade_warning(server_path: &Path, minecraft_version: &str) -> Option<String> {
+    let world_version = read_world_version(&world_folder(server_path)).ok()?;
+    let world_name = world_version.name?;
+    match compare_release_versions(&world_name, minecraft_version)? {
+        std::cmp::Ordering::Greater => Some(format!(
+            "The world was last saved with Minecraft {}; opening it with {} is a downgrade and may corrupt it",
+            world_name, minecraft_version
+        )),
+        _ => None,
+    }
+}
+
+/// Normalizes a Paper loader version to the pinned build number, or "" for the latest build
+fn paper_build_version(loader_version: Option<&str>) -> String {
+    loader_version
+        .and_then(PaperStrategy::pinned_build)
+        .map(|build| build.to_string())
+        .unwrap_or_default()
+}
+
+/// Spawns the command outside Allay's process group so signals aimed at Allay
+/// (terminal hangup, Ctrl+C, a force-quit of the group) don't reach the server
+fn detach_from_parent(command: &mut Command) {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // SAFETY: setsid is async-signal-safe and only affects the forked child
+        unsafe {
+            command.pre_exec(|| {
+                if libc::setsid() == -1 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+        command.creation_flags(CREATE_NEW_PROCESS_GROUP);
+    }
+}
+
+/// Returns the start time of a live process, or None if it has exited
+fn process_start_time(pid: u32) -> Option<u64> {
+    let mut system = System::new();
+    let pid = Pid::from_u32(pid);
+    if !system.refresh_process(pid) {
+        return None;
+    }
+    system
+        .process(pid)
+        .filter(|process| process.status() != ProcessStatus::Zombie)
+        .map(|process| process.start_time())
+}
+
+/// Undoes a migration whose setup failed: removes what the new loader installed and moves the
+/// old loader's files back from `previous_path`
+fn restore_previous_loader(server_path: &Path, previous_path: &Path, new_strategy: &dyn ModLoaderStrategy) -> Result<()> {
+    for entry in fs::read_dir(server_path)? {
+        let entry = entry?;
+        if new_strategy.is_loader_file(&entry.file_name().to_string_lossy()) {
+            let path = entry.path();
+            if path.is_dir() {
+                fs::remove_dir_all(&path)?;
+            } else {
+                fs::remove_file(&path)?;
+            }
+        }
+    }
+    for entry in fs::read_dir(previous_path)? {
+        let entry = entry?;
+        fs::rename(entry.path(), server_path.join(entry.file_name()))?;
+    }
+    fs::remove_dir(previous_path)?;
+    Ok(())
+}
+
+fn is_pid_alive(pid: u32) -> bool {
+    process_start_time(pid).is_some()
+}
+
+fn kill_pid(pid: u32) -> bool {
+    let mut system = System::new();
+    let pid = Pid::from_u32(pid);
+    system.refresh_process(pid);
+    system.process(pid).map(|process| process.kill()).unwrap_or(false)
+}
+
+/// The commands that shut a server down, "stop" unless the user configured a sequence
+fn stop_commands(server_name: &str) -> Vec<String> {
+    ServerFileManager::new(PathBuf::from("storage/server_config.json")).get_stop_commands(server_name)
+}
+
+/// Reads a protocol VarInt from the start of `data`, returning it and the number of bytes used
+fn read_varint(data: &[u8]) -> Option<(i32, usize)> {
+    let mut value = 0i32;
+    for (i, byte) in data.iter().take(5).enumerate() {
+        value |= ((byte & 0x7f) as i32) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Some((value, i + 1));
+        }
     }
-}
\ No newline at end of file
+    None
+}
+
+/// Next state from the body of a handshake packet: id 0, protocol version, server address,
+/// port, next state
+fn handshake_next_state(body: &[u8]) -> Option<i32> {
+    let (packet_id, mut pos) = read_varint(body)?;
+    if packet_id != 0 {
+        return None;
+    }
+    let (_protocol_version, used) = read_varint(&body[pos..])?;
+    pos += used;
+    let (address_len, used) = read_varint(&body[pos..])?;
+    pos += used;
+    // Skip the address and the two byte port
+    pos = pos.checked_add(usize::try_from(address_len).ok()?)?.checked_add(2)?;
+    read_varint(body.get(pos..)?).map(|(next_state, _)| next_state)
+}
+
+/// Reads the handshake a client opens with; None for anything else, such as the pre-1.7 ping
+async fn read_handshake_next_state(socket: &mut tokio::net::TcpStream) -> Option<i32> {
+    use tokio::io::AsyncReadExt;
+
+    let mut header = Vec::with_capacity(5);
+    let length = loop {
+        header.push(socket.read_u8().await.ok()?);
+        if let Some((length, _)) = read_varint(&header) {
+            break length;
+        }
+        if header.len() == 5 {
+            return None;
+        }
+    };
+    let length = usize::try_from(length).ok().filter(|len| (1..=MAX_HANDSHAKE_LEN).contains(len))?;
+    let mut body = vec![0; length];
+    socket.read_exact(&mut body).await.ok()?;
+    handshake_next_state(&body)
+}
+
+/// Stops a re-attached server through RCON since we don't own its stdin,
+/// killing it if it has not exited within the timeout
+async fn stop_attached_process(server_name: &str, pid: u32, timeout: Duration) -> Result<()> {
+    let rcon_manager = crate::services::rcon_global::get_rcon_manager();
+    for command in stop_commands(server_name) {
+        if let Err(e) = rcon_manager.execute_command(server_name, &command) {
+            log::warn!("Failed to send '{}' over RCON to {}: {}", command, server_name, e);
+        }
+    }
+
+    let started = Instant::now();
+    while is_pid_alive(pid) {
+        if started.elapsed() >= timeout {
+            log::warn!("Force killing server {}", server_name);
+            kill_pid(pid);
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    log::info!("Server {} stopped gracefully", server_name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn handshake(address: &str, next_state: u8) -> Vec<u8> {
+        let mut body = vec![0x00, 0xfd, 0x05]; // packet id, protocol 765
+        body.push(address.len() as u8);
+        body.extend_from_slice(address.as_bytes());
+        body.extend_from_slice(&25565u16.to_be_bytes());
+        body.push(next_state);
+        body
+    }
+
+    #[test]
+    fn reads_varints() {
+        assert_eq!(read_varint(&[0x00]), Some((0, 1)));
+        assert_eq!(read_varint(&[0xfd, 0x05, 0x10]), Some((765, 2)));
+        assert_eq!(read_varint(&[0xff, 0xff, 0xff, 0xff, 0x0f]), Some((-1, 5)));
+        assert_eq!(read_varint(&[0x80, 0x80]), None);
+        assert_eq!(read_varint(&[0x80; 6]), None);
+    }
+
+    #[test]
+    fn tells_logins_from_status_pings() {
+        assert_eq!(handshake_next_state(&handshake("play.example.com", 2)), Some(LOGIN_NEXT_STATE));
+        assert_eq!(handshake_next_state(&handshake("localhost", 1)), Some(1));
+
+        let login = handshake("localhost", 2);
+        assert_eq!(handshake_next_state(&login[..login.len() - 1]), None);
+        let mut other_packet = login.clone();
+        other_packet[0] = 0x01;
+        assert_eq!(handshake_next_state(&other_packet), None);
+        assert_eq!(handshake_next_state(&[0xfe, 0x01, 0xfa]), None);
+    }
+}
@@ -0,0 +1,182 @@
+use anyhow::Result;
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::path::Path;
+use crate::models::version::{LoaderType, VersionResponse};
+use crate::services::http_client::HttpClient;
+use crate::services::paper_strategy::PaperStrategy;
+use crate::services::version_service::VersionService;
+
+/// A Minecraft version with the loader version (or Paper build) running on it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServerVersion {
+    pub minecraft_version: String,
+    /// None for Vanilla and Spigot, which have no loader version of their own
+    pub loader_version: Option<String>,
+}
+
+/// How a server's installed version compares with the newest one available for its loader
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateCheck {
+    pub current: ServerVersion,
+    /// The newest loader version for the current Minecraft version, or the newest Minecraft
+    /// version when the loader is already up to date
+    pub latest: ServerVersion,
+    pub update_available: bool,
+    /// `latest` moves to another Minecraft version; worlds may not load in older versions again
+    pub is_major_change: bool,
+    /// Newest Minecraft version the loader supports, when newer than the current one
+    pub newer_minecraft_version: Option<String>,
+}
+
+/// Orders versions like "1.20.4" or "47.2.0" by their numeric parts, so "1.21" > "1.20.4"
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |version: &str| -> Vec<u32> {
+        version
+            .split(|c: char| !c.is_ascii_digit())
+            .filter_map(|part| part.parse().ok())
+            .collect()
+    };
+    parts(a).cmp(&parts(b))
+}
+
+/// Plain releases only; snapshots ("24w14a") and pre-releases ("1.21-pre1") are never offered
+fn is_release(minecraft_version: &str) -> bool {
+    !minecraft_version.is_empty() && minecraft_version.chars().all(|c| c.is_ascii_digit() || c == '.')
+}
+
+/// The bare loader version from what instances store, which is either the number itself or a
+/// version id such as "fabric-0.15.11-1.20.4", "forge-1.20.1-47.2.0" or "neoforge-21.0.167"
+fn loader_version_number(loader: &LoaderType, minecraft_version: &str, stored: &str) -> Option<String> {
+    let stored = stored.trim();
+    let number = match loader {
+        LoaderType::Vanilla | LoaderType::Spigot | LoaderType::Paper => return None,
+        LoaderType::Fabric | LoaderType::Quilt => {
+            let prefix = if *loader == LoaderType::Fabric { "fabric-" } else { "quilt-" };
+            match stored.strip_prefix(prefix) {
+                Some(rest) => rest.split('-').next().unwrap_or(rest),
+                None => stored,
+            }
+        }
+        LoaderType::Forge => {
+            let version = stored.strip_prefix("forge-").unwrap_or(stored);
+            version.strip_prefix(&format!("{}-", minecraft_version)).unwrap_or(version)
+        }
+        LoaderType::NeoForge => stored.strip_prefix("neoforge-").unwrap_or(stored),
+    };
+    (!number.is_empty()).then(|| number.to_string())
+}
+
+/// The newest loader version listed for one Minecraft version
+fn newest_loader_version(loader: &LoaderType, minecraft_version: &str, response: &VersionResponse) -> Option<String> {
+    match loader {
+        // Listed newest first, with the newest stable loader as the recommended one
+        LoaderType::Fabric | LoaderType::Quilt => response
+            .recommended
+            .as_ref()
+            .or(response.latest.as_ref())
+            .and_then(|version| loader_version_number(loader, minecraft_version, &version.id)),
+        _ => response
+            .versions
+            .iter()
+            .filter_map(|version| loader_version_number(loader, minecraft_version, &version.id))
+            .max_by(|a, b| compare_versions(a, b)),
+    }
+}
+
+/// The newest release the loader supports at all
+fn newest_minecraft_version(response: &VersionResponse) -> Option<String> {
+    response
+        .versions
+        .iter()
+        .map(|version| version.minecraft_version.clone().unwrap_or_else(|| version.id.clone()))
+        .filter(|version| is_release(version))
+        .max_by(|a, b| compare_versions(a, b))
+}
+
+/// Compares a server's Minecraft and loader versions (or Paper build) with the newest ones
+/// available. A newer loader for the same Minecraft version is preferred over a Minecraft
+/// upgrade, which is reported as a major change.
+pub async fn check_up_to_date(
+    versions: &VersionService,
+    client: &dyn HttpClient,
+    server_path: &Path,
+    loader: LoaderType,
+    minecraft_version: &str,
+    stored_loader_version: &str,
+) -> Result<UpdateCheck> {
+    let (installed_loader, newest_loader) = if loader == LoaderType::Paper {
+        let info = PaperStrategy::check_for_update(client, server_path, minecraft_version, stored_loader_version).await?;
+        (info.installed_build.map(|build| build.to_string()), Some(info.latest_build.to_string()))
+    } else if let Some(installed) = loader_version_number(&loader, minecraft_version, stored_loader_version) {
+        let response = versions
+            .get_versions_for_minecraft(loader.clone(), Some(minecraft_version.to_string()))
+            .await?;
+        (Some(installed), newest_loader_version(&loader, minecraft_version, &response))
+    } else {
+        (None, None)
+    };
+
+    let newer_minecraft_version = newest_minecraft_version(&versions.get_versions(loader.clone()).await?)
+        .filter(|newest| compare_versions(newest, minecraft_version) == Ordering::Greater);
+
+    let current = ServerVersion {
+        minecraft_version: minecraft_version.to_string(),
+        loader_version: installed_loader.clone(),
+    };
+    let loader_update = match (&installed_loader, &newest_loader) {
+        (Some(installed), Some(newest)) => compare_versions(newest, installed) == Ordering::Greater,
+        // An unpinned Paper build that was never recorded can't be compared; assume it's old
+        (None, Some(_)) => loader == LoaderType::Paper,
+        _ => false,
+    };
+
+    let (latest, is_major_change) = if loader_update {
+        let latest = ServerVersion {
+            minecraft_version: minecraft_version.to_string(),
+            loader_version: newest_loader,
+        };
+        (latest, false)
+    } else if let Some(newer) = &newer_minecraft_version {
+        // The loader version for another Minecraft version isn't known until it is picked
+        let latest = ServerVersion {
+            minecraft_version: newer.clone(),
+            loader_version: None,
+        };
+        (latest, true)
+    } else {
+        (current.clone(), false)
+    };
+
+    Ok(UpdateCheck {
+        update_available: latest != current,
+        current,
+        latest,
+        is_major_change,
+        newer_minecraft_version,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compares_versions_numerically() {
+        assert_eq!(compare_versions("1.21", "1.20.4"), Ordering::Greater);
+        assert_eq!(compare_versions("0.15.11", "0.15.9"), Ordering::Greater);
+        assert_eq!(compare_versions("21.0.167-beta", "21.0.167"), Ordering::Equal);
+        assert!(!is_release("24w14a"));
+        assert!(!is_release("1.21-pre1"));
+    }
+
+    #[test]
+    fn reads_loader_versions_from_stored_ids() {
+        let number = |loader, stored| loader_version_number(&loader, "1.20.1", stored);
+        assert_eq!(number(LoaderType::Fabric, "fabric-0.15.11-1.20.1").as_deref(), Some("0.15.11"));
+        assert_eq!(number(LoaderType::Quilt, "0.26.0").as_deref(), Some("0.26.0"));
+        assert_eq!(number(LoaderType::Forge, "forge-1.20.1-47.2.0").as_deref(), Some("47.2.0"));
+        assert_eq!(number(LoaderType::NeoForge, "neoforge-20.4.237").as_deref(), Some("20.4.237"));
+        assert_eq!(number(LoaderType::Vanilla, "1.20.1"), None);
+    }
+}
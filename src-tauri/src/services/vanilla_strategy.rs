@@ -3,8 +3,10 @@ use async_trait::async_trait;
 use reqwest::Client;
 use std::path::PathBuf;
 use std::fs;
-use std::process::Command;
+use crate::services::rate_limiter;
+use crate::services::resumable_download::Checksum;
 use crate::services::mod_loader_strategy::ModLoaderStrategy;
+use crate::services::http_client::{get_json, HttpClient};
 use crate::models::version::{LoaderType, VersionResponse, MinecraftVersion, VersionType, MojangVersionManifest};
 use crate::util::JarCacheManager;
 
@@ -13,9 +15,9 @@ pub struct VanillaStrategy;
 
 #[async_trait]
 impl ModLoaderStrategy for VanillaStrategy {
-    async fn get_versions(&self, client: &Client, _minecraft_version: Option<String>) -> Result<VersionResponse> {
+    async fn get_versions(&self, client: &dyn HttpClient, _minecraft_version: Option<String>) -> Result<VersionResponse> {
         let url = "https://launchermeta.mojang.com/mc/game/version_manifest.json";
-        let response: MojangVersionManifest = client.get(url).send().await?.json().await?;
+        let response: MojangVersionManifest = get_json(client, url).await?;
 
         let mut versions = Vec::new();
         let latest_release = response.latest.release.clone();
@@ -52,23 +54,7 @@ impl ModLoaderStrategy for VanillaStrategy {
     }
 
     async fn get_download_url(&self, client: &Client, minecraft_version: &str, _loader_version: &str) -> Result<String> {
-        // Get version manifest
-        let manifest_url = "https://launchermeta.mojang.com/mc/game/version_manifest.json";
-        let manifest: serde_json::Value = client.get(manifest_url).send().await?.json().await?;
-        
-        // Find the specific version
-        let versions = manifest["versions"].as_array()
-            .ok_or_else(|| anyhow!("Invalid version manifest"))?;
-        
-        let version_info = versions.iter()
-            .find(|v| v["id"].as_str() == Some(minecraft_version))
-            .ok_or_else(|| anyhow!("Minecraft version {} not found", minecraft_version))?;
-        
-        let version_url = version_info["url"].as_str()
-            .ok_or_else(|| anyhow!("Version URL not found"))?;
-        
-        // Get version details
-        let version_details: serde_json::Value = client.get(version_url).send().await?.json().await?;
+        let version_details = fetch_version_details(client, minecraft_version).await?;
         
         // Get server JAR URL
         let server_url = version_details["downloads"]["server"]["url"].as_str()
@@ -77,11 +63,22 @@ impl ModLoaderStrategy for VanillaStrategy {
         Ok(server_url.to_string())
     }
     
+    async fn get_checksum(&self, client: &Client, _download_url: &str, minecraft_version: &str, _loader_version: &str) -> Result<Option<Checksum>> {
+        let version_details = fetch_version_details(client, minecraft_version).await?;
+        Ok(version_details["downloads"]["server"]["sha1"]
+            .as_str()
+            .map(|sha1| Checksum::Sha1(sha1.to_string())))
+    }
+    
     fn get_filename(&self, minecraft_version: &str, _loader_version: &str) -> String {
         format!("server-{}.jar", minecraft_version)
     }
+
+    fn is_loader_file(&self, file_name: &str) -> bool {
+        file_name.starts_with("server-") && file_name.ends_with(".jar")
+    }
     
-    async fn setup_server(&self, _client: &Client, server_path: &PathBuf, minecraft_version: &str, _loader_version: &str) -> Result<()> {
+    async fn setup_server(&self, _client: &Client, java_command: &str, server_path: &PathBuf, minecraft_version: &str, _loader_version: &str) -> Result<()> {
         let jar_name = format!("server-{}.jar", minecraft_version);
         let jar_path = server_path.join(&jar_name);
         
@@ -94,14 +91,14 @@ impl ModLoaderStrategy for VanillaStrategy {
         let logs_folder = server_path.join("logs");
         
         if world_folder.exists() || logs_folder.exists() {
-            println!("Vanilla server already initialized: {:?}", jar_path);
+            log::debug!("Vanilla server already initialized: {:?}", jar_path);
             return Ok(());
         }
 
-        println!("Initializing Vanilla server...");
+        log::info!("Initializing Vanilla server...");
         
-        // Run the server JAR once to generate initial files
-        let output = Command::new("java")
+        // Run the server JAR once to generate initial files; killed if the setup is cancelled
+        let output = tokio::process::Command::new(java_command)
             .args(&[
                 "-Xmx1G",
                 "-Xms512M", 
@@ -110,7 +107,9 @@ impl ModLoaderStrategy for VanillaStrategy {
                 "nogui"
             ])
             .current_dir(server_path)
+            .kill_on_drop(true)
             .output()
+            .await
             .map_err(|e| {
                 if e.kind() == std::io::ErrorKind::NotFound {
                     anyhow!("Java is not installed or not found in PATH. Please install Java to run Minecraft servers.")
@@ -119,15 +118,15 @@ impl ModLoaderStrategy for VanillaStrategy {
                 }
             })?;
         
-        println!("Vanilla server initialization exit status: {:?}", output.status);
-        println!("Vanilla server initialized successfully");
+        log::debug!("Vanilla server initialization exit status: {:?}", output.status);
+        log::info!("Vanilla server initialized successfully");
         Ok(())
     }
     
-    fn build_start_command(&self, server_path: &PathBuf, memory_gb: u32, min_memory_gb: u32) -> Result<Vec<String>> {
+    fn build_start_command(&self, server_path: &PathBuf, memory_mb: u32, min_memory_mb: u32) -> Result<Vec<String>> {
         let mut args = vec![
-            format!("-Xmx{}G", memory_gb),
-            format!("-Xms{}G", min_memory_gb),
+            format!("-Xmx{}M", memory_mb),
+            format!("-Xms{}M", min_memory_mb),
             "-jar".to_string(),
         ];
 
@@ -144,4 +143,58 @@ impl ModLoaderStrategy for VanillaStrategy {
         }
         Err(anyhow!("Vanilla server JAR not found"))
     }
-}
\ No newline at end of file
+}
+
+/// Fetches the Mojang version JSON, which lists the server JAR URL and its SHA-1
+async fn fetch_version_details(client: &Client, minecraft_version: &str) -> Result<serde_json::Value> {
+    // Get version manifest
+    let manifest_url = "https://launchermeta.mojang.com/mc/game/version_manifest.json";
+    let manifest: serde_json::Value = rate_limiter::send(client.get(manifest_url)).await?.json().await?;
+    
+    // Find the specific version
+    let versions = manifest["versions"].as_array()
+        .ok_or_else(|| anyhow!("Invalid version manifest"))?;
+    
+    let version_info = versions.iter()
+        .find(|v| v["id"].as_str() == Some(minecraft_version))
+        .ok_or_else(|| anyhow!("Minecraft version {} not found", minecraft_version))?;
+    
+    let version_url = version_info["url"].as_str()
+        .ok_or_else(|| anyhow!("Version URL not found"))?;
+    
+    Ok(rate_limiter::send(client.get(version_url)).await?.json().await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::http_client::MockHttpClient;
+
+    const MANIFEST: &str = r#"{
+        "latest": { "release": "1.21.1", "snapshot": "24w33a" },
+        "versions": [
+            { "id": "24w33a", "type": "snapshot", "url": "https://piston-meta.mojang.com/v1/packages/a/24w33a.json", "time": "2024-08-15T12:39:06+00:00", "releaseTime": "2024-08-15T12:28:41+00:00" },
+            { "id": "1.21.1", "type": "release", "url": "https://piston-meta.mojang.com/v1/packages/b/1.21.1.json", "time": "2024-08-08T12:24:45+00:00", "releaseTime": "2024-08-08T12:24:45+00:00" },
+            { "id": "1.21", "type": "release", "url": "https://piston-meta.mojang.com/v1/packages/c/1.21.json", "time": "2024-06-13T08:32:38+00:00", "releaseTime": "2024-06-13T08:24:03+00:00" },
+            { "id": "b1.7.3", "type": "old_beta", "url": "https://piston-meta.mojang.com/v1/packages/d/b1.7.3.json", "time": "2010-09-07T22:00:00+00:00", "releaseTime": "2011-07-07T22:00:00+00:00" }
+        ]
+    }"#;
+
+    #[tokio::test]
+    async fn lists_only_releases_and_marks_latest() {
+        let client = MockHttpClient::new()
+            .with("https://launchermeta.mojang.com/mc/game/version_manifest.json", MANIFEST);
+
+        let response = VanillaStrategy.get_versions(&client, None).await.unwrap();
+
+        let ids: Vec<_> = response.versions.iter().map(|v| v.id.as_str()).collect();
+        assert_eq!(ids, ["1.21.1", "1.21"]);
+        assert_eq!(response.latest.unwrap().id, "1.21.1");
+        assert_eq!(response.recommended.unwrap().id, "1.21.1");
+    }
+
+    #[tokio::test]
+    async fn fails_when_the_manifest_is_unreachable() {
+        assert!(VanillaStrategy.get_versions(&MockHttpClient::new(), None).await.is_err());
+    }
+}
@@ -1,7 +1,11 @@
 use crate::models::version::*;
 use crate::services::mod_loader_strategy::get_strategy;
+use crate::services::rate_limiter::is_too_many_requests;
+use crate::services::world_info::compare_release_versions;
 use crate::util::version_cache_manager::{VersionCacheManager, CacheInfo};
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use reqwest::Client;
@@ -45,36 +49,72 @@ impl VersionManager {
 
         // Fetch from API using strategy pattern
         let strategy = get_strategy(&loader);
-        let response = strategy.get_versions(&self.client, minecraft_version.clone()).await?;
+        let response = match strategy.get_versions(&self.client, minecraft_version.clone()).await {
+            Ok(response) => response,
+            // While the API is backing off, an expired list beats no list; explicit refreshes
+            // still report the error
+            Err(e) if !should_force_refresh && is_too_many_requests(&e) => {
+                if let Ok(Some(cache)) = self.cache_manager.load_cache_including_expired(&loader) {
+                    log::warn!("Serving expired {:?} versions: {}", loader, e);
+                    let latest = cache.versions.iter().find(|v| v.latest).cloned();
+                    let recommended = cache.versions.iter().find(|v| v.recommended).cloned();
+                    return Ok(VersionResponse {
+                        latest,
+                        recommended,
+                        versions: cache.versions,
+                    });
+                }
+                return Err(e);
+            }
+            Err(e) => return Err(e),
+        };
         
         // Save to cache (only if no specific minecraft version was requested)
         if minecraft_version.is_none() {
             if let Err(e) = self.cache_manager.save_cache(loader, response.versions.clone()) {
-                eprintln!("Failed to save cache: {}", e);
+                log::warn!("Failed to save cache: {}", e);
             }
         }
 
         Ok(response)
     }
 
-    pub async fn get_all_versions(&self, force_refresh: bool) -> Result<HashMap<String, VersionResponse>> {
-        let mut results = HashMap::new();
-        let loaders = vec![
-            ("vanilla", LoaderType::Vanilla),
-            ("fabric", LoaderType::Fabric),
-            ("forge", LoaderType::Forge),
-            ("neoforge", LoaderType::NeoForge),
-            ("paper", LoaderType::Paper),
-            ("quilt", LoaderType::Quilt),
+    /// Fetches every loader's versions concurrently, so the call takes as long as the slowest
+    /// API rather than all of them combined. A loader that fails is listed in `failed` and
+    /// doesn't affect the others.
+    pub async fn get_all_versions(&self, force_refresh: bool) -> Result<AllVersionsResponse> {
+        let (vanilla, fabric, forge, neoforge, paper, quilt, spigot) = tokio::join!(
+            self.get_versions(LoaderType::Vanilla, force_refresh),
+            self.get_versions(LoaderType::Fabric, force_refresh),
+            self.get_versions(LoaderType::Forge, force_refresh),
+            self.get_versions(LoaderType::NeoForge, force_refresh),
+            self.get_versions(LoaderType::Paper, force_refresh),
+            self.get_versions(LoaderType::Quilt, force_refresh),
+            self.get_versions(LoaderType::Spigot, force_refresh),
+        );
+
+        let mut results = AllVersionsResponse {
+            versions: HashMap::new(),
+            failed: HashMap::new(),
+        };
+        let fetched = [
+            ("vanilla", vanilla),
+            ("fabric", fabric),
+            ("forge", forge),
+            ("neoforge", neoforge),
+            ("paper", paper),
+            ("quilt", quilt),
+            ("spigot", spigot),
         ];
 
-        for (name, loader) in loaders {
-            match self.get_versions(loader, force_refresh).await {
+        for (name, result) in fetched {
+            match result {
                 Ok(response) => {
-                    results.insert(name.to_string(), response);
+                    results.versions.insert(name.to_string(), response);
                 }
                 Err(e) => {
-                    eprintln!("Failed to get versions for {}: {}", name, e);
+                    log::warn!("Failed to get versions for {}: {}", name, e);
+                    results.failed.insert(name.to_string(), e.to_string());
                 }
             }
         }
@@ -82,6 +122,74 @@ impl VersionManager {
         Ok(results)
     }
 
+    /// Whether `loader` has builds for `minecraft_version`, judged from the same (cached) version
+    /// lists the UI offers, so an impossible pick is caught before anything is downloaded
+    pub async fn is_combination_supported(&self, loader: LoaderType, minecraft_version: &str) -> Result<CombinationSupport> {
+        let releases = self.get_versions(LoaderType::Vanilla, false).await?;
+        if !releases.versions.iter().any(|v| v.id == minecraft_version) {
+            return Ok(CombinationSupport::unsupported(format!("{} is not a Minecraft release", minecraft_version)));
+        }
+        if loader == LoaderType::Vanilla {
+            return Ok(CombinationSupport::supported());
+        }
+
+        // NeoForge forked from Forge during 1.20.1; its own builds start at 1.20.2
+        if loader == LoaderType::NeoForge
+            && compare_release_versions(minecraft_version, "1.20.2") == Some(Ordering::Less)
+        {
+            return Ok(CombinationSupport::unsupported(format!(
+                "NeoForge only exists for Minecraft 1.20.2 and newer, not {}", minecraft_version
+            )));
+        }
+
+        let builds = self.get_versions(loader.clone(), false).await?;
+        let targets = |v: &MinecraftVersion| match v.minecraft_version.as_deref() {
+            // NeoForge lists builds per minor line ("1.21" covers 1.21.x), like its version filter
+            Some(mc) if loader == LoaderType::NeoForge => minecraft_version.starts_with(mc),
+            Some(mc) => mc == minecraft_version,
+            None => false,
+        };
+        if builds.versions.iter().any(targets) {
+            return Ok(CombinationSupport::supported());
+        }
+
+        let newest = builds.versions.iter()
+            .filter_map(|v| v.minecraft_version.as_deref())
+            .filter(|mc| compare_release_versions(mc, "0").is_some())
+            .max_by(|a, b| compare_release_versions(a, b).unwrap_or(Ordering::Equal));
+        let reason = match newest {
+            Some(newest) if compare_release_versions(minecraft_version, newest) == Some(Ordering::Greater) => format!(
+                "{:?} does not support Minecraft {} yet; the newest supported version is {}",
+                loader, minecraft_version, newest
+            ),
+            _ => format!("{:?} has no builds for Minecraft {}", loader, minecraft_version),
+        };
+        Ok(CombinationSupport::unsupported(reason))
+    }
+
+    /// Versions of `loader` whose id or Minecraft version contains `query` ("1.20" finds every
+    /// 1.20.x), newest Minecraft version first. Works from the cached list, even an expired one,
+    /// and only fetches when nothing was ever cached.
+    pub async fn search_versions(&self, loader: LoaderType, query: &str) -> Result<Vec<MinecraftVersion>> {
+        let versions = match self.cache_manager.load_cache_including_expired(&loader) {
+            Ok(Some(cache)) => cache.versions,
+            _ => self.get_versions(loader, false).await?.versions,
+        };
+
+        let query = query.trim().to_lowercase();
+        let mut matches: Vec<MinecraftVersion> = versions
+            .into_iter()
+            .filter(|v| {
+                v.id.to_lowercase().contains(&query)
+                    || v.minecraft_version.as_deref().is_some_and(|mc| mc.to_lowercase().contains(&query))
+            })
+            .collect();
+
+        // Stable, so builds of the same Minecraft version keep the API's newest-first order
+        matches.sort_by_key(|v| std::cmp::Reverse(release_key(v.minecraft_version.as_deref().unwrap_or(&v.id))));
+        Ok(matches)
+    }
+
     pub fn get_cache_info(&self) -> Result<HashMap<String, CacheInfo>> {
         self.cache_manager.get_cache_info()
     }
@@ -110,6 +218,7 @@ impl VersionManager {
                 LoaderType::NeoForge => "neoforge",
                 LoaderType::Paper => "paper",
                 LoaderType::Quilt => "quilt",
+                LoaderType::Spigot => "spigot",
             };
 
             match self.get_versions(loader, true).await {
@@ -117,7 +226,7 @@ impl VersionManager {
                     results.insert(loader_name.to_string(), true);
                 }
                 Err(e) => {
-                    eprintln!("Failed to refresh cache for {}: {}", loader_name, e);
+                    log::warn!("Failed to refresh cache for {}: {}", loader_name, e);
                     results.insert(loader_name.to_string(), false);
                 }
             }
@@ -130,6 +239,7 @@ impl VersionManager {
                 ("neoforge", LoaderType::NeoForge),
                 ("paper", LoaderType::Paper),
                 ("quilt", LoaderType::Quilt),
+                ("spigot", LoaderType::Spigot),
             ];
 
             for (name, loader) in loaders {
@@ -138,7 +248,7 @@ impl VersionManager {
                         results.insert(name.to_string(), true);
                     }
                     Err(e) => {
-                        eprintln!("Failed to refresh cache for {}: {}", name, e);
+                        log::warn!("Failed to refresh cache for {}: {}", name, e);
                         results.insert(name.to_string(), false);
                     }
                 }
@@ -151,12 +261,13 @@ impl VersionManager {
     pub async fn get_version_summary(&self) -> Result<VersionSummary> {
         let cache_info = self.get_cache_info()?;
         let mut summary = VersionSummary {
-            total_loaders: 6,
+            total_loaders: 7,
             cached_loaders: 0,
             valid_cache_count: 0,
             expired_cache_count: 0,
             latest_versions: HashMap::new(),
             cache_status: cache_info.clone(),
+            loaders: HashMap::new(),
         };
 
         for (loader_name, info) in &cache_info {
@@ -170,7 +281,7 @@ impl VersionManager {
             }
         }
 
-        // Get latest versions from cache or API
+        // Report from the cache only; stale data is flagged rather than refreshed here
         let loaders = vec![
             ("vanilla", LoaderType::Vanilla),
             ("fabric", LoaderType::Fabric),
@@ -178,14 +289,38 @@ impl VersionManager {
             ("neoforge", LoaderType::NeoForge),
             ("paper", LoaderType::Paper),
             ("quilt", LoaderType::Quilt),
+            ("spigot", LoaderType::Spigot),
         ];
 
+        let now = Utc::now();
         for (name, loader) in loaders {
-            if let Ok(response) = self.get_versions(loader, false).await {
-                if let Some(latest) = response.latest {
-                    summary.latest_versions.insert(name.to_string(), latest.id);
+            let cache = self.cache_manager.load_cache_including_expired(&loader).ok().flatten();
+            let loader_summary = match cache {
+                Some(cache) => {
+                    let latest = cache.versions.iter()
+                        .find(|v| v.latest)
+                        .or_else(|| cache.versions.first())
+                        .map(|v| v.id.clone());
+                    if let Some(latest) = &latest {
+                        summary.latest_versions.insert(name.to_string(), latest.clone());
+                    }
+                    LoaderVersionSummary {
+                        version_count: cache.versions.len(),
+                        latest_version: latest,
+                        cached_at: Some(cache.last_updated),
+                        age_secs: Some((now - cache.last_updated).num_seconds().max(0)),
+                        stale: cache.expires_at <= now,
+                    }
                 }
-            }
+                None => LoaderVersionSummary {
+                    version_count: 0,
+                    latest_version: None,
+                    cached_at: None,
+                    age_secs: None,
+                    stale: true,
+                },
+            };
+            summary.loaders.insert(name.to_string(), loader_summary);
         }
 
         Ok(summary)
@@ -200,4 +335,52 @@ pub struct VersionSummary {
     pub expired_cache_count: usize,
     pub latest_versions: HashMap<String, String>,
     pub cache_status: HashMap<String, CacheInfo>,
+    /// Per-loader availability, keyed like `latest_versions`
+    pub loaders: HashMap<String, LoaderVersionSummary>,
+}
+
+/// Versions of every loader that could be fetched, and the error of each one that couldn't
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AllVersionsResponse {
+    pub versions: HashMap<String, VersionResponse>,
+    pub failed: HashMap<String, String>,
+}
+
+/// Sort key of a release version, padded so "1.20" and "1.20.0" compare equal; None for
+/// anything else (snapshots, unknown), which sorts after every release when reversed
+fn release_key(version: &str) -> Option<Vec<u32>> {
+    let mut parts: Vec<u32> = version.split('.').map(|part| part.parse().ok()).collect::<Option<_>>()?;
+    while parts.len() > 1 && parts.last() == Some(&0) {
+        parts.pop();
+    }
+    Some(parts)
+}
+
+/// Whether a loader can run a Minecraft version, and why not if it can't
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct CombinationSupport {
+    pub supported: bool,
+    pub reason: Option<String>,
+}
+
+impl CombinationSupport {
+    fn supported() -> Self {
+        Self { supported: true, reason: None }
+    }
+
+    fn unsupported(reason: String) -> Self {
+        Self { supported: false, reason: Some(reason) }
+    }
+}
+
+/// Version availability of one loader as known from the local cache
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LoaderVersionSummary {
+    pub version_count: usize,
+    pub latest_version: Option<String>,
+    pub cached_at: Option<DateTime<Utc>>,
+    /// Seconds since the cached list was fetched
+    pub age_secs: Option<i64>,
+    /// True when the cache is missing or past its expiry and should be refreshed
+    pub stale: bool,
 }
\ No newline at end of file
@@ -1,18 +1,23 @@
 use crate::models::version::*;
 use anyhow::Result;
 use chrono::{DateTime, Utc};
-use reqwest::Client;
+use crate::services::http_client::{get_json, HttpClient};
 use std::collections::HashMap;
+use crate::services::mod_loader_strategy::ModLoaderStrategy;
+use crate::services::spigot_strategy::SpigotStrategy;
 
 pub struct VersionService {
-    client: Client,
+    client: Box<dyn HttpClient>,
 }
 
 impl VersionService {
     pub fn new() -> Self {
-        Self {
-            client: Client::new(),
-        }
+        Self::with_client(Box::new(reqwest::Client::new()))
+    }
+
+    /// Uses `client` for every request, e.g. a mock serving captured responses in tests
+    pub fn with_client(client: Box<dyn HttpClient>) -> Self {
+        Self { client }
     }
 
     pub async fn get_versions(&self, loader: LoaderType) -> Result<VersionResponse> {
@@ -27,12 +32,13 @@ impl VersionService {
             LoaderType::NeoForge => self.get_neoforge_versions(minecraft_version).await,
             LoaderType::Paper => self.get_paper_versions(minecraft_version).await,
             LoaderType::Quilt => self.get_quilt_versions(minecraft_version).await,
+            LoaderType::Spigot => SpigotStrategy.get_versions(self.client.as_ref(), minecraft_version).await,
         }
     }
 
     async fn get_vanilla_versions(&self) -> Result<VersionResponse> {
         let url = "https://launchermeta.mojang.com/mc/game/version_manifest.json";
-        let response: MojangVersionManifest = self.client.get(url).send().await?.json().await?;
+        let response: MojangVersionManifest = get_json(self.client.as_ref(), url).await?;
 
         let mut versions = Vec::new();
         let latest_release = response.latest.release.clone();
@@ -72,11 +78,11 @@ impl VersionService {
     async fn get_fabric_versions(&self, minecraft_version: Option<String>) -> Result<VersionResponse> {
         // Get game versions
         let game_url = "https://meta.fabricmc.net/v2/versions";
-        let game_response: FabricVersions = self.client.get(game_url).send().await?.json().await?;
+        let game_response: FabricVersions = get_json(self.client.as_ref(), game_url).await?;
 
         // Get loader versions
         let loader_url = "https://meta.fabricmc.net/v2/versions/loader";
-        let loader_response: Vec<FabricLoaderVersion> = self.client.get(loader_url).send().await?.json().await?;
+        let loader_response: Vec<FabricLoaderVersion> = get_json(self.client.as_ref(), loader_url).await?;
 
         let mut versions = Vec::new();
         
@@ -160,7 +166,7 @@ impl VersionService {
 
     async fn get_forge_versions(&self, minecraft_version: Option<String>) -> Result<VersionResponse> {
         let url = "https://maven.minecraftforge.net/net/minecraftforge/forge/maven-metadata.xml";
-        let response = self.client.get(url).send().await?.text().await?;
+        let response = self.client.get_text(url).await?;
 
         // Parse XML manually (simple approach for this case)
         let mut versions = Vec::new();
@@ -238,7 +244,7 @@ impl VersionService {
 
     async fn get_neoforge_versions(&self, minecraft_version: Option<String>) -> Result<VersionResponse> {
         let url = "https://maven.neoforged.net/api/maven/versions/releases/net/neoforged/neoforge";
-        let response: NeoForgeVersions = self.client.get(url).send().await?.json().await?;
+        let response: NeoForgeVersions = get_json(self.client.as_ref(), url).await?;
 
         let mut versions = Vec::new();
         
@@ -357,7 +363,7 @@ impl VersionService {
 
     async fn get_paper_versions(&self, minecraft_version: Option<String>) -> Result<VersionResponse> {
         let url = "https://api.papermc.io/v2/projects/paper";
-        let response: PaperProject = self.client.get(url).send().await?.json().await?;
+        let response: PaperProject = get_json(self.client.as_ref(), url).await?;
 
         let mut versions = Vec::new();
         
@@ -403,7 +409,7 @@ impl VersionService {
 
     async fn get_quilt_versions(&self, minecraft_version: Option<String>) -> Result<VersionResponse> {
         let base_url = "https://meta.quiltmc.org/v3/versions";
-        let response: QuiltVersions = self.client.get(base_url).send().await?.json().await?;
+        let response: QuiltVersions = get_json(self.client.as_ref(), base_url).await?;
 
         let mut versions = Vec::new();
         
@@ -415,7 +421,7 @@ impl VersionService {
             if let Some(game_version) = target_game_version {
                 // Get loader versions for this game version (similar to Fabric pattern)
                 let loader_url = format!("{}/loader/{}", base_url, target_mc_version);
-                let loader_response: Vec<serde_json::Value> = self.client.get(&loader_url).send().await?.json().await?;
+                let loader_response: Vec<serde_json::Value> = get_json(self.client.as_ref(), &loader_url).await?;
                 
                 // Create versions for each loader version (using Fabric-like pattern)
                 for (i, loader) in loader_response.iter().enumerate() {
@@ -466,4 +472,60 @@ impl VersionService {
             versions,
         })
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::http_client::MockHttpClient;
+
+    const QUILT: &str = r#"{
+        "game": [
+            { "version": "1.21.1", "stable": true },
+            { "version": "24w33a", "stable": false },
+            { "version": "1.21", "stable": true }
+        ],
+        "mappings": []
+    }"#;
+
+    const QUILT_LOADERS: &str = r#"[
+        { "version": "0.26.4" },
+        { "version": "0.26.3" }
+    ]"#;
+
+    fn service() -> VersionService {
+        VersionService::with_client(Box::new(
+            MockHttpClient::new()
+                .with("https://meta.quiltmc.org/v3/versions", QUILT)
+                .with("https://meta.quiltmc.org/v3/versions/loader/1.21.1", QUILT_LOADERS),
+        ))
+    }
+
+    #[tokio::test]
+    async fn lists_stable_game_versions_through_the_given_client() {
+        let response = service().get_versions(LoaderType::Quilt).await.unwrap();
+
+        let ids: Vec<_> = response.versions.iter().map(|v| v.id.as_str()).collect();
+        assert_eq!(ids, ["quilt-1.21.1", "quilt-1.21"]);
+        assert_eq!(response.latest.unwrap().id, "quilt-1.21.1");
+    }
+
+    #[tokio::test]
+    async fn lists_loader_versions_for_a_game_version() {
+        let response = service()
+            .get_versions_for_minecraft(LoaderType::Quilt, Some("1.21.1".to_string()))
+            .await
+            .unwrap();
+
+        let ids: Vec<_> = response.versions.iter().map(|v| v.id.as_str()).collect();
+        assert_eq!(ids, ["quilt-0.26.4-1.21.1", "quilt-0.26.3-1.21.1"]);
+        assert_eq!(response.versions[0].minecraft_version.as_deref(), Some("1.21.1"));
+    }
+
+    #[tokio::test]
+    async fn reports_unreachable_endpoints() {
+        let service = VersionService::with_client(Box::new(MockHttpClient::new()));
+        assert!(service.get_versions(LoaderType::Vanilla).await.is_err());
+        assert!(service.get_versions(LoaderType::Quilt).await.is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use crate::services::http_client::HttpClient;
+use crate::services::player_profile::{hyphenate_uuid, lookup_uuid};
+use anyhow::{anyhow, Result};
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+const WHITELIST_FILE: &str = "whitelist.json";
+
+/// One entry of whitelist.json
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WhitelistEntry {
+    pub uuid: String,
+    pub name: String,
+}
+
+/// Entries of whitelist.json; a server that never had a whitelist has none
+pub fn read_whitelist(server_path: &Path) -> Result<Vec<WhitelistEntry>> {
+    let path = server_path.join(WHITELIST_FILE);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&path)?;
+    if content.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(&content).map_err(|e| anyhow!("{} is not valid: {}", WHITELIST_FILE, e))
+}
+
+fn write_whitelist(server_path: &Path, entries: &[WhitelistEntry]) -> Result<()> {
+    let path = server_path.join(WHITELIST_FILE);
+    let temp_path = server_path.join(format!("{}.tmp", WHITELIST_FILE));
+    fs::write(&temp_path, serde_json::to_string_pretty(entries)?)?;
+    fs::rename(&temp_path, &path)?;
+    Ok(())
+}
+
+/// Looks up the UUID the server will see for `name`: the Mojang account's UUID, or the
+/// name-derived one offline-mode servers use
+pub async fn resolve_player(client: &dyn HttpClient, name: &str, online_mode: bool) -> Result<WhitelistEntry> {
+    let name = name.trim();
+    if !(1..=16).contains(&name.len()) || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(anyhow!("'{}' is not a valid player name", name));
+    }
+
+    if !online_mode {
+        return Ok(WhitelistEntry { uuid: offline_uuid(name), name: name.to_string() });
+    }
+
+    let (uuid, name) = lookup_uuid(client, name).await?;
+    Ok(WhitelistEntry { uuid, name })
+}
+
+/// The UUID an offline-mode server assigns: a version 3 UUID of "OfflinePlayer:<name>"
+pub fn offline_uuid(name: &str) -> String {
+    let mut bytes: [u8; 16] = Md5::digest(format!("OfflinePlayer:{}", name).as_bytes()).into();
+    bytes[6] = (bytes[6] & 0x0f) | 0x30;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    hyphenate_uuid(&hex).unwrap_or(hex)
+}
+
+/// Adds a player to whitelist.json of a stopped server; false if they were already on it
+pub fn add_offline(server_path: &Path, entry: WhitelistEntry) -> Result<bool> {
+    let mut entries = read_whitelist(server_path)?;
+    if entries.iter().any(|e| e.uuid == entry.uuid || e.name.eq_ignore_ascii_case(&entry.name)) {
+        return Ok(false);
+    }
+    entries.push(entry);
+    write_whitelist(server_path, &entries)?;
+    Ok(true)
+}
+
+/// Removes a player from whitelist.json of a stopped server by name; false if they weren't on it
+pub fn remove_offline(server_path: &Path, name: &str) -> Result<bool> {
+    let mut entries = read_whitelist(server_path)?;
+    let before = entries.len();
+    entries.retain(|e| !e.name.eq_ignore_ascii_case(name.trim()));
+    if entries.len() == before {
+        return Ok(false);
+    }
+    write_whitelist(server_path, &entries)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::http_client::MockHttpClient;
+
+    #[test]
+    fn derives_offline_uuids() {
+        assert_eq!(offline_uuid("Notch"), "b50ad385-829d-3141-a216-7e7d7539ba7f");
+        assert_eq!(offline_uuid("jeb_"), "a762f560-4fce-3236-812a-b80efff0b62b");
+    }
+
+    #[tokio::test]
+    async fn resolves_offline_players_without_lookups() {
+        let client = MockHttpClient::new();
+        let entry = resolve_player(&client, " Notch ", false).await.unwrap();
+        assert_eq!(entry.name, "Notch");
+        assert_eq!(entry.uuid, "b50ad385-829d-3141-a216-7e7d7539ba7f");
+
+        for name in ["", "@a", "Steve Alex", "ThisNameIsTooLong"] {
+            assert!(resolve_player(&client, name, false).await.is_err(), "{}", name);
+        }
+    }
+}
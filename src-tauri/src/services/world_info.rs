@@ -0,0 +1,578 @@
+use crate::util::{check_level_name, ServerPropertiesManager};
+use anyhow::{anyhow, Result};
+use flate2::read::{GzDecoder, ZlibDecoder};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Version information stored in a world's level.dat
+#[derive(Debug, Clone, Serialize)]
+pub struct WorldVersion {
+    /// Data.Version.Name, e.g. "1.20.4" (absent in worlds last saved before 1.9)
+    pub name: Option<String>,
+    /// Data.DataVersion, the numeric world format version (absent before 1.9)
+    pub data_version: Option<i32>,
+    pub snapshot: bool,
+}
+
+/// Minimal NBT value model; only the tags needed to walk level.dat are kept in full
+#[derive(Debug, Clone)]
+enum Tag {
+    Number(i64),
+    Float(f64),
+    String(String),
+    Compound(HashMap<String, Tag>),
+    Other,
+}
+
+impl Tag {
+    fn get(&self, key: &str) -> Option<&Tag> {
+        match self {
+            Tag::Compound(map) => map.get(key),
+            _ => None,
+        }
+    }
+
+    fn as_i64(&self) -> Option<i64> {
+        match self {
+            Tag::Number(n) => Some(*n),
+            Tag::Float(f) => Some(*f as i64),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(s) => Some(s),
+            _ => None,
+        }
+    }
+}
+
+/// Folder of the server's main world, following level-name in server.properties. A level-name
+/// that isn't a plain folder name (such as "..", which would be the server folder itself) is
+/// ignored in favor of the default "world".
+pub fn world_folder(server_path: &Path) -> PathBuf {
+    let properties_path = server_path.join("server.properties");
+    let level_name = if properties_path.exists() {
+        ServerPropertiesManager::new(properties_path)
+            .load_properties()
+            .map(|properties| properties.level_name)
+            .unwrap_or_else(|_| "world".to_string())
+    } else {
+        "world".to_string()
+    };
+    if let Err(e) = check_level_name(&level_name) {
+        log::warn!("Ignoring level-name in {:?}: {}", server_path, e);
+        return server_path.join("world");
+    }
+    server_path.join(level_name)
+}
+
+/// Suffixes of the dimension folders Paper and Spigot keep next to the main world
+pub const DIMENSION_SUFFIXES: [&str; 2] = ["_nether", "_the_end"];
+
+/// The existing folders of the server's world: the main one and any Paper dimension siblings
+pub fn world_folders(server_path: &Path) -> Vec<PathBuf> {
+    let world = world_folder(server_path);
+    let level_name = world.file_name().unwrap_or_default().to_string_lossy().to_string();
+    std::iter::once("")
+        .chain(DIMENSION_SUFFIXES)
+        .map(|suffix| server_path.join(format!("{}{}", level_name, suffix)))
+        .filter(|folder| folder.is_dir())
+        .collect()
+}
+
+/// Deletes every world folder so the next start generates the world again from the seed in
+/// server.properties. Returns the names of the removed folders.
+pub fn clear_world(server_path: &Path) -> Result<Vec<String>> {
+    let mut removed = Vec::new();
+    for folder in world_folders(server_path) {
+        // Only ever a folder inside the server directory, never the directory itself
+        if folder.parent() != Some(server_path) {
+            return Err(anyhow!("Refusing to delete {:?}: it is not a world folder of {:?}", folder, server_path));
+        }
+        fs::remove_dir_all(&folder).map_err(|e| anyhow!("Failed to delete {:?}: {}", folder, e))?;
+        removed.push(folder.file_name().unwrap_or_default().to_string_lossy().to_string());
+    }
+    log::info!("Cleared world folders {:?} in {:?}", removed, server_path);
+    Ok(removed)
+}
+
+/// Renames the world folder (and any Paper dimension siblings) to `new_name` and updates
+/// level-name, so changing the name keeps the existing world instead of generating a new one.
+/// The server must be stopped; nothing is overwritten if a target folder already exists.
+pub fn rename_world(server_path: &Path, new_name: &str) -> Result<()> {
+    let new_name = new_name.trim();
+    check_level_name(new_name).map_err(|e| anyhow!(e))?;
+
+    let properties_path = server_path.join("server.properties");
+    let manager = ServerPropertiesManager::new(properties_path);
+    let mut properties = manager.load_properties()?;
+    let old_name = properties.level_name.clone();
+    if old_name == new_name {
+        return Ok(());
+    }
+
+    // Collect every folder to move and check all targets before touching anything
+    let mut moves = Vec::new();
+    for suffix in std::iter::once("").chain(DIMENSION_SUFFIXES) {
+        let from = server_path.join(format!("{}{}", old_name, suffix));
+        let to = server_path.join(format!("{}{}", new_name, suffix));
+        if to.exists() {
+            return Err(anyhow!(
+                "A folder named {}{} already exists in the server directory",
+                new_name,
+                suffix
+            ));
+        }
+        if from.is_dir() {
+            moves.push((from, to));
+        }
+    }
+
+    for (index, (from, to)) in moves.iter().enumerate() {
+        if let Err(e) = fs::rename(from, to) {
+            // Put back what was already moved so the world stays consistent
+            for (moved_from, moved_to) in moves[..index].iter().rev() {
+                let _ = fs::rename(moved_to, moved_from);
+            }
+            return Err(anyhow!("Failed to rename {:?}: {}", from, e));
+        }
+    }
+
+    properties.level_name = new_name.to_string();
+    if let Err(e) = manager.save_properties(&properties) {
+        for (from, to) in moves.iter().rev() {
+            let _ = fs::rename(to, from);
+        }
+        return Err(e.into());
+    }
+
+    log::info!("Renamed world {} to {} ({} folder(s) moved)", old_name, new_name, moves.len());
+    Ok(())
+}
+
+/// Moves the main world and any Paper dimension siblings into `stash_path`, which must not
+/// exist yet. Returns the level name they were kept under, or None if there is no world.
+pub fn stash_world(server_path: &Path, stash_path: &Path) -> Result<Option<String>> {
+    let world = world_folder(server_path);
+    if !world.is_dir() {
+        return Ok(None);
+    }
+    if stash_path.exists() {
+        return Err(anyhow!("{:?} already exists; move it out of the way first", stash_path));
+    }
+
+    let level_name = world
+        .file_name()
+        .ok_or_else(|| anyhow!("Invalid world folder {:?}", world))?
+        .to_string_lossy()
+        .to_string();
+    fs::create_dir_all(stash_path)?;
+    for suffix in std::iter::once("").chain(DIMENSION_SUFFIXES) {
+        let folder = format!("{}{}", level_name, suffix);
+        let from = server_path.join(&folder);
+        if from.is_dir() {
+            fs::rename(&from, stash_path.join(&folder))
+                .map_err(|e| anyhow!("Failed to move {:?} aside: {}", from, e))?;
+        }
+    }
+
+    log::info!("Moved world {} from {:?} to {:?}", level_name, server_path, stash_path);
+    Ok(Some(level_name))
+}
+
+/// Moves a world kept by [`stash_world`] back into `server_path` and points level-name at it,
+/// replacing any world the server generated under the same name
+pub fn restore_world(stash_path: &Path, server_path: &Path, level_name: &str) -> Result<()> {
+    for entry in fs::read_dir(stash_path)? {
+        let entry = entry?;
+        let target = server_path.join(entry.file_name());
+        if target.exists() {
+            fs::remove_dir_all(&target)?;
+        }
+        fs::rename(entry.path(), &target)?;
+    }
+    fs::remove_dir(stash_path)?;
+
+    ServerPropertiesManager::new(server_path.join("server.properties")).update_property("level-name", level_name)?;
+    log::info!("Restored world {} into {:?}", level_name, server_path);
+    Ok(())
+}
+
+/// Disk usage of a server's world, split by dimension folder
+#[derive(Debug, Clone, Serialize)]
+pub struct WorldSize {
+    pub total_bytes: u64,
+    /// One entry per existing folder (the world itself, then Paper's _nether/_the_end siblings)
+    pub folders: Vec<WorldFolderSize>,
+    pub measured_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorldFolderSize {
+    pub name: String,
+    pub bytes: u64,
+}
+
+/// Walking a large world takes seconds, so results are reused for this long
+const WORLD_SIZE_CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+lazy_static::lazy_static! {
+    static ref WORLD_SIZE_CACHE: Mutex<HashMap<PathBuf, (Instant, WorldSize)>> = Mutex::new(HashMap::new());
+}
+
+/// Size of the server's world, reusing a measurement younger than ten minutes unless
+/// `refresh` is set. A world that has not been generated yet has size zero.
+pub fn world_size(server_path: &Path, refresh: bool) -> WorldSize {
+    if !refresh {
+        let cache = WORLD_SIZE_CACHE.lock().unwrap();
+        if let Some((measured, size)) = cache.get(server_path) {
+            if measured.elapsed() < WORLD_SIZE_CACHE_TTL {
+                return size.clone();
+            }
+        }
+    }
+
+    let size = measure_world_size(server_path);
+    WORLD_SIZE_CACHE
+        .lock()
+        .unwrap()
+        .insert(server_path.to_path_buf(), (Instant::now(), size.clone()));
+    size
+}
+
+fn measure_world_size(server_path: &Path) -> WorldSize {
+    let world = world_folder(server_path);
+    let level_name = world.file_name().unwrap_or_default().to_string_lossy().to_string();
+
+    let folders: Vec<WorldFolderSize> = std::iter::once("")
+        .chain(DIMENSION_SUFFIXES)
+        .map(|suffix| format!("{}{}", level_name, suffix))
+        .filter(|name| server_path.join(name).is_dir())
+        .map(|name| WorldFolderSize { bytes: directory_size(&server_path.join(&name)), name })
+        .collect();
+
+    WorldSize {
+        total_bytes: folders.iter().map(|folder| folder.bytes).sum(),
+        folders,
+        measured_at: chrono::Utc::now(),
+    }
+}
+
+/// Sums file sizes below `path` without following symlinks; unreadable entries are skipped
+fn directory_size(path: &Path) -> u64 {
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(_) => return 0,
+    };
+
+    entries
+        .flatten()
+        .map(|entry| match entry.path().symlink_metadata() {
+            Ok(metadata) if metadata.is_dir() => directory_size(&entry.path()),
+            Ok(metadata) if metadata.is_file() => metadata.len(),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Reads the Minecraft version a world was last saved with from `<world_path>/level.dat`
+pub fn read_world_version(world_path: &Path) -> Result<WorldVersion> {
+    let level_dat = world_path.join("level.dat");
+    if !level_dat.exists() {
+        return Err(anyhow!("level.dat not found in {:?}; the world has not been generated yet", world_path));
+    }
+
+    let raw = fs::read(&level_dat)?;
+    let data = decompress(&raw)?;
+    let root = parse_nbt(&data)?;
+
+    let level = root.get("Data").ok_or_else(|| anyhow!("level.dat has no Data compound"))?;
+    let version = level.get("Version");
+
+    Ok(WorldVersion {
+        name: version.and_then(|v| v.get("Name")).and_then(Tag::as_str).map(str::to_string),
+        data_version: level.get("DataVersion").and_then(Tag::as_i64).map(|v| v as i32),
+        snapshot: version
+            .and_then(|v| v.get("Snapshot"))
+            .and_then(Tag::as_i64)
+            .map(|v| v != 0)
+            .unwrap_or(false),
+    })
+}
+
+/// level.dat is gzip-compressed, but some tools write it with zlib or uncompressed
+fn decompress(raw: &[u8]) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    match raw {
+        [0x1f, 0x8b, ..] => {
+            GzDecoder::new(raw).read_to_end(&mut data)?;
+        }
+        [0x78, ..] => {
+            ZlibDecoder::new(raw).read_to_end(&mut data)?;
+        }
+        _ => data.extend_from_slice(raw),
+    }
+    Ok(data)
+}
+
+fn parse_nbt(data: &[u8]) -> Result<Tag> {
+    let mut reader = NbtReader { data, pos: 0 };
+    let tag_type = reader.u8()?;
+    if tag_type != 10 {
+        return Err(anyhow!("level.dat does not start with a compound tag"));
+    }
+    reader.string()?; // root name, usually empty
+    reader.payload(10, 0)
+}
+
+/// Deeply nested files are rejected rather than risking a stack overflow
+const MAX_DEPTH: usize = 64;
+
+struct NbtReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl NbtReader<'_> {
+    fn take(&mut self, len: usize) -> Result<&[u8]> {
+        let end = self.pos.checked_add(len).filter(|end| *end <= self.data.len())
+            .ok_or_else(|| anyhow!("level.dat is truncated"))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn i16(&mut self) -> Result<i16> {
+        Ok(i16::from_be_bytes(self.take(2)?.try_into()?))
+    }
+
+    fn i32(&mut self) -> Result<i32> {
+        Ok(i32::from_be_bytes(self.take(4)?.try_into()?))
+    }
+
+    fn i64(&mut self) -> Result<i64> {
+        Ok(i64::from_be_bytes(self.take(8)?.try_into()?))
+    }
+
+    fn length(&mut self) -> Result<usize> {
+        let len = self.i32()?;
+        usize::try_from(len).map_err(|_| anyhow!("Negative length in level.dat"))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.i16()? as u16 as usize;
+        // NBT uses modified UTF-8; lossy decoding is fine for names and version strings
+        Ok(String::from_utf8_lossy(self.take(len)?).to_string())
+    }
+
+    fn payload(&mut self, tag_type: u8, depth: usize) -> Result<Tag> {
+        if depth > MAX_DEPTH {
+            return Err(anyhow!("level.dat is nested too deeply"));
+        }
+
+        Ok(match tag_type {
+            1 => Tag::Number(self.u8()? as i8 as i64),
+            2 => Tag::Number(self.i16()? as i64),
+            3 => Tag::Number(self.i32()? as i64),
+            4 => Tag::Number(self.i64()?),
+            5 => Tag::Float(f32::from_be_bytes(self.take(4)?.try_into()?) as f64),
+            6 => Tag::Float(f64::from_be_bytes(self.take(8)?.try_into()?)),
+            7 => {
+                let len = self.length()?;
+                self.take(len)?;
+                Tag::Other
+            }
+            8 => Tag::String(self.string()?),
+            9 => {
+                let item_type = self.u8()?;
+                let len = self.length()?;
+                for _ in 0..len {
+                    self.payload(item_type, depth + 1)?;
+                }
+                Tag::Other
+            }
+            10 => {
+                let mut map = HashMap::new();
+                loop {
+                    let child_type = self.u8()?;
+                    if child_type == 0 {
+                        break;
+                    }
+                    let name = self.string()?;
+                    let value = self.payload(child_type, depth + 1)?;
+                    map.insert(name, value);
+                }
+                Tag::Compound(map)
+            }
+            11 => {
+                let len = self.length()?;
+                self.take(len.checked_mul(4).ok_or_else(|| anyhow!("Invalid array length"))?)?;
+                Tag::Other
+            }
+            12 => {
+                let len = self.length()?;
+                self.take(len.checked_mul(8).ok_or_else(|| anyhow!("Invalid array length"))?)?;
+                Tag::Other
+            }
+            0 => Tag::Other,
+            other => return Err(anyhow!("Unknown NBT tag type {} in level.dat", other)),
+        })
+    }
+}
+
+/// Compares dotted release versions ("1.20.4" vs "1.21"); None if either isn't a plain release
+pub fn compare_release_versions(a: &str, b: &str) -> Option<std::cmp::Ordering> {
+    let parse = |v: &str| -> Option<Vec<u32>> { v.split('.').map(|part| part.parse().ok()).collect() };
+    let (mut a, mut b) = (parse(a)?, parse(b)?);
+    let len = a.len().max(b.len());
+    a.resize(len, 0);
+    b.resize(len, 0);
+    Some(a.cmp(&b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::cmp::Ordering;
+    use std::io::Write;
+
+    /// Tag type and name header of a named tag
+    fn named(tag_type: u8, name: &str) -> Vec<u8> {
+        let mut bytes = vec![tag_type];
+        bytes.extend_from_slice(&(name.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(name.as_bytes());
+        bytes
+    }
+
+    fn string_tag(name: &str, value: &str) -> Vec<u8> {
+        let mut bytes = named(8, name);
+        bytes.extend_from_slice(&(value.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(value.as_bytes());
+        bytes
+    }
+
+    /// A level.dat body saved by 1.20.4, with the tags Allay skips mixed in
+    fn level_dat() -> Vec<u8> {
+        let mut data = named(10, "");
+        data.extend(named(10, "Data"));
+        data.extend(named(3, "DataVersion"));
+        data.extend_from_slice(&3700i32.to_be_bytes());
+        data.extend(named(9, "ServerBrands"));
+        data.push(8);
+        data.extend_from_slice(&1i32.to_be_bytes());
+        data.extend_from_slice(&5u16.to_be_bytes());
+        data.extend_from_slice(b"paper");
+        data.extend(named(11, "DataPacks"));
+        data.extend_from_slice(&2i32.to_be_bytes());
+        data.extend_from_slice(&[0; 8]);
+        data.extend(named(6, "BorderSize"));
+        data.extend_from_slice(&5.9999968e7f64.to_be_bytes());
+        data.extend(named(10, "Version"));
+        data.extend(string_tag("Name", "1.20.4"));
+        data.extend(named(1, "Snapshot"));
+        data.push(0);
+        data.push(0); // end of Version
+        data.push(0); // end of Data
+        data.push(0); // end of root
+        data
+    }
+
+    #[test]
+    fn parses_level_dat_tags() {
+        let root = parse_nbt(&level_dat()).unwrap();
+        let level = root.get("Data").unwrap();
+        assert_eq!(level.get("DataVersion").and_then(Tag::as_i64), Some(3700));
+        assert_eq!(level.get("BorderSize").and_then(Tag::as_i64), Some(59999968));
+        assert_eq!(level.get("Version").and_then(|v| v.get("Name")).and_then(Tag::as_str), Some("1.20.4"));
+        assert!(matches!(level.get("ServerBrands"), Some(Tag::Other)));
+    }
+
+    #[test]
+    fn rejects_malformed_nbt() {
+        let data = level_dat();
+        assert!(parse_nbt(&data[..data.len() - 5]).is_err());
+        assert!(parse_nbt(&string_tag("", "not a compound")).is_err());
+
+        let mut negative = named(10, "");
+        negative.extend(named(7, "Bytes"));
+        negative.extend_from_slice(&(-1i32).to_be_bytes());
+        assert!(parse_nbt(&negative).is_err());
+
+        let mut unknown = named(10, "");
+        unknown.extend(named(13, "Future"));
+        assert!(parse_nbt(&unknown).is_err());
+
+        let mut nested = named(10, "");
+        for _ in 0..=MAX_DEPTH {
+            nested.extend(named(10, "a"));
+        }
+        nested.extend(vec![0; MAX_DEPTH + 2]);
+        assert!(parse_nbt(&nested).is_err());
+    }
+
+    #[test]
+    fn reads_compressed_and_plain_level_dat() {
+        let dir = std::env::temp_dir().join(format!("allay-world-info-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&level_dat()).unwrap();
+        for contents in [encoder.finish().unwrap(), level_dat()] {
+            fs::write(dir.join("level.dat"), contents).unwrap();
+            let version = read_world_version(&dir).unwrap();
+            assert_eq!(version.name.as_deref(), Some("1.20.4"));
+            assert_eq!(version.data_version, Some(3700));
+            assert!(!version.snapshot);
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(read_world_version(&dir).is_err());
+    }
+
+    #[test]
+    fn clears_only_world_folders() {
+        let dir = std::env::temp_dir().join(format!("allay-clear-world-{}", std::process::id()));
+        for folder in ["world", "world_nether", "mods"] {
+            fs::create_dir_all(dir.join(folder)).unwrap();
+        }
+        // Hand-edited to point at the server folder itself
+        fs::write(dir.join("server.properties"), "level-name=foo/..\n").unwrap();
+
+        let removed = clear_world(&dir).unwrap();
+        let server_left = dir.join("server.properties").is_file() && dir.join("mods").is_dir();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(removed, vec!["world", "world_nether"]);
+        assert!(server_left);
+    }
+
+    #[test]
+    fn compares_release_versions() {
+        let cases = [
+            ("1.20.4", "1.21", Some(Ordering::Less)),
+            ("1.21", "1.21.0", Some(Ordering::Equal)),
+            ("1.21.1", "1.21", Some(Ordering::Greater)),
+            ("1.9", "1.10", Some(Ordering::Less)),
+            ("1.20.5", "1.20.5", Some(Ordering::Equal)),
+            ("24w14a", "1.20.5", None),
+            ("1.21-pre1", "1.21", None),
+            ("1.21", "", None),
+        ];
+        for (a, b, expected) in cases {
+            assert_eq!(compare_release_versions(a, b), expected, "{} vs {}", a, b);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::services::world_info::{compare_release_versions, world_folder};
+use crate::util::ServerPropertiesManager;
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::path::Path;
+
+/// World generator presets that can be selected from server.properties alone
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum WorldPreset {
+    Default,
+    Flat,
+    Amplified,
+    LargeBiomes,
+    /// One biome everywhere (1.13-1.15 "buffet" and 1.19+ "single_biome_surface")
+    SingleBiome,
+}
+
+/// What `apply_world_preset` wrote to server.properties
+#[derive(Debug, Clone, Serialize)]
+pub struct WorldPresetResult {
+    pub level_type: String,
+    pub generator_settings: String,
+    /// Set when a world already exists, since generator settings only affect new worlds
+    pub warning: Option<String>,
+}
+
+/// The server.properties formats differ between these version ranges
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Era {
+    /// Before 1.13: plain names such as largeBiomes and the superflat preset string
+    Legacy,
+    /// 1.13-1.15: JSON generator settings and the buffet type
+    Json,
+    /// 1.16-1.18: JSON generator settings, no single-biome type
+    NoBuffet,
+    /// 1.19+: namespaced level types
+    Namespaced,
+}
+
+fn era(minecraft_version: &str) -> Era {
+    let at_least = |v: &str| {
+        // Snapshots and unknown formats are treated as current
+        compare_release_versions(minecraft_version, v).map(|o| o != Ordering::Less).unwrap_or(true)
+    };
+    if at_least("1.19") {
+        Era::Namespaced
+    } else if at_least("1.16") {
+        Era::NoBuffet
+    } else if at_least("1.13") {
+        Era::Json
+    } else {
+        Era::Legacy
+    }
+}
+
+/// Bedrock, two dirt and grass on plains: the classic superflat
+fn flat_settings_json(biome: &str) -> String {
+    serde_json::json!({
+        "layers": [
+            { "block": "minecraft:bedrock", "height": 1 },
+            { "block": "minecraft:dirt", "height": 2 },
+            { "block": "minecraft:grass_block", "height": 1 },
+        ],
+        "biome": biome,
+    })
+    .to_string()
+}
+
+/// level-type and generator-settings values for a preset on `minecraft_version`
+pub fn preset_properties(preset: WorldPreset, minecraft_version: &str, biome: &str) -> Result<(String, String)> {
+    let era = era(minecraft_version);
+    let namespaced = era == Era::Namespaced;
+
+    let (level_type, settings) = match preset {
+        WorldPreset::Default => (if namespaced { "minecraft:normal" } else { "default" }, String::new()),
+        WorldPreset::Amplified => (if namespaced { "minecraft:amplified" } else { "amplified" }, String::new()),
+        WorldPreset::LargeBiomes => (if namespaced { "minecraft:large_biomes" } else { "largeBiomes" }, String::new()),
+        WorldPreset::Flat => match era {
+            Era::Legacy => ("flat", "3;minecraft:bedrock,2*minecraft:dirt,minecraft:grass;1;village".to_string()),
+            Era::Json | Era::NoBuffet => ("flat", flat_settings_json(biome)),
+            Era::Namespaced => ("minecraft:flat", flat_settings_json(biome)),
+        },
+        WorldPreset::SingleBiome => match era {
+            Era::Json => (
+                "buffet",
+                serde_json::json!({
+                    "biome_source": { "type": "minecraft:fixed", "options": { "biomes": [biome] } },
+                    "chunk_generator": { "type": "minecraft:surface" },
+                })
+                .to_string(),
+            ),
+            Era::Namespaced => ("minecraft:single_biome_surface", serde_json::json!({ "biome": biome }).to_string()),
+            Era::Legacy | Era::NoBuffet => {
+                return Err(anyhow!(
+                    "Minecraft {} has no single-biome world type; use a datapack instead",
+                    minecraft_version
+                ))
+            }
+        },
+    };
+
+    Ok((level_type.to_string(), settings))
+}
+
+/// Writes the preset into server.properties. The world is not touched, so a warning is
+/// returned if one already exists and would keep its original generator.
+pub fn apply_world_preset(
+    server_path: &Path,
+    minecraft_version: &str,
+    preset: WorldPreset,
+    biome: Option<&str>,
+) -> Result<WorldPresetResult> {
+    let biome = biome.map(str::trim).filter(|b| !b.is_empty()).unwrap_or("minecraft:plains");
+    let (level_type, generator_settings) = preset_properties(preset, minecraft_version, biome)?;
+
+    let manager = ServerPropertiesManager::new(server_path.join("server.properties"));
+    let mut properties = manager.load_properties()?;
+    properties.level_type = level_type.clone();
+    properties.generator_settings = generator_settings.clone();
+    manager.save_properties(&properties)?;
+
+    let world = world_folder(server_path);
+    let warning = world.join("level.dat").exists().then(|| {
+        format!(
+            "The world '{}' has already been generated; the preset only applies to a new world",
+            world.file_name().unwrap_or_default().to_string_lossy()
+        )
+    });
+
+    log::info!("Applied {:?} world preset ({}) to {:?}", preset, level_type, server_path);
+    Ok(WorldPresetResult { level_type, generator_settings, warning })
+}
@@ -0,0 +1,122 @@
+use chrono::Local;
+use log::{LevelFilter, Log, Metadata, Record};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Size at which allay.log is rotated to allay.log.1
+const MAX_LOG_SIZE_BYTES: u64 = 5 * 1024 * 1024;
+/// Number of rotated files kept next to the active log
+const MAX_ROTATED_FILES: u32 = 3;
+
+/// Path of the application log users attach to bug reports
+pub fn app_log_path() -> PathBuf {
+    PathBuf::from("storage").join("logs").join("allay.log")
+}
+
+/// Parses a level name from the settings file, falling back to Info
+pub fn parse_log_level(level: &str) -> LevelFilter {
+    match level.trim().to_lowercase().as_str() {
+        "off" => LevelFilter::Off,
+        "error" => LevelFilter::Error,
+        "warn" | "warning" => LevelFilter::Warn,
+        "debug" => LevelFilter::Debug,
+        "trace" => LevelFilter::Trace,
+        _ => LevelFilter::Info,
+    }
+}
+
+/// Writes log records to stdout and to storage/logs/allay.log with size-based rotation
+struct AppLogger {
+    log_path: PathBuf,
+    file: Mutex<Option<File>>,
+}
+
+impl AppLogger {
+    fn open(&self) -> Option<File> {
+        if let Some(parent) = self.log_path.parent() {
+            fs::create_dir_all(parent).ok()?;
+        }
+        OpenOptions::new().create(true).append(true).open(&self.log_path).ok()
+    }
+
+    /// Shifts allay.log -> allay.log.1 -> allay.log.2 ..., dropping the oldest
+    fn rotate(&self) {
+        for index in (1..MAX_ROTATED_FILES).rev() {
+            let from = self.rotated_path(index);
+            if from.exists() {
+                let _ = fs::rename(&from, self.rotated_path(index + 1));
+            }
+        }
+        let _ = fs::rename(&self.log_path, self.rotated_path(1));
+    }
+
+    fn rotated_path(&self, index: u32) -> PathBuf {
+        let mut name = self.log_path.as_os_str().to_owned();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+}
+
+impl Log for AppLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let timestamp = Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
+        let line = format!("[{}] [{}] [{}] {}\n", timestamp, record.level(), record.target(), record.args());
+        print!("{}", line);
+
+        let mut file = match self.file.lock() {
+            Ok(file) => file,
+            Err(_) => return,
+        };
+
+        let needs_rotation = file
+            .as_ref()
+            .and_then(|f| f.metadata().ok())
+            .map(|m| m.len() >= MAX_LOG_SIZE_BYTES)
+            .unwrap_or(false);
+        if needs_rotation {
+            *file = None;
+            self.rotate();
+        }
+
+        if file.is_none() {
+            *file = self.open();
+        }
+        if let Some(file) = file.as_mut() {
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(file) = file.as_mut() {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+/// Installs the global logger. Safe to call more than once; later calls only change the level.
+pub fn init_app_logger(level: LevelFilter) {
+    let logger = AppLogger {
+        log_path: app_log_path(),
+        file: Mutex::new(None),
+    };
+
+    let _ = log::set_boxed_logger(Box::new(logger));
+    log::set_max_level(level);
+}
+
+/// Changes the active log level without reinstalling the logger
+pub fn set_app_log_level(level: LevelFilter) {
+    log::set_max_level(level);
+}
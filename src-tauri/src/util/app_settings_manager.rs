@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+
+/// Lowest monitor polling interval we accept, to avoid hammering servers
+pub const MIN_MONITOR_INTERVAL_SECS: u64 = 3;
+
+/// Most Query retries we accept; attempts share one timeout budget, so each retry shortens them all
+pub const MAX_QUERY_RETRIES: u32 = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppSettings {
+    #[serde(default = "default_monitor_interval")]
+    pub monitor_interval_secs: u64,
+    /// Extra attempts for on-demand Query/ping requests before a server is reported offline
+    #[serde(default)]
+    pub query_retries: u32,
+    /// One of off, error, warn, info, debug, trace
+    #[serde(default = "default_log_level")]
+    pub log_level: String,
+    /// Serve the local HTTP control API (requires the http-api feature)
+    #[serde(default)]
+    pub http_api_enabled: bool,
+    #[serde(default = "default_http_api_bind")]
+    pub http_api_bind: String,
+    #[serde(default = "default_http_api_port")]
+    pub http_api_port: u16,
+    /// Bearer token required by every API request; generated when the API is first enabled
+    #[serde(default)]
+    pub http_api_token: Option<String>,
+    /// Status-change webhooks keyed by server name
+    #[serde(default)]
+    pub webhooks: HashMap<String, WebhookConfig>,
+    /// Launch servers on a Java runtime Allay downloads into storage/runtimes instead of the
+    /// system Java
+    #[serde(default)]
+    pub use_bundled_java: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Message template with {server}, {status} and {time} placeholders
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Statuses that trigger a notification
+    #[serde(default = "default_webhook_events")]
+    pub events: Vec<String>,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            monitor_interval_secs: default_monitor_interval(),
+            query_retries: 0,
+            log_level: default_log_level(),
+            http_api_enabled: false,
+            http_api_bind: default_http_api_bind(),
+            http_api_port: default_http_api_port(),
+            http_api_token: None,
+            webhooks: HashMap::new(),
+            use_bundled_java: false,
+        }
+    }
+}
+
+fn default_monitor_interval() -> u64 {
+    15
+}
+
+fn default_log_level() -> String {
+    "info".to_string()
+}
+
+pub fn default_webhook_events() -> Vec<String> {
+    vec!["online".to_string(), "offline".to_string(), "crashed".to_string()]
+}
+
+fn default_http_api_bind() -> String {
+    "127.0.0.1".to_string()
+}
+
+fn default_http_api_port() -> u16 {
+    7878
+}
+
+/// Clamp a requested monitor interval to the allowed minimum
+pub fn clamp_monitor_interval(secs: u64) -> u64 {
+    secs.max(MIN_MONITOR_INTERVAL_SECS)
+}
+
+/// Clamp a requested Query retry count to the allowed maximum
+pub fn clamp_query_retries(retries: u32) -> u32 {
+    retries.min(MAX_QUERY_RETRIES)
+}
+
+pub struct AppSettingsManager {
+    settings_path: PathBuf,
+}
+
+impl AppSettingsManager {
+    pub fn new(settings_path: PathBuf) -> Self {
+        Self { settings_path }
+    }
+
+    pub fn load_settings(&self) -> Result<AppSettings, Error> {
+        if !self.settings_path.exists() {
+            return Ok(AppSettings::default());
+        }
+
+        let content = fs::read_to_string(&self.settings_path)?;
+        let content = content.trim();
+
+        if content.is_empty() {
+            return Ok(AppSettings::default());
+        }
+
+        serde_json::from_str(content)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, format!("Failed to parse app settings: {}", e)))
+    }
+
+    pub fn save_settings(&self, settings: &AppSettings) -> Result<(), Error> {
+        if let Some(parent) = self.settings_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = serde_json::to_string_pretty(settings)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+        fs::write(&self.settings_path, content)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,444 @@
+use crate::services::world_info::{clear_world, read_world_version, world_folder, world_folders};
+use crate::util::BackupRetention;
+use anyhow::{anyhow, Context, Result};
+use chrono::{DateTime, Local, Utc};
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Server files small enough to always back up; they hold the server's players and settings
+const CONFIG_FILES: [&str; 7] = [
+    "server.properties",
+    "eula.txt",
+    "ops.json",
+    "whitelist.json",
+    "banned-players.json",
+    "banned-ips.json",
+    "usercache.json",
+];
+
+/// Sidecar files next to each archive hold its metadata
+const METADATA_EXTENSION: &str = "json";
+
+lazy_static! {
+    /// Archives being restored right now; pruning leaves them alone
+    static ref RESTORING: Mutex<HashSet<PathBuf>> = Mutex::new(HashSet::new());
+}
+
+/// What a backup holds, written next to the archive as <archive>.json when it is created
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BackupMetadata {
+    created_at: DateTime<Utc>,
+    includes_world: bool,
+    /// Game version the world was last saved with, from level.dat
+    world_version: Option<String>,
+    label: Option<String>,
+}
+
+/// A backup archive in storage/backups/<server>
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupInfo {
+    pub file_name: String,
+    pub path: PathBuf,
+    pub created_at: DateTime<Utc>,
+    pub size_bytes: u64,
+    /// False for config-only backups
+    pub includes_world: bool,
+    pub world_version: Option<String>,
+    pub label: Option<String>,
+}
+
+/// Payload of the "backup-completed" event sent after a scheduled backup
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupCompletedEvent {
+    pub server_name: String,
+    pub backup: Option<BackupInfo>,
+    pub error: Option<String>,
+}
+
+/// Outcome of restoring a backup
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreResult {
+    pub restored: BackupInfo,
+    /// The backup of the server as it was before the restore
+    pub previous_state: BackupInfo,
+}
+
+/// Outcome of clearing a server's world
+#[derive(Debug, Clone, Serialize)]
+pub struct ClearWorldResult {
+    pub removed_folders: Vec<String>,
+    /// The backup taken right before the folders were deleted
+    pub backup: BackupInfo,
+}
+
+/// Zips a server's world and configuration into storage/backups/<server>/
+pub struct BackupManager {
+    backups_dir: PathBuf,
+}
+
+impl BackupManager {
+    pub fn new(backups_dir: PathBuf) -> Self {
+        Self { backups_dir }
+    }
+
+    pub fn server_backups_dir(&self, server_name: &str) -> PathBuf {
+        self.backups_dir.join(server_name)
+    }
+
+    /// Archives the config files and, with `include_world`, every world folder of the server.
+    /// `label` ends up in the file name so backups taken for a reason are easy to spot.
+    /// The server should be stopped (or have saving turned off) so the world is consistent.
+    pub fn create_backup(&self, server_name: &str, server_path: &Path, include_world: bool, label: &str) -> Result<BackupInfo> {
+        let dir = self.server_backups_dir(server_name);
+        fs::create_dir_all(&dir)?;
+
+        let stamp = Local::now().format("%Y-%m-%d_%H-%M-%S");
+        let base_name = if label.is_empty() {
+            stamp.to_string()
+        } else {
+            format!("{}-{}", stamp, label)
+        };
+        // Build under a temporary name so a failed backup never looks like a complete one
+        let (file_name, partial) = reserve_backup_name(&dir, &base_name)?;
+        let path = dir.join(&file_name);
+        if let Err(e) = write_archive(&partial, server_path, include_world) {
+            let _ = fs::remove_file(&partial);
+            return Err(e);
+        }
+        fs::rename(&partial, &path)?;
+
+        let metadata = BackupMetadata {
+            created_at: Utc::now(),
+            includes_world: include_world,
+            world_version: read_world_version(&world_folder(server_path)).ok().and_then(|version| version.name),
+            label: (!label.is_empty()).then(|| label.to_string()),
+        };
+        if let Err(e) = fs::write(metadata_path(&path), serde_json::to_string_pretty(&metadata)?) {
+            log::warn!("Failed to write the metadata of backup {}: {}", file_name, e);
+        }
+
+        let info = backup_info(&path)?;
+        log::info!("Backed up {} to {:?} ({} bytes)", server_name, path, info.size_bytes);
+        Ok(info)
+    }
+
+    /// The server's backups, newest first
+    pub fn list_backups(&self, server_name: &str) -> Result<Vec<BackupInfo>> {
+        let dir = self.server_backups_dir(server_name);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+
+        let mut backups: Vec<BackupInfo> = fs::read_dir(&dir)?
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "zip"))
+            .filter_map(|path| backup_info(&path).ok())
+            .collect();
+        backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+        Ok(backups)
+    }
+
+    /// Deletes the backups `retention` doesn't keep and returns their file names. A backup
+    /// being restored is never deleted, even when it is past the limits.
+    pub fn prune_backups(&self, server_name: &str, retention: &BackupRetention) -> Result<Vec<String>> {
+        let backups = self.list_backups(server_name)?;
+        let cutoff = retention.keep_days.map(|days| Utc::now() - chrono::Duration::days(days as i64));
+        let restoring = RESTORING.lock().unwrap().clone();
+
+        let mut pruned = Vec::new();
+        for (index, backup) in backups.iter().enumerate() {
+            let over_count = retention.keep_last.is_some_and(|keep| index >= keep as usize);
+            let too_old = cutoff.is_some_and(|cutoff| backup.created_at < cutoff);
+            if !(over_count || too_old) {
+                continue;
+            }
+            if restoring.contains(&backup.path) {
+                log::info!("Keeping backup {} of {} while it is being restored", backup.file_name, server_name);
+                continue;
+            }
+
+            fs::remove_file(&backup.path)?;
+            let _ = fs::remove_file(metadata_path(&backup.path));
+            pruned.push(backup.file_name.clone());
+        }
+
+        if !pruned.is_empty() {
+            log::info!("Pruned {} backup(s) of {}: {:?}", pruned.len(), server_name, pruned);
+        }
+        Ok(pruned)
+    }
+
+    /// Puts the files of a backup back into a stopped server. The current state is backed up
+    /// first (and retention applied, which skips the backup being restored). When the backup
+    /// includes the world, the current world folders are removed so no stale region files remain.
+    pub fn restore_backup(
+        &self,
+        server_name: &str,
+        file_name: &str,
+        server_path: &Path,
+        retention: &BackupRetention,
+    ) -> Result<RestoreResult> {
+        if !Path::new(file_name).components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(anyhow!("Invalid backup name: {}", file_name));
+        }
+        let path = self.server_backups_dir(server_name).join(file_name);
+        let restored = backup_info(&path).map_err(|_| anyhow!("Backup {} of {} not found", file_name, server_name))?;
+
+        let _restoring = RestoringGuard::new(&path);
+        let previous_state = self
+            .create_backup(server_name, server_path, true, "before-restore")
+            .map_err(|e| anyhow!("Backup of the current state failed, nothing was restored: {}", e))?;
+        if let Err(e) = self.prune_backups(server_name, retention) {
+            log::warn!("Failed to prune backups of {}: {}", server_name, e);
+        }
+
+        if restored.includes_world {
+            clear_world(server_path)?;
+        }
+        extract_archive(&path, server_path)?;
+
+        log::info!("Restored {} from backup {}", server_name, file_name);
+        Ok(RestoreResult { restored, previous_state })
+    }
+}
+
+/// Claims "<base_name>.zip" in `dir` by creating its .part file, or "<base_name>-2.zip" and so
+/// on when a backup was already taken within the same second
+fn reserve_backup_name(dir: &Path, base_name: &str) -> Result<(String, PathBuf)> {
+    for attempt in 1..=100 {
+        let file_name = if attempt == 1 {
+            format!("{}.zip", base_name)
+        } else {
+            format!("{}-{}.zip", base_name, attempt)
+        };
+        if dir.join(&file_name).exists() {
+            continue;
+        }
+        let partial = dir.join(format!("{}.part", file_name));
+        match OpenOptions::new().write(true).create_new(true).open(&partial) {
+            Ok(_) => return Ok((file_name, partial)),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(anyhow!("Too many backups named {} already exist", base_name))
+}
+
+/// Marks an archive as being restored until dropped
+struct RestoringGuard(PathBuf);
+
+impl RestoringGuard {
+    fn new(path: &Path) -> Self {
+        RESTORING.lock().unwrap().insert(path.to_path_buf());
+        Self(path.to_path_buf())
+    }
+}
+
+impl Drop for RestoringGuard {
+    fn drop(&mut self) {
+        RESTORING.lock().unwrap().remove(&self.0);
+    }
+}
+
+fn metadata_path(archive_path: &Path) -> PathBuf {
+    let mut name = archive_path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(METADATA_EXTENSION);
+    PathBuf::from(name)
+}
+
+fn backup_info(path: &Path) -> Result<BackupInfo> {
+    let file_metadata = fs::metadata(path)?;
+    let metadata = fs::read_to_string(metadata_path(path))
+        .ok()
+        .and_then(|content| serde_json::from_str::<BackupMetadata>(&content).ok());
+
+    let (created_at, includes_world, world_version, label) = match metadata {
+        Some(metadata) => (metadata.created_at, metadata.includes_world, metadata.world_version, metadata.label),
+        // Without a sidecar, fall back to what the archive itself shows
+        None => (
+            file_metadata.modified().map(DateTime::<Utc>::from).unwrap_or_else(|_| Utc::now()),
+            archive_has_folders(path),
+            None,
+            None,
+        ),
+    };
+
+    Ok(BackupInfo {
+        file_name: path.file_name().unwrap_or_default().to_string_lossy().to_string(),
+        path: path.to_path_buf(),
+        created_at,
+        size_bytes: file_metadata.len(),
+        includes_world,
+        world_version,
+        label,
+    })
+}
+
+/// Config files sit at the top of an archive, so any nested entry is part of a world
+fn archive_has_folders(path: &Path) -> bool {
+    File::open(path)
+        .ok()
+        .and_then(|file| ZipArchive::new(file).ok())
+        .is_some_and(|archive| archive.file_names().any(|name| name.contains('/')))
+}
+
+fn extract_archive(archive_path: &Path, server_path: &Path) -> Result<()> {
+    let mut archive = ZipArchive::new(File::open(archive_path)?)?;
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        // Reject entries that would escape the server folder
+        let relative = entry
+            .enclosed_name()
+            .ok_or_else(|| anyhow!("Backup contains an unsafe path: {}", entry.name()))?;
+
+        let target = server_path.join(relative);
+        if entry.is_dir() {
+            fs::create_dir_all(&target)?;
+        } else {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            std::io::copy(&mut entry, &mut File::create(&target)?)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_archive(archive_path: &Path, server_path: &Path, include_world: bool) -> Result<()> {
+    let file = File::create(archive_path)
+        .with_context(|| format!("Failed to create backup file {:?}", archive_path))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .large_file(true);
+
+    for name in CONFIG_FILES {
+        let path = server_path.join(name);
+        if path.is_file() {
+            zip.start_file(name, options)?;
+            std::io::copy(&mut File::open(&path)?, &mut zip)?;
+        }
+    }
+
+    if include_world {
+        for folder in world_folders(server_path) {
+            add_directory(&mut zip, server_path, &folder, options)?;
+        }
+    }
+
+    zip.finish()?;
+    Ok(())
+}
+
+fn add_directory(zip: &mut ZipWriter<File>, root: &Path, dir: &Path, options: SimpleFileOptions) -> Result<()> {
+    for entry in fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        let relative = path.strip_prefix(root)?.to_string_lossy().replace('\\', "/");
+
+        if path.is_dir() {
+            zip.add_directory(format!("{}/", relative), options)?;
+            add_directory(zip, root, &path, options)?;
+        } else if entry.file_name() != "session.lock" {
+            // session.lock is held open by a running server and is recreated on start
+            zip.start_file(relative, options)?;
+            std::io::copy(&mut File::open(&path)?, zip)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A server folder with server.properties and a one-file world, and an empty backups folder
+    fn setup(test_name: &str) -> (PathBuf, PathBuf, BackupManager) {
+        let dir = std::env::temp_dir().join(format!("allay-backups-{}-{}", test_name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let server_path = dir.join("server");
+        fs::create_dir_all(server_path.join("world/region")).unwrap();
+        fs::write(server_path.join("server.properties"), "level-name=world\nmotd=original\n").unwrap();
+        fs::write(server_path.join("world/region/r.0.0.mca"), "original terrain").unwrap();
+        let manager = BackupManager::new(dir.join("backups"));
+        (dir, server_path, manager)
+    }
+
+    fn set_created_at(backup: &BackupInfo, created_at: DateTime<Utc>) {
+        let metadata_path = metadata_path(&backup.path);
+        let mut metadata: BackupMetadata = serde_json::from_str(&fs::read_to_string(&metadata_path).unwrap()).unwrap();
+        metadata.created_at = created_at;
+        fs::write(metadata_path, serde_json::to_string(&metadata).unwrap()).unwrap();
+    }
+
+    #[test]
+    fn backups_in_the_same_second_get_distinct_names() {
+        let (dir, server_path, manager) = setup("names");
+        let first = manager.create_backup("test", &server_path, false, "manual").unwrap();
+        let second = manager.create_backup("test", &server_path, false, "manual").unwrap();
+        let listed = manager.list_backups("test").unwrap().len();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_ne!(first.file_name, second.file_name);
+        assert_eq!(listed, 2);
+    }
+
+    #[test]
+    fn prunes_by_count_and_age_but_keeps_backups_being_restored() {
+        let (dir, server_path, manager) = setup("prune");
+        let backups: Vec<BackupInfo> = (0..4)
+            .map(|_| manager.create_backup("test", &server_path, false, "").unwrap())
+            .collect();
+        for (days_old, backup) in [30, 20, 2, 1].iter().zip(&backups) {
+            set_created_at(backup, Utc::now() - chrono::Duration::days(*days_old));
+        }
+
+        let mut by_age = manager.prune_backups("test", &BackupRetention { keep_last: None, keep_days: Some(7) }).unwrap();
+        let oldest_left = manager.list_backups("test").unwrap().last().unwrap().file_name.clone();
+
+        let _restoring = RestoringGuard::new(&backups[2].path);
+        let by_count = manager.prune_backups("test", &BackupRetention { keep_last: Some(1), keep_days: None }).unwrap();
+        let left = manager.list_backups("test").unwrap().len();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let mut expected_by_age = vec![backups[0].file_name.clone(), backups[1].file_name.clone()];
+        expected_by_age.sort();
+        by_age.sort();
+        assert_eq!(by_age, expected_by_age);
+        assert_eq!(oldest_left, backups[2].file_name);
+        // Only the newest is within keep_last; the older one is being restored
+        assert!(by_count.is_empty());
+        assert_eq!(left, 2);
+    }
+
+    #[test]
+    fn restore_puts_the_world_back_and_keeps_the_current_state() {
+        let (dir, server_path, manager) = setup("restore");
+        let backup = manager.create_backup("test", &server_path, true, "").unwrap();
+
+        fs::write(server_path.join("server.properties"), "level-name=world\nmotd=changed\n").unwrap();
+        fs::write(server_path.join("world/region/r.0.0.mca"), "changed terrain").unwrap();
+        fs::write(server_path.join("world/region/r.1.0.mca"), "new region").unwrap();
+
+        let result = manager.restore_backup("test", &backup.file_name, &server_path, &BackupRetention::default()).unwrap();
+        let terrain = fs::read_to_string(server_path.join("world/region/r.0.0.mca")).unwrap();
+        let properties = fs::read_to_string(server_path.join("server.properties")).unwrap();
+        let stale_region = server_path.join("world/region/r.1.0.mca").exists();
+        let escape = manager.restore_backup("test", "../elsewhere.zip", &server_path, &BackupRetention::default());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(result.restored.file_name, backup.file_name);
+        assert_eq!(result.previous_state.label.as_deref(), Some("before-restore"));
+        assert_eq!(terrain, "original terrain");
+        assert!(properties.contains("motd=original"));
+        assert!(!stale_region);
+        assert!(escape.is_err());
+    }
+}
@@ -0,0 +1,59 @@
+use std::io::Error;
+use std::path::Path;
+use sysinfo::Disks;
+
+/// Space reserved for downloading a server JAR or installer into the cache
+pub const DOWNLOAD_SPACE_BYTES: u64 = 256 * 1024 * 1024;
+/// Space reserved for installing a loader and generating the initial world
+pub const SETUP_SPACE_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// Returns the free space in bytes on the filesystem that holds `path`.
+/// Paths that don't exist yet are resolved through their closest existing parent.
+pub fn available_space(path: &Path) -> Option<u64> {
+    let absolute = std::env::current_dir().ok()?.join(path);
+
+    let mut existing = absolute.as_path();
+    while !existing.exists() {
+        existing = existing.parent()?;
+    }
+    let existing = existing.canonicalize().ok()?;
+
+    let disks = Disks::new_with_refreshed_list();
+    disks
+        .list()
+        .iter()
+        .filter(|disk| existing.starts_with(disk.mount_point()))
+        .max_by_key(|disk| disk.mount_point().as_os_str().len())
+        .map(|disk| disk.available_space())
+}
+
+/// Errors early when the filesystem holding `path` has less than `required_bytes` free.
+/// If free space can't be measured the check is skipped rather than blocking the user.
+pub fn ensure_free_space(path: &Path, required_bytes: u64) -> Result<(), Error> {
+    match available_space(path) {
+        Some(available) if available < required_bytes => Err(Error::other(format!(
+            "Not enough disk space: {} needed but only {} available",
+            format_bytes(required_bytes),
+            format_bytes(available)
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Formats a byte count as a human readable size (e.g. "1.5 GB")
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
@@ -1,8 +1,39 @@
 use crate::models::version::LoaderType;
+use crate::services::paper_strategy::PaperStrategy;
+use crate::services::spigot_strategy::SpigotStrategy;
+use crate::services::resumable_download::sha256_file;
 use anyhow::{Result, anyhow};
+use lazy_static::lazy_static;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::OwnedMutexGuard;
 
+lazy_static! {
+    /// One lock per cache entry in use, shared by every JarCacheManager
+    static ref ENTRY_LOCKS: Mutex<HashMap<PathBuf, Arc<tokio::sync::Mutex<()>>>> = Mutex::new(HashMap::new());
+}
+
+/// Holds a cache entry returned by [`JarCacheManager::lock_entry`]. Once nobody holds or waits
+/// for the entry any more its lock is dropped, so the lock table only grows with concurrent use.
+pub struct CacheEntryGuard {
+    cache_subdir: PathBuf,
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl Drop for CacheEntryGuard {
+    fn drop(&mut self) {
+        // Release the entry first; waiters keep their own reference to the lock
+        self.guard.take();
+        let mut locks = ENTRY_LOCKS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if locks.get(&self.cache_subdir).is_some_and(|lock| Arc::strong_count(lock) == 1) {
+            locks.remove(&self.cache_subdir);
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct JarCacheManager {
     cache_dir: PathBuf,
 }
@@ -53,11 +84,20 @@ impl JarCacheManager {
                 };
                 format!("neoforge-{}", clean_version)
             }
-            LoaderType::Paper => format!("paper-{}", minecraft_version),
+            // Pinned builds are cached separately from the rolling "latest" download
+            LoaderType::Paper => match PaperStrategy::pinned_build(loader_version.unwrap_or("")) {
+                Some(build) => format!("paper-{}-{}", minecraft_version, build),
+                None => format!("paper-{}", minecraft_version),
+            },
             LoaderType::Quilt => {
                 let loader_ver = loader_version.unwrap_or("unknown");
                 format!("quilt-{}-{}", minecraft_version, loader_ver)
             }
+            LoaderType::Spigot => format!(
+                "{}-{}",
+                SpigotStrategy::variant(loader_version.unwrap_or("")),
+                minecraft_version
+            ),
         }
     }
 
@@ -103,11 +143,19 @@ impl JarCacheManager {
                     format!("neoforge-{}-installer.jar", loader_ver)
                 }
             }
-            LoaderType::Paper => format!("paper-{}.jar", minecraft_version),
+            LoaderType::Paper => match PaperStrategy::pinned_build(loader_version.unwrap_or("")) {
+                Some(build) => format!("paper-{}-{}.jar", minecraft_version, build),
+                None => format!("paper-{}.jar", minecraft_version),
+            },
             LoaderType::Quilt => {
                 // Quilt downloads the server profile JSON first
                 "quilt-server-profile.json".to_string()
             }
+            LoaderType::Spigot => format!(
+                "{}-{}.jar",
+                SpigotStrategy::variant(loader_version.unwrap_or("")),
+                minecraft_version
+            ),
         }
     }
 
@@ -134,30 +182,101 @@ impl JarCacheManager {
         cached_path.exists()
     }
 
-    /// Store a JAR file in cache
-    pub fn cache_jar(
+    /// Waits until no one else is filling or reading this cache entry. Server creations and
+    /// updates download without holding the service lock, so two of them can want the same
+    /// version at once; hold the guard from the cache check until the JAR is copied out and
+    /// the version is downloaded once while the rest copy the finished file.
+    pub async fn lock_entry(
+        &self,
+        loader: &LoaderType,
+        minecraft_version: &str,
+        loader_version: Option<&str>,
+    ) -> CacheEntryGuard {
+        let cache_subdir = self.cache_dir.join(self.get_jar_cache_key(loader, minecraft_version, loader_version));
+        let lock = ENTRY_LOCKS
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(cache_subdir.clone())
+            .or_default()
+            .clone();
+        CacheEntryGuard {
+            cache_subdir,
+            guard: Some(lock.lock_owned().await),
+        }
+    }
+
+    /// Create the cache folder for a JAR and return the path it should be written to
+    pub fn prepare_cache_entry(
         &self,
         loader: &LoaderType,
         minecraft_version: &str,
         loader_version: Option<&str>,
-        jar_data: &[u8],
     ) -> Result<PathBuf> {
-        let cache_key = self.get_jar_cache_key(loader, minecraft_version, loader_version);
-        let cache_subdir = self.cache_dir.join(&cache_key);
-        
-        // Create subdirectory for this specific version
-        if !cache_subdir.exists() {
-            fs::create_dir_all(&cache_subdir)?;
+        let cached_path = self.get_cached_jar_path(loader, minecraft_version, loader_version);
+        if let Some(cache_subdir) = cached_path.parent() {
+            fs::create_dir_all(cache_subdir)?;
         }
+        Ok(cached_path)
+    }
 
-        let filename = self.get_jar_filename(loader, minecraft_version, loader_version);
-        let cached_path = cache_subdir.join(&filename);
+    /// Stores the SHA-256 of a freshly cached file in a `.sha256` sidecar next to it, so
+    /// audit_cache can later tell a good file from one damaged on disk. Failing to write the
+    /// sidecar only costs the audit, so it is logged rather than returned.
+    pub fn record_checksum(&self, cached_path: &Path) {
+        let result = sha256_file(cached_path)
+            .and_then(|hash| Ok(fs::write(checksum_path(cached_path), format!("{}\n", hash))?));
+        if let Err(e) = result {
+            log::warn!("Failed to record the checksum of {:?}: {}", cached_path, e);
+        }
+    }
 
-        // Write the JAR data to cache
-        fs::write(&cached_path, jar_data)?;
+    /// Re-hashes every cached JAR against its sidecar
+    pub fn audit_cache(&self) -> Result<Vec<CacheAuditEntry>> {
+        let mut results = Vec::new();
+        if !self.cache_dir.exists() {
+            return Ok(results);
+        }
 
-        println!("JAR cached successfully: {:?}", cached_path);
-        Ok(cached_path)
+        for entry in fs::read_dir(&self.cache_dir)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let key = entry.file_name().to_string_lossy().to_string();
+
+            for file in fs::read_dir(entry.path())? {
+                let path = file?.path();
+                let file_name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+                if !path.is_file() || !file_name.ends_with(".jar") {
+                    continue;
+                }
+
+                let expected = fs::read_to_string(checksum_path(&path))
+                    .ok()
+                    .map(|content| content.trim().to_lowercase());
+                let (status, actual) = match (&expected, sha256_file(&path)) {
+                    (_, Err(e)) => {
+                        log::warn!("Failed to hash cached {:?}: {}", path, e);
+                        (CacheAuditStatus::Unreadable, None)
+                    }
+                    (None, Ok(actual)) => (CacheAuditStatus::MissingChecksum, Some(actual)),
+                    (Some(expected), Ok(actual)) if *expected == actual => (CacheAuditStatus::Ok, Some(actual)),
+                    (Some(_), Ok(actual)) => (CacheAuditStatus::Corrupted, Some(actual)),
+                };
+
+                results.push(CacheAuditEntry {
+                    key: key.clone(),
+                    file_name,
+                    passed: status == CacheAuditStatus::Ok,
+                    status,
+                    expected_sha256: expected,
+                    actual_sha256: actual,
+                });
+            }
+        }
+
+        results.sort_by(|a, b| (&a.key, &a.file_name).cmp(&(&b.key, &b.file_name)));
+        Ok(results)
     }
 
     /// Copy a cached JAR to a server directory
@@ -185,7 +304,7 @@ impl JarCacheManager {
         // Copy the cached JAR to the server directory
         fs::copy(&cached_path, &server_jar_path)?;
 
-        println!("JAR copied from cache to server: {:?} -> {:?}", cached_path, server_jar_path);
+        log::debug!("JAR copied from cache to server: {:?} -> {:?}", cached_path, server_jar_path);
         Ok(server_jar_path)
     }
 
@@ -218,7 +337,7 @@ impl JarCacheManager {
         
         if cache_subdir.exists() {
             fs::remove_dir_all(&cache_subdir)?;
-            println!("Cleared JAR cache for: {}", cache_key);
+            log::info!("Cleared JAR cache for: {}", cache_key);
         }
         
         Ok(())
@@ -229,50 +348,165 @@ impl JarCacheManager {
         if self.cache_dir.exists() {
             fs::remove_dir_all(&self.cache_dir)?;
             fs::create_dir_all(&self.cache_dir)?;
-            println!("Cleared all JAR cache");
+            log::info!("Cleared all JAR cache");
         }
         Ok(())
     }
 
-    /// Get cache statistics
-    pub fn get_cache_stats(&self) -> Result<CacheStats> {
-        let mut stats = CacheStats {
-            total_cached_jars: 0,
-            total_cache_size: 0,
-            cached_loaders: Vec::new(),
-        };
-
+    /// Every cache entry (one folder per cache key) with the JARs it holds and its size
+    pub fn list_cache_entries(&self) -> Result<Vec<CacheEntry>> {
+        let mut entries = Vec::new();
         if !self.cache_dir.exists() {
-            return Ok(stats);
+            return Ok(entries);
         }
 
         for entry in fs::read_dir(&self.cache_dir)? {
             let entry = entry?;
-            if entry.file_type()?.is_dir() {
-                let dir_name = entry.file_name().to_string_lossy().to_string();
-                stats.cached_loaders.push(dir_name);
-
-                // Count JARs in this subdirectory
-                for jar_entry in fs::read_dir(entry.path())? {
-                    let jar_entry = jar_entry?;
-                    if jar_entry.file_type()?.is_file() && 
-                       jar_entry.file_name().to_string_lossy().ends_with(".jar") {
-                        stats.total_cached_jars += 1;
-                        if let Ok(metadata) = jar_entry.metadata() {
-                            stats.total_cache_size += metadata.len();
-                        }
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+
+            let mut cache_entry = CacheEntry {
+                key: entry.file_name().to_string_lossy().to_string(),
+                jar_count: 0,
+                size_bytes: 0,
+            };
+            for file in fs::read_dir(entry.path())? {
+                let file = file?;
+                if !file.file_type()?.is_file() {
+                    continue;
+                }
+                if file.file_name().to_string_lossy().ends_with(".jar") {
+                    cache_entry.jar_count += 1;
+                    if let Ok(metadata) = file.metadata() {
+                        cache_entry.size_bytes += metadata.len();
                     }
                 }
             }
+            entries.push(cache_entry);
+        }
+
+        entries.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(entries)
+    }
+
+    /// Cache entries whose key isn't in `referenced_keys`, i.e. that no server runs
+    pub fn find_orphaned_entries(&self, referenced_keys: &HashSet<String>) -> Result<Vec<CacheEntry>> {
+        Ok(self
+            .list_cache_entries()?
+            .into_iter()
+            .filter(|entry| !referenced_keys.contains(&entry.key))
+            .collect())
+    }
+
+    /// Deletes the orphaned entries and returns them. Entries being downloaded or copied right
+    /// now are skipped, since a server is about to reference them.
+    pub fn clear_orphaned_entries(&self, referenced_keys: &HashSet<String>) -> Result<Vec<CacheEntry>> {
+        let mut cleared = Vec::new();
+        for entry in self.find_orphaned_entries(referenced_keys)? {
+            let cache_subdir = self.cache_dir.join(&entry.key);
+            let lock = ENTRY_LOCKS.lock().unwrap().get(&cache_subdir).cloned();
+            let _guard = match &lock {
+                Some(lock) => match lock.try_lock() {
+                    Ok(guard) => Some(guard),
+                    Err(_) => {
+                        log::debug!("Skipping cache entry in use: {}", entry.key);
+                        continue;
+                    }
+                },
+                None => None,
+            };
+
+            fs::remove_dir_all(&cache_subdir)?;
+            log::info!("Cleared orphaned JAR cache: {}", entry.key);
+            cleared.push(entry);
         }
+        Ok(cleared)
+    }
 
-        Ok(stats)
+    /// Get cache statistics
+    pub fn get_cache_stats(&self) -> Result<CacheStats> {
+        let entries = self.list_cache_entries()?;
+        Ok(CacheStats {
+            total_cached_jars: entries.iter().map(|entry| entry.jar_count).sum(),
+            total_cache_size: entries.iter().map(|entry| entry.size_bytes).sum(),
+            cached_loaders: entries.into_iter().map(|entry| entry.key).collect(),
+        })
     }
 }
 
+/// Path of the `.sha256` sidecar of a cached file
+pub fn checksum_path(cached_path: &Path) -> PathBuf {
+    let mut name = cached_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".sha256");
+    cached_path.with_file_name(name)
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheAuditStatus {
+    Ok,
+    /// The JAR no longer matches the hash recorded when it was cached
+    Corrupted,
+    /// Cached before checksums were recorded, so it can't be checked
+    MissingChecksum,
+    Unreadable,
+}
+
+/// The audit result of one cached JAR
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CacheAuditEntry {
+    pub key: String,
+    pub file_name: String,
+    pub passed: bool,
+    pub status: CacheAuditStatus,
+    pub expected_sha256: Option<String>,
+    pub actual_sha256: Option<String>,
+}
+
+/// One folder of the JAR cache
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct CacheEntry {
+    /// The cache key, e.g. "fabric-1.20.4-0.15.7"
+    pub key: String,
+    pub jar_count: usize,
+    /// Size of the JARs in the entry
+    pub size_bytes: u64,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CacheStats {
     pub total_cached_jars: usize,
     pub total_cache_size: u64,
     pub cached_loaders: Vec<String>,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn entry_locks_are_exclusive_and_forgotten_once_released() {
+        let dir = std::env::temp_dir().join(format!("allay-jar-cache-lock-{}", std::process::id()));
+        let cache = JarCacheManager::new(dir.clone()).unwrap();
+        let key = dir.join("jars").join(cache.get_jar_cache_key(&LoaderType::Paper, "1.21.1", Some("130")));
+
+        let first = cache.lock_entry(&LoaderType::Paper, "1.21.1", Some("130")).await;
+        let waiting = {
+            let cache = cache.clone();
+            tokio::spawn(async move { cache.lock_entry(&LoaderType::Paper, "1.21.1", Some("130")).await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!waiting.is_finished());
+
+        // Still referenced by the waiter, so it stays in the table
+        drop(first);
+        let second = waiting.await.unwrap();
+        assert!(ENTRY_LOCKS.lock().unwrap().contains_key(&key));
+
+        drop(second);
+        assert!(!ENTRY_LOCKS.lock().unwrap().contains_key(&key));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,12 +1,26 @@
+pub mod app_logger;
+pub mod app_settings_manager;
+pub mod backup_manager;
+pub mod disk_space;
 pub mod file_manager_trait;
 pub mod jar_cache_manager;
+pub mod network;
+pub mod process_state_manager;
+pub mod property_schema;
 pub mod rcon_logger;
 pub mod server_file_manager;
 pub mod server_properties_manager;
 pub mod version_cache_manager;
 
+pub use app_logger::*;
+pub use app_settings_manager::*;
+pub use backup_manager::*;
+pub use disk_space::*;
 pub use file_manager_trait::*;
 pub use jar_cache_manager::*;
+pub use network::*;
+pub use process_state_manager::*;
+pub use property_schema::*;
 pub use rcon_logger::*;
 pub use server_file_manager::*;
 pub use server_properties_manager::*;
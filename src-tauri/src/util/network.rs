@@ -0,0 +1,41 @@
+use std::io::{Error, ErrorKind};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+
+/// "host:port" for display and for `ToSocketAddrs`, with IPv6 literals bracketed ("[::1]:25575")
+pub fn format_socket_address(host: &str, port: u16) -> String {
+    let host = host.trim();
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V6(ip)) => format!("[{}]:{}", ip, port),
+        _ => format!("{}:{}", host, port),
+    }
+}
+
+/// Resolves a host (IPv4, IPv6 with or without brackets, or a name) and port to a socket address
+pub fn resolve_socket_address(host: &str, port: u16) -> Result<SocketAddr, Error> {
+    let host = host.trim();
+    let host = host.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(host);
+    (host, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| Error::new(ErrorKind::AddrNotAvailable, format!("No address found for {}", host)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn brackets_ipv6_hosts() {
+        assert_eq!(format_socket_address("::1", 25575), "[::1]:25575");
+        assert_eq!(format_socket_address("127.0.0.1", 25575), "127.0.0.1:25575");
+        assert_eq!(format_socket_address("mc.example.com", 25565), "mc.example.com:25565");
+    }
+
+    #[test]
+    fn resolves_ipv6_literals_with_or_without_brackets() {
+        let expected: SocketAddr = "[2001:db8::1]:25575".parse().unwrap();
+        assert_eq!(resolve_socket_address("2001:db8::1", 25575).unwrap(), expected);
+        assert_eq!(resolve_socket_address("[2001:db8::1]", 25575).unwrap(), expected);
+        assert!(resolve_socket_address("127.0.0.1", 25575).unwrap().is_ipv4());
+    }
+}
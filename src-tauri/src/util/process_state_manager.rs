@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+
+/// A server process started by Allay, persisted so it can be re-attached after an app restart
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackedProcess {
+    pub server_name: String,
+    pub pid: u32,
+    /// Process start time in seconds since the Unix epoch, used to detect PID reuse
+    pub started_at: u64,
+}
+
+pub struct ProcessStateManager {
+    state_path: PathBuf,
+}
+
+impl ProcessStateManager {
+    pub fn new(state_path: PathBuf) -> Self {
+        Self { state_path }
+    }
+
+    pub fn load_processes(&self) -> Result<Vec<TrackedProcess>, Error> {
+        if !self.state_path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.state_path)?;
+        let content = content.trim();
+
+        if content.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        serde_json::from_str(content)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, format!("Failed to parse process state: {}", e)))
+    }
+
+    pub fn save_processes(&self, processes: &[TrackedProcess]) -> Result<(), Error> {
+        if let Some(parent) = self.state_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = serde_json::to_string_pretty(processes)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+
+        fs::write(&self.state_path, content)?;
+        Ok(())
+    }
+
+    /// Records a started process, replacing any stale entry for the same server
+    pub fn track_process(&self, process: TrackedProcess) -> Result<(), Error> {
+        let mut processes = self.load_processes().unwrap_or_default();
+        processes.retain(|p| p.server_name != process.server_name);
+        processes.push(process);
+        self.save_processes(&processes)
+    }
+
+    pub fn untrack_process(&self, server_name: &str) -> Result<(), Error> {
+        let mut processes = self.load_processes().unwrap_or_default();
+        let before = processes.len();
+        processes.retain(|p| p.server_name != server_name);
+
+        if processes.len() != before {
+            self.save_processes(&processes)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,107 @@
+use crate::util::ServerProperties;
+use serde::Serialize;
+
+/// Value type of a server.properties key, as the settings editor should render it
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PropertyKind {
+    Bool,
+    Int { min: i64, max: i64 },
+    Enum { values: Vec<&'static str> },
+    String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PropertySchema {
+    pub key: &'static str,
+    #[serde(flatten)]
+    pub kind: PropertyKind,
+    /// Value Allay writes for a new server
+    pub default: String,
+}
+
+const PORT: PropertyKind = PropertyKind::Int { min: 1, max: 65535 };
+const PERMISSION_LEVEL: PropertyKind = PropertyKind::Int { min: 0, max: 4 };
+const COUNT: PropertyKind = PropertyKind::Int { min: 0, max: i32::MAX as i64 };
+
+/// Type information for keys that aren't plain text. Booleans are detected from the default value.
+fn kind_of(key: &str) -> Option<PropertyKind> {
+    Some(match key {
+        "gamemode" => PropertyKind::Enum { values: vec!["survival", "creative", "adventure", "spectator"] },
+        "difficulty" => PropertyKind::Enum { values: vec!["peaceful", "easy", "normal", "hard"] },
+        "level-type" => PropertyKind::Enum {
+            values: vec![
+                "default",
+                "flat",
+                "largeBiomes",
+                "amplified",
+                "buffet",
+                "minecraft:normal",
+                "minecraft:flat",
+                "minecraft:large_biomes",
+                "minecraft:amplified",
+                "minecraft:single_biome_surface",
+            ],
+        },
+        "server-port" | "rcon.port" | "query.port" | "enable-jmx-monitoring.port" | "enable-jmx-monitoring.rmi.port" => PORT,
+        "op-permission-level" => PERMISSION_LEVEL,
+        "function-permission-level" => PropertyKind::Int { min: 1, max: 4 },
+        "view-distance" | "simulation-distance" => PropertyKind::Int { min: 3, max: 32 },
+        "max-world-size" => PropertyKind::Int { min: 1, max: 29_999_984 },
+        "entity-broadcast-range-percentage" => PropertyKind::Int { min: 10, max: 1000 },
+        // -1 disables the watchdog / compression / the neighbor update limit
+        "max-tick-time" | "network-compression-threshold" | "max-chained-neighbor-updates" => PropertyKind::Int { min: -1, max: i32::MAX as i64 },
+        "max-players" | "spawn-protection" | "rate-limit" | "player-idle-timeout" | "pause-when-empty-seconds" => COUNT,
+        _ => return None,
+    })
+}
+
+/// level-name is a folder inside the server directory, so it must be a single plain folder name
+pub fn check_level_name(name: &str) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() || name == "." || name.contains("..") || name.contains(['/', '\\', ':']) {
+        return Err(format!("'{}' is not a valid world folder name", name));
+    }
+    Ok(())
+}
+
+/// Checks a value against the schema of `key`; keys without a schema entry are not checked here
+pub fn validate_property(key: &str, value: &str) -> Result<(), String> {
+    if key == "level-name" {
+        return check_level_name(value);
+    }
+    let kind = match property_schema().into_iter().find(|schema| schema.key == key) {
+        Some(schema) => schema.kind,
+        None => return Ok(()),
+    };
+
+    match kind {
+        PropertyKind::Bool if value != "true" && value != "false" => {
+            Err(format!("{} must be true or false", key))
+        }
+        PropertyKind::Int { min, max } => match value.parse::<i64>() {
+            Ok(number) if number >= min && number <= max => Ok(()),
+            _ => Err(format!("{} must be a whole number between {} and {}", key, min, max)),
+        },
+        PropertyKind::Enum { values } if !values.contains(&value) => {
+            Err(format!("{} must be one of {}", key, values.join(", ")))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Describes every property `ServerProperties` models so the frontend can render
+/// toggles, dropdowns and bounded number inputs instead of plain text fields
+pub fn property_schema() -> Vec<PropertySchema> {
+    ServerProperties::default()
+        .to_key_values()
+        .into_iter()
+        .map(|(key, default)| {
+            let kind = kind_of(key).unwrap_or(match default.as_str() {
+                "true" | "false" => PropertyKind::Bool,
+                _ => PropertyKind::String,
+            });
+            PropertySchema { key, kind, default }
+        })
+        .collect()
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -17,6 +18,86 @@ pub struct ServerInstance {
     pub memory_mb: u32,
     #[serde(default)]
     pub creation_status: ServerCreationStatus,
+    /// RCON host override for servers managed on another machine (defaults to 127.0.0.1)
+    #[serde(default)]
+    pub rcon_host: Option<String>,
+    /// RCON port override, used instead of rcon.port from server.properties
+    #[serde(default)]
+    pub rcon_port: Option<u16>,
+    /// RCON password override, for remote servers without a local server.properties
+    #[serde(default)]
+    pub rcon_password: Option<String>,
+    /// Start this server automatically when Allay launches
+    #[serde(default)]
+    pub auto_start: bool,
+    /// Stop the server after it has been empty for this many minutes (None disables idle shutdown)
+    #[serde(default)]
+    pub idle_shutdown_minutes: Option<u32>,
+    /// After an idle shutdown, listen on the game port and start the server when someone connects
+    #[serde(default)]
+    pub wake_on_demand: bool,
+    /// Run the server in its own process group so it keeps running when Allay closes.
+    /// Allay then has no console access and stops it through RCON.
+    #[serde(default)]
+    pub detached_mode: bool,
+    /// Warn players over RCON for this many seconds before stopping or restarting
+    #[serde(default)]
+    pub stop_countdown_secs: Option<u32>,
+    /// Extra environment variables for the server process (or the Forge/NeoForge run script)
+    #[serde(default)]
+    pub env_vars: HashMap<String, String>,
+    /// Install the newest Paper build before each start; updating pins the server to that build
+    #[serde(default)]
+    pub auto_update_paper: bool,
+    /// Pass "nogui" at launch; off opens the vanilla server window, for local debugging
+    #[serde(default = "default_nogui")]
+    pub nogui: bool,
+    /// Which backups to keep; older ones are pruned after every new backup
+    #[serde(default)]
+    pub backup_retention: BackupRetention,
+    /// When to back the server up automatically (None disables automatic backups)
+    #[serde(default)]
+    pub backup_schedule: Option<BackupSchedule>,
+    /// Free-form labels for grouping servers ("prod", "modpack", ...)
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Position in the user's server list; unordered servers follow in creation order
+    #[serde(default)]
+    pub sort_order: Option<u32>,
+    /// Missing for servers created before this was recorded
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// When Allay last started the server process; None if it never has
+    #[serde(default)]
+    pub last_started_at: Option<DateTime<Utc>>,
+    /// Console commands sent in order to shut the server down, e.g. "save-all" then "stop"
+    #[serde(default = "default_stop_commands")]
+    pub stop_commands: Vec<String>,
+    /// A setting that only applies on restart was changed while the server was running
+    #[serde(default)]
+    pub needs_restart: bool,
+}
+
+/// When automatic backups run
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BackupSchedule {
+    /// Every N minutes, counted from when Allay starts
+    Interval { minutes: u32 },
+    /// Once a day at a local "HH:MM" time
+    Daily { time: String },
+}
+
+/// Limits on a server's backups. A backup is pruned once it breaks either limit;
+/// with neither set, every backup is kept.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BackupRetention {
+    /// Keep only this many of the newest backups
+    #[serde(default)]
+    pub keep_last: Option<u32>,
+    /// Delete backups older than this many days
+    #[serde(default)]
+    pub keep_days: Option<u32>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -39,6 +120,14 @@ impl Default for ServerCreationStatus {
     }
 }
 
+fn default_nogui() -> bool {
+    true
+}
+
+fn default_stop_commands() -> Vec<String> {
+    vec!["stop".to_string()]
+}
+
 fn default_memory() -> u32 {
     2048 // Default 2GB in MB
 }
@@ -56,6 +145,26 @@ impl ServerConfig {
     }
 }
 
+/// Outcome of checking server_config.json, and of repairing it when it was damaged
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConfigValidationReport {
+    /// True when the file parsed as-is and nothing was changed
+    pub valid: bool,
+    /// Instances kept in the repaired config
+    pub recovered: Vec<String>,
+    /// Entries that couldn't be read, with the reason
+    pub dropped: Vec<DroppedConfigEntry>,
+    /// Copy of the damaged file, kept next to it before the repaired version was written
+    pub backup_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DroppedConfigEntry {
+    /// The instance key, or a description when the damage made it unreadable
+    pub name: String,
+    pub reason: String,
+}
+
 pub struct ServerFileManager {
     config_path: PathBuf,
 }
@@ -80,14 +189,63 @@ impl ServerFileManager {
         
         let config: ServerConfig = serde_json::from_str(content)
             .map_err(|e| {
-                eprintln!("JSON parsing error: {}", e);
-                eprintln!("File content: '{}'", content);
+                log::error!("JSON parsing error: {}", e);
+                log::debug!("File content: '{}'", content);
                 Error::new(ErrorKind::InvalidData, format!("Failed to parse JSON: {}", e))
             })?;
         
         Ok(config)
     }
 
+    /// Checks that the config parses. If it doesn't, every instance entry that can still be
+    /// read is kept: the damaged file is copied aside and a cleaned config written in its place.
+    pub fn validate_config(&self) -> Result<ConfigValidationReport, Error> {
+        let content = match fs::read_to_string(&self.config_path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                return Ok(ConfigValidationReport { valid: true, ..Default::default() });
+            }
+            Err(e) => return Err(e),
+        };
+        if content.trim().is_empty() || serde_json::from_str::<ServerConfig>(content.trim()).is_ok() {
+            return Ok(ConfigValidationReport { valid: true, ..Default::default() });
+        }
+
+        log::warn!("{:?} is damaged, recovering what can be read", self.config_path);
+        let (entries, mut dropped) = read_instance_entries(&content);
+
+        let mut config = ServerConfig::new();
+        for (name, value) in entries {
+            match serde_json::from_value::<ServerInstance>(value) {
+                Ok(mut instance) => {
+                    // The key is what every lookup uses
+                    instance.name = name.clone();
+                    config.instances.insert(name, instance);
+                }
+                Err(e) => dropped.push(DroppedConfigEntry { name, reason: e.to_string() }),
+            }
+        }
+
+        let stamp = Utc::now().format("%Y%m%d-%H%M%S");
+        let backup_path = self.config_path.with_extension(format!("json.corrupt-{}", stamp));
+        fs::copy(&self.config_path, &backup_path)?;
+        self.save_config(&config)?;
+
+        let mut recovered: Vec<String> = config.instances.into_keys().collect();
+        recovered.sort();
+        for entry in &dropped {
+            log::warn!("Dropped config entry {}: {}", entry.name, entry.reason);
+        }
+        log::info!("Repaired {:?}: kept {} instance(s), damaged file saved as {:?}", self.config_path, recovered.len(), backup_path);
+
+        Ok(ConfigValidationReport {
+            valid: false,
+            recovered,
+            dropped,
+            backup_path: Some(backup_path),
+        })
+    }
+
     pub fn save_config(&self, config: &ServerConfig) -> Result<(), Error> {
         if let Some(parent) = self.config_path.parent() {
             fs::create_dir_all(parent)?;
@@ -96,7 +254,10 @@ impl ServerFileManager {
         let content = serde_json::to_string_pretty(config)
             .map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
         
-        fs::write(&self.config_path, content)?;
+        // Write a sibling file and rename it over the original so a crash can't truncate it
+        let temp_path = self.config_path.with_extension("json.tmp");
+        fs::write(&temp_path, content)?;
+        fs::rename(&temp_path, &self.config_path)?;
         Ok(())
     }
 
@@ -178,9 +339,50 @@ impl ServerFileManager {
         Ok(config.instances.get(name).cloned())
     }
 
+    /// Every instance in list order: by sort_order, then the unordered ones oldest first
     pub fn get_all_instances(&self) -> Result<Vec<ServerInstance>, Error> {
         let config = self.load_config()?;
-        Ok(config.instances.values().cloned().collect())
+        let mut instances: Vec<ServerInstance> = config.instances.into_values().collect();
+        instances.sort_by(|a, b| {
+            let position = |instance: &ServerInstance| (instance.sort_order.is_none(), instance.sort_order);
+            position(a)
+                .cmp(&position(b))
+                // Servers from before created_at was recorded are the oldest
+                .then_with(|| a.created_at.cmp(&b.created_at))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(instances)
+    }
+
+    /// Numbers the named servers in the given order with one save; servers left out lose
+    /// their position and go after them. Unknown names fail without changing anything.
+    pub fn reorder_instances(&self, ordered_names: &[String]) -> Result<(), Error> {
+        let mut config = self.load_config()?;
+
+        if let Some(unknown) = ordered_names.iter().find(|name| !config.instances.contains_key(*name)) {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("Instance with name '{}' not found", unknown),
+            ));
+        }
+
+        for instance in config.instances.values_mut() {
+            instance.sort_order = ordered_names
+                .iter()
+                .position(|name| *name == instance.name)
+                .map(|position| position as u32);
+        }
+        self.save_config(&config)
+    }
+
+    /// Instances carrying `tag`, compared without regard to case
+    pub fn get_instances_by_tag(&self, tag: &str) -> Result<Vec<ServerInstance>, Error> {
+        let tag = tag.trim();
+        Ok(self
+            .get_all_instances()?
+            .into_iter()
+            .filter(|instance| instance.has_tag(tag))
+            .collect())
     }
 
     pub fn get_server_memory(&self, name: &str) -> Option<u32> {
@@ -190,6 +392,45 @@ impl ServerFileManager {
         }
     }
 
+    pub fn get_stop_countdown(&self, name: &str) -> u32 {
+        match self.get_instance(name) {
+            Ok(Some(instance)) => instance.stop_countdown_secs.unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    pub fn get_env_vars(&self, name: &str) -> HashMap<String, String> {
+        match self.get_instance(name) {
+            Ok(Some(instance)) => instance.env_vars,
+            _ => HashMap::new(),
+        }
+    }
+
+    pub fn is_nogui(&self, name: &str) -> bool {
+        match self.get_instance(name) {
+            Ok(Some(instance)) => instance.nogui,
+            _ => true,
+        }
+    }
+
+    pub fn get_backup_retention(&self, name: &str) -> BackupRetention {
+        match self.get_instance(name) {
+            Ok(Some(instance)) => instance.backup_retention,
+            _ => BackupRetention::default(),
+        }
+    }
+
+    pub fn get_stop_commands(&self, name: &str) -> Vec<String> {
+        match self.get_instance(name) {
+            Ok(Some(instance)) if !instance.stop_commands.is_empty() => instance.stop_commands,
+            _ => default_stop_commands(),
+        }
+    }
+
+    pub fn is_detached_mode(&self, name: &str) -> bool {
+        matches!(self.get_instance(name), Ok(Some(instance)) if instance.detached_mode)
+    }
+
     pub fn instance_exists(&self, name: &str) -> Result<bool, Error> {
         let config = self.load_config()?;
         Ok(config.instances.contains_key(name))
@@ -225,6 +466,41 @@ impl ServerFileManager {
         Ok(())
     }
 
+    /// Stamps last_started_at with the current time; a fresh start has applied any pending changes
+    pub fn record_server_started(&self, name: &str) -> Result<(), Error> {
+        let mut config = self.load_config()?;
+        
+        match config.instances.get_mut(name) {
+            Some(instance) => {
+                instance.last_started_at = Some(Utc::now());
+                instance.needs_restart = false;
+            }
+            None => {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!("Instance with name '{}' not found", name),
+                ))
+            }
+        }
+        self.save_config(&config)
+    }
+
+    /// Flags that a changed setting only takes effect once the server restarts
+    pub fn mark_needs_restart(&self, name: &str) -> Result<(), Error> {
+        let mut config = self.load_config()?;
+        
+        match config.instances.get_mut(name) {
+            Some(instance) => instance.needs_restart = true,
+            None => {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!("Instance with name '{}' not found", name),
+                ))
+            }
+        }
+        self.save_config(&config)
+    }
+
     pub fn get_incomplete_servers(&self, base_storage_path: &Path) -> Result<Vec<String>, Error> {
         let config = self.load_config()?;
         let mut incomplete_servers = Vec::new();
@@ -239,7 +515,7 @@ impl ServerFileManager {
     }
 
     pub fn cleanup_incomplete_server(&self, name: &str, base_storage_path: &Path) -> Result<(), Error> {
-        println!("Cleaning up incomplete server: {}", name);
+        log::info!("Cleaning up incomplete server: {}", name);
         
         // Remove from config and delete storage directory
         self.remove_instance_with_storage(name, base_storage_path)?;
@@ -248,7 +524,81 @@ impl ServerFileManager {
     }
 }
 
+/// Reads the entries of the "instances" object one at a time, so a syntax error (such as a
+/// file cut off mid-write) only loses the entries from that point on
+fn read_instance_entries(content: &str) -> (Vec<(String, serde_json::Value)>, Vec<DroppedConfigEntry>) {
+    let mut entries = Vec::new();
+    let mut dropped = Vec::new();
+
+    let start = content
+        .find("\"instances\"")
+        .and_then(|key| content[key..].find('{').map(|brace| key + brace + 1));
+    let Some(mut rest) = start.map(|start| &content[start..]) else {
+        dropped.push(DroppedConfigEntry {
+            name: "instances".to_string(),
+            reason: "The config has no readable \"instances\" object".to_string(),
+        });
+        return (entries, dropped);
+    };
+
+    loop {
+        rest = rest.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+        if rest.is_empty() || rest.starts_with('}') {
+            break;
+        }
+
+        let mut keys = serde_json::Deserializer::from_str(rest).into_iter::<String>();
+        let name = match keys.next() {
+            Some(Ok(name)) => name,
+            _ => {
+                dropped.push(DroppedConfigEntry {
+                    name: format!("entries after {}", entries.last().map(|(name, _)| name.as_str()).unwrap_or("the start")),
+                    reason: "The rest of the file is unreadable".to_string(),
+                });
+                break;
+            }
+        };
+        rest = rest[keys.byte_offset()..].trim_start();
+
+        let value = rest
+            .strip_prefix(':')
+            .and_then(|after_colon| {
+                let mut values = serde_json::Deserializer::from_str(after_colon).into_iter::<serde_json::Value>();
+                match values.next() {
+                    Some(Ok(value)) => Some((value, &after_colon[values.byte_offset()..])),
+                    _ => None,
+                }
+            });
+        match value {
+            Some((value, after_value)) => {
+                entries.push((name, value));
+                rest = after_value;
+            }
+            None => {
+                dropped.push(DroppedConfigEntry {
+                    name,
+                    reason: "The entry is cut off or not valid JSON".to_string(),
+                });
+                break;
+            }
+        }
+    }
+
+    (entries, dropped)
+}
+
+/// Portable environment variable name: a letter or underscore followed by letters, digits or underscores
+pub fn is_valid_env_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 impl ServerInstance {
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|existing| existing.eq_ignore_ascii_case(tag))
+    }
+
     pub fn new(
         name: String,
         version: String,
@@ -267,6 +617,98 @@ impl ServerInstance {
             storage_path,
             memory_mb: default_memory(),
             creation_status: ServerCreationStatus::Pending,
+            rcon_host: None,
+            rcon_port: None,
+            rcon_password: None,
+            auto_start: false,
+            idle_shutdown_minutes: None,
+            wake_on_demand: false,
+            detached_mode: false,
+            stop_countdown_secs: None,
+            env_vars: HashMap::new(),
+            auto_update_paper: false,
+            nogui: true,
+            backup_retention: BackupRetention::default(),
+            backup_schedule: None,
+            tags: Vec::new(),
+            sort_order: None,
+            created_at: Some(Utc::now()),
+            last_started_at: None,
+            stop_commands: default_stop_commands(),
+            needs_restart: false,
         })
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instance_json(name: &str) -> String {
+        format!(
+            r#"{{"name": "{0}", "version": "1.20.4", "mod_loader": "vanilla", "mod_loader_version": "none", "storage_path": "storage/{0}"}}"#,
+            name
+        )
+    }
+
+    /// Validates `content` as a config file in its own temporary folder and returns the report
+    /// with the file as it is afterwards
+    fn validate(test_name: &str, content: &str) -> (ConfigValidationReport, String) {
+        let dir = std::env::temp_dir().join(format!("allay-config-{}-{}", test_name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let config_path = dir.join("server_config.json");
+        fs::write(&config_path, content).unwrap();
+
+        let report = ServerFileManager::new(config_path.clone()).validate_config().unwrap();
+        let after = fs::read_to_string(&config_path).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        (report, after)
+    }
+
+    #[test]
+    fn leaves_a_valid_config_untouched() {
+        let content = format!(r#"{{"instances": {{"alpha": {}}}}}"#, instance_json("alpha"));
+        let (report, after) = validate("valid", &content);
+
+        assert!(report.valid);
+        assert!(report.backup_path.is_none());
+        assert_eq!(after, content);
+    }
+
+    #[test]
+    fn keeps_the_entries_before_a_file_cut_off_mid_entry() {
+        let full = format!(r#"{{"instances": {{"alpha": {}, "beta": {}}}}}"#, instance_json("alpha"), instance_json("beta"));
+        let truncated = &full[..full.find("\"beta\"").unwrap() + 30];
+        let (report, after) = validate("truncated", truncated);
+
+        assert!(!report.valid);
+        assert_eq!(report.recovered, vec!["alpha".to_string()]);
+        assert_eq!(report.dropped.len(), 1);
+        assert_eq!(report.dropped[0].name, "beta");
+        assert!(serde_json::from_str::<ServerConfig>(&after).unwrap().instances.contains_key("alpha"));
+    }
+
+    #[test]
+    fn drops_entries_with_a_field_of_the_wrong_type() {
+        let wrong_type = instance_json("beta").replace(r#""version": "1.20.4""#, r#""version": 1204"#);
+        let content = format!(r#"{{"instances": {{"alpha": {}, "beta": {}}}}}"#, instance_json("alpha"), wrong_type);
+        let (report, after) = validate("wrong-type", &content);
+
+        assert!(!report.valid);
+        assert_eq!(report.recovered, vec!["alpha".to_string()]);
+        assert_eq!(report.dropped[0].name, "beta");
+        assert!(!serde_json::from_str::<ServerConfig>(&after).unwrap().instances.contains_key("beta"));
+    }
+
+    #[test]
+    fn reports_a_config_without_instances() {
+        let (entries, dropped) = read_instance_entries(r#"{"servers": {}}"#);
+        assert!(entries.is_empty());
+        assert_eq!(dropped[0].name, "instances");
+
+        let (report, after) = validate("no-instances", r#"{"servers": {}}"#);
+        assert!(!report.valid);
+        assert!(report.recovered.is_empty());
+        assert!(serde_json::from_str::<ServerConfig>(&after).unwrap().instances.is_empty());
+    }
+}
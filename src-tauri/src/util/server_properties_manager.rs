@@ -2,7 +2,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::io::{Error, ErrorKind};
+use std::net::IpAddr;
 use std::path::{Path, PathBuf};
+use crate::util::{check_level_name, validate_property};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerProperties {
@@ -140,128 +142,184 @@ impl Default for ServerProperties {
 }
 
 impl ServerProperties {
+    /// Every modelled property as (server.properties key, value), in declaration order
+    pub fn to_key_values(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("server-port", self.server_port.to_string()),
+            ("gamemode", self.gamemode.to_string()),
+            ("difficulty", self.difficulty.to_string()),
+            ("level-name", self.level_name.to_string()),
+            ("max-players", self.max_players.to_string()),
+            ("motd", self.motd.to_string()),
+            ("online-mode", self.online_mode.to_string()),
+            ("pvp", self.pvp.to_string()),
+            ("level-seed", self.level_seed.to_string()),
+            ("spawn-protection", self.spawn_protection.to_string()),
+            ("white-list", self.white_list.to_string()),
+            ("enable-command-block", self.enable_command_block.to_string()),
+            ("spawn-monsters", self.spawn_monsters.to_string()),
+            ("spawn-animals", self.spawn_animals.to_string()),
+            ("spawn-npcs", self.spawn_npcs.to_string()),
+            ("allow-flight", self.allow_flight.to_string()),
+            ("view-distance", self.view_distance.to_string()),
+            ("simulation-distance", self.simulation_distance.to_string()),
+            ("op-permission-level", self.op_permission_level.to_string()),
+            ("allow-nether", self.allow_nether.to_string()),
+            ("enable-rcon", self.enable_rcon.to_string()),
+            ("rcon.port", self.rcon_port.to_string()),
+            ("rcon.password", self.rcon_password.to_string()),
+            ("query.port", self.query_port.to_string()),
+            ("enable-query", self.enable_query.to_string()),
+            ("generator-settings", self.generator_settings.to_string()),
+            ("level-type", self.level_type.to_string()),
+            ("hardcore", self.hardcore.to_string()),
+            ("enable-status", self.enable_status.to_string()),
+            ("enable-jmx-monitoring", self.enable_jmx_monitoring.to_string()),
+            ("broadcast-rcon-to-ops", self.broadcast_rcon_to_ops.to_string()),
+            ("broadcast-console-to-ops", self.broadcast_console_to_ops.to_string()),
+            ("enforce-whitelist", self.enforce_whitelist.to_string()),
+            ("resource-pack", self.resource_pack.to_string()),
+            ("resource-pack-prompt", self.resource_pack_prompt.to_string()),
+            ("resource-pack-sha1", self.resource_pack_sha1.to_string()),
+            ("require-resource-pack", self.require_resource_pack.to_string()),
+            ("max-world-size", self.max_world_size.to_string()),
+            ("function-permission-level", self.function_permission_level.to_string()),
+            ("max-tick-time", self.max_tick_time.to_string()),
+            ("rate-limit", self.rate_limit.to_string()),
+            ("network-compression-threshold", self.network_compression_threshold.to_string()),
+            ("use-native-transport", self.use_native_transport.to_string()),
+            ("enable-jmx-monitoring.port", self.enable_jmx_monitoring_port.to_string()),
+            ("enable-jmx-monitoring.rmi.port", self.enable_jmx_monitoring_rmi_port.to_string()),
+            ("sync-chunk-writes", self.sync_chunk_writes.to_string()),
+            ("server-ip", self.server_ip.to_string()),
+            ("prevent-proxy-connections", self.prevent_proxy_connections.to_string()),
+            ("hide-online-players", self.hide_online_players.to_string()),
+            ("entity-broadcast-range-percentage", self.entity_broadcast_range_percentage.to_string()),
+            ("player-idle-timeout", self.player_idle_timeout.to_string()),
+            ("force-gamemode", self.force_gamemode.to_string()),
+            ("debug", self.debug.to_string()),
+            ("max-chained-neighbor-updates", self.max_chained_neighbor_updates.to_string()),
+            ("text-filtering-config", self.text_filtering_config.to_string()),
+            ("initial-disabled-packs", self.initial_disabled_packs.to_string()),
+            ("initial-enabled-packs", self.initial_enabled_packs.to_string()),
+            ("log-ips", self.log_ips.to_string()),
+            ("pause-when-empty-seconds", self.pause_when_empty_seconds.to_string()),
+            ("accepts-transfers", self.accepts_transfers.to_string()),
+            ("generate-structures", self.generate_structures.to_string()),
+            ("snooper-enabled", self.snooper_enabled.to_string()),
+        ]
+    }
+    
+    /// Keys written for newer servers that Allay doesn't model yet, with the game's defaults
+    const UNMODELLED_DEFAULTS: [(&'static str, &'static str); 5] = [
+        ("bug-report-link", ""),
+        ("enforce-secure-profile", "true"),
+        ("region-file-compression", "deflate"),
+        ("resource-pack-id", ""),
+        ("text-filtering-version", "0"),
+    ];
+
+    /// Renders every modelled property exactly once, sorted by key like the game writes them
     pub fn to_properties_string(&self) -> String {
-        format!(
-            r#"#Minecraft server properties
-#Generated by Allay
-accepts-transfers={}
-allow-flight={}
-allow-nether={}
-broadcast-console-to-ops={}
-broadcast-rcon-to-ops={}
-bug-report-link=
-difficulty={}
-enable-command-block={}
-enable-jmx-monitoring={}
-enable-query={}
-enable-rcon={}
-enable-status={}
-enforce-secure-profile=true
-enforce-whitelist={}
-entity-broadcast-range-percentage={}
-force-gamemode={}
-function-permission-level={}
-gamemode={}
-generate-structures={}
-generator-settings={}
-hardcore={}
-hide-online-players={}
-initial-disabled-packs={}
-initial-enabled-packs={}
-level-name={}
-level-seed={}
-level-type={}
-log-ips={}
-max-chained-neighbor-updates={}
-max-players={}
-max-tick-time={}
-max-world-size={}
-motd={}
-network-compression-threshold={}
-online-mode={}
-op-permission-level={}
-pause-when-empty-seconds={}
-player-idle-timeout={}
-prevent-proxy-connections={}
-pvp={}
-query.port={}
-rate-limit={}
-rcon.password={}
-rcon.port={}
-region-file-compression=deflate
-require-resource-pack={}
-resource-pack={}
-resource-pack-id=
-resource-pack-prompt={}
-resource-pack-sha1={}
-server-ip={}
-server-port={}
-simulation-distance={}
-spawn-monsters={}
-spawn-protection={}
-sync-chunk-writes={}
-text-filtering-config={}
-text-filtering-version=0
-use-native-transport={}
-view-distance={}
-white-list={}"#,
-            self.accepts_transfers,
-            self.allow_flight,
-            self.allow_nether,
-            self.broadcast_console_to_ops,
-            self.broadcast_rcon_to_ops,
-            self.difficulty,
-            self.enable_command_block,
-            self.enable_jmx_monitoring,
-            self.enable_query,
-            self.enable_rcon,
-            self.enable_status,
-            self.enforce_whitelist,
-            self.entity_broadcast_range_percentage,
-            self.force_gamemode,
-            self.function_permission_level,
-            self.gamemode,
-            self.generate_structures,
-            self.generator_settings,
-            self.hardcore,
-            self.hide_online_players,
-            self.initial_disabled_packs,
-            self.initial_enabled_packs,
-            self.level_name,
-            self.level_seed,
-            self.level_type,
-            self.log_ips,
-            self.max_chained_neighbor_updates,
-            self.max_players,
-            self.max_tick_time,
-            self.max_world_size,
-            self.motd,
-            self.network_compression_threshold,
-            self.online_mode,
-            self.op_permission_level,
-            self.pause_when_empty_seconds,
-            self.player_idle_timeout,
-            self.prevent_proxy_connections,
-            self.pvp,
-            self.query_port,
-            self.rate_limit,
-            self.rcon_password,
-            self.rcon_port,
-            self.require_resource_pack,
-            self.resource_pack,
-            self.resource_pack_prompt,
-            self.resource_pack_sha1,
-            self.server_ip,
-            self.server_port,
-            self.simulation_distance,
-            self.spawn_monsters,
-            self.spawn_protection,
-            self.sync_chunk_writes,
-            self.text_filtering_config,
-            self.use_native_transport,
-            self.view_distance,
-            self.white_list
-        )
+        let mut entries = self.to_key_values();
+        entries.extend(Self::UNMODELLED_DEFAULTS.iter().map(|(key, value)| (*key, value.to_string())));
+        entries.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut content = String::from("#Minecraft server properties\n#Generated by Allay\n");
+        for (key, value) in entries {
+            content.push_str(key);
+            content.push('=');
+            content.push_str(&escape_value(&value));
+            content.push('\n');
+        }
+        content
+    }
+    
+    /// Where Allay reaches the server for RCON and Query: the address in server-ip, or loopback
+    /// when the server listens on every interface
+    pub fn connect_host(&self) -> String {
+        let server_ip = self.server_ip.trim();
+        match server_ip.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) if ip.is_unspecified() => "127.0.0.1".to_string(),
+            Ok(IpAddr::V6(ip)) if ip.is_unspecified() => "::1".to_string(),
+            Ok(ip) => ip.to_string(),
+            Err(_) if server_ip.is_empty() => "127.0.0.1".to_string(),
+            Err(_) => server_ip.to_string(),
+        }
+    }
+
+    /// Sets a property from its server.properties key, rejecting unknown keys, values of the
+    /// wrong type and values outside the range or choices in the property schema
+    pub fn set_value(&mut self, key: &str, value: &str) -> Result<(), Error> {
+        let value = value.trim();
+        validate_property(key, value).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+        
+        match key {
+            "server-port" => self.server_port = parse_value(key, value)?,
+            "gamemode" => self.gamemode = value.to_string(),
+            "difficulty" => self.difficulty = value.to_string(),
+            "level-name" => self.level_name = value.to_string(),
+            "max-players" => self.max_players = parse_value(key, value)?,
+            "motd" => self.motd = value.to_string(),
+            "online-mode" => self.online_mode = parse_value(key, value)?,
+            "pvp" => self.pvp = parse_value(key, value)?,
+            "level-seed" => self.level_seed = value.to_string(),
+            "spawn-protection" => self.spawn_protection = parse_value(key, value)?,
+            "white-list" => self.white_list = parse_value(key, value)?,
+            "enable-command-block" => self.enable_command_block = parse_value(key, value)?,
+            "spawn-monsters" => self.spawn_monsters = parse_value(key, value)?,
+            "spawn-animals" => self.spawn_animals = parse_value(key, value)?,
+            "spawn-npcs" => self.spawn_npcs = parse_value(key, value)?,
+            "allow-flight" => self.allow_flight = parse_value(key, value)?,
+            "view-distance" => self.view_distance = parse_value(key, value)?,
+            "simulation-distance" => self.simulation_distance = parse_value(key, value)?,
+            "op-permission-level" => self.op_permission_level = parse_value(key, value)?,
+            "allow-nether" => self.allow_nether = parse_value(key, value)?,
+            "enable-rcon" => self.enable_rcon = parse_value(key, value)?,
+            "rcon.port" => self.rcon_port = parse_value(key, value)?,
+            "rcon.password" => self.rcon_password = value.to_string(),
+            "query.port" => self.query_port = parse_value(key, value)?,
+            "enable-query" => self.enable_query = parse_value(key, value)?,
+            "generator-settings" => self.generator_settings = value.to_string(),
+            "level-type" => self.level_type = value.to_string(),
+            "hardcore" => self.hardcore = parse_value(key, value)?,
+            "enable-status" => self.enable_status = parse_value(key, value)?,
+            "enable-jmx-monitoring" => self.enable_jmx_monitoring = parse_value(key, value)?,
+            "broadcast-rcon-to-ops" => self.broadcast_rcon_to_ops = parse_value(key, value)?,
+            "broadcast-console-to-ops" => self.broadcast_console_to_ops = parse_value(key, value)?,
+            "enforce-whitelist" => self.enforce_whitelist = parse_value(key, value)?,
+            "resource-pack" => self.resource_pack = value.to_string(),
+            "resource-pack-prompt" => self.resource_pack_prompt = value.to_string(),
+            "resource-pack-sha1" => self.resource_pack_sha1 = value.to_string(),
+            "require-resource-pack" => self.require_resource_pack = parse_value(key, value)?,
+            "max-world-size" => self.max_world_size = parse_value(key, value)?,
+            "function-permission-level" => self.function_permission_level = parse_value(key, value)?,
+            "max-tick-time" => self.max_tick_time = parse_value(key, value)?,
+            "rate-limit" => self.rate_limit = parse_value(key, value)?,
+            "network-compression-threshold" => self.network_compression_threshold = parse_value(key, value)?,
+            "use-native-transport" => self.use_native_transport = parse_value(key, value)?,
+            "enable-jmx-monitoring.port" => self.enable_jmx_monitoring_port = parse_value(key, value)?,
+            "enable-jmx-monitoring.rmi.port" => self.enable_jmx_monitoring_rmi_port = parse_value(key, value)?,
+            "sync-chunk-writes" => self.sync_chunk_writes = parse_value(key, value)?,
+            "server-ip" => self.server_ip = value.to_string(),
+            "prevent-proxy-connections" => self.prevent_proxy_connections = parse_value(key, value)?,
+            "hide-online-players" => self.hide_online_players = parse_value(key, value)?,
+            "entity-broadcast-range-percentage" => self.entity_broadcast_range_percentage = parse_value(key, value)?,
+            "player-idle-timeout" => self.player_idle_timeout = parse_value(key, value)?,
+            "force-gamemode" => self.force_gamemode = parse_value(key, value)?,
+            "debug" => self.debug = parse_value(key, value)?,
+            "max-chained-neighbor-updates" => self.max_chained_neighbor_updates = parse_value(key, value)?,
+            "text-filtering-config" => self.text_filtering_config = value.to_string(),
+            "initial-disabled-packs" => self.initial_disabled_packs = value.to_string(),
+            "initial-enabled-packs" => self.initial_enabled_packs = value.to_string(),
+            "log-ips" => self.log_ips = parse_value(key, value)?,
+            "pause-when-empty-seconds" => self.pause_when_empty_seconds = parse_value(key, value)?,
+            "accepts-transfers" => self.accepts_transfers = parse_value(key, value)?,
+            "generate-structures" => self.generate_structures = parse_value(key, value)?,
+            "snooper-enabled" => self.snooper_enabled = parse_value(key, value)?,
+            _ => return Err(Error::new(ErrorKind::InvalidInput, format!("Unknown property: {}", key))),
+        }
+        Ok(())
     }
     
     pub fn from_properties_string(content: &str) -> Result<Self, Error> {
@@ -275,7 +333,7 @@ white-list={}"#,
             
             if let Some((key, value)) = line.split_once('=') {
                 let key = key.trim();
-                let value = value.trim();
+                let value = &unescape_value(value.trim());
                 
                 match key {
                     "server-port" => properties.server_port = value.parse().unwrap_or(25565),
@@ -349,6 +407,198 @@ white-list={}"#,
     }
 }
 
+fn parse_value<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, Error> {
+    value
+        .parse()
+        .map_err(|_| Error::new(ErrorKind::InvalidInput, format!("Invalid value '{}' for {}", value, key)))
+}
+
+/// Escapes a value the way Java's Properties writes it, since the game reads server.properties
+/// as ISO-8859-1: backslashes are doubled, control characters get their escape and anything
+/// outside ASCII becomes \uXXXX (a UTF-16 surrogate pair for emoji)
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            ' '..='~' => escaped.push(c),
+            _ => {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    escaped.push_str(&format!("\\u{:04X}", unit));
+                }
+            }
+        }
+    }
+    escaped
+}
+
+/// Undoes escape_value and the escapes the game itself writes, e.g. "minecraft\:normal".
+/// Malformed \u escapes are kept as written.
+fn unescape_value(value: &str) -> String {
+    let mut units: Vec<u16> = Vec::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buffer = [0u16; 2];
+            units.extend_from_slice(c.encode_utf16(&mut buffer));
+            continue;
+        }
+        match chars.next() {
+            Some('u') => {
+                let hex: String = chars.clone().take(4).collect();
+                match u16::from_str_radix(&hex, 16) {
+                    Ok(unit) if hex.len() == 4 => {
+                        units.push(unit);
+                        chars.nth(3);
+                    }
+                    _ => units.extend("\\u".encode_utf16()),
+                }
+            }
+            Some('n') => units.push('\n' as u16),
+            Some('r') => units.push('\r' as u16),
+            Some('t') => units.push('\t' as u16),
+            Some('f') => units.push(0x0C),
+            Some(other) => {
+                let mut buffer = [0u16; 2];
+                units.extend_from_slice(other.encode_utf16(&mut buffer));
+            }
+            None => units.push('\\' as u16),
+        }
+    }
+    // Surrogate pairs are decoded together; a lone one can't be shown and becomes U+FFFD
+    String::from_utf16_lossy(&units)
+}
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// A properties file without the UTF-8 BOM Notepad writes and with CRLF line endings turned into LF,
+/// so the first key and the values match like those of any other file. Files that aren't UTF-8 are
+/// read as ISO-8859-1, the encoding Java's Properties uses, as older servers and editors write.
+fn read_properties_text(path: &Path) -> Result<String, Error> {
+    Ok(decode_properties(&fs::read(path)?))
+}
+
+fn decode_properties(raw: &[u8]) -> String {
+    let raw = raw.strip_prefix(UTF8_BOM).unwrap_or(raw);
+    let content = match std::str::from_utf8(raw) {
+        Ok(content) => content.to_string(),
+        Err(_) => raw.iter().map(|byte| char::from(*byte)).collect(),
+    };
+    content.replace("\r\n", "\n")
+}
+
+/// Rewrites the lines of the changed keys in the raw file `raw` and appends the keys it doesn't
+/// have yet. Every other line is kept byte for byte, so a BOM, CRLF endings and ISO-8859-1 text
+/// survive; the written lines are ASCII thanks to escape_value and fit either encoding.
+fn apply_changes(raw: &[u8], changes: &[(&str, String)]) -> Vec<u8> {
+    let (bom, body) = match raw.strip_prefix(UTF8_BOM) {
+        Some(body) => (UTF8_BOM, body),
+        None => (&[][..], raw),
+    };
+    let latin1 = std::str::from_utf8(body).is_err();
+    let line_ending: &[u8] = if body.windows(2).any(|pair| pair == b"\r\n") { b"\r\n" } else { b"\n" };
+
+    let mut written = vec![false; changes.len()];
+    let mut output = Vec::with_capacity(raw.len());
+    output.extend_from_slice(bom);
+
+    for raw_line in body.split_inclusive(|byte| *byte == b'\n') {
+        let content_len = raw_line.iter().rposition(|byte| *byte != b'\n' && *byte != b'\r').map_or(0, |i| i + 1);
+        let (content, ending) = raw_line.split_at(content_len);
+        let line = if latin1 {
+            content.iter().map(|byte| char::from(*byte)).collect()
+        } else {
+            String::from_utf8_lossy(content).to_string()
+        };
+
+        let trimmed = line.trim_start();
+        let change = if trimmed.starts_with('#') || trimmed.starts_with('!') {
+            None
+        } else {
+            trimmed.split_once('=').and_then(|(key, _)| {
+                changes.iter().position(|(changed, _)| *changed == key.trim())
+            })
+        };
+
+        match change {
+            Some(index) => {
+                // Duplicated keys all get the new value; the game reads the last one
+                let (key, value) = &changes[index];
+                output.extend_from_slice(format!("{}={}", key, escape_value(value)).as_bytes());
+                written[index] = true;
+            }
+            None => output.extend_from_slice(content),
+        }
+        output.extend_from_slice(if ending.is_empty() { line_ending } else { ending });
+    }
+
+    for ((key, value), _) in changes.iter().zip(&written).filter(|(_, written)| !**written) {
+        output.extend_from_slice(format!("{}={}", key, escape_value(value)).as_bytes());
+        output.extend_from_slice(line_ending);
+    }
+    output
+}
+
+/// Outcome of a bulk property update; nothing is saved unless `errors` is empty
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PropertyUpdateResult {
+    pub updated: Vec<String>,
+    /// Rejected keys with the reason
+    pub errors: HashMap<String, String>,
+}
+
+/// Replaces each ${NAME} in `text` with its value from `vars`
+fn substitute_variables(text: &str, vars: &HashMap<String, String>) -> Result<String, Error> {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        output.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("Unclosed variable in '{}'", text)))?;
+        let name = &rest[start + 2..start + end];
+        let value = vars
+            .get(name)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, format!("Unknown template variable ${{{}}}", name)))?;
+        output.push_str(value);
+        rest = &rest[start + end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Reads a server.properties template, substituting ${NAME} placeholders from `vars`, into
+/// key/value changes. Values are unescaped like those of server.properties itself.
+/// Unknown variables and lines that aren't key=value are errors.
+pub fn render_properties_template(template: &str, vars: &HashMap<String, String>) -> Result<HashMap<String, String>, Error> {
+    let mut changes = HashMap::new();
+    for (index, line) in template.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        
+        let (key, value) = line.split_once('=').ok_or_else(|| {
+            Error::new(ErrorKind::InvalidInput, format!("Line {} of the template is not key=value", index + 1))
+        })?;
+        // Unescape before substituting so backslashes in the variables are taken literally
+        changes.insert(key.trim().to_string(), substitute_variables(&unescape_value(value.trim()), vars)?);
+    }
+    Ok(changes)
+}
+
+/// connect_host() of a managed server, falling back to loopback without a server.properties
+pub fn server_connect_host(server_name: &str) -> String {
+    ServerPropertiesManager::new(PathBuf::from("storage").join(server_name).join("server.properties"))
+        .load_properties()
+        .map(|properties| properties.connect_host())
+        .unwrap_or_else(|_| "127.0.0.1".to_string())
+}
+
 pub struct ServerPropertiesManager {
     properties_path: PathBuf,
 }
@@ -363,20 +613,74 @@ impl ServerPropertiesManager {
             return Ok(ServerProperties::default());
         }
         
-        let content = fs::read_to_string(&self.properties_path)?;
+        let content = read_properties_text(&self.properties_path)?;
         ServerProperties::from_properties_string(&content)
     }
     
+    /// Writes `properties`. An existing file is edited in place: only the lines of keys whose
+    /// value changed are rewritten, keys it lacks are appended, and comments, ordering and
+    /// keys Allay doesn't know are kept as they are, in the file's own encoding and line endings.
     pub fn save_properties(&self, properties: &ServerProperties) -> Result<(), Error> {
         if let Some(parent) = self.properties_path.parent() {
             fs::create_dir_all(parent)?;
         }
         
-        let content = properties.to_properties_string();
-        fs::write(&self.properties_path, content)?;
+        let content = if self.properties_path.exists() {
+            let existing = fs::read(&self.properties_path)?;
+            let current = ServerProperties::from_properties_string(&decode_properties(&existing))?.to_key_values();
+            let changes: Vec<(&str, String)> = properties
+                .to_key_values()
+                .into_iter()
+                .zip(current)
+                .filter(|((_, new), (_, old))| new != old)
+                .map(|(change, _)| change)
+                .collect();
+            if changes.is_empty() {
+                return Ok(());
+            }
+            apply_changes(&existing, &changes)
+        } else {
+            properties.to_properties_string().into_bytes()
+        };
+        
+        // Write a sibling file and rename it over the original so a crash can't truncate it
+        let temp_path = self.properties_path.with_extension("properties.tmp");
+        fs::write(&temp_path, content)?;
+        fs::rename(&temp_path, &self.properties_path)?;
         Ok(())
     }
     
+    /// Validates every change, then applies them all with a single save. If any key is
+    /// rejected the file is left untouched and the failures are reported per key.
+    pub fn update_properties(&self, changes: &HashMap<String, String>) -> Result<PropertyUpdateResult, Error> {
+        let mut properties = self.load_properties()?;
+        let mut result = PropertyUpdateResult::default();
+        
+        for (key, value) in changes {
+            match properties.set_value(key, value) {
+                Ok(()) => result.updated.push(key.clone()),
+                Err(e) => {
+                    result.errors.insert(key.clone(), e.to_string());
+                }
+            }
+        }
+        
+        if result.errors.is_empty() {
+            self.save_properties(&properties)?;
+            result.updated.sort();
+        } else {
+            result.updated.clear();
+        }
+        Ok(result)
+    }
+    
+    /// Renders a properties template and applies it like update_properties: an invalid value
+    /// or unknown key rejects the whole template and nothing is written
+    pub fn apply_template(&self, template: &str, vars: &HashMap<String, String>) -> Result<PropertyUpdateResult, Error> {
+        let changes = render_properties_template(template, vars)?;
+        self.update_properties(&changes)
+    }
+    
     pub fn update_property(&self, key: &str, value: &str) -> Result<(), Error> {
         let mut properties = self.load_properties()?;
         
@@ -384,7 +688,10 @@ impl ServerPropertiesManager {
             "server-port" => properties.server_port = value.parse().map_err(|_| Error::new(ErrorKind::InvalidInput, "Invalid port number"))?,
             "gamemode" => properties.gamemode = value.to_string(),
             "difficulty" => properties.difficulty = value.to_string(),
-            "level-name" => properties.level_name = value.to_string(),
+            "level-name" => {
+                check_level_name(value).map_err(|e| Error::new(ErrorKind::InvalidInput, e))?;
+                properties.level_name = value.to_string()
+            }
             "max-players" => properties.max_players = value.parse().map_err(|_| Error::new(ErrorKind::InvalidInput, "Invalid max players number"))?,
             "motd" => properties.motd = value.to_string(),
             "online-mode" => properties.online_mode = value.parse().map_err(|_| Error::new(ErrorKind::InvalidInput, "Invalid boolean value"))?,
@@ -439,4 +746,268 @@ impl ServerPropertiesManager {
         self.save_properties(&properties)?;
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every field set to something other than its default, so a dropped key can't pass by accident
+    fn non_default_properties() -> ServerProperties {
+        ServerProperties {
+            server_port: 25570,
+            gamemode: "creative".to_string(),
+            difficulty: "hard".to_string(),
+            level_name: "survival_world".to_string(),
+            max_players: 42,
+            motd: "A Minecraft Server managed with §bAllay".to_string(),
+            online_mode: false,
+            pvp: false,
+            level_seed: "-4172144997902289642".to_string(),
+            spawn_protection: 0,
+            white_list: true,
+            enable_command_block: true,
+            spawn_monsters: false,
+            spawn_animals: false,
+            spawn_npcs: false,
+            allow_flight: false,
+            view_distance: 16,
+            simulation_distance: 8,
+            op_permission_level: 3,
+            allow_nether: false,
+            enable_rcon: true,
+            rcon_port: 25580,
+            rcon_password: "allay_Xy7q".to_string(),
+            query_port: 25590,
+            enable_query: false,
+            generator_settings: r#"{"layers":[{"block":"minecraft:bedrock","height":1}],"biome":"minecraft:plains"}"#.to_string(),
+            level_type: "minecraft:flat".to_string(),
+            hardcore: true,
+            enable_status: false,
+            enable_jmx_monitoring: true,
+            broadcast_rcon_to_ops: false,
+            broadcast_console_to_ops: true,
+            enforce_whitelist: true,
+            resource_pack: "https://example.com/pack.zip".to_string(),
+            resource_pack_prompt: "Please accept".to_string(),
+            resource_pack_sha1: "da39a3ee5e6b4b0d3255bfef95601890afd80709".to_string(),
+            require_resource_pack: true,
+            max_world_size: 10000,
+            function_permission_level: 4,
+            max_tick_time: 120000,
+            rate_limit: 500,
+            network_compression_threshold: -1,
+            use_native_transport: false,
+            enable_jmx_monitoring_port: 9000,
+            enable_jmx_monitoring_rmi_port: 9001,
+            sync_chunk_writes: false,
+            server_ip: "192.168.1.20".to_string(),
+            prevent_proxy_connections: true,
+            hide_online_players: true,
+            entity_broadcast_range_percentage: 250,
+            player_idle_timeout: 15,
+            force_gamemode: true,
+            debug: true,
+            max_chained_neighbor_updates: 500000,
+            text_filtering_config: "filter.json".to_string(),
+            initial_disabled_packs: "bundle".to_string(),
+            initial_enabled_packs: "vanilla,trade_rebalance".to_string(),
+            log_ips: false,
+            pause_when_empty_seconds: 300,
+            accepts_transfers: true,
+            generate_structures: false,
+            snooper_enabled: false,
+        }
+    }
+
+    #[test]
+    fn every_field_differs_from_its_default() {
+        let defaults = ServerProperties::default().to_key_values();
+        for ((key, value), (_, default)) in non_default_properties().to_key_values().iter().zip(&defaults) {
+            assert_ne!(value, default, "{} is left at its default", key);
+        }
+    }
+
+    #[test]
+    fn round_trips_every_field() {
+        let original = non_default_properties();
+
+        let parsed = ServerProperties::from_properties_string(&original.to_properties_string()).unwrap();
+
+        assert_eq!(parsed.to_key_values(), original.to_key_values());
+    }
+
+    #[test]
+    fn round_trips_through_a_file() {
+        let dir = std::env::temp_dir().join(format!("allay-properties-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let manager = ServerPropertiesManager::new(dir.join("server.properties"));
+        let original = non_default_properties();
+
+        manager.save_properties(&original).unwrap();
+        let loaded = manager.load_properties();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(loaded.unwrap().to_key_values(), original.to_key_values());
+    }
+
+    #[test]
+    fn writes_each_key_once_with_dotted_names() {
+        let content = ServerProperties::default().to_properties_string();
+        let keys: Vec<&str> = content
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .filter_map(|line| line.split_once('=').map(|(key, _)| key))
+            .collect();
+
+        let mut unique = keys.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(keys.len(), unique.len(), "duplicated keys in {:?}", keys);
+
+        for (key, _) in ServerProperties::default().to_key_values() {
+            assert!(keys.contains(&key), "{} is missing", key);
+        }
+        for key in ["rcon.port", "rcon.password", "query.port", "online-mode", "white-list"] {
+            assert!(keys.contains(&key), "{} is missing", key);
+        }
+    }
+
+    #[test]
+    fn saving_edits_changed_lines_in_place() {
+        let dir = std::env::temp_dir().join(format!("allay-properties-edit-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("server.properties");
+        let original = "#Hand-edited\nmotd=Old name\n# keep PvP on for the arena\npvp=true\ncustom-plugin-key=42\n";
+        fs::write(&path, original).unwrap();
+
+        let manager = ServerPropertiesManager::new(path.clone());
+        let mut properties = manager.load_properties().unwrap();
+        properties.motd = "New name".to_string();
+        properties.max_players = 8;
+        manager.save_properties(&properties).unwrap();
+        let saved = fs::read_to_string(&path);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            saved.unwrap(),
+            "#Hand-edited\nmotd=New name\n# keep PvP on for the arena\npvp=true\ncustom-plugin-key=42\nmax-players=8\n"
+        );
+    }
+
+    #[test]
+    fn connects_to_the_bound_address_or_loopback() {
+        let mut properties = ServerProperties::default();
+        for (server_ip, host) in [("", "127.0.0.1"), ("0.0.0.0", "127.0.0.1"), ("::", "::1"), (" 192.168.1.20 ", "192.168.1.20")] {
+            properties.server_ip = server_ip.to_string();
+            assert_eq!(properties.connect_host(), host, "server-ip={:?}", server_ip);
+        }
+    }
+
+    #[test]
+    fn renders_templates_with_variables() {
+        let vars = HashMap::from([
+            ("SERVER_NAME".to_string(), "lobby".to_string()),
+            ("PORT".to_string(), "25570".to_string()),
+        ]);
+        let template = "# shared settings
+motd=Welcome to ${SERVER_NAME}
+server-port=${PORT}
+";
+
+        let changes = render_properties_template(template, &vars).unwrap();
+        assert_eq!(changes["motd"], "Welcome to lobby");
+        assert_eq!(changes["server-port"], "25570");
+
+        let unknown = render_properties_template("level-name=${WORLD}", &vars).unwrap_err();
+        assert!(unknown.to_string().contains("${WORLD}"));
+        assert!(render_properties_template("motd=${SERVER_NAME", &vars).is_err());
+
+        let escaped = render_properties_template("level-type=minecraft\\:flat\nmotd=\\u00A7a${SERVER_NAME}\n", &vars).unwrap();
+        assert_eq!(escaped["level-type"], "minecraft:flat");
+        assert_eq!(escaped["motd"], "§alobby");
+    }
+
+    #[test]
+    fn reads_files_with_a_bom_and_crlf_line_endings() {
+        let dir = std::env::temp_dir().join(format!("allay-properties-bom-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("server.properties");
+        fs::write(&path, "\u{feff}motd=Edited in Notepad\r\nserver-port=25570 \r\nrcon.port=25580\r\n").unwrap();
+
+        let manager = ServerPropertiesManager::new(path.clone());
+        let properties = manager.load_properties().unwrap();
+        let mut changed = properties.clone();
+        changed.motd = "Renamed".to_string();
+        changed.max_players = 8;
+        manager.save_properties(&changed).unwrap();
+        let saved = fs::read_to_string(&path);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(properties.motd, "Edited in Notepad");
+        assert_eq!(properties.server_port, 25570);
+        assert_eq!(properties.rcon_port, 25580);
+        // The first key is edited in place rather than appended again, keeping the BOM and CRLF
+        assert_eq!(saved.unwrap(), "\u{feff}motd=Renamed\r\nserver-port=25570 \r\nrcon.port=25580\r\nmax-players=8\r\n");
+    }
+
+    #[test]
+    fn reads_latin1_files() {
+        let dir = std::env::temp_dir().join(format!("allay-properties-latin1-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("server.properties");
+        fs::write(&path, b"motd=Caf\xE9 des Amis\nmax-players=12\n").unwrap();
+
+        let manager = ServerPropertiesManager::new(path.clone());
+        let properties = manager.load_properties().unwrap();
+        let mut changed = properties.clone();
+        changed.max_players = 8;
+        manager.save_properties(&changed).unwrap();
+        let saved = fs::read(&path);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(properties.motd, "Café des Amis");
+        assert_eq!(properties.max_players, 12);
+        // Untouched lines stay ISO-8859-1 instead of being re-encoded as UTF-8
+        assert_eq!(saved.unwrap(), b"motd=Caf\xE9 des Amis\nmax-players=8\n");
+    }
+
+    #[test]
+    fn escapes_non_ascii_values_as_java_does() {
+        let original = ServerProperties {
+            motd: "Café des Amis 🎉".to_string(),
+            level_name: "Niño's wörld".to_string(),
+            ..ServerProperties::default()
+        };
+
+        let content = original.to_properties_string();
+        assert!(content.contains("motd=Caf\\u00E9 des Amis \\uD83C\\uDF89\n"), "{}", content);
+        assert!(content.contains("level-name=Ni\\u00F1o's w\\u00F6rld\n"), "{}", content);
+        assert!(content.is_ascii());
+
+        let parsed = ServerProperties::from_properties_string(&content).unwrap();
+        assert_eq!(parsed.motd, "Café des Amis 🎉");
+        assert_eq!(parsed.level_name, "Niño's wörld");
+    }
+
+    #[test]
+    fn reads_values_escaped_by_the_game() {
+        let parsed = ServerProperties::from_properties_string(
+            "level-type=minecraft\\:normal\nmotd=\\u00A7aGreen \\\\ slash\ntext-filtering-config=\\uZZZZ\n",
+        ).unwrap();
+
+        assert_eq!(parsed.level_type, "minecraft:normal");
+        assert_eq!(parsed.motd, "§aGreen \\ slash");
+        assert_eq!(parsed.text_filtering_config, "\\uZZZZ");
+    }
+
+    #[test]
+    fn rejects_level_names_outside_the_server_folder() {
+        let mut properties = ServerProperties::default();
+        for name in ["..", "foo/..", "../world", "a\\b", "C:world", "", "."] {
+            assert!(properties.set_value("level-name", name).is_err(), "{}", name);
+        }
+        properties.set_value("level-name", "Niño's world").unwrap();
+        assert_eq!(properties.level_name, "Niño's world");
+    }
+}
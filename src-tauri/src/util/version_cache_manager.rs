@@ -32,6 +32,7 @@ impl VersionCacheManager {
             LoaderType::NeoForge => "neoforge_versions.json",
             LoaderType::Paper => "paper_versions.json",
             LoaderType::Quilt => "quilt_versions.json",
+            LoaderType::Spigot => "spigot_versions.json",
         };
         self.cache_dir.join(filename)
     }
@@ -52,21 +53,29 @@ impl VersionCacheManager {
     }
 
     pub fn load_cache(&self, loader: &LoaderType) -> Result<Option<VersionCache>> {
-        let cache_file = self.get_cache_file_path(loader);
+        let cache = match self.load_cache_including_expired(loader)? {
+            Some(cache) => cache,
+            None => return Ok(None),
+        };
         
-        if !cache_file.exists() {
+        // Check if cache is expired
+        if cache.expires_at <= Utc::now() {
             return Ok(None);
         }
 
-        let content = fs::read_to_string(cache_file)?;
-        let cache: VersionCache = serde_json::from_str(&content)?;
+        Ok(Some(cache))
+    }
+
+    /// Loads the cached versions even if they are past their expiry, for reporting staleness
+    pub fn load_cache_including_expired(&self, loader: &LoaderType) -> Result<Option<VersionCache>> {
+        let cache_file = self.get_cache_file_path(loader);
         
-        // Check if cache is expired
-        if cache.expires_at <= Utc::now() {
+        if !cache_file.exists() {
             return Ok(None);
         }
 
-        Ok(Some(cache))
+        let content = fs::read_to_string(cache_file)?;
+        Ok(Some(serde_json::from_str(&content)?))
     }
 
     pub fn save_cache(&self, loader: LoaderType, versions: Vec<MinecraftVersion>) -> Result<()> {
@@ -101,6 +110,7 @@ impl VersionCacheManager {
             LoaderType::NeoForge,
             LoaderType::Paper,
             LoaderType::Quilt,
+            LoaderType::Spigot,
         ];
 
         for loader in loaders {
@@ -119,12 +129,13 @@ impl VersionCacheManager {
             ("neoforge", LoaderType::NeoForge),
             ("paper", LoaderType::Paper),
             ("quilt", LoaderType::Quilt),
+            ("spigot", LoaderType::Spigot),
         ];
 
         for (name, loader) in loaders {
             let cache_file = self.get_cache_file_path(&loader);
             let cache_info = if cache_file.exists() {
-                match self.load_cache(&loader) {
+                match self.load_cache_including_expired(&loader) {
                     Ok(Some(cache)) => CacheInfo {
                         exists: true,
                         last_updated: Some(cache.last_updated),
@@ -165,6 +176,7 @@ impl VersionCacheManager {
             ("neoforge", LoaderType::NeoForge),
             ("paper", LoaderType::Paper),
             ("quilt", LoaderType::Quilt),
+            ("spigot", LoaderType::Spigot),
         ];
 
         for (name, loader) in loaders {
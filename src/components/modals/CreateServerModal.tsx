@@ -143,6 +143,11 @@ export const CreateServerModal = ({ isOpen, onClose, onCreateServer }: CreateSer
             value: 'quilt', 
             label: 'Quilt', 
             description: 'Community-driven fork of Fabric' 
+        },
+        { 
+            value: 'spigot', 
+            label: 'Spigot', 
+            description: 'Classic plugin server, built locally with BuildTools' 
         }
     ];
 